@@ -2,7 +2,7 @@
 set -euo pipefail
 
 PROJECT_DIR="${PROJECT_DIR:-/home/openclaw-manager}"
-WEB_HOST="${OPENCLAW_WEB_HOST:-0.0.0.0}"
+WEB_HOST="${OPENCLAW_WEB_HOST:-}"
 WEB_PORT="${OPENCLAW_WEB_PORT:-17890}"
 WEB_STATIC_DIR="${OPENCLAW_WEB_STATIC_DIR:-$PROJECT_DIR/dist}"
 COOKIE_SECURE="${OPENCLAW_WEB_COOKIE_SECURE:-false}"
@@ -22,10 +22,16 @@ Options:
 
 Environment:
   PROJECT_DIR               项目目录（默认 /home/openclaw-manager）
-  OPENCLAW_WEB_HOST         监听地址（默认 0.0.0.0）
+  OPENCLAW_WEB_HOST         监听地址（默认 127.0.0.1，仅本机可访问）
+  OPENCLAW_WEB_EXPOSE       置为 1 且未设置 OPENCLAW_WEB_HOST 时监听 0.0.0.0
   OPENCLAW_WEB_PORT         监听端口（默认 17890）
   OPENCLAW_WEB_STATIC_DIR   前端静态目录（默认 $PROJECT_DIR/dist）
   OPENCLAW_WEB_COOKIE_SECURE Cookie Secure（默认 false）
+  OPENCLAW_WEB_TLS_CERT     HTTPS 证书（PEM，需与 OPENCLAW_WEB_TLS_KEY 同时设置）
+  OPENCLAW_WEB_TLS_KEY      HTTPS 私钥（PEM）
+  OPENCLAW_WEB_MAX_BODY_BYTES 请求体上限（字节，默认 2MB）
+  OPENCLAW_WEB_READ_TIMEOUT_SECS 读取请求超时（秒，默认 15）
+  OPENCLAW_WEB_READONLY     置为 1 时启用只读模式，只允许白名单中的查询命令
   OPENCLAW_WEB_LOG_FILE     守护模式日志文件（默认 $PROJECT_DIR/logs/web-server.log）
   OPENCLAW_WEB_PID_FILE     守护模式 PID 文件（默认 $PROJECT_DIR/run/web-server.pid）
   FORCE_BUILD               置为 1 时强制构建
@@ -87,7 +93,9 @@ if [[ ! -f "$WEB_STATIC_DIR/index.html" ]]; then
   exit 1
 fi
 
-export OPENCLAW_WEB_HOST="$WEB_HOST"
+if [[ -n "$WEB_HOST" ]]; then
+  export OPENCLAW_WEB_HOST="$WEB_HOST"
+fi
 export OPENCLAW_WEB_PORT="$WEB_PORT"
 export OPENCLAW_WEB_STATIC_DIR="$WEB_STATIC_DIR"
 export OPENCLAW_WEB_COOKIE_SECURE="$COOKIE_SECURE"
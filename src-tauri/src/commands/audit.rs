@@ -0,0 +1,355 @@
+use super::config;
+use crate::utils::{file, platform};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::command;
+
+/// 桌面端（Tauri）调用记录的用户名
+const LOCAL_USER: &str = "local";
+
+/// 参数摘要的最大字符数，超出部分截断
+const ARGS_SUMMARY_MAX_CHARS: usize = 512;
+
+/// 读取审计日志时默认返回的条数
+const DEFAULT_AUDIT_ENTRIES: usize = 100;
+
+/// 会修改配置、服务状态或外部环境（含在本机创建文件、启动进程）的命令，调用时写入审计日志
+pub(crate) const MUTATING_COMMANDS: &[&str] = &[
+    "start_service",
+    "stop_service",
+    "restart_service",
+    "kill_gateway_process",
+    "clear_logs",
+    "install_autostart",
+    "uninstall_autostart",
+    "clear_old_sessions",
+    "clear_credentials",
+    "save_config",
+    "apply_config_change",
+    "rollback_config",
+    "import_config_bundle",
+    "create_profile",
+    "switch_profile",
+    "save_agents_list",
+    "set_agent_model",
+    "add_agent",
+    "update_agent",
+    "delete_agent",
+    "save_bindings",
+    "migrate_bindings",
+    "repair_orphaned_bindings",
+    "save_env_value",
+    "save_env_values",
+    "remove_env_key",
+    "save_channel_config",
+    "import_channels",
+    "clear_channel_config",
+    "set_channel_enabled",
+    "set_channels_enabled",
+    "rotate_gateway_token",
+    "save_gateway_settings",
+    "save_provider",
+    "delete_provider",
+    "clone_provider",
+    "set_primary_model",
+    "set_max_concurrent",
+    "add_available_model",
+    "remove_available_model",
+    "update_model_cost",
+    "enable_plugin",
+    "disable_plugin",
+    "repair_plugin_consistency",
+    "fix_config_permissions",
+    "install_feishu_plugin",
+    "send_test_message",
+    "start_channel_login",
+    "install_nodejs",
+    "set_preferred_node_path",
+    "install_openclaw",
+    "init_openclaw_config",
+    "uninstall_openclaw",
+    "update_openclaw",
+    "run_openclaw_command",
+    "get_or_create_gateway_token",
+    "open_config_file",
+    "open_config_dir",
+    "open_install_terminal",
+];
+
+/// 无副作用的查询命令；新增命令必须归入本列表或 MUTATING_COMMANDS 之一
+pub(crate) const READ_ONLY_COMMANDS: &[&str] = &[
+    "get_service_status",
+    "get_logs",
+    "is_autostart_enabled",
+    "get_sessions_info",
+    "get_usage_stats",
+    "list_credentials",
+    "check_openclaw_installed",
+    "get_openclaw_version",
+    "get_node_version",
+    "check_port_in_use",
+    "check_port_owner",
+    "list_gateway_processes",
+    "get_config",
+    "get_config_schema",
+    "get_effective_config",
+    "preview_config_change",
+    "list_config_backups",
+    "export_config_bundle",
+    "list_profiles",
+    "current_profile",
+    "get_agents_list",
+    "get_agent",
+    "get_bindings",
+    "get_env_value",
+    "get_env_values",
+    "get_env_keys",
+    "get_ai_providers",
+    "get_channels_config",
+    "validate_channel_accounts",
+    "get_dashboard_url",
+    "get_gateway_settings",
+    "verify_gateway_token",
+    "watch_config",
+    "get_official_providers",
+    "get_ai_config",
+    "get_agent_defaults",
+    "list_plugins",
+    "audit_plugin_consistency",
+    "check_channel_plugin",
+    "check_feishu_plugin",
+    "run_doctor",
+    "test_ai_connection",
+    "test_provider_connection",
+    "test_channel",
+    "get_system_info",
+    "get_paths",
+    "check_environment",
+    "list_node_installations",
+    "check_openclaw_update",
+    "get_audit_log",
+];
+
+/// 审计日志条目（每行一个 JSON）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// RFC3339 时间
+    pub timestamp: String,
+    /// 操作用户：Web 端为登录用户名，桌面端为 local
+    pub user: String,
+    pub command: String,
+    /// 脱敏后的参数摘要
+    pub args: String,
+    /// 执行结果；桌面端在命令执行前记录，结果未知时为 None
+    #[serde(default)]
+    pub success: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 是否为需要审计的修改类命令
+pub(crate) fn is_mutating_command(command: &str) -> bool {
+    MUTATING_COMMANDS.contains(&command)
+}
+
+/// 是否为已归类的只读查询命令（未归类的命令视为非只读）
+pub(crate) fn is_read_only_command(command: &str) -> bool {
+    READ_ONLY_COMMANDS.contains(&command)
+}
+
+/// 参数中携带 env 值的命令：env 存放 API Key 等密钥，值无论键名如何都脱敏
+const ENV_VALUE_COMMANDS: &[&str] = &["save_env_value", "save_env_values"];
+
+/// 将 env 命令的 value / values 参数替换为 "***"（values 保留变量名）
+fn redact_env_values(args: &mut Value) {
+    let Some(obj) = args.as_object_mut() else {
+        return;
+    };
+    if let Some(value) = obj.get_mut("value") {
+        *value = json!("***");
+    }
+    if let Some(values) = obj.get_mut("values") {
+        match values.as_object_mut() {
+            Some(values) => values.values_mut().for_each(|v| *v = json!("***")),
+            None => *values = json!("***"),
+        }
+    }
+}
+
+/// 生成参数摘要：密钥字段与 env 值脱敏，过长时截断
+fn summarize_args(command: &str, args: &Value) -> String {
+    let mut redacted = config::redact_sensitive_value(args);
+    if ENV_VALUE_COMMANDS.contains(&command) {
+        redact_env_values(&mut redacted);
+    }
+    let text = match &redacted {
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    if text.chars().count() <= ARGS_SUMMARY_MAX_CHARS {
+        text
+    } else {
+        let truncated: String = text.chars().take(ARGS_SUMMARY_MAX_CHARS).collect();
+        format!("{}...", truncated)
+    }
+}
+
+/// 构造审计条目
+fn build_audit_entry(
+    user: &str,
+    command: &str,
+    args: &Value,
+    result: Option<Result<(), &str>>,
+) -> AuditEntry {
+    AuditEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        user: user.to_string(),
+        command: command.to_string(),
+        args: summarize_args(command, args),
+        success: result.map(|r| r.is_ok()),
+        error: result.and_then(|r| r.err()).map(str::to_string),
+    }
+}
+
+/// 记录一次修改类命令调用（非修改类命令直接忽略）；写入失败只记录警告，不影响命令本身
+/// user 为 None 表示桌面端调用；result 为 None 表示调用前记录、结果未知
+pub(crate) fn record_command(
+    user: Option<&str>,
+    command: &str,
+    args: &Value,
+    result: Option<Result<(), &str>>,
+) {
+    if !is_mutating_command(command) {
+        return;
+    }
+    let entry = build_audit_entry(user.unwrap_or(LOCAL_USER), command, args, result);
+    if let Err(e) = append_audit_entry(&platform::get_audit_log_path(), &entry) {
+        warn!("[审计] 写入审计日志失败: {}", e);
+    }
+}
+
+/// 以 JSON 行追加审计条目（Unix 上日志文件权限为 0600）
+fn append_audit_entry(path: &str, entry: &AuditEntry) -> Result<(), String> {
+    let line = serde_json::to_string(entry).map_err(|e| format!("序列化审计条目失败: {}", e))?;
+    file::append_private_file(path, &line).map_err(|e| e.to_string())
+}
+
+/// 读取最后 limit 条审计条目，跳过无法解析的行
+fn read_audit_entries(path: &str, limit: usize) -> Result<Vec<AuditEntry>, String> {
+    if !std::path::Path::new(path).exists() {
+        return Ok(Vec::new());
+    }
+    let lines = file::read_last_lines(path, limit).map_err(|e| format!("读取审计日志失败: {}", e))?;
+    Ok(lines
+        .iter()
+        .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+        .collect())
+}
+
+/// 读取最近的审计日志（按时间顺序，最多 limit 条）
+#[command]
+pub async fn get_audit_log(limit: Option<u32>) -> Result<Vec<AuditEntry>, String> {
+    let limit = limit.map(|n| n as usize).unwrap_or(DEFAULT_AUDIT_ENTRIES);
+    info!("[审计] 读取最近 {} 条审计日志...", limit);
+    read_audit_entries(&platform::get_audit_log_path(), limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        append_audit_entry, build_audit_entry, is_mutating_command, is_read_only_command,
+        read_audit_entries, summarize_args, ARGS_SUMMARY_MAX_CHARS, MUTATING_COMMANDS,
+        READ_ONLY_COMMANDS,
+    };
+    use crate::commands::config::tests::unique_temp_dir;
+    use crate::commands::COMMAND_NAMES;
+    use serde_json::json;
+
+    #[test]
+    fn mutating_commands_are_registered_commands() {
+        for command in MUTATING_COMMANDS {
+            assert!(COMMAND_NAMES.contains(command), "未注册的命令: {}", command);
+        }
+        assert!(is_mutating_command("save_config"));
+        assert!(!is_mutating_command("get_config"));
+        assert!(!is_mutating_command("get_audit_log"));
+        assert!(is_mutating_command("run_openclaw_command"));
+        assert!(is_mutating_command("open_install_terminal"));
+    }
+
+    #[test]
+    fn every_command_is_classified_exactly_once() {
+        for command in COMMAND_NAMES {
+            assert!(
+                is_mutating_command(command) != is_read_only_command(command),
+                "命令必须且只能归入 MUTATING_COMMANDS 或 READ_ONLY_COMMANDS: {}",
+                command
+            );
+        }
+        for command in READ_ONLY_COMMANDS {
+            assert!(COMMAND_NAMES.contains(command), "未注册的命令: {}", command);
+        }
+    }
+
+    #[test]
+    fn audit_args_are_redacted_and_truncated() {
+        let summary = summarize_args(
+            "save_provider",
+            &json!({
+                "key": "OPENAI_API_KEY",
+                "provider": { "apiKey": "sk-live-secret", "baseUrl": "https://api.openai.com" }
+            }),
+        );
+        assert!(!summary.contains("sk-live-secret"), "{}", summary);
+        assert!(summary.contains("api.openai.com"), "{}", summary);
+
+        // env 值与键名无关，一律脱敏
+        let summary = summarize_args(
+            "save_env_value",
+            &json!({ "key": "OPENAI_API_KEY", "value": "sk-live-secret" }),
+        );
+        assert!(!summary.contains("sk-live-secret"), "{}", summary);
+        assert!(summary.contains("OPENAI_API_KEY"), "{}", summary);
+        let summary = summarize_args(
+            "save_env_values",
+            &json!({ "values": { "DEEPSEEK_KEY": "sk-ds", "OPENCLAW_FEISHU_APPID": "cli_a1" } }),
+        );
+        assert!(!summary.contains("sk-ds") && !summary.contains("cli_a1"), "{}", summary);
+        assert!(summary.contains("DEEPSEEK_KEY"), "{}", summary);
+
+        let summary = summarize_args("save_config", &json!({ "config": "x".repeat(2000) }));
+        assert_eq!(summary.chars().count(), ARGS_SUMMARY_MAX_CHARS + 3);
+        assert_eq!(summarize_args("save_config", &json!(null)), "");
+    }
+
+    #[test]
+    fn audit_entries_round_trip_through_log_file() {
+        let path = unique_temp_dir("audit").join("manager-audit.log");
+        let path = path.to_string_lossy().to_string();
+        assert!(read_audit_entries(&path, 10).unwrap().is_empty());
+
+        let ok = build_audit_entry("admin", "save_config", &json!({}), Some(Ok(())));
+        let failed = build_audit_entry("local", "delete_provider", &json!({}), Some(Err("不存在")));
+        let unknown = build_audit_entry("local", "start_service", &json!({}), None);
+        for entry in [&ok, &failed, &unknown] {
+            append_audit_entry(&path, entry).unwrap();
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let entries = read_audit_entries(&path, 2).unwrap();
+        assert_eq!(entries, vec![failed.clone(), unknown.clone()]);
+        assert_eq!(entries[0].success, Some(false));
+        assert_eq!(entries[0].error.as_deref(), Some("不存在"));
+        assert_eq!(entries[1].success, None);
+        assert_eq!(read_audit_entries(&path, 10).unwrap()[0], ok);
+
+        let _ = std::fs::remove_dir_all(std::path::Path::new(&path).parent().unwrap());
+    }
+}
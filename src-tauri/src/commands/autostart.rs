@@ -0,0 +1,368 @@
+use super::config;
+use crate::utils::{file, platform, shell};
+use log::{info, warn};
+use tauri::command;
+
+/// launchd 服务标识（与 openclaw 自带的 daemon 区分）
+const LAUNCHD_LABEL: &str = "com.openclaw-manager.gateway";
+/// systemd --user 单元名
+const SYSTEMD_UNIT: &str = "openclaw-manager-gateway.service";
+/// Windows 计划任务名
+const WINDOWS_TASK_NAME: &str = "OpenClawManagerGateway";
+
+/// 开机自启的 Gateway 启动参数
+struct AutostartSpec {
+    openclaw_path: String,
+    env_file: String,
+    port: u16,
+    path_env: String,
+    log_path: String,
+    /// 配置档或配置目录不是默认位置时注入的 OPENCLAW_CONFIG_PATH / OPENCLAW_STATE_DIR
+    location_env: Vec<(&'static str, String)>,
+}
+
+impl AutostartSpec {
+    /// 按当前环境解析 openclaw 路径、env 文件、端口与配置档
+    fn resolve() -> Result<Self, String> {
+        let openclaw_path = shell::get_openclaw_path()
+            .ok_or_else(|| "找不到 openclaw 命令，请先通过 npm install -g openclaw 安装".to_string())?;
+        Ok(Self {
+            openclaw_path,
+            env_file: platform::get_env_file_path(),
+            port: config::get_gateway_port(),
+            path_env: shell::get_extended_path(),
+            log_path: platform::get_log_file_path(),
+            location_env: platform::get_config_location_env(),
+        })
+    }
+}
+
+/// XML 文本转义
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Unix 启动脚本：每次启动时重新加载 env 文件（与 source ~/.openclaw/env 一致），再前台运行 gateway
+fn unix_launch_script(spec: &AutostartSpec) -> String {
+    let env_file = shell::shell_quote(&spec.env_file);
+    format!(
+        "set -a; [ -f {env} ] && . {env}; set +a; exec {bin} gateway --port {port}",
+        env = env_file,
+        bin = shell::shell_quote(&spec.openclaw_path),
+        port = spec.port
+    )
+}
+
+/// 生成 launchd plist（~/Library/LaunchAgents）
+fn build_launchd_plist(spec: &AutostartSpec) -> String {
+    let mut env_entries = format!(
+        "        <key>PATH</key>\n        <string>{}</string>\n",
+        xml_escape(&spec.path_env)
+    );
+    for (key, value) in &spec.location_env {
+        env_entries.push_str(&format!(
+            "        <key>{}</key>\n        <string>{}</string>\n",
+            key,
+            xml_escape(value)
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>/bin/sh</string>
+        <string>-c</string>
+        <string>{script}</string>
+    </array>
+    <key>EnvironmentVariables</key>
+    <dict>
+{env}    </dict>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+        label = LAUNCHD_LABEL,
+        script = xml_escape(&unix_launch_script(spec)),
+        env = env_entries,
+        log = xml_escape(&spec.log_path),
+    )
+}
+
+/// systemd 单元中的双引号参数转义（$ 与 % 需要转义，避免被 systemd 展开）
+fn systemd_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%")
+        .replace('$', "$$");
+    format!("\"{}\"", escaped)
+}
+
+/// 生成 systemd --user 单元
+fn build_systemd_unit(spec: &AutostartSpec) -> String {
+    let path_env = format!("PATH={}", spec.path_env);
+    let mut environment = format!("Environment={}\n", systemd_quote(&path_env));
+    for (key, value) in &spec.location_env {
+        environment.push_str(&format!(
+            "Environment={}\n",
+            systemd_quote(&format!("{}={}", key, value))
+        ));
+    }
+
+    format!(
+        "[Unit]\n\
+         Description=OpenClaw Gateway (OpenClaw Manager)\n\
+         After=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         {environment}\
+         ExecStart=/bin/sh -c {script}\n\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        environment = environment,
+        script = systemd_quote(&unix_launch_script(spec)),
+    )
+}
+
+/// 生成 Windows 计划任务调用的启动脚本：逐行加载 env 文件（兼容 export 前缀）后启动 gateway
+fn build_windows_script(spec: &AutostartSpec) -> String {
+    let mut script = String::from("@echo off\r\nsetlocal EnableDelayedExpansion\r\n");
+    script.push_str(&format!("if exist \"{}\" (\r\n", spec.env_file));
+    script.push_str(&format!(
+        "  for /f \"usebackq eol=# tokens=1,* delims==\" %%a in (\"{}\") do (\r\n",
+        spec.env_file
+    ));
+    script.push_str("    set \"key=%%a\"\r\n");
+    script.push_str("    set \"key=!key:export =!\"\r\n");
+    script.push_str("    set \"!key!=%%~b\"\r\n");
+    script.push_str("  )\r\n)\r\n");
+    for (key, value) in &spec.location_env {
+        script.push_str(&format!("set \"{}={}\"\r\n", key, value));
+    }
+    script.push_str(&format!(
+        "call \"{}\" gateway --port {} >> \"{}\" 2>&1\r\n",
+        spec.openclaw_path, spec.port, spec.log_path
+    ));
+    script
+}
+
+/// 写入启动文件（自动创建父目录）
+fn write_autostart_file(path: &str, content: &str) -> Result<(), String> {
+    file::write_file(path, content).map_err(|e| format!("写入 {} 失败: {}", path, e))
+}
+
+/// 删除启动文件（不存在时忽略）
+fn remove_autostart_file(path: &str) -> Result<(), String> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("删除 {} 失败: {}", path, e)),
+    }
+}
+
+/// 安装开机自启：macOS 写入 launchd plist 并加载，Linux 写入 systemd --user 单元并启用，
+/// Windows 注册登录时运行的计划任务
+#[command]
+pub async fn install_autostart() -> Result<String, String> {
+    info!("[开机自启] 安装开机自启...");
+    let spec = AutostartSpec::resolve()?;
+    // 确保配置中已有 Gateway Token，开机启动的 gateway 与 Dashboard 使用同一 Token
+    config::get_or_create_gateway_token().await?;
+
+    if platform::is_macos() {
+        let path = platform::get_launch_agent_path(LAUNCHD_LABEL);
+        write_autostart_file(&path, &build_launchd_plist(&spec))?;
+        let _ = shell::run_command_output("launchctl", &["unload", &path]);
+        shell::run_command_output("launchctl", &["load", "-w", &path])
+            .map_err(|e| format!("launchctl load 失败: {}", e))?;
+        info!("[开机自启] ✓ 已写入并加载 {}", path);
+        Ok(format!("已启用开机自启（launchd: {}）", path))
+    } else if platform::is_linux() {
+        let path = platform::get_systemd_user_unit_path(SYSTEMD_UNIT);
+        write_autostart_file(&path, &build_systemd_unit(&spec))?;
+        shell::run_command_output("systemctl", &["--user", "daemon-reload"])
+            .map_err(|e| format!("systemctl daemon-reload 失败: {}", e))?;
+        shell::run_command_output("systemctl", &["--user", "enable", SYSTEMD_UNIT])
+            .map_err(|e| format!("systemctl enable 失败: {}", e))?;
+        info!("[开机自启] ✓ 已写入并启用 {}", path);
+        Ok(format!("已启用开机自启（systemd: {}）", path))
+    } else if platform::is_windows() {
+        let path = platform::get_autostart_script_path();
+        write_autostart_file(&path, &build_windows_script(&spec))?;
+        let task_command = format!("\"{}\"", path);
+        let args = [
+            "/Create", "/F", "/SC", "ONLOGON", "/RL", "LIMITED", "/TN", WINDOWS_TASK_NAME, "/TR",
+            &task_command,
+        ];
+        shell::run_command_output("schtasks", &args)
+        .map_err(|e| format!("注册计划任务失败: {}", e))?;
+        info!("[开机自启] ✓ 已注册计划任务 {}", WINDOWS_TASK_NAME);
+        Ok(format!("已启用开机自启（计划任务: {}）", WINDOWS_TASK_NAME))
+    } else {
+        Err(format!("当前系统不支持开机自启: {}", platform::get_os()))
+    }
+}
+
+/// 卸载开机自启
+#[command]
+pub async fn uninstall_autostart() -> Result<String, String> {
+    info!("[开机自启] 卸载开机自启...");
+    if platform::is_macos() {
+        let path = platform::get_launch_agent_path(LAUNCHD_LABEL);
+        if std::path::Path::new(&path).exists() {
+            if let Err(e) = shell::run_command_output("launchctl", &["unload", "-w", &path]) {
+                warn!("[开机自启] launchctl unload 失败: {}", e);
+            }
+        }
+        remove_autostart_file(&path)?;
+    } else if platform::is_linux() {
+        let path = platform::get_systemd_user_unit_path(SYSTEMD_UNIT);
+        let disabled = shell::run_command_output("systemctl", &["--user", "disable", SYSTEMD_UNIT]);
+        if let Err(e) = disabled {
+            warn!("[开机自启] systemctl disable 失败: {}", e);
+        }
+        remove_autostart_file(&path)?;
+        let _ = shell::run_command_output("systemctl", &["--user", "daemon-reload"]);
+    } else if platform::is_windows() {
+        let args = ["/Delete", "/F", "/TN", WINDOWS_TASK_NAME];
+        if let Err(e) = shell::run_command_output("schtasks", &args) {
+            warn!("[开机自启] 删除计划任务失败: {}", e);
+        }
+        remove_autostart_file(&platform::get_autostart_script_path())?;
+    } else {
+        return Err(format!("当前系统不支持开机自启: {}", platform::get_os()));
+    }
+    info!("[开机自启] ✓ 已关闭开机自启");
+    Ok("已关闭开机自启".to_string())
+}
+
+/// 解析 `systemctl --user is-enabled` 的输出
+/// 未启用时命令以非零状态退出但 stdout 仍给出状态（disabled/not-found 等），
+/// 没有输出时返回 None
+fn parse_systemd_enabled_state(stdout: &str) -> Option<bool> {
+    let state = stdout.lines().next()?.trim();
+    if state.is_empty() {
+        return None;
+    }
+    Some(state == "enabled")
+}
+
+/// 查询是否已启用开机自启
+#[command]
+pub async fn is_autostart_enabled() -> Result<bool, String> {
+    if platform::is_macos() {
+        Ok(std::path::Path::new(&platform::get_launch_agent_path(LAUNCHD_LABEL)).exists())
+    } else if platform::is_linux() {
+        // systemctl 不可用（如容器内）或没有给出状态时以单元文件是否存在为准
+        let state = shell::run_command("systemctl", &["--user", "is-enabled", SYSTEMD_UNIT])
+            .ok()
+            .and_then(|output| {
+                parse_systemd_enabled_state(&String::from_utf8_lossy(&output.stdout))
+            });
+        match state {
+            Some(enabled) => Ok(enabled),
+            None => Ok(std::path::Path::new(&platform::get_systemd_user_unit_path(SYSTEMD_UNIT))
+                .exists()),
+        }
+    } else if platform::is_windows() {
+        Ok(shell::run_command_output("schtasks", &["/Query", "/TN", WINDOWS_TASK_NAME]).is_ok())
+    } else {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_launchd_plist, build_systemd_unit, build_windows_script,
+        parse_systemd_enabled_state, AutostartSpec, LAUNCHD_LABEL,
+    };
+
+    fn spec(config_path: Option<&str>) -> AutostartSpec {
+        let location_env = config_path
+            .map(|path| vec![("OPENCLAW_CONFIG_PATH", path.to_string())])
+            .unwrap_or_default();
+        AutostartSpec {
+            openclaw_path: "/opt/node/bin/openclaw".to_string(),
+            env_file: "/home/me/.openclaw/env".to_string(),
+            port: 18789,
+            path_env: "/opt/node/bin:/usr/bin".to_string(),
+            log_path: "/tmp/openclaw-gateway.log".to_string(),
+            location_env,
+        }
+    }
+
+    #[test]
+    fn launchd_plist_sources_env_file_and_runs_resolved_openclaw() {
+        let profile_config = "/home/me/.openclaw/profiles/a&b/openclaw.json";
+        let plist = build_launchd_plist(&spec(Some(profile_config)));
+        assert!(plist.contains(&format!("<string>{}</string>", LAUNCHD_LABEL)));
+        assert!(plist.contains(
+            "set -a; [ -f &apos;/home/me/.openclaw/env&apos; ] &amp;&amp; \
+             . &apos;/home/me/.openclaw/env&apos;"
+        ));
+        assert!(plist.contains("exec &apos;/opt/node/bin/openclaw&apos; gateway --port 18789"));
+        assert!(plist.contains("<string>/opt/node/bin:/usr/bin</string>"));
+        assert!(plist.contains("/profiles/a&amp;b/openclaw.json</string>"));
+        assert!(plist.contains("<key>RunAtLoad</key>\n    <true/>"));
+    }
+
+    #[test]
+    fn systemd_unit_and_windows_script_inject_env_file() {
+        let unit = build_systemd_unit(&spec(None));
+        assert!(unit.contains("Environment=\"PATH=/opt/node/bin:/usr/bin\"\n"));
+        assert!(unit.contains(
+            "ExecStart=/bin/sh -c \"set -a; \
+             [ -f '/home/me/.openclaw/env' ] && . '/home/me/.openclaw/env'; \
+             set +a; exec '/opt/node/bin/openclaw' gateway --port 18789\"\n"
+        ));
+        assert!(unit.contains("WantedBy=default.target"));
+        assert!(!unit.contains("OPENCLAW_CONFIG_PATH"));
+
+        let mut relocated = spec(Some("/data/openclaw/openclaw.json"));
+        relocated.location_env.insert(0, ("OPENCLAW_STATE_DIR", "/data/openclaw".to_string()));
+        let unit = build_systemd_unit(&relocated);
+        assert!(unit.contains("Environment=\"OPENCLAW_STATE_DIR=/data/openclaw\"\n"));
+        assert!(unit.contains("\"OPENCLAW_CONFIG_PATH=/data/openclaw/openclaw.json\"\n"));
+
+        let mut windows = spec(Some(r"C:\Users\me\.openclaw\profiles\work\openclaw.json"));
+        windows.openclaw_path = r"C:\Users\me\AppData\Roaming\npm\openclaw.cmd".to_string();
+        windows.env_file = r"C:\Users\me\.openclaw\env".to_string();
+        let script = build_windows_script(&windows);
+        assert!(script.contains(r#"in ("C:\Users\me\.openclaw\env") do ("#));
+        assert!(script.contains(r#"set "OPENCLAW_CONFIG_PATH=C:\Users\me\.openclaw\profiles\"#));
+        assert!(script.contains(r#"call "C:\Users\me\AppData\Roaming\npm\openclaw.cmd" gateway"#));
+        assert!(script.contains("set \"key=!key:export =!\""));
+    }
+
+    #[test]
+    fn systemd_enabled_state_is_read_from_stdout() {
+        assert_eq!(parse_systemd_enabled_state("enabled\n"), Some(true));
+        // 未启用或单元不存在时 is-enabled 以非零状态退出，状态仍在 stdout 中
+        assert_eq!(parse_systemd_enabled_state("disabled\n"), Some(false));
+        assert_eq!(parse_systemd_enabled_state("not-found\n"), Some(false));
+        assert_eq!(parse_systemd_enabled_state("masked"), Some(false));
+        assert_eq!(parse_systemd_enabled_state(""), None);
+        assert_eq!(parse_systemd_enabled_state("\n"), None);
+    }
+}
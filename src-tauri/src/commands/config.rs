@@ -1,15 +1,16 @@
 use crate::models::{
-    AIConfigOverview, BindingEntry, BindingsConfig, ChannelConfig, ConfiguredModel,
-    ConfiguredProvider, ModelConfig, ModelCostConfig, OfficialProvider,
-    OpenClawConfig, ProviderConfig, SuggestedModel,
+    AgentEntry, AIConfigOverview, BindingEntry, BindingsConfig, ChannelConfig, ConfiguredModel,
+    ConfiguredProvider, GatewayConfig, ModelConfig, ModelCostConfig, OfficialProvider,
+    OpenClawConfig, SuggestedModel,
 };
 use crate::utils::{file, platform, shell};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
 use tauri::command;
 
@@ -28,16 +29,43 @@ fn parse_openclaw_config_content(content: &str) -> Result<Value, String> {
     }
 }
 
-/// 获取 openclaw.json 原始配置（不做变量替换，用于写回场景）
-fn load_openclaw_config_raw() -> Result<Value, String> {
+/// 配置版本号：openclaw.json 磁盘内容的 FNV-1a 哈希（文件不存在时按空内容计算）
+fn config_content_version(content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in content.as_bytes() {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// 读取 openclaw.json 磁盘内容（文件不存在时返回 None）
+fn read_openclaw_config_content() -> Result<Option<String>, String> {
     let config_path = platform::get_config_file_path();
 
     if !file::file_exists(&config_path) {
-        return Ok(json!({}));
+        return Ok(None);
+    }
+
+    file::read_file(&config_path)
+        .map(Some)
+        .map_err(|e| format!("读取配置文件失败: {}", e))
+}
+
+/// 获取 openclaw.json 原始配置及其版本号
+fn load_openclaw_config_raw_versioned() -> Result<(Value, String), String> {
+    match read_openclaw_config_content()? {
+        Some(content) => Ok((
+            parse_openclaw_config_content(&content)?,
+            config_content_version(&content),
+        )),
+        None => Ok((json!({}), config_content_version(""))),
     }
+}
 
-    let content = file::read_file(&config_path).map_err(|e| format!("读取配置文件失败: {}", e))?;
-    parse_openclaw_config_content(&content)
+/// 获取 openclaw.json 原始配置（不做变量替换，用于写回场景）
+fn load_openclaw_config_raw() -> Result<Value, String> {
+    load_openclaw_config_raw_versioned().map(|(config, _)| config)
 }
 
 /// 读取 ~/.openclaw/env 环境变量
@@ -90,10 +118,24 @@ fn join_config_path(path: &str, segment: &str) -> String {
 }
 
 /// 字符串中的变量替换：支持 ${VAR}；支持 $${VAR} 作为字面量 ${VAR}
+/// 变量先取进程环境变量，再取 env 文件，均不存在时报错
 fn replace_config_vars_in_string(
     input: &str,
     env_file_vars: &HashMap<String, String>,
     config_path: &str,
+) -> Result<String, String> {
+    substitute_config_vars_in_string(input, config_path, &mut |var_name| {
+        std::env::var(var_name)
+            .ok()
+            .or_else(|| env_file_vars.get(var_name).cloned())
+    })
+}
+
+/// 变量替换核心逻辑，resolve 返回 None 表示变量缺失
+fn substitute_config_vars_in_string(
+    input: &str,
+    config_path: &str,
+    resolve: &mut dyn FnMut(&str) -> Option<String>,
 ) -> Result<String, String> {
     let mut output = String::with_capacity(input.len());
     let bytes = input.as_bytes();
@@ -131,16 +173,13 @@ fn replace_config_vars_in_string(
                         ));
                     }
 
-                    let var_value = std::env::var(var_name)
-                        .ok()
-                        .or_else(|| env_file_vars.get(var_name).cloned())
-                        .ok_or_else(|| {
-                            format!(
-                                "配置变量替换失败: 路径 {} 缺失变量 {}",
-                                format_config_path(config_path),
-                                var_name
-                            )
-                        })?;
+                    let var_value = resolve(var_name).ok_or_else(|| {
+                        format!(
+                            "配置变量替换失败: 路径 {} 缺失变量 {}",
+                            format_config_path(config_path),
+                            var_name
+                        )
+                    })?;
 
                     output.push_str(&var_value);
                     i = end + 1;
@@ -167,21 +206,31 @@ fn replace_config_vars(
     value: &mut Value,
     env_file_vars: &HashMap<String, String>,
     path: &str,
+) -> Result<(), String> {
+    walk_config_strings(value, path, &mut |s, string_path| {
+        *s = replace_config_vars_in_string(s, env_file_vars, string_path)?;
+        Ok(())
+    })
+}
+
+/// 递归遍历 Value 中的所有字符串（携带 JSON Pointer 路径）
+fn walk_config_strings(
+    value: &mut Value,
+    path: &str,
+    visit: &mut dyn FnMut(&mut String, &str) -> Result<(), String>,
 ) -> Result<(), String> {
     match value {
-        Value::String(s) => {
-            *s = replace_config_vars_in_string(s, env_file_vars, path)?;
-        }
+        Value::String(s) => visit(s, path)?,
         Value::Array(arr) => {
             for (idx, item) in arr.iter_mut().enumerate() {
                 let child_path = join_config_path(path, &idx.to_string());
-                replace_config_vars(item, env_file_vars, &child_path)?;
+                walk_config_strings(item, &child_path, visit)?;
             }
         }
         Value::Object(map) => {
             for (key, v) in map.iter_mut() {
                 let child_path = join_config_path(path, &escape_json_pointer_segment(key));
-                replace_config_vars(v, env_file_vars, &child_path)?;
+                walk_config_strings(v, &child_path, visit)?;
             }
         }
         _ => {}
@@ -190,8 +239,13 @@ fn replace_config_vars(
     Ok(())
 }
 
+/// 检查当前 openclaw.json 能否被解析（文件不存在视为有效），用于启动/重启前的预检
+pub(crate) fn check_config_parses() -> Result<(), String> {
+    load_openclaw_config_raw().map(|_| ())
+}
+
 /// 获取 openclaw.json 配置（读取后执行 ${VAR} 替换）
-fn load_openclaw_config() -> Result<Value, String> {
+pub(crate) fn load_openclaw_config() -> Result<Value, String> {
     let mut config = load_openclaw_config_raw()?;
     let env_file_vars = load_env_file_vars();
     replace_config_vars(&mut config, &env_file_vars, "")?;
@@ -223,30 +277,363 @@ fn normalize_and_validate_config(config: &Value) -> Result<Value, String> {
     Ok(config.clone())
 }
 
+/// openclaw.json 写入锁：串行化各命令的“读取-修改-保存”，避免并发调用互相覆盖
+/// Tauri 与 web_server 共用同一份 commands 代码，因此共享这把进程级锁
+static CONFIG_WRITE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// 等待锁文件释放的最长时间
+const CONFIG_LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// 锁文件超过该时长未释放视为持有进程已异常退出
+const CONFIG_LOCK_STALE_AFTER: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// 跨进程的配置写入锁（~/.openclaw/.manager.lock），释放时删除锁文件
+pub(crate) struct ConfigFileLock {
+    path: PathBuf,
+    /// 写入锁文件的持有者标识，释放时据此确认锁文件仍属于自己
+    token: String,
+}
+
+impl Drop for ConfigFileLock {
+    fn drop(&mut self) {
+        // 锁文件可能已被当作过期锁清理并由其他进程重新创建，此时不能删除
+        if fs::read_to_string(&self.path).is_ok_and(|content| content == self.token) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// 进程内递增序号，用于生成唯一的锁持有者标识与临时文件名
+fn next_lock_seq() -> u64 {
+    static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
+/// 清理过期锁：先把锁文件原子地改名，再核对内容是否仍是判定为过期时读到的 observed；
+/// 若改名拿到的是其他进程刚创建的新锁，则用硬链接（目标已存在时失败）放回，不覆盖更新的锁
+fn remove_stale_lock(path: &Path, observed: &str) {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("lock");
+    let aside = path.with_file_name(format!(
+        "{}.stale-{}-{}",
+        name,
+        std::process::id(),
+        next_lock_seq()
+    ));
+    if fs::rename(path, &aside).is_err() {
+        // 已被其他等待者清理
+        return;
+    }
+    if fs::read_to_string(&aside).is_ok_and(|content| content == observed) {
+        warn!("[配置锁] 清理过期锁文件: {}（持有者: {}）", path.display(), observed.trim());
+    } else if let Err(e) = fs::hard_link(&aside, path) {
+        warn!("[配置锁] 放回他人新建的锁文件失败: {}: {}", path.display(), e);
+    }
+    let _ = fs::remove_file(&aside);
+}
+
+/// 锁文件是否已过期（按修改时间判断）
+fn is_stale_lock(path: &Path, stale_after: std::time::Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .map(|age| age > stale_after)
+        .unwrap_or(false)
+}
+
+/// 获取锁文件：以独占方式创建，已存在时轮询等待，过期锁会被清理
+///
+/// 等待期间会阻塞当前线程，异步代码应通过 lock_file_exclusive 在阻塞线程池中调用
+fn acquire_config_file_lock(
+    path: &Path,
+    timeout: std::time::Duration,
+    stale_after: std::time::Duration,
+) -> Result<ConfigFileLock, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(mut file) => {
+                use std::io::Write;
+                let token = format!(
+                    "{} {} {}\n",
+                    std::process::id(),
+                    chrono::Utc::now().to_rfc3339(),
+                    next_lock_seq()
+                );
+                let _ = file.write_all(token.as_bytes());
+                return Ok(ConfigFileLock {
+                    path: path.to_path_buf(),
+                    token,
+                });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                let Ok(holder) = fs::read_to_string(path) else {
+                    // 锁文件刚被释放，立即重试
+                    continue;
+                };
+                if is_stale_lock(path, stale_after) {
+                    remove_stale_lock(path, &holder);
+                    continue;
+                }
+                if std::time::Instant::now() >= deadline {
+                    return Err(format!(
+                        "配置文件正被其他进程修改（锁文件 {}，持有者: {}），请稍后重试",
+                        path.display(),
+                        holder.trim()
+                    ));
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            Err(e) => return Err(format!("创建配置锁文件失败: {}", e)),
+        }
+    }
+}
+
+/// 获取指定路径的跨进程锁文件（与配置写入锁相同的等待与过期策略）
+///
+/// 轮询等待放在阻塞线程池中执行，不占用异步运行时的工作线程
+pub(crate) async fn lock_file_exclusive(path: PathBuf) -> Result<ConfigFileLock, String> {
+    tokio::task::spawn_blocking(move || {
+        acquire_config_file_lock(&path, CONFIG_LOCK_TIMEOUT, CONFIG_LOCK_STALE_AFTER)
+    })
+    .await
+    .map_err(|e| format!("等待配置锁失败: {}", e))?
+}
+
+/// 配置写入守卫：同时持有进程内锁与跨进程锁文件
+pub(crate) struct ConfigWriteGuard {
+    _file_lock: ConfigFileLock,
+    _guard: tokio::sync::MutexGuard<'static, ()>,
+}
+
+/// 获取配置写入锁，持有期间完成整个读取-修改-保存流程
+pub(crate) async fn lock_config_writes() -> Result<ConfigWriteGuard, String> {
+    let guard = CONFIG_WRITE_LOCK.lock().await;
+    let lock_path = PathBuf::from(platform::get_config_lock_path());
+    let file_lock = lock_file_exclusive(lock_path).await?;
+    Ok(ConfigWriteGuard {
+        _file_lock: file_lock,
+        _guard: guard,
+    })
+}
+
 /// 保存 openclaw.json 配置
 fn save_openclaw_config(config: &Value) -> Result<(), String> {
-    let config_path = platform::get_config_file_path();
-
     let normalized = normalize_and_validate_config(config)?;
+    write_openclaw_config(&normalized)
+}
+
+/// 保存按字段原地修改后的配置
+/// 只改动了命令涉及的字段，不对整份文件做强类型校验，其它字段的类型问题不会阻塞修改
+fn save_openclaw_config_edit(config: &Value) -> Result<(), String> {
+    if !config.is_object() {
+        return Err("配置结构无效：顶层必须为对象".to_string());
+    }
+    write_openclaw_config(config)
+}
 
-    let content = serde_json::to_string_pretty(&normalized)
+/// 序列化并写入 openclaw.json
+fn write_openclaw_config(config: &Value) -> Result<(), String> {
+    let config_path = platform::get_config_file_path();
+    let content = serde_json::to_string_pretty(config)
         .map_err(|e| format!("序列化配置失败: {}", e))?;
 
-    file::write_file(&config_path, &content).map_err(|e| format!("写入配置文件失败: {}", e))
+    file::write_file(&config_path, &content).map_err(|e| format!("写入配置文件失败: {}", e))?;
+    note_own_config_write(config_content_version(&content));
+    Ok(())
+}
+
+/// 取得指定路径上的对象，路径上缺失或不是对象的节点替换为空对象
+fn object_at_mut<'a>(
+    config: &'a mut Value,
+    path: &[&str],
+) -> &'a mut serde_json::Map<String, Value> {
+    let mut node = config;
+    for key in path {
+        if !node.is_object() {
+            *node = json!({});
+        }
+        node = node
+            .as_object_mut()
+            .expect("节点已确保为对象")
+            .entry(key.to_string())
+            .or_insert_with(|| json!({}));
+    }
+    if !node.is_object() {
+        *node = json!({});
+    }
+    node.as_object_mut().expect("节点已确保为对象")
+}
+
+/// 更新 meta.lastTouchedAt
+fn touch_config_meta(config: &mut Value) {
+    object_at_mut(config, &["meta"])
+        .insert("lastTouchedAt".to_string(), json!(chrono::Utc::now().to_rfc3339()));
+}
+
+/// 将原始 Value 转为强类型配置（未建模字段保存在各层 extra 中）
+fn typed_config_from_value(config: Value) -> Result<OpenClawConfig, String> {
+    serde_json::from_value::<OpenClawConfig>(config)
+        .map_err(|e| format!("配置结构无效（请检查字段类型）: {}", e))
+}
+
+/// 带版本号的完整配置，保存时回传 version 用于检测并发修改
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionedConfig {
+    pub config: Value,
+    /// 读取时磁盘内容的哈希
+    pub version: String,
+}
+
+/// 配置冲突错误前缀（web_server 据此返回 409）
+pub(crate) const CONFIG_CONFLICT_ERROR: &str = "配置冲突";
+
+/// 获取 openclaw.json 的 JSON Schema（供前端生成表单与校验）
+#[command]
+pub async fn get_config_schema() -> Result<Value, String> {
+    Ok(crate::models::openclaw_config_schema())
 }
 
 /// 获取完整配置
 #[command]
-pub async fn get_config() -> Result<Value, String> {
+pub async fn get_config() -> Result<VersionedConfig, String> {
     info!("[获取配置] 读取 openclaw.json 配置...");
-    let result = load_openclaw_config().and_then(|config| normalize_and_validate_config(&config));
+    let result = load_openclaw_config_raw_versioned().and_then(|(mut config, version)| {
+        replace_config_vars(&mut config, &load_env_file_vars(), "")?;
+        Ok(VersionedConfig {
+            config: normalize_and_validate_config(&config)?,
+            version,
+        })
+    });
     match &result {
-        Ok(_) => info!("[获取配置] ✓ 配置读取成功"),
+        Ok(versioned) => info!("[获取配置] ✓ 配置读取成功 (版本 {})", versioned.version),
         Err(e) => error!("[获取配置] ✗ 配置读取失败: {}", e),
     }
     result
 }
 
+/// 配置变量来源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigVarSource {
+    /// 进程环境变量（优先级最高）
+    ProcessEnv,
+    /// ~/.openclaw/env 文件
+    EnvFile,
+    /// 未找到，保留 ${VAR} 原样
+    Unresolved,
+}
+
+/// 单个 ${VAR} 的解析结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigVarResolution {
+    pub name: String,
+    /// 引用该变量的配置路径（JSON Pointer）
+    pub path: String,
+    pub source: ConfigVarSource,
+}
+
+/// 最终生效的配置（Gateway 实际看到的内容）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveConfig {
+    pub config: Value,
+    pub variables: Vec<ConfigVarResolution>,
+    /// 因缺失而补上默认值的字段路径
+    pub defaults_applied: Vec<String>,
+}
+
+/// 宽松模式变量替换：缺失变量保留原样，并记录每个变量的来源
+fn resolve_config_vars_with_sources(
+    value: &mut Value,
+    env_file_vars: &HashMap<String, String>,
+) -> Result<Vec<ConfigVarResolution>, String> {
+    let mut variables = Vec::new();
+    walk_config_strings(value, "", &mut |s, path| {
+        *s = substitute_config_vars_in_string(s, path, &mut |name| {
+            let (source, resolved) = match std::env::var(name) {
+                Ok(v) => (ConfigVarSource::ProcessEnv, v),
+                Err(_) => match env_file_vars.get(name) {
+                    Some(v) => (ConfigVarSource::EnvFile, v.clone()),
+                    None => (ConfigVarSource::Unresolved, format!("${{{}}}", name)),
+                },
+            };
+            variables.push(ConfigVarResolution {
+                name: name.to_string(),
+                path: format_config_path(path),
+                source,
+            });
+            Some(resolved)
+        })?;
+        Ok(())
+    })?;
+    Ok(variables)
+}
+
+/// 补齐 Gateway 使用的默认值，返回补齐的字段路径
+fn apply_effective_defaults(config: &mut Value) -> Vec<String> {
+    let mut applied = Vec::new();
+    if !config.is_object() {
+        *config = json!({});
+    }
+    if !config.get("gateway").is_some_and(|v| v.is_object()) {
+        config["gateway"] = json!({});
+    }
+    if config.pointer("/gateway/port").is_none() {
+        config["gateway"]["port"] = json!(DEFAULT_GATEWAY_PORT);
+        applied.push("/gateway/port".to_string());
+    }
+    applied
+}
+
+/// 获取最终生效的配置：变量已替换（缺失变量保留原样）、默认值已补齐，并列出各变量来源
+#[command]
+pub async fn get_effective_config() -> Result<EffectiveConfig, String> {
+    info!("[生效配置] 解析最终生效配置...");
+    let mut config = load_openclaw_config_raw()?;
+    let variables = resolve_config_vars_with_sources(&mut config, &load_env_file_vars())?;
+    let defaults_applied = apply_effective_defaults(&mut config);
+    let config = normalize_and_validate_config(&config)?;
+
+    let unresolved: Vec<&str> = variables
+        .iter()
+        .filter(|v| v.source == ConfigVarSource::Unresolved)
+        .map(|v| v.name.as_str())
+        .collect();
+    if !unresolved.is_empty() {
+        warn!("[生效配置] 未解析的变量: {:?}", unresolved);
+    }
+    info!(
+        "[生效配置] ✓ {} 个变量引用，{} 个默认值",
+        variables.len(),
+        defaults_applied.len()
+    );
+    Ok(EffectiveConfig {
+        config,
+        variables,
+        defaults_applied,
+    })
+}
+
+/// 校验调用方读取时的版本号与磁盘内容一致，不一致说明配置已被其他窗口或进程修改
+fn ensure_config_version(expected_version: Option<&str>) -> Result<(), String> {
+    let Some(expected) = expected_version else {
+        return Ok(());
+    };
+    let current = config_content_version(&read_openclaw_config_content()?.unwrap_or_default());
+    if current == expected {
+        Ok(())
+    } else {
+        warn!("[保存配置] 版本不一致: 期望 {}，当前 {}", expected, current);
+        Err(format!(
+            "{}：配置文件已被其他窗口或进程修改，请重新加载后再保存",
+            CONFIG_CONFLICT_ERROR
+        ))
+    }
+}
+
 /// 合并 gateway 关键字段，避免保存配置时误丢失关键网络参数
 fn merge_gateway_critical_fields(target: &mut Value, source: &Value) {
     let Some(source_gateway) = source.get("gateway").and_then(|v| v.as_object()) else {
@@ -270,6 +657,98 @@ fn merge_gateway_critical_fields(target: &mut Value, source: &Value) {
     }
 }
 
+/// gateway.bind 支持的绑定模式关键字
+const GATEWAY_BIND_MODES: &[&str] = &["loopback", "lan", "tailnet", "auto", "custom"];
+
+/// 校验 IP 或 CIDR（如 10.0.0.0/8、fd00::/8）
+fn is_valid_ip_or_cidr(value: &str) -> bool {
+    match value.split_once('/') {
+        Some((ip, prefix)) => match (ip.parse::<std::net::IpAddr>(), prefix.parse::<u8>()) {
+            (Ok(std::net::IpAddr::V4(_)), Ok(prefix)) => prefix <= 32,
+            (Ok(std::net::IpAddr::V6(_)), Ok(prefix)) => prefix <= 128,
+            _ => false,
+        },
+        None => value.parse::<std::net::IpAddr>().is_ok(),
+    }
+}
+
+/// 校验主机名（字母、数字、- 组成的点分标签）
+fn is_valid_hostname(host: &str) -> bool {
+    !host.is_empty()
+        && host.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// 校验 gateway.bind：绑定模式关键字、localhost、IP 或 host:port
+fn is_valid_gateway_bind(bind: &str) -> bool {
+    if GATEWAY_BIND_MODES.contains(&bind) || bind == "localhost" {
+        return true;
+    }
+    let unbracketed = bind.trim_start_matches('[').trim_end_matches(']');
+    if unbracketed.parse::<std::net::IpAddr>().is_ok() {
+        return true;
+    }
+    match bind.rsplit_once(':') {
+        Some((host, port)) => {
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            let host_ok = host.parse::<std::net::IpAddr>().is_ok() || is_valid_hostname(host);
+            host_ok && port.parse::<u16>().is_ok_and(|port| port != 0)
+        }
+        None => false,
+    }
+}
+
+/// 校验 gateway 网络字段（bind / trustedProxies / port），返回全部问题
+fn validate_gateway_network_fields(config: &Value) -> Result<(), String> {
+    let Some(gateway) = config.get("gateway") else {
+        return Ok(());
+    };
+    let mut issues = Vec::new();
+
+    if let Some(port) = gateway.get("port") {
+        if !port.as_u64().is_some_and(|port| (1..=65535).contains(&port)) {
+            issues.push(format!("gateway.port 必须是 1-65535 之间的整数（当前: {}）", port));
+        }
+    }
+
+    if let Some(bind) = gateway.get("bind") {
+        match bind.as_str() {
+            Some(value) if is_valid_gateway_bind(value.trim()) => {}
+            _ => issues.push(format!(
+                "gateway.bind 必须是 {}、IP 地址或 host:port（当前: {}）",
+                GATEWAY_BIND_MODES.join("/"),
+                bind
+            )),
+        }
+    }
+
+    if let Some(proxies) = gateway.get("trustedProxies") {
+        match proxies.as_array() {
+            Some(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    if !item.as_str().is_some_and(|v| is_valid_ip_or_cidr(v.trim())) {
+                        issues.push(format!(
+                            "gateway.trustedProxies[{}] 必须是 IP 或 CIDR（当前: {}）",
+                            idx, item
+                        ));
+                    }
+                }
+            }
+            None => issues.push(format!(
+                "gateway.trustedProxies 必须是 IP/CIDR 字符串数组（当前: {}）",
+                proxies
+            )),
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("gateway 配置无效: {}", issues.join("; ")))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigValidationIssue {
     pub path: String,
@@ -316,6 +795,8 @@ pub struct PreviewConfigResponse {
 pub struct ApplyConfigResponse {
     pub backup_path: String,
     pub applied_at: String,
+    /// 写入后的配置版本，作为下一次应用的 expected_version
+    pub version: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -368,7 +849,28 @@ fn is_sensitive_key(key: &str) -> bool {
     .any(|keyword| lower.contains(keyword))
 }
 
-fn redact_sensitive_value(value: &Value) -> Value {
+/// 密钥展示时最多保留的首尾字符数
+const MASK_MAX_PREFIX: usize = 3;
+const MASK_MAX_SUFFIX: usize = 2;
+/// 明文字符最多占密钥长度的 1/MASK_REVEAL_DIVISOR，过短的密钥完全隐藏
+const MASK_REVEAL_DIVISOR: usize = 5;
+
+/// 脱敏展示密钥：始终隐藏中间部分，最多显示前 3 位与后 2 位
+pub(crate) fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    let budget = (chars.len() / MASK_REVEAL_DIVISOR).min(MASK_MAX_PREFIX + MASK_MAX_SUFFIX);
+    if budget < 2 {
+        return "****".to_string();
+    }
+
+    let suffix_len = (budget / 2).min(MASK_MAX_SUFFIX);
+    let prefix_len = (budget - suffix_len).min(MASK_MAX_PREFIX);
+    let prefix: String = chars[..prefix_len].iter().collect();
+    let suffix: String = chars[chars.len() - suffix_len..].iter().collect();
+    format!("{}...{}", prefix, suffix)
+}
+
+pub(crate) fn redact_sensitive_value(value: &Value) -> Value {
     match value {
         Value::Object(obj) => {
             let mut next = serde_json::Map::new();
@@ -700,8 +1202,14 @@ fn resolve_backup_path(backup_path: &str) -> PathBuf {
     }
 }
 /// 保存配置
+/// expected_version 为 get_config 返回的版本号，传入时若磁盘内容已变化则拒绝保存
 #[command]
-pub async fn save_config(mut config: Value) -> Result<String, String> {
+pub async fn save_config(
+    mut config: Value,
+    expected_version: Option<String>,
+) -> Result<String, String> {
+    let _config_guard = lock_config_writes().await?;
+    ensure_config_version(expected_version.as_deref())?;
 
     info!("[保存配置] 保存 openclaw.json 配置...");
     debug!("[保存配置] 请求包含字段: {}", config.as_object().map(|o| o.len()).unwrap_or(0));
@@ -713,6 +1221,7 @@ pub async fn save_config(mut config: Value) -> Result<String, String> {
     if let Ok(existing) = load_openclaw_config_raw() {
         merge_gateway_critical_fields(&mut config, &existing);
     }
+    validate_gateway_network_fields(&config)?;
 
     match save_openclaw_config(&config) {
         Ok(_) => {
@@ -742,8 +1251,13 @@ pub async fn preview_config_change(input_config: Value) -> Result<PreviewConfigR
 }
 
 #[command]
-pub async fn apply_config_change(input_config: Value) -> Result<ApplyConfigResponse, String> {
+pub async fn apply_config_change(
+    input_config: Value,
+    expected_version: Option<String>,
+) -> Result<ApplyConfigResponse, String> {
     info!("[配置应用] 开始应用配置变更...");
+    let _config_guard = lock_config_writes().await?;
+    ensure_config_version(expected_version.as_deref())?;
 
     let validation = validate_preview_input(&input_config);
     if !validation.valid {
@@ -764,10 +1278,12 @@ pub async fn apply_config_change(input_config: Value) -> Result<ApplyConfigRespo
 
     let backup_path = write_backup_snapshot(&existing_config)?;
     save_openclaw_config(&next_config)?;
+    let version = config_content_version(&read_openclaw_config_content()?.unwrap_or_default());
 
     Ok(ApplyConfigResponse {
         backup_path,
         applied_at: format_now_rfc3339(),
+        version,
     })
 }
 
@@ -788,6 +1304,7 @@ pub async fn list_config_backups() -> Result<Vec<ConfigBackupItem>, String> {
 #[command]
 pub async fn rollback_config(backup_path: Option<String>) -> Result<RollbackConfigResponse, String> {
     info!("[配置回滚] 开始回滚配置...");
+    let _config_guard = lock_config_writes().await?;
 
     let selected_backup = if let Some(path) = backup_path {
         if path.trim().is_empty() {
@@ -841,6 +1358,7 @@ pub async fn get_agents_list() -> Result<Value, String> {
 #[command]
 pub async fn save_agents_list(agents_list: Value) -> Result<String, String> {
     info!("[Agents List] 保存 agents.list...");
+    let _config_guard = lock_config_writes().await?;
 
     // 显式校验：要求数组结构，便于前端定位错误
     if !agents_list.is_array() {
@@ -860,177 +1378,1331 @@ pub async fn save_agents_list(agents_list: Value) -> Result<String, String> {
     Ok("agents.list 已保存".to_string())
 }
 
-/// 获取 bindings（向后兼容：不存在时返回 []）
-#[command]
-pub async fn get_bindings() -> Result<Value, String> {
-    info!("[Bindings] 获取 bindings...");
-    let config = load_openclaw_config_raw()?;
+/// 在 agents.list 中按 id 查找 Agent 下标
+fn find_agent_index(config: &Value, agent_id: &str) -> Option<usize> {
+    config
+        .pointer("/agents/list")
+        .and_then(|v| v.as_array())?
+        .iter()
+        .position(|agent| agent.get("id").and_then(|v| v.as_str()) == Some(agent_id))
+}
 
-    let typed: OpenClawConfig = serde_json::from_value(config)
-        .map_err(|e| format!("bindings 结构无效（应为数组或对象）: {}", e))?;
+/// 写入单个 Agent 的模型覆盖（结构与 agents.defaults.model 一致：{ primary, fallbacks }）
+/// - primary 为空：移除该 Agent 的 model 字段，回退到 agents.defaults.model
+/// - 所有模型必须已存在于 agents.defaults.models
+fn apply_agent_model(
+    config: &mut Value,
+    agent_id: &str,
+    primary: Option<&str>,
+    fallbacks: &[String],
+) -> Result<(), String> {
+    let index = find_agent_index(config, agent_id)
+        .ok_or_else(|| format!("Agent 不存在: {}", agent_id))?;
 
-    Ok(typed
-        .bindings
-        .as_ref()
-        .map(|b| b.as_value())
-        .unwrap_or_else(|| json!([])))
-}
+    let primary = primary.map(|s| s.trim()).filter(|s| !s.is_empty());
+    let Some(primary) = primary else {
+        if let Some(agent) = config["agents"]["list"][index].as_object_mut() {
+            agent.remove("model");
+        }
+        return Ok(());
+    };
 
-/// 保存 bindings（全量写入）
-#[command]
-pub async fn save_bindings(bindings: Value) -> Result<String, String> {
-    info!("[Bindings] 保存 bindings...");
+    let available = config
+        .pointer("/agents/defaults/models")
+        .and_then(|v| v.as_object());
+    for model_id in std::iter::once(primary).chain(fallbacks.iter().map(|s| s.as_str())) {
+        if !available.map(|m| m.contains_key(model_id)).unwrap_or(false) {
+            return Err(format!("模型 {} 不在可用模型列表中", model_id));
+        }
+    }
 
-    // 显式校验：仅接受数组或对象
-    if !bindings.is_array() && !bindings.is_object() {
-        return Err("bindings 结构无效：必须为数组或对象".to_string());
+    let mut model_obj = json!({ "primary": primary });
+    if !fallbacks.is_empty() {
+        model_obj["fallbacks"] = json!(fallbacks);
     }
+    config["agents"]["list"][index]["model"] = model_obj;
+    Ok(())
+}
 
-    let mut config = load_openclaw_config_raw()?;
+/// 获取单个 Agent
+#[command]
+pub async fn get_agent(agent_id: String) -> Result<Value, String> {
+    info!("[Agent] 获取 Agent: {}", agent_id);
+    let config = load_openclaw_config_raw()?;
 
-    // 使用强类型做一次转换校验，返回更清晰错误语义
-    let typed_bindings: BindingsConfig = serde_json::from_value(bindings)
-        .map_err(|e| format!("bindings 结构无效：{}", e))?;
+    let index = find_agent_index(&config, &agent_id)
+        .ok_or_else(|| format!("Agent 不存在: {}", agent_id))?;
+    Ok(config["agents"]["list"][index].clone())
+}
 
-    config["bindings"] = typed_bindings.into_value();
+/// 设置单个 Agent 的模型覆盖（primary 为空时恢复继承默认模型）
+#[command]
+pub async fn set_agent_model(
+    agent_id: String,
+    primary: Option<String>,
+    fallbacks: Option<Vec<String>>,
+) -> Result<String, String> {
+    info!("[Agent] 设置 Agent {} 模型: {:?}", agent_id, primary);
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+    apply_agent_model(
+        &mut config,
+        &agent_id,
+        primary.as_deref(),
+        &fallbacks.unwrap_or_default(),
+    )?;
     save_openclaw_config(&config)?;
 
-    info!("[Bindings] ✓ bindings 保存成功");
-    Ok("bindings 已保存".to_string())
+    info!("[Agent] ✓ Agent {} 模型已更新", agent_id);
+    Ok(format!("Agent {} 模型已更新", agent_id))
 }
 
-/// 获取环境变量值
-#[command]
-pub async fn get_env_value(key: String) -> Result<Option<String>, String> {
-    info!("[获取环境变量] 读取环境变量: {}", key);
-    let env_path = platform::get_env_file_path();
-    let value = file::read_env_value(&env_path, &key);
-    match &value {
-        Some(v) => debug!(
-            "[获取环境变量] {}={} (已脱敏)",
-            key,
-            if v.len() > 8 { "***" } else { v }
-        ),
-        None => debug!("[获取环境变量] {} 不存在", key),
+/// 确保 agents.list 数组存在
+fn ensure_agents_list(config: &mut Value) -> Result<&mut Vec<Value>, String> {
+    if config.get("agents").and_then(|v| v.as_object()).is_none() {
+        config["agents"] = json!({});
     }
-    Ok(value)
+    if config["agents"].get("list").is_none() {
+        config["agents"]["list"] = json!([]);
+    }
+    config["agents"]["list"]
+        .as_array_mut()
+        .ok_or_else(|| "agents.list 结构无效：必须为数组".to_string())
 }
 
-/// 保存环境变量值
-#[command]
-pub async fn save_env_value(key: String, value: String) -> Result<String, String> {
-    info!("[保存环境变量] 保存环境变量: {}", key);
-    let env_path = platform::get_env_file_path();
-    debug!("[保存环境变量] 环境文件路径: {}", env_path);
-    
-    match file::set_env_value(&env_path, &key, &value) {
-        Ok(_) => {
-            info!("[保存环境变量] ✓ 环境变量 {} 保存成功", key);
-            Ok("环境变量已保存".to_string())
-        }
-        Err(e) => {
-            error!("[保存环境变量] ✗ 保存失败: {}", e);
-            Err(format!("保存环境变量失败: {}", e))
-        }
+/// 新增 Agent（id 必填且不可重复）
+fn insert_agent(config: &mut Value, agent: AgentEntry) -> Result<String, String> {
+    let agent_id = agent
+        .id
+        .as_deref()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| "Agent id 不能为空".to_string())?
+        .to_string();
+
+    if find_agent_index(config, &agent_id).is_some() {
+        return Err(format!("Agent 已存在: {}", agent_id));
     }
+
+    let agent_value = serde_json::to_value(AgentEntry {
+        id: Some(agent_id.clone()),
+        ..agent
+    })
+    .map_err(|e| format!("Agent 序列化失败: {}", e))?;
+    ensure_agents_list(config)?.push(agent_value);
+    Ok(agent_id)
 }
 
-// ============ Gateway Token 命令 ============
+/// 更新 Agent：强类型字段按传入值整体替换，extra 未知字段与原值合并（传入优先）
+fn replace_agent(config: &mut Value, agent_id: &str, agent: AgentEntry) -> Result<(), String> {
+    let index = find_agent_index(config, agent_id)
+        .ok_or_else(|| format!("Agent 不存在: {}", agent_id))?;
 
-/// 生成随机 token
-fn generate_token() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_nanos();
-    
-    // 使用时间戳和随机数生成 token
-    let random_part: u64 = (timestamp as u64) ^ 0x5DEECE66Du64;
-    format!("{:016x}{:016x}{:016x}", 
-        random_part, 
-        random_part.wrapping_mul(0x5DEECE66Du64),
-        timestamp as u64
-    )
-}
+    if let Some(new_id) = agent.id.as_deref() {
+        if new_id != agent_id {
+            return Err(format!("不允许修改 Agent id: {} -> {}", agent_id, new_id));
+        }
+    }
 
-/// 获取或生成 Gateway Token
+    let existing: AgentEntry = serde_json::from_value(config["agents"]["list"][index].clone())
+        .map_err(|e| format!("Agent {} 结构无效: {}", agent_id, e))?;
+    let mut extra = existing.extra;
+    extra.extend(agent.extra);
+
+    config["agents"]["list"][index] = serde_json::to_value(AgentEntry {
+        id: Some(agent_id.to_string()),
+        extra,
+        ..agent
+    })
+    .map_err(|e| format!("Agent 序列化失败: {}", e))?;
+    Ok(())
+}
+
+/// 删除 Agent，并清理指向它的 bindings 与渠道账号 agentId
+fn remove_agent(config: &mut Value, agent_id: &str) -> Result<(), String> {
+    let index = find_agent_index(config, agent_id)
+        .ok_or_else(|| format!("Agent 不存在: {}", agent_id))?;
+    ensure_agents_list(config)?.remove(index);
+
+    if let Some(bindings) = config.get("bindings") {
+        config["bindings"] = retain_bindings_by_agent(bindings, false, |bound| bound != agent_id);
+    }
+
+    if let Some(channels) = config.get_mut("channels").and_then(|v| v.as_object_mut()) {
+        for channel in channels.values_mut() {
+            let Some(accounts) = channel.get_mut("accounts").and_then(|v| v.as_object_mut()) else {
+                continue;
+            };
+            for account in accounts.values_mut() {
+                let Some(account_obj) = account.as_object_mut() else {
+                    continue;
+                };
+                if account_obj.get("agentId").and_then(|v| v.as_str()) == Some(agent_id) {
+                    account_obj.remove("agentId");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 新增单个 Agent
 #[command]
-pub async fn get_or_create_gateway_token() -> Result<String, String> {
-    info!("[Gateway Token] 获取或创建 Gateway Token...");
-    
+pub async fn add_agent(agent: AgentEntry) -> Result<String, String> {
+    info!("[Agent] 新增 Agent: {:?}", agent.id);
+    let _config_guard = lock_config_writes().await?;
+
     let mut config = load_openclaw_config_raw()?;
+    let agent_id = insert_agent(&mut config, agent)?;
+    save_openclaw_config(&config)?;
 
-    // 检查是否已有 token
-    if let Some(token) = config
-        .pointer("/gateway/auth/token")
-        .and_then(|v| v.as_str())
-    {
-        if !token.is_empty() {
-            info!("[Gateway Token] ✓ 使用现有 Token");
-            return Ok(token.to_string());
-        }
+    info!("[Agent] ✓ Agent {} 已新增", agent_id);
+    Ok(format!("Agent {} 已新增", agent_id))
+}
+
+/// 更新单个 Agent（保留未知字段）
+#[command]
+pub async fn update_agent(agent_id: String, agent: AgentEntry) -> Result<String, String> {
+    info!("[Agent] 更新 Agent: {}", agent_id);
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+    replace_agent(&mut config, &agent_id, agent)?;
+    save_openclaw_config(&config)?;
+
+    info!("[Agent] ✓ Agent {} 已更新", agent_id);
+    Ok(format!("Agent {} 已更新", agent_id))
+}
+
+/// 删除单个 Agent（同时清理相关 bindings）
+#[command]
+pub async fn delete_agent(agent_id: String) -> Result<String, String> {
+    info!("[Agent] 删除 Agent: {}", agent_id);
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+    remove_agent(&mut config, &agent_id)?;
+    save_openclaw_config(&config)?;
+
+    info!("[Agent] ✓ Agent {} 已删除", agent_id);
+    Ok(format!("Agent {} 已删除", agent_id))
+}
+
+/// 获取 bindings（向后兼容：不存在时返回 []）
+#[command]
+pub async fn get_bindings() -> Result<Value, String> {
+    info!("[Bindings] 获取 bindings...");
+    let config = load_openclaw_config_raw()?;
+
+    let typed: OpenClawConfig = serde_json::from_value(config)
+        .map_err(|e| format!("bindings 结构无效（应为数组或对象）: {}", e))?;
+
+    Ok(typed
+        .bindings
+        .as_ref()
+        .map(|b| b.as_value())
+        .unwrap_or_else(|| json!([])))
+}
+
+/// 保存 bindings（全量写入）
+#[command]
+pub async fn save_bindings(bindings: Value) -> Result<String, String> {
+    info!("[Bindings] 保存 bindings...");
+    let _config_guard = lock_config_writes().await?;
+
+    // 显式校验：仅接受数组或对象
+    if !bindings.is_array() && !bindings.is_object() {
+        return Err("bindings 结构无效：必须为数组或对象".to_string());
     }
+
+    let mut config = load_openclaw_config_raw()?;
+
+    // 使用强类型做一次转换校验，返回更清晰错误语义
+    let typed_bindings: BindingsConfig = serde_json::from_value(bindings)
+        .map_err(|e| format!("bindings 结构无效：{}", e))?;
+
+    config["bindings"] = typed_bindings.into_value();
+    save_openclaw_config(&config)?;
+
+    info!("[Bindings] ✓ bindings 保存成功");
+    Ok("bindings 已保存".to_string())
+}
+
+/// 获取环境变量值
+#[command]
+pub async fn get_env_value(key: String) -> Result<Option<String>, String> {
+    info!("[获取环境变量] 读取环境变量: {}", key);
+    let env_path = platform::get_env_file_path();
+    let value = file::read_env_value(&env_path, &key);
+    match &value {
+        Some(v) => debug!("[获取环境变量] {}={} (已脱敏)", key, mask_secret(v)),
+        None => debug!("[获取环境变量] {} 不存在", key),
+    }
+    Ok(value)
+}
+
+/// 保存环境变量值
+#[command]
+pub async fn save_env_value(key: String, value: String) -> Result<String, String> {
+    info!("[保存环境变量] 保存环境变量: {}", key);
+    let env_path = platform::get_env_file_path();
+    debug!("[保存环境变量] 环境文件路径: {}", env_path);
+    file::validate_env_entry(&key, &value)?;
     
-    // 生成新 token
-    let new_token = generate_token();
-    info!("[Gateway Token] 生成新 Token: {}...", &new_token[..8]);
-    
-    // 确保路径存在
-    if config.get("gateway").is_none() {
-        config["gateway"] = json!({});
+    match file::set_env_value(&env_path, &key, &value) {
+        Ok(_) => {
+            info!("[保存环境变量] ✓ 环境变量 {} 保存成功", key);
+            Ok("环境变量已保存".to_string())
+        }
+        Err(e) => {
+            error!("[保存环境变量] ✗ 保存失败: {}", e);
+            Err(format!("保存环境变量失败: {}", e))
+        }
+    }
+}
+
+/// 批量获取环境变量值（只读取一次 env 文件；不做脱敏，由调用方决定如何展示）
+#[command]
+pub async fn get_env_values(keys: Vec<String>) -> Result<HashMap<String, Option<String>>, String> {
+    info!("[获取环境变量] 批量读取 {} 个环境变量", keys.len());
+    Ok(file::read_env_values(&platform::get_env_file_path(), &keys))
+}
+
+/// 批量保存环境变量值（一次原子写入 env 文件）
+#[command]
+pub async fn save_env_values(values: HashMap<String, String>) -> Result<String, String> {
+    let mut values: Vec<(String, String)> = values.into_iter().collect();
+    values.sort();
+    let keys: Vec<&str> = values.iter().map(|(key, _)| key.as_str()).collect();
+    info!("[保存环境变量] 批量保存环境变量: {}", keys.join(", "));
+    for (key, value) in &values {
+        file::validate_env_entry(key, value)?;
     }
-    if config["gateway"].get("auth").is_none() {
-        config["gateway"]["auth"] = json!({});
+
+    match file::set_env_values(&platform::get_env_file_path(), &values) {
+        Ok(_) => {
+            info!("[保存环境变量] ✓ 已保存 {} 个环境变量", values.len());
+            Ok(format!("已保存 {} 个环境变量", values.len()))
+        }
+        Err(e) => {
+            error!("[保存环境变量] ✗ 保存失败: {}", e);
+            Err(format!("保存环境变量失败: {}", e))
+        }
     }
-    
-    // 设置 token 和 mode
-    config["gateway"]["auth"]["token"] = json!(new_token);
-    config["gateway"]["auth"]["mode"] = json!("token");
-    config["gateway"]["mode"] = json!("local");
-    
-    // 保存配置
-    save_openclaw_config(&config)?;
-    
-    info!("[Gateway Token] ✓ Token 已保存到配置");
-    Ok(new_token)
 }
 
-/// 获取 Dashboard URL（带 token）
+/// 环境变量条目（仅返回脱敏后的预览，不返回明文）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvKeyEntry {
+    pub key: String,
+    /// 脱敏预览
+    pub masked_value: String,
+}
+
+/// 列出 env 文件中的全部变量名（按名称排序）
 #[command]
-pub async fn get_dashboard_url() -> Result<String, String> {
-    info!("[Dashboard URL] 获取 Dashboard URL...");
+pub async fn get_env_keys() -> Result<Vec<EnvKeyEntry>, String> {
+    info!("[获取环境变量] 列出环境变量...");
+    let mut entries: Vec<EnvKeyEntry> = load_env_file_vars()
+        .into_iter()
+        .map(|(key, value)| EnvKeyEntry {
+            key,
+            masked_value: mask_secret(&value),
+        })
+        .collect();
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    info!("[获取环境变量] ✓ 共 {} 个环境变量", entries.len());
+    Ok(entries)
+}
+
+/// 删除环境变量
+#[command]
+pub async fn remove_env_key(key: String) -> Result<String, String> {
+    info!("[删除环境变量] 删除环境变量: {}", key);
+    if !load_env_file_vars().contains_key(&key) {
+        return Err(format!("环境变量不存在: {}", key));
+    }
+
+    let env_path = platform::get_env_file_path();
+    file::remove_env_value(&env_path, &key).map_err(|e| {
+        error!("[删除环境变量] ✗ 删除失败: {}", e);
+        format!("删除环境变量失败: {}", e)
+    })?;
+    info!("[删除环境变量] ✓ 环境变量 {} 已删除", key);
+    Ok("环境变量已删除".to_string())
+}
+
+// ============ 配置包导入导出 ============
+
+/// 配置包格式版本，导入时必须一致
+const CONFIG_BUNDLE_VERSION: u32 = 1;
+const BUNDLE_MANIFEST_FILE: &str = "manifest.json";
+const BUNDLE_CONFIG_FILE: &str = "openclaw.json";
+const BUNDLE_ENV_FILE: &str = "env";
+
+/// 配置包清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundleManifest {
+    pub version: u32,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "managerVersion")]
+    pub manager_version: String,
+    #[serde(rename = "includesSecrets")]
+    pub includes_secrets: bool,
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportConfigBundleResponse {
+    pub restored: Vec<String>,
+    pub backup_paths: Vec<String>,
+    pub manifest: ConfigBundleManifest,
+}
+
+/// 移除敏感字段（${VAR} 占位符不属于明文密钥，予以保留）
+fn strip_sensitive_value(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut next = serde_json::Map::new();
+            for (key, nested) in obj {
+                let is_secret = is_sensitive_key(key)
+                    && nested.as_str().is_some_and(|s| !s.contains("${"));
+                if !is_secret {
+                    next.insert(key.clone(), strip_sensitive_value(nested));
+                }
+            }
+            Value::Object(next)
+        }
+        Value::Array(arr) => Value::Array(arr.iter().map(strip_sensitive_value).collect()),
+        _ => value.clone(),
+    }
+}
+
+/// 移除 env 文件中的敏感变量行
+fn strip_sensitive_env(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            let assignment = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+            match assignment.split_once('=') {
+                Some((key, _)) if !trimmed.starts_with('#') => !is_sensitive_key(key.trim()),
+                _ => true,
+            }
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn append_bundle_file(
+    builder: &mut tar::Builder<Vec<u8>>,
+    name: &str,
+    content: &[u8],
+) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, name, content)
+        .map_err(|e| format!("写入配置包 {} 失败: {}", name, e))
+}
+
+/// 打包配置（tar 格式）：openclaw.json、env 与 manifest.json
+fn build_config_bundle(
+    config: &Value,
+    env_content: Option<&str>,
+    include_secrets: bool,
+) -> Result<Vec<u8>, String> {
+    let config = if include_secrets {
+        config.clone()
+    } else {
+        strip_sensitive_value(config)
+    };
+
+    let mut files = vec![BUNDLE_CONFIG_FILE.to_string()];
+    if env_content.is_some() {
+        files.push(BUNDLE_ENV_FILE.to_string());
+    }
+    let manifest = ConfigBundleManifest {
+        version: CONFIG_BUNDLE_VERSION,
+        created_at: format_now_rfc3339(),
+        manager_version: env!("CARGO_PKG_VERSION").to_string(),
+        includes_secrets: include_secrets,
+        files,
+    };
+
+    let mut builder = tar::Builder::new(Vec::new());
+    let manifest_content = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("序列化配置包清单失败: {}", e))?;
+    append_bundle_file(&mut builder, BUNDLE_MANIFEST_FILE, manifest_content.as_bytes())?;
+
+    let config_content = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("序列化配置失败: {}", e))?;
+    append_bundle_file(&mut builder, BUNDLE_CONFIG_FILE, config_content.as_bytes())?;
+
+    if let Some(env_content) = env_content {
+        let env_content = if include_secrets {
+            env_content.to_string()
+        } else {
+            strip_sensitive_env(env_content)
+        };
+        append_bundle_file(&mut builder, BUNDLE_ENV_FILE, env_content.as_bytes())?;
+    }
+
+    builder
+        .into_inner()
+        .map_err(|e| format!("生成配置包失败: {}", e))
+}
+
+/// 解析配置包，校验清单版本与配置结构
+fn read_config_bundle(
+    bytes: &[u8],
+) -> Result<(ConfigBundleManifest, Value, Option<String>), String> {
+    let mut archive = tar::Archive::new(bytes);
+    let mut contents: HashMap<String, String> = HashMap::new();
+
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("配置包格式无效: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("配置包格式无效: {}", e))?;
+        let name = entry
+            .path()
+            .map_err(|e| format!("配置包格式无效: {}", e))?
+            .to_string_lossy()
+            .to_string();
+        if ![BUNDLE_MANIFEST_FILE, BUNDLE_CONFIG_FILE, BUNDLE_ENV_FILE].contains(&name.as_str()) {
+            continue;
+        }
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut entry, &mut content)
+            .map_err(|e| format!("读取配置包 {} 失败: {}", name, e))?;
+        contents.insert(name, content);
+    }
+
+    let manifest_content = contents
+        .get(BUNDLE_MANIFEST_FILE)
+        .ok_or_else(|| "配置包缺少 manifest.json".to_string())?;
+    let manifest: ConfigBundleManifest = serde_json::from_str(manifest_content)
+        .map_err(|e| format!("配置包清单无效: {}", e))?;
+    if manifest.version != CONFIG_BUNDLE_VERSION {
+        return Err(format!(
+            "配置包版本不兼容: {}（当前支持 {}）",
+            manifest.version, CONFIG_BUNDLE_VERSION
+        ));
+    }
+
+    let config_content = contents
+        .get(BUNDLE_CONFIG_FILE)
+        .ok_or_else(|| "配置包缺少 openclaw.json".to_string())?;
+    let config = parse_openclaw_config_content(config_content)?;
+    normalize_and_validate_config(&config)?;
+
+    let env_content = contents.remove(BUNDLE_ENV_FILE);
+    Ok((manifest, config, env_content))
+}
+
+/// 解码前端传来的 base64 配置包
+fn decode_config_bundle(data: &str) -> Result<Vec<u8>, String> {
+    BASE64
+        .decode(data.trim())
+        .map_err(|e| format!("配置包不是有效的 base64 数据: {}", e))
+}
+
+/// 导出配置包（include_secrets 为 false 时移除 API Key、Token 等敏感字段）
+/// 返回 base64 编码的 tar 数据，避免经 IPC 以数字数组传输
+#[command]
+pub async fn export_config_bundle(include_secrets: bool) -> Result<String, String> {
+    info!("[配置包] 导出配置包 (包含密钥: {})", include_secrets);
 
-    let token = get_or_create_gateway_token().await?;
     let config = load_openclaw_config_raw()?;
-    let port = config
-        .pointer("/gateway/port")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(18789);
+    let env_content = file::read_file(&platform::get_env_file_path()).ok();
+    let bytes = build_config_bundle(&config, env_content.as_deref(), include_secrets)?;
 
-    let url = format!("http://localhost:{}?token={}", port, token);
+    info!("[配置包] ✓ 导出完成，大小 {} 字节", bytes.len());
+    Ok(BASE64.encode(bytes))
+}
 
-    info!("[Dashboard URL] ✓ URL: {}...", &url[..50.min(url.len())]);
-    Ok(url)
+/// 导入配置包（data 为 base64 编码的 tar 数据）：校验后恢复 openclaw.json 与 env，
+/// 覆盖前自动备份现有文件
+#[command]
+pub async fn import_config_bundle(
+    data: String,
+    overwrite: bool,
+) -> Result<ImportConfigBundleResponse, String> {
+    let bytes = decode_config_bundle(&data)?;
+    info!("[配置包] 导入配置包 ({} 字节)", bytes.len());
+    let _config_guard = lock_config_writes().await?;
+
+    let (manifest, config, env_content) = read_config_bundle(&bytes)?;
+    let config_path = platform::get_config_file_path();
+    let env_path = platform::get_env_file_path();
+
+    let config_exists = file::file_exists(&config_path);
+    let env_conflict = env_content.is_some() && file::file_exists(&env_path);
+    if !overwrite && (config_exists || env_conflict) {
+        return Err("目标配置已存在，如需覆盖请开启 overwrite".to_string());
+    }
+
+    let mut backup_paths = Vec::new();
+    if config_exists {
+        backup_paths.push(write_backup_snapshot(&load_openclaw_config_raw()?)?);
+    }
+    if env_conflict {
+        let env_backup_dir = ensure_backup_dir()?.join("env");
+        fs::create_dir_all(&env_backup_dir).map_err(|e| format!("创建 env 备份目录失败: {}", e))?;
+        let env_backup = env_backup_dir.join(format!("env-{}", timestamp_for_backup_name()));
+        fs::copy(&env_path, &env_backup).map_err(|e| format!("备份 env 文件失败: {}", e))?;
+        backup_paths.push(env_backup.to_string_lossy().to_string());
+    }
+
+    let mut restored = vec![BUNDLE_CONFIG_FILE.to_string()];
+    save_openclaw_config(&config)?;
+    if let Some(env_content) = env_content {
+        file::write_secret_file(&env_path, &env_content)
+            .map_err(|e| format!("写入 env 文件失败: {}", e))?;
+        restored.push(BUNDLE_ENV_FILE.to_string());
+    }
+
+    info!("[配置包] ✓ 已恢复: {:?}", restored);
+    Ok(ImportConfigBundleResponse {
+        restored,
+        backup_paths,
+        manifest,
+    })
 }
 
-// ============ AI 配置相关命令 ============
+// ============ 在外部编辑器中打开 ============
 
-/// 获取官方 Provider 列表（预设模板）
+/// 配置文件不存在错误前缀（前端据此询问是否创建空配置）
+pub(crate) const CONFIG_FILE_MISSING_ERROR: &str = "配置文件不存在";
+
+/// 用系统默认程序（编辑器）打开 openclaw.json
+/// 文件不存在且 create_if_missing 不为 true 时返回“配置文件不存在”错误，由前端询问后重试
 #[command]
-pub async fn get_official_providers() -> Result<Vec<OfficialProvider>, String> {
-    info!("[官方 Provider] 获取官方 Provider 预设列表...");
+pub async fn open_config_file(create_if_missing: Option<bool>) -> Result<String, String> {
+    let config_path = platform::get_config_file_path();
+    info!("[打开配置] 打开配置文件: {}", config_path);
 
-    let providers = vec![
-        OfficialProvider {
-            id: "anthropic".to_string(),
-            name: "Anthropic Claude".to_string(),
-            icon: "🟣".to_string(),
-            default_base_url: Some("https://api.anthropic.com".to_string()),
-            api_type: "anthropic-messages".to_string(),
-            requires_api_key: true,
-            docs_url: Some("https://docs.openclaw.ai/providers/anthropic".to_string()),
+    if !file::file_exists(&config_path) {
+        if !create_if_missing.unwrap_or(false) {
+            return Err(format!("{}: {}", CONFIG_FILE_MISSING_ERROR, config_path));
+        }
+        file::write_file(&config_path, "{}\n")
+            .map_err(|e| format!("创建配置文件失败: {}", e))?;
+        info!("[打开配置] 配置文件不存在，已创建空配置");
+    }
+
+    shell::open_with_default_handler(&config_path)?;
+    Ok(config_path)
+}
+
+/// 用系统文件管理器打开配置目录（目录不存在时先创建）
+#[command]
+pub async fn open_config_dir() -> Result<String, String> {
+    let config_dir = platform::get_config_dir();
+    info!("[打开配置] 打开配置目录: {}", config_dir);
+
+    fs::create_dir_all(&config_dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    shell::open_with_default_handler(&config_dir)?;
+    Ok(config_dir)
+}
+
+// ============ Gateway Token 命令 ============
+
+/// Gateway 默认端口
+pub(crate) const DEFAULT_GATEWAY_PORT: u16 = 18789;
+
+/// 读取配置中的 gateway.port（缺失或无效时使用默认端口）
+pub(crate) fn get_gateway_port() -> u16 {
+    load_openclaw_config_raw()
+        .ok()
+        .and_then(|config| config.pointer("/gateway/port").and_then(|v| v.as_u64()))
+        .and_then(|port| u16::try_from(port).ok())
+        .filter(|port| *port != 0)
+        .unwrap_or(DEFAULT_GATEWAY_PORT)
+}
+
+/// 写入 gateway.port
+pub(crate) async fn set_gateway_port(port: u16) -> Result<(), String> {
+    let _config_guard = lock_config_writes().await?;
+    let mut config = load_openclaw_config_raw()?;
+    if config.get("gateway").is_none() {
+        config["gateway"] = json!({});
+    }
+    config["gateway"]["port"] = json!(port);
+    save_openclaw_config(&config)
+}
+
+/// 生成随机 token（系统 CSPRNG，24 字节，十六进制编码）
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 24];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("生成随机 Token 失败: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 将 token 写入 gateway.auth.token，并启用 token 认证模式
+/// 已有的 gateway.mode（如 remote）保持不变，仅在未设置时默认为 local
+fn set_gateway_token(config: &mut Value, token: &str) {
+    let auth = object_at_mut(config, &["gateway", "auth"]);
+    auth.insert("token".to_string(), json!(token));
+    auth.insert("mode".to_string(), json!("token"));
+    object_at_mut(config, &["gateway"])
+        .entry("mode")
+        .or_insert_with(|| json!("local"));
+}
+
+/// 获取或生成 Gateway Token
+#[command]
+pub async fn get_or_create_gateway_token() -> Result<String, String> {
+    info!("[Gateway Token] 获取或创建 Gateway Token...");
+    let _config_guard = lock_config_writes().await?;
+    get_or_create_gateway_token_locked()
+}
+
+/// 读取配置中的 gateway.auth.token（已做变量替换），未配置时返回 None
+pub(crate) fn configured_gateway_token() -> Option<String> {
+    load_openclaw_config()
+        .ok()?
+        .pointer("/gateway/auth/token")
+        .and_then(|v| v.as_str())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_string())
+}
+
+/// 获取或生成 Gateway Token，调用方需持有配置写入锁
+fn get_or_create_gateway_token_locked() -> Result<String, String> {
+    let mut config = load_openclaw_config_raw()?;
+
+    // 检查是否已有 token
+    if let Some(token) = config
+        .pointer("/gateway/auth/token")
+        .and_then(|v| v.as_str())
+    {
+        if !token.is_empty() {
+            info!("[Gateway Token] ✓ 使用现有 Token");
+            return Ok(configured_gateway_token().unwrap_or_else(|| token.to_string()));
+        }
+    }
+    
+    // 生成新 token
+    let new_token = generate_token()?;
+    info!("[Gateway Token] 生成新 Token: {}", mask_secret(&new_token));
+    
+    set_gateway_token(&mut config, &new_token);
+    save_openclaw_config(&config)?;
+    
+    info!("[Gateway Token] ✓ Token 已保存到配置");
+    Ok(new_token)
+}
+
+/// 轮换 Gateway Token：生成新 token 覆盖 gateway.auth.token 并返回
+/// 旧 token 立即失效；restart=true 时重启 Gateway 使新 token 生效
+#[command]
+pub async fn rotate_gateway_token(restart: Option<bool>) -> Result<String, String> {
+    info!("[Gateway Token] 轮换 Gateway Token...");
+    let new_token = {
+        let _config_guard = lock_config_writes().await?;
+        let mut config = load_openclaw_config_raw()?;
+        let new_token = generate_token()?;
+        set_gateway_token(&mut config, &new_token);
+        save_openclaw_config(&config)?;
+        new_token
+    };
+    info!("[Gateway Token] ✓ 新 Token 已保存: {}", mask_secret(&new_token));
+
+    if restart.unwrap_or(false) {
+        if let Err(e) = super::service::restart_service(None).await {
+            warn!("[Gateway Token] 重启 Gateway 失败，请手动重启: {}", e);
+        }
+    }
+    Ok(new_token)
+}
+
+// ============ Gateway 网络设置 ============
+
+/// 获取 gateway 配置（原始值，不做变量替换）
+#[command]
+pub async fn get_gateway_settings() -> Result<GatewayConfig, String> {
+    info!("[Gateway 设置] 读取 gateway 配置...");
+    let config = load_openclaw_config_raw()?;
+    let gateway = config.get("gateway").cloned().unwrap_or_else(|| json!({}));
+    serde_json::from_value(gateway).map_err(|e| format!("gateway 配置结构无效: {}", e))
+}
+
+/// 将 gateway 设置合并到原始配置：只覆盖提供的字段，未提供的字段（含 auth）保持不变
+fn merge_gateway_settings(config: &mut Value, settings: &GatewayConfig) -> Result<(), String> {
+    let settings_value =
+        serde_json::to_value(settings).map_err(|e| format!("序列化 gateway 配置失败: {}", e))?;
+    let Some(fields) = settings_value.as_object() else {
+        return Ok(());
+    };
+
+    if !config.is_object() {
+        *config = json!({});
+    }
+    if !config.get("gateway").is_some_and(|v| v.is_object()) {
+        config["gateway"] = json!({});
+    }
+    for (key, value) in fields {
+        config["gateway"][key] = value.clone();
+    }
+    Ok(())
+}
+
+/// gateway.mode 允许的取值
+const GATEWAY_MODES: &[&str] = &["local", "remote"];
+
+/// 修改后需要重启 Gateway 才能生效的字段（reload 由 Gateway 热重载）
+const GATEWAY_RESTART_FIELDS: &[&str] = &["mode", "port", "bind", "trustedProxies", "auth"];
+
+/// 保存 gateway 设置的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewaySettingsSaveResult {
+    pub message: String,
+    /// 是否需要重启服务才能生效
+    pub restart_required: bool,
+}
+
+/// 校验 gateway.mode
+fn validate_gateway_mode(mode: Option<&str>) -> Result<(), String> {
+    match mode {
+        Some(mode) if !GATEWAY_MODES.contains(&mode) => Err(format!(
+            "gateway.mode 必须是 {}（当前: {}）",
+            GATEWAY_MODES.join("/"),
+            mode
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// 比较前后两份配置，判断是否修改了需要重启才生效的 gateway 字段
+fn gateway_restart_required(before: &Value, after: &Value) -> bool {
+    GATEWAY_RESTART_FIELDS.iter().any(|field| {
+        let pointer = format!("/gateway/{}", field);
+        before.pointer(&pointer) != after.pointer(&pointer)
+    })
+}
+
+/// 保存 gateway 配置（仅修改 gateway 子树，未提供的字段含 auth 保持不变）
+#[command]
+pub async fn save_gateway_settings(
+    settings: GatewayConfig,
+) -> Result<GatewaySettingsSaveResult, String> {
+    info!("[Gateway 设置] 保存 gateway 配置...");
+    validate_gateway_mode(settings.mode.as_deref())?;
+    let _config_guard = lock_config_writes().await?;
+
+    let before = load_openclaw_config_raw()?;
+    let mut config = before.clone();
+    merge_gateway_settings(&mut config, &settings)?;
+    validate_gateway_network_fields(&config)?;
+
+    let restart_required = gateway_restart_required(&before, &config);
+    if config != before {
+        save_openclaw_config(&config)?;
+    }
+
+    let message = if restart_required {
+        "Gateway 配置已保存，重启服务后生效"
+    } else {
+        "Gateway 配置已保存"
+    };
+    info!("[Gateway 设置] ✓ {}", message);
+    Ok(GatewaySettingsSaveResult {
+        message: message.to_string(),
+        restart_required,
+    })
+}
+
+/// 将 gateway.bind 映射为可点击访问的主机名
+/// 具体 IP/主机名原样保留（去掉 host:port 中的端口，IPv6 加方括号）；
+/// 绑定模式需先经 resolve_bind_mode 解析，未能解析的模式与通配地址按 loopback 处理
+fn dashboard_host_for_bind(bind: Option<&str>) -> String {
+    let bind = bind.map(str::trim).unwrap_or("");
+    match bind {
+        "" | "0.0.0.0" | "::" | "[::]" | "127.0.0.1" | "::1" | "loopback" | "lan" | "auto"
+        | "tailnet" | "custom" => "localhost".to_string(),
+        // [IPv6]:port 或 [IPv6]
+        host if host.starts_with('[') => match host.find(']') {
+            Some(end) => host[..=end].to_string(),
+            None => host.to_string(),
+        },
+        // 只有一个冒号时为 host:port，去掉端口；多个冒号为裸 IPv6 地址
+        host if host.matches(':').count() == 1 => {
+            dashboard_host_for_bind(host.split(':').next())
+        }
+        host if host.contains(':') => format!("[{}]", host),
+        host => host.to_string(),
+    }
+}
+
+/// 将绑定模式解析为实际监听地址
+/// custom 取 gateway.customBindHost，tailnet 取本机 Tailscale IP，
+/// lan/auto 与通配地址取本机局域网 IP（未能获取时保留原值）；其它取值原样返回
+fn resolve_bind_mode(
+    bind: Option<&str>,
+    custom_bind_host: Option<&str>,
+    tailnet_ip: impl FnOnce() -> Option<String>,
+    lan_ip: impl FnOnce() -> Option<String>,
+) -> Option<String> {
+    match bind.map(str::trim) {
+        Some("custom") => custom_bind_host
+            .map(str::trim)
+            .filter(|host| !host.is_empty())
+            .map(str::to_string),
+        Some("tailnet") => tailnet_ip(),
+        Some(mode @ ("lan" | "auto" | "0.0.0.0" | "::" | "[::]")) => {
+            lan_ip().or_else(|| Some(mode.to_string()))
+        }
+        other => other.map(str::to_string),
+    }
+}
+
+/// 获取本机主要的局域网 IPv4 地址（默认路由所在网卡；UDP connect 不会发送数据）
+fn primary_lan_ipv4() -> Option<String> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then(|| ip.to_string())
+}
+
+/// 查询本机的 Tailscale IPv4 地址（tailscale 未安装或未登录时返回 None）
+fn tailscale_ipv4() -> Option<String> {
+    shell::run_command_output("tailscale", &["ip", "-4"])
+        .ok()?
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// Gateway 是否启用了 TLS（gateway.tls.enabled 为 true）
+fn gateway_tls_enabled(config: &Value) -> bool {
+    config
+        .pointer("/gateway/tls/enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// 生成 Dashboard URL
+/// external_host 用于反向代理场景：带协议时作为完整基础地址，否则替换主机名；
+/// tls=true 时使用 https
+fn build_dashboard_url(
+    bind: Option<&str>,
+    external_host: Option<&str>,
+    port: u64,
+    tls: bool,
+    token: &str,
+) -> String {
+    let scheme = if tls { "https" } else { "http" };
+    let base = match external_host.map(str::trim).filter(|h| !h.is_empty()) {
+        Some(host) if host.starts_with("http://") || host.starts_with("https://") => {
+            host.trim_end_matches('/').to_string()
+        }
+        Some(host) => format!("{}://{}:{}", scheme, host, port),
+        None => format!("{}://{}:{}", scheme, dashboard_host_for_bind(bind), port),
+    };
+    format!("{}?token={}", base, token)
+}
+
+/// 获取 Dashboard URL（带 token）
+/// 主机名取自 gateway.bind，external_host 可覆盖（反向代理场景）
+#[command]
+pub async fn get_dashboard_url(external_host: Option<String>) -> Result<String, String> {
+    info!("[Dashboard URL] 获取 Dashboard URL...");
+
+    let token = get_or_create_gateway_token().await?;
+    let config = load_openclaw_config_raw()?;
+    let port = config
+        .pointer("/gateway/port")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_GATEWAY_PORT as u64);
+    let bind = resolve_bind_mode(
+        config.pointer("/gateway/bind").and_then(|v| v.as_str()),
+        config.pointer("/gateway/customBindHost").and_then(|v| v.as_str()),
+        tailscale_ipv4,
+        primary_lan_ipv4,
+    );
+
+    let tls = gateway_tls_enabled(&config);
+
+    let url = build_dashboard_url(bind.as_deref(), external_host.as_deref(), port, tls, &token);
+
+    info!("[Dashboard URL] ✓ URL: {}...", &url[..50.min(url.len())]);
+    Ok(url)
+}
+
+/// Gateway Token 校验结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GatewayTokenVerification {
+    /// Gateway 是否可连接
+    pub gateway_reachable: bool,
+    /// 配置中的 token 是否被 Gateway 接受
+    pub accepted: bool,
+    /// Gateway 是否使用内置的 DEFAULT_GATEWAY_TOKEN 而非配置中的 token 启动
+    pub uses_default_token: bool,
+    pub message: String,
+}
+
+/// 根据探测结果判断 token 是否可用
+/// config_status / default_status 为分别使用配置 token 与内置默认 token 请求 Gateway 的 HTTP 状态码，
+/// None 表示请求失败（Gateway 未运行或不可达）或未探测
+fn gateway_token_verdict(
+    config_token: &str,
+    config_status: Option<u16>,
+    default_status: Option<u16>,
+) -> GatewayTokenVerification {
+    let is_accepted = |status: u16| status != 401 && status != 403;
+
+    let Some(config_status) = config_status else {
+        return GatewayTokenVerification {
+            gateway_reachable: false,
+            accepted: false,
+            uses_default_token: false,
+            message: "Gateway 未运行或无法连接，无法校验 Token".to_string(),
+        };
+    };
+
+    if is_accepted(config_status) {
+        return GatewayTokenVerification {
+            gateway_reachable: true,
+            accepted: true,
+            uses_default_token: config_token == shell::DEFAULT_GATEWAY_TOKEN,
+            message: "Gateway 已接受配置中的 Token".to_string(),
+        };
+    }
+
+    let uses_default_token =
+        config_token != shell::DEFAULT_GATEWAY_TOKEN && default_status.is_some_and(is_accepted);
+    let message = if uses_default_token {
+        "Gateway 使用内置默认 Token 启动，与配置中的 gateway.auth.token 不一致，请重启服务".to_string()
+    } else {
+        format!("Gateway 拒绝了配置中的 Token (HTTP {})", config_status)
+    };
+    GatewayTokenVerification {
+        gateway_reachable: true,
+        accepted: false,
+        uses_default_token,
+        message,
+    }
+}
+
+/// 使用指定 token 请求 Gateway 的鉴权接口，返回 HTTP 状态码
+async fn probe_gateway_token(client: &reqwest::Client, port: u16, token: &str) -> Option<u16> {
+    let url = format!("http://127.0.0.1:{}/tools/invoke", port);
+    client
+        .post(&url)
+        .bearer_auth(token)
+        .json(&json!({ "tool": "sessions_list", "args": {} }))
+        .send()
+        .await
+        .map(|response| response.status().as_u16())
+        .map_err(|e| debug!("[Gateway Token] 请求 {} 失败: {}", url, e))
+        .ok()
+}
+
+/// 校验运行中的 Gateway 是否接受配置中的 Token
+#[command]
+pub async fn verify_gateway_token() -> Result<GatewayTokenVerification, String> {
+    info!("[Gateway Token] 校验 Gateway Token...");
+
+    let config = load_openclaw_config()?;
+    let token = config
+        .pointer("/gateway/auth/token")
+        .and_then(|v| v.as_str())
+        .filter(|token| !token.is_empty())
+        .ok_or("配置中没有 gateway.auth.token")?
+        .to_string();
+    let port = get_gateway_port();
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+
+    let config_status = probe_gateway_token(&client, port, &token).await;
+    let default_status = match config_status {
+        Some(401) | Some(403) if token != shell::DEFAULT_GATEWAY_TOKEN => {
+            probe_gateway_token(&client, port, shell::DEFAULT_GATEWAY_TOKEN).await
+        }
+        _ => None,
+    };
+
+    let result = gateway_token_verdict(&token, config_status, default_status);
+    info!("[Gateway Token] {}", result.message);
+    Ok(result)
+}
+
+// ============ 配置文件变更监听 ============
+
+/// 配置文件轮询间隔
+const CONFIG_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// 文件保持不变超过该时长才视为写入完成（合并连续写入）
+const CONFIG_WATCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// 配置文件变更状态（Tauri 事件 config-changed 的负载，Web 端通过 watch_config 轮询）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ConfigChangeStatus {
+    /// 配置文件是否存在
+    pub exists: bool,
+    /// 最后修改时间（Unix 毫秒）
+    pub last_modified: Option<u64>,
+    /// 磁盘内容版本号（与 get_config 返回的 version 一致）
+    pub version: String,
+    /// 已检测到的外部修改次数
+    pub change_count: u64,
+    /// 相对调用方传入的 since，是否有新的外部修改
+    #[serde(default)]
+    pub changed: bool,
+}
+
+/// 配置文件指纹（存在性、修改时间、大小），用于低成本判断文件是否变化
+#[derive(Debug, Clone, PartialEq)]
+struct ConfigFileFingerprint {
+    exists: bool,
+    modified_ms: Option<u64>,
+    len: u64,
+}
+
+fn config_file_fingerprint(path: &str) -> ConfigFileFingerprint {
+    match fs::metadata(path) {
+        Ok(meta) => ConfigFileFingerprint {
+            exists: true,
+            modified_ms: meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64),
+            len: meta.len(),
+        },
+        Err(_) => ConfigFileFingerprint {
+            exists: false,
+            modified_ms: None,
+            len: 0,
+        },
+    }
+}
+
+/// 防抖：指纹变化后需在 debounce 时长内保持稳定才上报
+struct ConfigWatchDebouncer {
+    baseline: ConfigFileFingerprint,
+    pending: Option<(ConfigFileFingerprint, std::time::Instant)>,
+}
+
+impl ConfigWatchDebouncer {
+    fn new(baseline: ConfigFileFingerprint) -> Self {
+        Self {
+            baseline,
+            pending: None,
+        }
+    }
+
+    /// 观察一次当前指纹，返回 true 表示文件已发生变化且趋于稳定
+    fn observe(
+        &mut self,
+        current: ConfigFileFingerprint,
+        now: std::time::Instant,
+        debounce: std::time::Duration,
+    ) -> bool {
+        if current == self.baseline {
+            self.pending = None;
+            return false;
+        }
+        match &self.pending {
+            Some((pending, since)) if *pending == current => {
+                if now.duration_since(*since) < debounce {
+                    return false;
+                }
+                self.baseline = current;
+                self.pending = None;
+                true
+            }
+            _ => {
+                self.pending = Some((current, now));
+                false
+            }
+        }
+    }
+}
+
+/// 当前配置文件状态（监听线程启动后持续更新）
+static CONFIG_WATCH_STATUS: std::sync::Mutex<Option<ConfigChangeStatus>> =
+    std::sync::Mutex::new(None);
+/// Manager 自身最近一次写入的版本号，监听线程据此忽略自身写入
+static LAST_OWN_CONFIG_VERSION: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+/// 配置外部修改回调（如 Tauri 事件推送）
+pub(crate) type ConfigChangeListener = Box<dyn Fn(&ConfigChangeStatus) + Send>;
+static CONFIG_CHANGE_LISTENERS: std::sync::Mutex<Vec<ConfigChangeListener>> =
+    std::sync::Mutex::new(Vec::new());
+static CONFIG_WATCHER_STARTED: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+fn note_own_config_write(version: String) {
+    *LAST_OWN_CONFIG_VERSION.lock().unwrap_or_else(|e| e.into_inner()) = Some(version);
+}
+
+/// 读取配置文件当前状态
+fn read_config_change_status(path: &str) -> ConfigChangeStatus {
+    let fingerprint = config_file_fingerprint(path);
+    let content = file::read_file(path).unwrap_or_default();
+    ConfigChangeStatus {
+        exists: fingerprint.exists,
+        last_modified: fingerprint.modified_ms,
+        version: config_content_version(&content),
+        change_count: 0,
+        changed: false,
+    }
+}
+
+/// 文件稳定后处理一次变化：内容确有变化且不是 Manager 自身写入时计为外部修改
+/// 返回新状态及是否需要通知前端
+fn apply_config_file_change(
+    previous: &ConfigChangeStatus,
+    mut current: ConfigChangeStatus,
+    own_version: Option<&str>,
+) -> (ConfigChangeStatus, bool) {
+    current.change_count = previous.change_count;
+    let external = current.version != previous.version && own_version != Some(&current.version);
+    if external {
+        current.change_count += 1;
+    }
+    (current, external)
+}
+
+/// 启动配置文件监听线程（重复调用只会追加回调，不会重复启动）
+/// 通过轮询文件元数据实现，兼容文件被删除后重新创建的情况
+pub(crate) fn start_config_watcher(listener: Option<ConfigChangeListener>) {
+    if let Some(listener) = listener {
+        CONFIG_CHANGE_LISTENERS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(listener);
+    }
+    if CONFIG_WATCHER_STARTED.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        return;
+    }
+
+    let mut path = platform::get_config_file_path();
+    *CONFIG_WATCH_STATUS.lock().unwrap_or_else(|e| e.into_inner()) =
+        Some(read_config_change_status(&path));
+    info!("[配置监听] 开始监听配置文件: {}", path);
+
+    std::thread::spawn(move || {
+        let mut debouncer = ConfigWatchDebouncer::new(config_file_fingerprint(&path));
+        loop {
+            std::thread::sleep(CONFIG_WATCH_INTERVAL);
+
+            // 切换配置档后改为监听新文件，切换本身不算外部修改
+            let current_path = platform::get_config_file_path();
+            if current_path != path {
+                path = current_path;
+                debouncer = ConfigWatchDebouncer::new(config_file_fingerprint(&path));
+                let mut status = read_config_change_status(&path);
+                let mut guard = CONFIG_WATCH_STATUS.lock().unwrap_or_else(|e| e.into_inner());
+                status.change_count = guard.as_ref().map(|s| s.change_count).unwrap_or(0);
+                *guard = Some(status);
+                continue;
+            }
+
+            let fingerprint = config_file_fingerprint(&path);
+            if !debouncer.observe(fingerprint, std::time::Instant::now(), CONFIG_WATCH_DEBOUNCE) {
+                continue;
+            }
+
+            let own_version = LAST_OWN_CONFIG_VERSION
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone();
+            let (status, external) = {
+                let mut guard = CONFIG_WATCH_STATUS.lock().unwrap_or_else(|e| e.into_inner());
+                let previous = guard.clone().unwrap_or_default();
+                let (status, external) = apply_config_file_change(
+                    &previous,
+                    read_config_change_status(&path),
+                    own_version.as_deref(),
+                );
+                *guard = Some(status.clone());
+                (status, external)
+            };
+
+            if external {
+                info!(
+                    "[配置监听] 检测到配置文件被外部修改 (存在: {}, 版本 {})",
+                    status.exists, status.version
+                );
+                for listener in CONFIG_CHANGE_LISTENERS
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .iter()
+                {
+                    listener(&status);
+                }
+            }
+        }
+    });
+}
+
+/// 获取配置文件变更状态（首次调用时启动监听）
+/// since 为上次拿到的 change_count，返回值中的 changed 表示此后是否有外部修改
+#[command]
+pub async fn watch_config(since: Option<u64>) -> Result<ConfigChangeStatus, String> {
+    start_config_watcher(None);
+    let mut status = CONFIG_WATCH_STATUS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default();
+    status.changed = since.is_some_and(|since| status.change_count > since);
+    Ok(status)
+}
+
+// ============ AI 配置相关命令 ============
+
+/// 获取官方 Provider 列表（预设模板）
+#[command]
+pub async fn get_official_providers() -> Result<Vec<OfficialProvider>, String> {
+    info!("[官方 Provider] 获取官方 Provider 预设列表...");
+
+    let providers = official_providers().to_vec();
+
+    info!(
+        "[官方 Provider] ✓ 返回 {} 个官方 Provider 预设",
+        providers.len()
+    );
+    Ok(providers)
+}
+
+/// Provider 是否需要 API Key：以官方预设的定义为准，自定义 Provider 按需要处理
+fn provider_requires_api_key(provider_name: &str) -> bool {
+    official_providers()
+        .iter()
+        .find(|p| p.id == provider_name)
+        .map_or(true, |p| p.requires_api_key)
+}
+
+/// 官方 Provider 预设模板（首次使用时构建，之后复用）
+fn official_providers() -> &'static [OfficialProvider] {
+    static PROVIDERS: std::sync::OnceLock<Vec<OfficialProvider>> = std::sync::OnceLock::new();
+    PROVIDERS.get_or_init(build_official_providers)
+}
+
+fn build_official_providers() -> Vec<OfficialProvider> {
+    vec![
+        OfficialProvider {
+            id: "anthropic".to_string(),
+            name: "Anthropic Claude".to_string(),
+            icon: "🟣".to_string(),
+            default_base_url: Some("https://api.anthropic.com".to_string()),
+            api_type: "anthropic-messages".to_string(),
+            requires_api_key: true,
+            docs_url: Some("https://docs.openclaw.ai/providers/anthropic".to_string()),
             suggested_models: vec![
                 SuggestedModel {
                     id: "claude-opus-4-5-20251101".to_string(),
@@ -1253,1522 +2925,5032 @@ pub async fn get_official_providers() -> Result<Vec<OfficialProvider>, String> {
                 },
             ],
         },
-    ];
+    ]
+}
+
+/// 解析模型成本配置（缺失字段按 0 处理，cost 不是对象时返回 None）
+pub(crate) fn parse_model_cost(value: Option<&Value>) -> Option<ModelCostConfig> {
+    const KNOWN: [&str; 4] = ["input", "output", "cacheRead", "cacheWrite"];
+    let cost = value?.as_object()?;
+    let read = |key: &str| cost.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    Some(ModelCostConfig {
+        input: read("input"),
+        output: read("output"),
+        cache_read: read("cacheRead"),
+        cache_write: read("cacheWrite"),
+        extra: cost
+            .iter()
+            .filter(|(key, _)| !KNOWN.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect(),
+    })
+}
+
+/// 模型支持的输入类型（缺失或为空时默认 ["text"]）
+fn model_inputs(input: &[String]) -> Vec<String> {
+    if input.is_empty() {
+        vec!["text".to_string()]
+    } else {
+        input.to_vec()
+    }
+}
+
+/// 将 models.providers.<provider>.models[] 中的单个模型转为界面展示结构
+fn parse_configured_model(
+    provider_name: &str,
+    model: &ModelConfig,
+    primary_model: Option<&str>,
+) -> ConfiguredModel {
+    let full_id = format!("{}/{}", provider_name, model.id);
+    let is_primary = primary_model == Some(full_id.as_str());
+
+    info!("[AI 配置] 解析模型: {} (is_primary: {})", full_id, is_primary);
+
+    ConfiguredModel {
+        full_id,
+        id: model.id.clone(),
+        name: if model.name.is_empty() {
+            model.id.clone()
+        } else {
+            model.name.clone()
+        },
+        api_type: model.api.clone(),
+        context_window: model.context_window,
+        max_tokens: model.max_tokens,
+        is_primary,
+        reasoning: model.reasoning,
+        cost: model.cost.clone(),
+        inputs: model_inputs(&model.input),
+    }
+}
+
+/// 获取 AI 配置概览
+#[command]
+pub async fn get_ai_config() -> Result<AIConfigOverview, String> {
+    info!("[AI 配置] 获取 AI 配置概览...");
+
+    let config_path = platform::get_config_file_path();
+    info!("[AI 配置] 配置文件路径: {}", config_path);
+
+    let config = load_openclaw_config()?;
+    let typed = typed_config_from_value(normalize_and_validate_config(&config)?)?;
+
+    let primary_model = typed.agents.defaults.model.primary.clone();
+    info!("[AI 配置] 主模型: {:?}", primary_model);
+
+    let mut available_models: Vec<String> =
+        typed.agents.defaults.models.keys().cloned().collect();
+    available_models.sort();
+    info!("[AI 配置] 可用模型数: {}", available_models.len());
+
+    // 解析已配置的 Provider（按名称排序，保证展示顺序稳定）
+    let mut providers: Vec<_> = typed.models.providers.iter().collect();
+    providers.sort_by(|a, b| a.0.cmp(b.0));
+    info!("[AI 配置] 找到 {} 个 Provider", providers.len());
+
+    let configured_providers: Vec<ConfiguredProvider> = providers
+        .into_iter()
+        .map(|(provider_name, provider)| {
+            let models: Vec<ConfiguredModel> = provider
+                .models
+                .iter()
+                .map(|m| parse_configured_model(provider_name, m, primary_model.as_deref()))
+                .collect();
+            info!(
+                "[AI 配置] Provider {} 解析完成: {} 个模型",
+                provider_name,
+                models.len()
+            );
+
+            ConfiguredProvider {
+                name: provider_name.clone(),
+                base_url: provider.base_url.clone(),
+                api_key_masked: provider.api_key.as_deref().map(mask_secret),
+                has_api_key: provider.api_key.is_some(),
+                models,
+            }
+        })
+        .collect();
+
+    info!(
+        "[AI 配置] ✓ 最终结果 - 主模型: {:?}, {} 个 Provider, {} 个可用模型",
+        primary_model,
+        configured_providers.len(),
+        available_models.len()
+    );
+
+    Ok(AIConfigOverview {
+        primary_model,
+        configured_providers,
+        available_models,
+        agents_list: typed.agents.list,
+        bindings: typed.bindings,
+    })
+}
+
+/// 规范化 Provider baseUrl
+/// - 必须包含 http/https 协议头，否则拒绝
+/// - 去除末尾多余的 "/"
+/// - 需要 API Key 的 Provider 使用明文 http 访问非本机地址时返回告警（不拒绝）
+/// - 含 ${VAR} 占位符时跳过校验，原样保留
+fn normalize_base_url(
+    raw: &str,
+    requires_api_key: bool,
+) -> Result<(String, Option<String>), String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err("baseUrl 不能为空".to_string());
+    }
+    if trimmed.contains("${") {
+        return Ok((trimmed.to_string(), None));
+    }
+
+    let Some((scheme, rest)) = trimmed.split_once("://") else {
+        return Err(format!(
+            "baseUrl 缺少协议头（例如 https://）: {}",
+            trimmed
+        ));
+    };
+    let scheme = scheme.to_lowercase();
+    if scheme != "http" && scheme != "https" {
+        return Err(format!("baseUrl 仅支持 http/https 协议: {}", trimmed));
+    }
+    let rest = rest.trim_end_matches('/');
+    if rest.is_empty() {
+        return Err(format!("baseUrl 缺少主机地址: {}", trimmed));
+    }
+
+    let normalized = format!("{}://{}", scheme, rest);
+
+    let authority = rest.split('/').next().unwrap_or(rest);
+    let host = if authority.starts_with('[') {
+        authority.split(']').next().map(|h| format!("{}]", h)).unwrap_or_default()
+    } else {
+        authority.split(':').next().unwrap_or(authority).to_string()
+    };
+    let is_local = matches!(host.as_str(), "localhost" | "[::1]" | "0.0.0.0")
+        || host.starts_with("127.");
+
+    let warning = if scheme == "http" && requires_api_key && !is_local {
+        Some(format!(
+            "{} 使用明文 http 传输 API Key，建议改为 https",
+            normalized
+        ))
+    } else {
+        None
+    };
+
+    Ok((normalized, warning))
+}
+
+/// 添加或更新 Provider
+#[command]
+pub async fn save_provider(
+    provider_name: String,
+    base_url: String,
+    api_key: Option<String>,
+    api_type: String,
+    models: Vec<ModelConfig>,
+) -> Result<String, String> {
+    let _config_guard = lock_config_writes().await?;
+    info!(
+        "[保存 Provider] 保存 Provider: {} ({} 个模型)",
+        provider_name,
+        models.len()
+    );
+
+    let mut config = load_openclaw_config_raw()?;
+    let existing = config
+        .pointer("/models/providers")
+        .and_then(|providers| providers.get(&provider_name))
+        .cloned();
+
+    // 构建模型配置
+    let models_typed: Vec<ModelConfig> = models
+        .iter()
+        .map(|m| ModelConfig {
+            api: Some(m.api.clone().unwrap_or_else(|| api_type.clone())),
+            input: if m.input.is_empty() {
+                vec!["text".to_string()]
+            } else {
+                m.input.clone()
+            },
+            ..m.clone()
+        })
+        .collect();
+
+    // 处理 API Key：传入新的非空 key 则使用新的；None 或空字符串表示不更改，保留原有的
+    let api_key = match api_key.filter(|key| !key.is_empty()) {
+        Some(key) => {
+            info!("[保存 Provider] 使用新的 API Key");
+            Some(key)
+        }
+        None => {
+            let existing_key = existing
+                .as_ref()
+                .and_then(|p| p.get("apiKey"))
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string());
+            if existing_key.is_some() {
+                info!("[保存 Provider] 保留原有的 API Key");
+            }
+            existing_key
+        }
+    };
+
+    let requires_api_key = provider_requires_api_key(&provider_name);
+    let (base_url, base_url_warning) = normalize_base_url(&base_url, requires_api_key)?;
+    if let Some(warning) = &base_url_warning {
+        warn!("[保存 Provider] {}", warning);
+    }
+
+    // 保存 Provider 配置（在原 Provider 对象上修改，保留其它字段）
+    let models_value =
+        serde_json::to_value(&models_typed).map_err(|e| format!("序列化模型配置失败: {}", e))?;
+    let mut provider = match existing {
+        Some(Value::Object(provider)) => provider,
+        _ => serde_json::Map::new(),
+    };
+    provider.insert("baseUrl".to_string(), json!(base_url));
+    match api_key {
+        Some(key) => provider.insert("apiKey".to_string(), json!(key)),
+        None => provider.remove("apiKey"),
+    };
+    provider.insert("models".to_string(), models_value);
+    object_at_mut(&mut config, &["models", "providers"])
+        .insert(provider_name.clone(), Value::Object(provider));
+
+    // 将模型添加到 agents.defaults.models（已有条目保持不变）
+    let default_models = object_at_mut(&mut config, &["agents", "defaults", "models"]);
+    for model in &models {
+        let full_id = format!("{}/{}", provider_name, model.id);
+        default_models.entry(full_id).or_insert_with(|| json!({}));
+    }
+
+    touch_config_meta(&mut config);
+
+    save_openclaw_config_edit(&config)?;
+    info!("[保存 Provider] ✓ Provider {} 保存成功", provider_name);
+
+    match base_url_warning {
+        Some(warning) => Ok(format!(
+            "Provider {} 已保存（baseUrl: {}）。注意：{}",
+            provider_name, base_url, warning
+        )),
+        None => Ok(format!(
+            "Provider {} 已保存（baseUrl: {}）",
+            provider_name, base_url
+        )),
+    }
+}
+
+/// 从配置中移除 Provider 及其模型；主模型属于该 Provider 时直接移除 primary 字段
+fn remove_provider(config: &mut Value, provider_name: &str) {
+    if let Some(providers) = config
+        .pointer_mut("/models/providers")
+        .and_then(|v| v.as_object_mut())
+    {
+        providers.remove(provider_name);
+    }
+
+    let prefix = format!("{}/", provider_name);
+    let Some(defaults) = config
+        .pointer_mut("/agents/defaults")
+        .and_then(|v| v.as_object_mut())
+    else {
+        return;
+    };
+    if let Some(models) = defaults.get_mut("models").and_then(|v| v.as_object_mut()) {
+        models.retain(|model_id, _| !model_id.starts_with(&prefix));
+    }
+
+    let is_removed = |v: &Value| v.as_str().is_some_and(|primary| primary.starts_with(&prefix));
+    match defaults.get_mut("model") {
+        // model 的简写形式为字符串
+        Some(model) if is_removed(model) => {
+            defaults.remove("model");
+        }
+        Some(Value::Object(model)) if model.get("primary").is_some_and(is_removed) => {
+            model.remove("primary");
+        }
+        _ => {}
+    }
+}
+
+/// 删除 Provider
+#[command]
+pub async fn delete_provider(provider_name: String) -> Result<String, String> {
+    info!("[删除 Provider] 删除 Provider: {}", provider_name);
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+    remove_provider(&mut config, &provider_name);
+
+    save_openclaw_config_edit(&config)?;
+    info!("[删除 Provider] ✓ Provider {} 已删除", provider_name);
+
+    Ok(format!("Provider {} 已删除", provider_name))
+}
+
+/// 复制 Provider：深拷贝配置并清空 apiKey，同时以 new/ 前缀复制 agents.defaults.models 中的模型
+/// 模型条目中的 alias 不复制，避免两个模型使用同一别名
+fn clone_provider_config(
+    config: &mut Value,
+    source_name: &str,
+    new_name: &str,
+) -> Result<(), String> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err("新 Provider 名称不能为空".to_string());
+    }
+    if new_name.contains('/') {
+        return Err(format!("Provider 名称不能包含 \"/\": {}", new_name));
+    }
+    let providers = config.pointer("/models/providers");
+    if providers.and_then(|p| p.get(new_name)).is_some() {
+        return Err(format!("Provider 已存在: {}", new_name));
+    }
+    let mut cloned = providers
+        .and_then(|p| p.get(source_name))
+        .cloned()
+        .ok_or_else(|| format!("Provider 不存在: {}", source_name))?;
+    if let Some(provider) = cloned.as_object_mut() {
+        provider.remove("apiKey");
+    }
+
+    let model_ids: Vec<String> = cloned
+        .get("models")
+        .and_then(|v| v.as_array())
+        .map(|models| {
+            models
+                .iter()
+                .filter_map(|m| m.get("id").and_then(|v| v.as_str()))
+                .map(|id| id.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    let default_models = object_at_mut(config, &["agents", "defaults", "models"]);
+    for model_id in model_ids {
+        let mut entry = default_models
+            .get(&format!("{}/{}", source_name, model_id))
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        if let Some(entry) = entry.as_object_mut() {
+            entry.remove("alias");
+        }
+        default_models.insert(format!("{}/{}", new_name, model_id), entry);
+    }
+    object_at_mut(config, &["models", "providers"]).insert(new_name.to_string(), cloned);
+    Ok(())
+}
+
+/// 复制 Provider（用于同一 API 的多个账号），复制后需为新 Provider 设置 API Key
+#[command]
+pub async fn clone_provider(source_name: String, new_name: String) -> Result<String, String> {
+    info!("[复制 Provider] 复制 Provider: {} -> {}", source_name, new_name);
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+    clone_provider_config(&mut config, &source_name, &new_name)?;
+    touch_config_meta(&mut config);
+
+    save_openclaw_config_edit(&config)?;
+    info!("[复制 Provider] ✓ Provider {} 已复制为 {}", source_name, new_name.trim());
+
+    Ok(format!(
+        "Provider {} 已复制为 {}，请为其设置新的 API Key",
+        source_name,
+        new_name.trim()
+    ))
+}
+
+/// 校验模型引用：格式为 provider/model 且 provider 已在 models.providers 中配置；
+/// require_known_model 时模型还须在该 provider 的 models 列表或 agents.defaults.models 中
+fn validate_model_ref(
+    config: &Value,
+    model_ref: &str,
+    require_known_model: bool,
+) -> Result<(), String> {
+    let (provider_name, model_id) = model_ref
+        .split_once('/')
+        .filter(|(provider, model)| !provider.trim().is_empty() && !model.trim().is_empty())
+        .ok_or_else(|| format!("模型 ID 格式无效（应为 provider/model）: {}", model_ref))?;
+    let provider = config
+        .pointer("/models/providers")
+        .and_then(|providers| providers.get(provider_name))
+        .ok_or_else(|| format!("Provider 未配置: {}（请先在 models.providers 中添加）", provider_name))?;
+    if !require_known_model {
+        return Ok(());
+    }
+
+    let in_provider = provider
+        .get("models")
+        .and_then(|v| v.as_array())
+        .is_some_and(|models| {
+            models
+                .iter()
+                .any(|m| m.get("id").and_then(|v| v.as_str()) == Some(model_id))
+        });
+    let in_defaults = config
+        .pointer("/agents/defaults/models")
+        .and_then(|v| v.as_object())
+        .is_some_and(|models| models.contains_key(model_ref));
+    if in_provider || in_defaults {
+        Ok(())
+    } else {
+        Err(format!("模型 {} 不在 Provider {} 的模型列表中", model_id, provider_name))
+    }
+}
+
+/// 设置主模型
+#[command]
+pub async fn set_primary_model(model_id: String) -> Result<String, String> {
+    info!("[设置主模型] 设置主模型: {}", model_id);
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+    validate_model_ref(&config, &model_id, true)?;
+    let defaults = object_at_mut(&mut config, &["agents", "defaults"]);
+    match defaults.get_mut("model") {
+        // 保持 model 原有的简写（字符串）或对象写法
+        Some(model @ Value::String(_)) => *model = json!(model_id),
+        _ => {
+            object_at_mut(&mut config, &["agents", "defaults", "model"])
+                .insert("primary".to_string(), json!(model_id));
+        }
+    }
+
+    save_openclaw_config_edit(&config)?;
+    info!("[设置主模型] ✓ 主模型已设置为: {}", model_id);
+
+    Ok(format!("主模型已设置为 {}", model_id))
+}
+
+/// agents.defaults.maxConcurrent 允许的范围
+const MAX_CONCURRENT_RANGE: std::ops::RangeInclusive<u32> = 1..=64;
+
+/// agents.defaults 中的标量设置（compaction 等对象配置不在此返回）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentDefaultsSettings {
+    pub primary_model: Option<String>,
+    pub fallbacks: Vec<String>,
+    pub max_concurrent: Option<u32>,
+    pub workspace: Option<String>,
+}
+
+/// 获取 agents.defaults 的标量设置
+///
+/// 直接读取原始 JSON，配置中其它部分的类型问题不影响这里的展示
+#[command]
+pub async fn get_agent_defaults() -> Result<AgentDefaultsSettings, String> {
+    let config = load_openclaw_config_raw()?;
+    let defaults = config.pointer("/agents/defaults").cloned().unwrap_or(json!({}));
+    let model = defaults.get("model");
+    // model 可以是 "provider/model" 简写，也可以是 { primary, fallbacks } 对象
+    let primary_model = match model {
+        Some(Value::String(primary)) => Some(primary.clone()),
+        Some(model) => model.get("primary").and_then(|v| v.as_str()).map(str::to_string),
+        None => None,
+    };
+    let fallbacks = model
+        .and_then(|m| m.get("fallbacks"))
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(AgentDefaultsSettings {
+        primary_model,
+        fallbacks,
+        max_concurrent: defaults
+            .get("maxConcurrent")
+            .and_then(|v| v.as_u64())
+            .and_then(|v| u32::try_from(v).ok()),
+        workspace: defaults
+            .get("workspace")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+    })
+}
+
+/// 设置 agents.defaults.maxConcurrent（1-64），其它 defaults 字段保持不变
+///
+/// 直接修改 JSON 而不经强类型往返，其它字段（包括 `${VAR}` 占位符）按原样写回
+#[command]
+pub async fn set_max_concurrent(max_concurrent: u32) -> Result<String, String> {
+    info!("[Agent 默认配置] 设置最大并发数: {}", max_concurrent);
+    if !MAX_CONCURRENT_RANGE.contains(&max_concurrent) {
+        return Err(format!(
+            "最大并发数必须在 {}-{} 之间",
+            MAX_CONCURRENT_RANGE.start(),
+            MAX_CONCURRENT_RANGE.end()
+        ));
+    }
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+    object_at_mut(&mut config, &["agents", "defaults"])
+        .insert("maxConcurrent".to_string(), json!(max_concurrent));
+    touch_config_meta(&mut config);
+    save_openclaw_config_edit(&config)?;
+    info!("[Agent 默认配置] ✓ 最大并发数已设置为: {}", max_concurrent);
+
+    Ok(format!("最大并发数已设置为 {}", max_concurrent))
+}
+
+/// 添加模型到可用列表
+#[command]
+pub async fn add_available_model(model_id: String) -> Result<String, String> {
+    info!("[添加模型] 添加模型到可用列表: {}", model_id);
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+    validate_model_ref(&config, &model_id, false)?;
+
+    // 确保路径存在
+    if config.get("agents").is_none() {
+        config["agents"] = json!({});
+    }
+    if config["agents"].get("defaults").is_none() {
+        config["agents"]["defaults"] = json!({});
+    }
+    if config["agents"]["defaults"].get("models").is_none() {
+        config["agents"]["defaults"]["models"] = json!({});
+    }
+
+    // 添加模型
+    config["agents"]["defaults"]["models"][&model_id] = json!({});
+
+    save_openclaw_config(&config)?;
+    info!("[添加模型] ✓ 模型 {} 已添加", model_id);
+
+    Ok(format!("模型 {} 已添加", model_id))
+}
+
+/// 从可用列表移除模型
+#[command]
+pub async fn remove_available_model(model_id: String) -> Result<String, String> {
+    info!("[移除模型] 从可用列表移除模型: {}", model_id);
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+
+    if let Some(models) = config
+        .pointer_mut("/agents/defaults/models")
+        .and_then(|v| v.as_object_mut())
+    {
+        models.remove(&model_id);
+    }
+
+    save_openclaw_config(&config)?;
+    info!("[移除模型] ✓ 模型 {} 已移除", model_id);
+
+    Ok(format!("模型 {} 已移除", model_id))
+}
+
+/// 修改 models.providers.<provider>.models[] 中指定模型的 cost，其余字段保持不变
+fn apply_model_cost(
+    config: &mut Value,
+    full_id: &str,
+    cost: &ModelCostConfig,
+) -> Result<(), String> {
+    let (provider_name, model_id) = full_id
+        .split_once('/')
+        .ok_or_else(|| format!("模型 ID 格式无效（应为 provider/model）: {}", full_id))?;
+    let model = config
+        .pointer_mut("/models/providers")
+        .and_then(|providers| providers.get_mut(provider_name))
+        .and_then(|provider| provider.get_mut("models"))
+        .and_then(|v| v.as_array_mut())
+        .and_then(|models| {
+            models
+                .iter_mut()
+                .find(|m| m.get("id").and_then(|v| v.as_str()) == Some(model_id))
+        })
+        .and_then(|m| m.as_object_mut())
+        .ok_or_else(|| format!("模型不存在: {}", full_id))?;
+
+    let cost = serde_json::to_value(cost).map_err(|e| format!("序列化成本配置失败: {}", e))?;
+    model.insert("cost".to_string(), cost);
+    Ok(())
+}
+
+/// 修改单个模型的成本配置（无需提交整个 Provider）
+#[command]
+pub async fn update_model_cost(full_id: String, cost: ModelCostConfig) -> Result<String, String> {
+    info!("[模型成本] 更新模型成本: {}", full_id);
+    let _config_guard = lock_config_writes().await?;
+
+    let mut config = load_openclaw_config_raw()?;
+    apply_model_cost(&mut config, &full_id, &cost)?;
+
+    save_openclaw_config(&config)?;
+    info!("[模型成本] ✓ 模型 {} 成本已更新", full_id);
+
+    Ok(format!("模型 {} 成本已更新", full_id))
+}
+
+// ============ 旧版兼容 ============
+
+/// 获取所有支持的 AI Provider（旧版兼容）
+#[command]
+pub async fn get_ai_providers() -> Result<Vec<crate::models::AIProviderOption>, String> {
+    info!("[AI Provider] 获取支持的 AI Provider 列表（旧版）...");
+
+    let official = get_official_providers().await?;
+    let providers: Vec<crate::models::AIProviderOption> = official
+        .into_iter()
+        .map(|p| crate::models::AIProviderOption {
+            id: p.id,
+            name: p.name,
+            icon: p.icon,
+            default_base_url: p.default_base_url,
+            requires_api_key: p.requires_api_key,
+            models: p
+                .suggested_models
+                .into_iter()
+                .map(|m| crate::models::AIModelOption {
+                    id: m.id,
+                    name: m.name,
+                    description: m.description,
+                    recommended: m.recommended,
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(providers)
+}
+
+// ============ 渠道配置 ============
+
+/// bindings 中重复出现的 (channel, accountId) 映射（保留首个，其余被忽略）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateAccountBinding {
+    pub channel: String,
+    pub account_id: String,
+    pub kept_agent_id: String,
+    pub ignored_agent_id: String,
+}
+
+/// 解析 bindings 时的收集器：同一 (channel, accountId) 只保留首次出现的映射
+#[derive(Default)]
+struct AccountBindingsCollector {
+    pairs: HashMap<(String, String), String>,
+    duplicates: Vec<DuplicateAccountBinding>,
+}
+
+impl AccountBindingsCollector {
+    fn insert(&mut self, channel: &str, account_id: &str, agent_id: &str) {
+        let key = (channel.to_string(), account_id.to_string());
+        if let Some(kept) = self.pairs.get(&key) {
+            self.duplicates.push(DuplicateAccountBinding {
+                channel: key.0,
+                account_id: key.1,
+                kept_agent_id: kept.clone(),
+                ignored_agent_id: agent_id.to_string(),
+            });
+            return;
+        }
+        self.pairs.insert(key, agent_id.to_string());
+    }
+}
+
+fn parse_account_bindings(bindings: &Value) -> HashMap<(String, String), String> {
+    parse_account_bindings_with_duplicates(bindings).0
+}
+
+/// 解析 bindings 中的账号映射，并返回被忽略的重复项
+fn parse_account_bindings_with_duplicates(
+    bindings: &Value,
+) -> (HashMap<(String, String), String>, Vec<DuplicateAccountBinding>) {
+    let mut collector = AccountBindingsCollector::default();
+
+    if let Ok(entries) = serde_json::from_value::<Vec<BindingEntry>>(bindings.clone()) {
+        for entry in entries {
+            let Some(agent_id) = entry.agent_id else {
+                continue;
+            };
+            let Some(m) = entry.r#match else {
+                continue;
+            };
+            let Some(channel) = m.channel else {
+                continue;
+            };
+            let Some(account_id) = m.account_id else {
+                continue;
+            };
+
+            collector.insert(&channel, &account_id, &agent_id);
+        }
+
+        if !collector.pairs.is_empty() {
+            return (collector.pairs, collector.duplicates);
+        }
+    }
+
+    if let Some(arr) = bindings.as_array() {
+        for item in arr {
+            let Some(agent_id) = item.get("agentId").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let Some(m) = item.get("match").and_then(|v| v.as_object()) else {
+                continue;
+            };
+            let Some(channel) = m.get("channel").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let Some(account_id) = m.get("accountId").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            collector.insert(channel, account_id, agent_id);
+        }
+        return (collector.pairs, collector.duplicates);
+    }
+
+    if let Some(obj) = bindings.as_object() {
+        for (key, value) in obj {
+            // 扁平格式：{"telegram/default":"main"}，兼容 ":" 与 "." 分隔
+            if let Some(agent_id) = value.as_str() {
+                let split = key
+                    .split_once('/')
+                    .or_else(|| key.split_once(':'))
+                    .or_else(|| key.split_once('.'));
+                if let Some((channel, account_id)) = split {
+                    collector.insert(channel, account_id, agent_id);
+                    continue;
+                }
+            }
+
+            // 分组格式：{"telegram":{"default":"main"}}
+            if let Some(accounts_obj) = value.as_object() {
+                for (account_id, nested) in accounts_obj {
+                    if let Some(agent_id) = nested.as_str() {
+                        collector.insert(key, account_id, agent_id);
+                        continue;
+                    }
+
+                    if let Some(nested_obj) = nested.as_object() {
+                        if let Some(agent_id) = nested_obj.get("agentId").and_then(|v| v.as_str()) {
+                            collector.insert(key, account_id, agent_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    (collector.pairs, collector.duplicates)
+}
+
+/// 检查渠道账号冲突，返回可展示给前端的告警
+/// - 同一渠道下账号 id 大小写不敏感重复
+/// - bindings 中同一 (channel, accountId) 重复出现（保留首个）
+/// - bindings 指向的账号在 channels.<channel>.accounts 中不存在
+fn collect_channel_account_issues(config: &Value) -> Vec<ConfigValidationIssue> {
+    let mut issues = Vec::new();
+    let channels = config.get("channels").and_then(|v| v.as_object());
+
+    if let Some(channels) = channels {
+        for (channel_id, channel) in channels {
+            let Some(accounts) = channel.get("accounts").and_then(|v| v.as_object()) else {
+                continue;
+            };
+            let mut seen: HashMap<String, &String> = HashMap::new();
+            for account_id in accounts.keys() {
+                if let Some(first) = seen.get(&account_id.to_lowercase()) {
+                    issues.push(ConfigValidationIssue {
+                        path: format!("channels.{}.accounts.{}", channel_id, account_id),
+                        message: format!(
+                            "账号 {} 与 {} 仅大小写不同，可能是重复账号",
+                            account_id, first
+                        ),
+                        variable: None,
+                    });
+                } else {
+                    seen.insert(account_id.to_lowercase(), account_id);
+                }
+            }
+        }
+    }
+
+    let bindings = config.get("bindings").cloned().unwrap_or(json!([]));
+    let (pairs, duplicates) = parse_account_bindings_with_duplicates(&bindings);
+    for dup in duplicates {
+        issues.push(ConfigValidationIssue {
+            path: format!("bindings.{}.{}", dup.channel, dup.account_id),
+            message: format!(
+                "账号 {}/{} 存在重复绑定：保留 {}，忽略 {}",
+                dup.channel, dup.account_id, dup.kept_agent_id, dup.ignored_agent_id
+            ),
+            variable: None,
+        });
+    }
+
+    let mut bound: Vec<&(String, String)> = pairs.keys().collect();
+    bound.sort();
+    for (channel_id, account_id) in bound {
+        let accounts = channels
+            .and_then(|c| c.get(channel_id))
+            .and_then(|c| c.get("accounts"))
+            .and_then(|v| v.as_object());
+        if let Some(accounts) = accounts {
+            if !accounts.contains_key(account_id) {
+                issues.push(ConfigValidationIssue {
+                    path: format!("bindings.{}.{}", channel_id, account_id),
+                    message: format!(
+                        "绑定的账号 {} 在渠道 {} 的 accounts 中不存在",
+                        account_id, channel_id
+                    ),
+                    variable: None,
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+/// 检查渠道账号与 bindings 冲突（只读，不修改配置）
+#[command]
+pub async fn validate_channel_accounts() -> Result<Vec<ConfigValidationIssue>, String> {
+    info!("[渠道配置] 检查渠道账号冲突...");
+    let config = load_openclaw_config_raw()?;
+    let issues = collect_channel_account_issues(&config);
+    for issue in &issues {
+        warn!("[渠道配置] {}: {}", issue.path, issue.message);
+    }
+    Ok(issues)
+}
+
+/// bindings 的三种常见写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BindingsShape {
+    /// 官方数组结构：[{"agentId":"main","match":{"channel":"telegram","accountId":"default"}}]
+    Array,
+    /// 扁平对象：{"telegram/default":"main"}
+    Flat,
+    /// 分组对象：{"telegram":{"default":"main"}}
+    Grouped,
+}
+
+impl BindingsShape {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "array" => Ok(Self::Array),
+            "flat" => Ok(Self::Flat),
+            "grouped" => Ok(Self::Grouped),
+            other => Err(format!(
+                "不支持的 bindings 结构: {}（可选 array / flat / grouped）",
+                other
+            )),
+        }
+    }
+
+    fn detect(bindings: &Value) -> Self {
+        match bindings.as_object() {
+            Some(obj) if obj.values().all(|v| v.is_string()) => Self::Flat,
+            Some(_) => Self::Grouped,
+            None => Self::Array,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Array => "array",
+            Self::Flat => "flat",
+            Self::Grouped => "grouped",
+        }
+    }
+}
+
+/// 按指定结构生成 bindings（按 channel/accountId 排序，保证输出稳定）
+fn build_bindings_payload(
+    shape: BindingsShape,
+    all_pairs: &HashMap<(String, String), String>,
+) -> Value {
+    let mut sorted: Vec<(&(String, String), &String)> = all_pairs.iter().collect();
+    sorted.sort();
+
+    match shape {
+        BindingsShape::Array => Value::Array(
+            sorted
+                .into_iter()
+                .map(|((channel, account_id), agent_id)| {
+                    json!({
+                        "agentId": agent_id,
+                        "match": {
+                            "channel": channel,
+                            "accountId": account_id,
+                        }
+                    })
+                })
+                .collect(),
+        ),
+        BindingsShape::Flat => {
+            let mut flat = serde_json::Map::new();
+            for ((channel, account_id), agent_id) in sorted {
+                flat.insert(format!("{}/{}", channel, account_id), json!(agent_id));
+            }
+            Value::Object(flat)
+        }
+        BindingsShape::Grouped => {
+            let mut grouped_obj = serde_json::Map::new();
+            for ((channel, account_id), agent_id) in sorted {
+                let group = grouped_obj
+                    .entry(channel.clone())
+                    .or_insert_with(|| json!({}));
+                group[account_id] = json!(agent_id);
+            }
+            Value::Object(grouped_obj)
+        }
+    }
+}
+
+fn merge_bindings_payload_by_shape(
+    original_bindings: &Value,
+    all_pairs: &HashMap<(String, String), String>,
+) -> Value {
+    // 默认与数组格式都写回官方数组结构
+    build_bindings_payload(BindingsShape::detect(original_bindings), all_pairs)
+}
+
+/// 按 agentId 过滤 bindings
+/// 数组结构逐条过滤，account_only 时只过滤带 accountId 的账号路由，其它路由原样保留；
+/// 对象结构经账号映射重建并保持原写法
+fn retain_bindings_by_agent(
+    bindings: &Value,
+    account_only: bool,
+    keep: impl Fn(&str) -> bool,
+) -> Value {
+    if let Some(entries) = bindings.as_array() {
+        return Value::Array(
+            entries
+                .iter()
+                .filter(|entry| {
+                    let account_bound =
+                        entry.pointer("/match/accountId").is_some_and(|v| v.is_string());
+                    if account_only && !account_bound {
+                        return true;
+                    }
+                    entry.get("agentId").and_then(|v| v.as_str()).map_or(true, &keep)
+                })
+                .cloned()
+                .collect(),
+        );
+    }
+
+    let mut all_pairs = parse_account_bindings(bindings);
+    all_pairs.retain(|_, agent_id| keep(agent_id));
+    merge_bindings_payload_by_shape(bindings, &all_pairs)
+}
+
+/// 指向不存在 Agent 的账号路由
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrphanedBinding {
+    pub channel: String,
+    pub account_id: String,
+    pub agent_id: String,
+}
+
+/// agents.list 中的 Agent id；未配置 list 时只有默认的 main
+fn known_agent_ids(config: &Value) -> Vec<String> {
+    let ids: Vec<String> = config
+        .pointer("/agents/list")
+        .and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|agent| agent.get("id").and_then(|v| v.as_str()))
+                .map(|id| id.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if ids.is_empty() {
+        vec!["main".to_string()]
+    } else {
+        ids
+    }
+}
+
+/// 查找孤立 bindings（agentId 不在 agents.list 中），按 channel/accountId 排序
+fn find_orphaned_bindings(config: &Value) -> Vec<OrphanedBinding> {
+    let known = known_agent_ids(config);
+    let bindings = config.get("bindings").cloned().unwrap_or(json!([]));
+
+    let mut orphans: Vec<OrphanedBinding> = parse_account_bindings(&bindings)
+        .into_iter()
+        .filter(|(_, agent_id)| !known.contains(agent_id))
+        .map(|((channel, account_id), agent_id)| OrphanedBinding {
+            channel,
+            account_id,
+            agent_id,
+        })
+        .collect();
+    orphans.sort_by(|a, b| (&a.channel, &a.account_id).cmp(&(&b.channel, &b.account_id)));
+    orphans
+}
+
+/// 移除孤立 bindings，返回被移除的账号路由
+/// 与 find_orphaned_bindings 一致只处理账号路由，其它维度的路由不在报告中，原样保留
+fn remove_orphaned_bindings(config: &mut Value) -> Vec<OrphanedBinding> {
+    let orphans = find_orphaned_bindings(config);
+    let known = known_agent_ids(config);
+    if let Some(bindings) = config.get("bindings") {
+        config["bindings"] = retain_bindings_by_agent(bindings, true, |agent_id| {
+            known.iter().any(|id| id == agent_id)
+        });
+    }
+    orphans
+}
+
+/// 读取配置并检查孤立 bindings（供诊断使用）
+pub(crate) fn check_orphaned_bindings() -> Result<Vec<OrphanedBinding>, String> {
+    let config = load_openclaw_config_raw()?;
+    Ok(find_orphaned_bindings(&config))
+}
+
+/// 修复孤立 bindings：移除指向不存在 Agent 的路由，写入前自动备份
+#[command]
+pub async fn repair_orphaned_bindings() -> Result<Vec<OrphanedBinding>, String> {
+    info!("[Bindings] 修复孤立 bindings...");
+    let _config_guard = lock_config_writes().await?;
+
+    let original = load_openclaw_config_raw()?;
+    let mut config = original.clone();
+    let removed = remove_orphaned_bindings(&mut config);
+    if config == original {
+        info!("[Bindings] 未发现孤立 bindings");
+        return Ok(removed);
+    }
+
+    let backup_path = write_backup_snapshot(&original)?;
+    save_openclaw_config(&config)?;
+
+    info!(
+        "[Bindings] ✓ 已移除 {} 条孤立 bindings，备份: {}",
+        removed.len(),
+        backup_path
+    );
+    Ok(removed)
+}
+
+/// 将 bindings 转换为目标结构
+/// 数组中不含 channel/accountId 的路由（如按 peer 匹配）无法用对象结构表达：
+/// 目标为 array 时原样保留，目标为 flat/grouped 时拒绝迁移以免丢失
+/// 数组迁移为数组时保持条目顺序（路由按顺序匹配），只规范化每条的结构
+fn convert_bindings_shape(bindings: &Value, target: BindingsShape) -> Result<Value, String> {
+    if let (BindingsShape::Array, Some(entries)) = (target, bindings.as_array()) {
+        let normalized = entries
+            .iter()
+            .map(|entry| {
+                serde_json::from_value::<BindingEntry>(entry.clone())
+                    .and_then(serde_json::to_value)
+                    .unwrap_or_else(|_| entry.clone())
+            })
+            .collect();
+        return Ok(Value::Array(normalized));
+    }
+
+    let all_pairs = parse_account_bindings(bindings);
+
+    let extra_entries: Vec<Value> = bindings
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter(|entry| {
+                    entry.pointer("/match/channel").and_then(|v| v.as_str()).is_none()
+                        || entry.pointer("/match/accountId").and_then(|v| v.as_str()).is_none()
+                        || entry.get("agentId").and_then(|v| v.as_str()).is_none()
+                })
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if target != BindingsShape::Array && !extra_entries.is_empty() {
+        return Err(format!(
+            "bindings 中有 {} 条非账号维度的路由，无法迁移为 {} 结构",
+            extra_entries.len(),
+            target.as_str()
+        ));
+    }
+
+    let mut payload = build_bindings_payload(target, &all_pairs);
+    if let Some(entries) = payload.as_array_mut() {
+        entries.extend(extra_entries);
+    }
+    Ok(payload)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrateBindingsResponse {
+    pub backup_path: String,
+    pub shape: String,
+    pub count: usize,
+}
+
+/// 将 bindings 迁移为指定结构（array / flat / grouped），写入前自动备份
+#[command]
+pub async fn migrate_bindings(target_shape: String) -> Result<MigrateBindingsResponse, String> {
+    info!("[Bindings] 迁移 bindings 结构为: {}", target_shape);
+    let _config_guard = lock_config_writes().await?;
+    let target = BindingsShape::parse(&target_shape)?;
+
+    let mut config = load_openclaw_config_raw()?;
+    let bindings = config.get("bindings").cloned().unwrap_or(json!([]));
+    let migrated = convert_bindings_shape(&bindings, target)?;
+    let count = parse_account_bindings(&migrated).len();
+
+    let backup_path = write_backup_snapshot(&config)?;
+    config["bindings"] = migrated;
+    save_openclaw_config(&config)?;
+
+    info!(
+        "[Bindings] ✓ 已迁移为 {} 结构（{} 条账号映射），备份: {}",
+        target.as_str(),
+        count,
+        backup_path
+    );
+    Ok(MigrateBindingsResponse {
+        backup_path,
+        shape: target.as_str().to_string(),
+        count,
+    })
+}
+
+/// 支持的渠道类型列表及其测试字段（测试字段只保存到 env 文件）
+const CHANNEL_TYPES: &[(&str, &str, &[&str])] = &[
+    ("telegram", "telegram", &["userId"]),
+    ("discord", "discord", &["testChannelId"]),
+    ("slack", "slack", &["testChannelId"]),
+    ("feishu", "feishu", &["testChatId"]),
+    ("whatsapp", "whatsapp", &[]),
+    ("imessage", "imessage", &[]),
+    ("wechat", "wechat", &[]),
+    ("dingtalk", "dingtalk", &[]),
+    ("matrix", "matrix", &["testRoomId"]),
+    ("signal", "signal", &["testNumber"]),
+];
+
+/// 基于扫码登录会话（而非 token）的渠道
+const SESSION_CHANNELS: &[&str] = &["whatsapp", "imessage"];
+
+/// openclaw channels status 输出的缓存时间
+const CHANNEL_STATUS_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// 缓存的 openclaw channels status 输出（执行时间, 输出）
+static CHANNEL_STATUS_CACHE: std::sync::Mutex<Option<(std::time::Instant, Option<String>)>> =
+    std::sync::Mutex::new(None);
+
+/// 获取 openclaw channels status 输出，短时间内复用缓存，避免频繁调用 CLI
+fn cached_channels_status_output() -> Option<String> {
+    let mut cache = CHANNEL_STATUS_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((fetched_at, output)) = cache.as_ref() {
+        if fetched_at.elapsed() < CHANNEL_STATUS_CACHE_TTL {
+            return output.clone();
+        }
+    }
+
+    debug!("[渠道配置] 执行 openclaw channels status 获取登录状态...");
+    let output = shell::run_openclaw(&["channels", "status"])
+        .map_err(|e| debug!("[渠道配置] 获取渠道状态失败: {}", e))
+        .ok();
+    *cache = Some((std::time::Instant::now(), output.clone()));
+    output
+}
+
+/// 从 openclaw channels status 文本输出解析会话渠道的登录状态
+/// 格式: "- WhatsApp default: enabled, configured, linked, ..."
+fn parse_session_login_status(output: &str, channel_id: &str) -> &'static str {
+    let channel_lower = channel_id.to_lowercase();
+    let Some(line) = output
+        .lines()
+        .map(|line| line.trim().to_lowercase())
+        .find(|line| line.starts_with("- ") && line.contains(&channel_lower))
+    else {
+        return "unknown";
+    };
+
+    let unlinked_markers = ["not linked", "unlinked", "not logged", "logged out"];
+    if unlinked_markers.iter().any(|m| line.contains(m)) {
+        "unlinked"
+    } else if line.contains("linked") {
+        "linked"
+    } else {
+        "unknown"
+    }
+}
+
+/// 所有渠道测试字段的并集（去重）
+fn channel_test_fields() -> Vec<&'static str> {
+    let mut fields: Vec<&'static str> = Vec::new();
+    for (_, _, test_fields) in CHANNEL_TYPES {
+        for field in test_fields.iter() {
+            if !fields.contains(field) {
+                fields.push(field);
+            }
+        }
+    }
+    fields
+}
+
+/// 获取渠道配置 - 从 openclaw.json 和 env 文件读取
+#[command]
+pub async fn get_channels_config() -> Result<Vec<ChannelConfig>, String> {
+    info!("[渠道配置] 获取渠道配置列表...");
+
+    let config = load_openclaw_config()?;
+    let channels_obj = config.get("channels").cloned().unwrap_or(json!({}));
+    let bindings_obj = config.get("bindings").cloned().unwrap_or(json!([]));
+    let account_bindings = parse_account_bindings(&bindings_obj);
+    let env_path = platform::get_env_file_path();
+    debug!("[渠道配置] 环境文件路径: {}", env_path);
+
+    let mut channels = Vec::new();
+    // 会话渠道的状态输出只获取一次
+    let mut session_status_output: Option<Option<String>> = None;
+
+    for &(channel_id, channel_type, test_fields) in CHANNEL_TYPES {
+        let channel_config = channels_obj.get(channel_id);
+
+        let mut accounts = channel_config
+            .and_then(|c| c.get("accounts"))
+            .and_then(|v| v.as_object())
+            .map(|obj| {
+                obj.iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect::<HashMap<String, Value>>()
+            })
+            .unwrap_or_default();
+
+        // bindings 存在但 accounts 不存在时，自动补齐空账号节点，便于前端直接编辑
+        for ((binding_channel, account_id), agent_id) in &account_bindings {
+            if binding_channel == channel_id {
+                let entry = accounts.entry(account_id.clone()).or_insert_with(|| json!({}));
+                if let Some(obj) = entry.as_object_mut() {
+                    // bindings 为权威来源，始终写入 agentId，避免账号内遗留旧值
+                    obj.insert("agentId".to_string(), json!(agent_id));
+                }
+            }
+        }
+
+        let explicit_enabled = channel_config
+            .and_then(|c| c.get("enabled"))
+            .and_then(|v| v.as_bool());
+
+        // 将渠道配置转换为 HashMap（兼容旧版前端平铺字段）
+        let mut config_map: HashMap<String, Value> = if let Some(cfg) = channel_config {
+            if let Some(obj) = cfg.as_object() {
+                obj.iter()
+                    .filter(|(k, _)| *k != "enabled" && *k != "accounts")
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect()
+            } else {
+                HashMap::new()
+            }
+        } else {
+            HashMap::new()
+        };
+
+        // 从 env 文件读取测试字段
+        for field in test_fields {
+            let env_key = format!(
+                "OPENCLAW_{}_{}",
+                channel_id.to_uppercase(),
+                field.to_uppercase()
+            );
+            if let Some(value) = file::read_env_value(&env_path, &env_key) {
+                config_map.insert(field.to_string(), json!(value));
+            }
+        }
+
+        let has_accounts = !accounts.is_empty();
+        let has_config = !config_map.is_empty() || explicit_enabled == Some(true) || has_accounts;
+        // 未写 enabled 时与 OpenClaw 一致：openclaw.json 中已配置的渠道默认启用
+        let enabled = explicit_enabled.unwrap_or(channel_config.is_some() && has_config);
+
+        let login_status = if has_config && SESSION_CHANNELS.contains(&channel_id) {
+            let output = session_status_output.get_or_insert_with(cached_channels_status_output);
+            let status = output
+                .as_deref()
+                .map(|o| parse_session_login_status(o, channel_id))
+                .unwrap_or("unknown");
+            Some(status.to_string())
+        } else {
+            None
+        };
+
+        channels.push(ChannelConfig {
+            id: channel_id.to_string(),
+            channel_type: channel_type.to_string(),
+            enabled,
+            configured: has_config,
+            config: config_map,
+            accounts: if accounts.is_empty() { None } else { Some(accounts) },
+            login_status,
+        });
+    }
+
+    info!("[渠道配置] ✓ 返回 {} 个渠道配置", channels.len());
+    for ch in &channels {
+        debug!(
+            "[渠道配置] - {}: enabled={}, configured={}",
+            ch.id, ch.enabled, ch.configured
+        );
+    }
+    Ok(channels)
+}
+
+/// 将单个渠道写入配置（channels、plugins.allow/entries、bindings）
+/// 返回需写入 env 文件的测试字段（这些字段不保存到 openclaw.json）
+fn apply_channel_config(config: &mut Value, channel: &ChannelConfig) -> Vec<(String, String)> {
+    // 确保 channels 对象存在
+    if config.get("channels").is_none() {
+        config["channels"] = json!({});
+    }
+
+    // 这些字段只用于测试，不保存到 openclaw.json，而是保存到 env 文件
+    let test_only_fields = channel_test_fields();
+    let mut env_values = Vec::new();
+
+    // 构建渠道配置
+    let mut channel_obj = json!({
+        "enabled": true
+    });
+
+    // 添加渠道特定配置
+    for (key, value) in &channel.config {
+        if test_only_fields.contains(&key.as_str()) {
+            let env_key = format!(
+                "OPENCLAW_{}_{}",
+                channel.id.to_uppercase(),
+                key.to_uppercase()
+            );
+            if let Some(val_str) = value.as_str() {
+                env_values.push((env_key, val_str.to_string()));
+            }
+        } else {
+            // 保存到 openclaw.json
+            channel_obj[key] = value.clone();
+        }
+    }
+
+    // 保留/写入 accounts 多账号配置（兼容 channels.<provider>.accounts）
+    if let Some(accounts) = &channel.accounts {
+        let accounts_obj = accounts
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<serde_json::Map<String, Value>>();
+        if !accounts_obj.is_empty() {
+            channel_obj["accounts"] = Value::Object(accounts_obj);
+        }
+    } else if let Some(existing_accounts) = config
+        .pointer(&format!("/channels/{}/accounts", channel.id))
+        .cloned()
+    {
+        channel_obj["accounts"] = existing_accounts;
+    }
 
-    info!(
-        "[官方 Provider] ✓ 返回 {} 个官方 Provider 预设",
-        providers.len()
-    );
-    Ok(providers)
+    // 更新 channels 配置
+    config["channels"][&channel.id] = channel_obj;
+
+    // 更新 plugins.allow / plugins.entries - 确保渠道插件已启用
+    set_plugin_enabled(config, &channel.id, true);
+
+    // 同步更新 bindings：只替换当前 channel 的账号映射，其它渠道保持不变
+    let existing_bindings = config.get("bindings").cloned().unwrap_or(json!([]));
+    let mut all_pairs = parse_account_bindings(&existing_bindings);
+
+    all_pairs.retain(|(binding_channel, _), _| binding_channel != &channel.id);
+
+    if let Some(accounts) = &channel.accounts {
+        for (account_id, account_cfg) in accounts {
+            if let Some(obj) = account_cfg.as_object() {
+                if let Some(agent_id) = obj.get("agentId").and_then(|v| v.as_str()) {
+                    if !agent_id.trim().is_empty() {
+                        all_pairs.insert(
+                            (channel.id.clone(), account_id.clone()),
+                            agent_id.to_string(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    config["bindings"] = merge_bindings_payload_by_shape(&existing_bindings, &all_pairs);
+    env_values
 }
 
-/// 获取 AI 配置概览
+/// 对比保存前后的 bindings，列出被丢弃的账号绑定：
+/// 原有的重复绑定（只保留第一条）以及该渠道中不再出现的绑定
+fn dropped_channel_bindings(
+    before: &Value,
+    after: &Value,
+    channel_id: &str,
+) -> Vec<ConfigValidationIssue> {
+    let empty = json!([]);
+    let (before_pairs, duplicates) =
+        parse_account_bindings_with_duplicates(before.get("bindings").unwrap_or(&empty));
+    let after_pairs = parse_account_bindings(after.get("bindings").unwrap_or(&empty));
+
+    let mut issues: Vec<ConfigValidationIssue> = duplicates
+        .into_iter()
+        .map(|dup| ConfigValidationIssue {
+            path: format!("bindings.{}.{}", dup.channel, dup.account_id),
+            message: format!(
+                "账号 {}/{} 的重复绑定已丢弃：保留 {}，丢弃 {}",
+                dup.channel, dup.account_id, dup.kept_agent_id, dup.ignored_agent_id
+            ),
+            variable: None,
+        })
+        .collect();
+
+    let mut removed: Vec<(&(String, String), &String)> = before_pairs
+        .iter()
+        .filter(|(key, _)| key.0 == channel_id && !after_pairs.contains_key(*key))
+        .collect();
+    removed.sort();
+    for ((channel, account_id), agent_id) in removed {
+        issues.push(ConfigValidationIssue {
+            path: format!("bindings.{}.{}", channel, account_id),
+            message: format!(
+                "账号 {}/{} 未设置 agentId，原有绑定 {} 已移除",
+                channel, account_id, agent_id
+            ),
+            variable: None,
+        });
+    }
+    issues
+}
+
+/// 保存渠道配置的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveChannelResult {
+    pub message: String,
+    /// 保存时被丢弃的绑定与仍存在的账号冲突，供前端提示
+    pub issues: Vec<ConfigValidationIssue>,
+}
+
+/// 保存渠道配置 - 保存到 openclaw.json
 #[command]
-pub async fn get_ai_config() -> Result<AIConfigOverview, String> {
-    info!("[AI 配置] 获取 AI 配置概览...");
+pub async fn save_channel_config(channel: ChannelConfig) -> Result<SaveChannelResult, String> {
+    let _config_guard = lock_config_writes().await?;
+    info!(
+        "[保存渠道配置] 保存渠道配置: {} ({})",
+        channel.id, channel.channel_type
+    );
 
-    let config_path = platform::get_config_file_path();
-    info!("[AI 配置] 配置文件路径: {}", config_path);
+    let mut config = load_openclaw_config_raw()?;
+    let original = config.clone();
+    let env_values = apply_channel_config(&mut config, &channel);
 
-    let config = load_openclaw_config()?;
-    let normalized = normalize_and_validate_config(&config)?;
-    debug!(
-        "[AI 配置] 配置根字段数: {}",
-        normalized.as_object().map(|o| o.len()).unwrap_or(0)
+    let mut issues = dropped_channel_bindings(&original, &config, &channel.id);
+    issues.extend(collect_channel_account_issues(&config));
+    for issue in &issues {
+        warn!("[保存渠道配置] {}: {}", issue.path, issue.message);
+    }
+
+    // 保存配置
+    info!("[保存渠道配置] 写入配置文件...");
+    if let Err(e) = save_openclaw_config(&config) {
+        error!("[保存渠道配置] ✗ 保存失败: {}", e);
+        return Err(e);
+    }
+    // 配置保存成功后再一次性写入 env，避免配置保存失败时留下孤立的 env 值
+    if !env_values.is_empty() {
+        let env_path = platform::get_env_file_path();
+        debug!("[保存渠道配置] 环境文件路径: {}", env_path);
+        file::set_env_values(&env_path, &env_values)
+            .map_err(|e| format!("写入环境变量文件失败: {}", e))?;
+    }
+    info!(
+        "[保存渠道配置] ✓ {} 配置保存成功",
+        channel.channel_type
     );
+    drop(_config_guard);
+
+    // 渠道依赖的插件未安装时提示，网关否则会在运行时加载失败
+    let plugin = check_channel_plugin(channel.id.clone()).await?;
+    let message = match plugin.notice() {
+        Some(notice) => {
+            warn!("[保存渠道配置] 渠道 {} 的插件不可用: {}", channel.id, notice);
+            format!("{} 配置已保存。注意：{}", channel.channel_type, notice)
+        }
+        None => format!("{} 配置已保存", channel.channel_type),
+    };
+    Ok(SaveChannelResult { message, issues })
+}
 
-    // 解析主模型
-    let primary_model = normalized
-        .pointer("/agents/defaults/model/primary")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string());
-    info!("[AI 配置] 主模型: {:?}", primary_model);
+/// 各类型渠道必须提供的字段（写在渠道顶层，或每个账号中都提供）
+const CHANNEL_REQUIRED_FIELDS: &[(&str, &[&str])] = &[
+    ("telegram", &["botToken"]),
+    ("discord", &["botToken"]),
+    ("slack", &["botToken"]),
+    ("feishu", &["appId", "appSecret"]),
+];
 
-    // 解析可用模型列表
-    let available_models: Vec<String> = normalized
-        .pointer("/agents/defaults/models")
-        .and_then(|v| v.as_object())
-        .map(|obj| obj.keys().cloned().collect())
+/// 校验渠道 id 与按类型必填的字段
+fn validate_channel_required_fields(channel: &ChannelConfig) -> Result<(), String> {
+    if channel.id.trim().is_empty() {
+        return Err("渠道 id 不能为空".to_string());
+    }
+    let channel_type = if channel.channel_type.is_empty() {
+        channel.id.as_str()
+    } else {
+        channel.channel_type.as_str()
+    };
+    let required = CHANNEL_REQUIRED_FIELDS
+        .iter()
+        .find(|(id, _)| *id == channel_type)
+        .map(|(_, fields)| *fields)
         .unwrap_or_default();
-    info!("[AI 配置] 可用模型数: {}", available_models.len());
 
-    // 解析已配置的 Provider
-    let mut configured_providers: Vec<ConfiguredProvider> = Vec::new();
+    let filled = |value: Option<&Value>| {
+        value
+            .and_then(|v| v.as_str())
+            .is_some_and(|s| !s.trim().is_empty())
+    };
+    let accounts: Vec<&Value> = channel.accounts.iter().flat_map(|a| a.values()).collect();
+    let missing: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|field| {
+            !filled(channel.config.get(*field))
+                && (accounts.is_empty() || accounts.iter().any(|a| !filled(a.get(*field))))
+        })
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("缺少必填字段: {}", missing.join(", ")))
+    }
+}
 
-    let providers_value = normalized.pointer("/models/providers");
-    info!("[AI 配置] providers 节点存在: {}", providers_value.is_some());
+/// 单个渠道的导入结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelImportResult {
+    pub channel_id: String,
+    pub success: bool,
+    pub message: String,
+}
 
-    if let Some(providers) = providers_value.and_then(|v| v.as_object()) {
-        info!("[AI 配置] 找到 {} 个 Provider", providers.len());
+/// 批量导入渠道：先逐个校验，全部通过后一次读取-修改-保存
+/// 任一渠道校验失败时不写入任何内容，结果中标明失败原因
+#[command]
+pub async fn import_channels(
+    channels: Vec<ChannelConfig>,
+) -> Result<Vec<ChannelImportResult>, String> {
+    info!("[导入渠道] 导入 {} 个渠道...", channels.len());
+    if channels.is_empty() {
+        return Err("未提供要导入的渠道".to_string());
+    }
 
-        for (provider_name, provider_config) in providers {
-            info!("[AI 配置] 解析 Provider: {}", provider_name);
+    let errors: Vec<Option<String>> = channels
+        .iter()
+        .enumerate()
+        .map(|(index, channel)| {
+            if channels[..index].iter().any(|c| c.id == channel.id) {
+                return Some(format!("渠道 {} 重复", channel.id));
+            }
+            validate_channel_required_fields(channel).err()
+        })
+        .collect();
+    if errors.iter().any(Option::is_some) {
+        warn!("[导入渠道] 存在校验失败的渠道，未写入任何配置");
+        return Ok(channels
+            .iter()
+            .zip(errors)
+            .map(|(channel, error)| ChannelImportResult {
+                channel_id: channel.id.clone(),
+                success: false,
+                message: error.unwrap_or_else(|| "未导入：其它渠道校验失败".to_string()),
+            })
+            .collect());
+    }
 
-            let base_url = provider_config
-                .get("baseUrl")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
+    let config_guard = lock_config_writes().await?;
+    let mut config = load_openclaw_config_raw()?;
+    let env_values: Vec<(String, String)> = channels
+        .iter()
+        .flat_map(|channel| apply_channel_config(&mut config, channel))
+        .collect();
+    for issue in collect_channel_account_issues(&config) {
+        warn!("[导入渠道] {}: {}", issue.path, issue.message);
+    }
+    save_openclaw_config(&config)?;
+    // 配置保存成功后再一次性写入 env，避免配置保存失败时留下孤立的 env 值
+    if !env_values.is_empty() {
+        file::set_env_values(&platform::get_env_file_path(), &env_values)
+            .map_err(|e| format!("写入环境变量文件失败: {}", e))?;
+    }
+    drop(config_guard);
+
+    let mut results = Vec::new();
+    for channel in &channels {
+        let plugin = check_channel_plugin(channel.id.clone()).await?;
+        let message = match plugin.notice() {
+            Some(notice) => format!("已导入。注意：{}", notice),
+            None => "已导入".to_string(),
+        };
+        results.push(ChannelImportResult {
+            channel_id: channel.id.clone(),
+            success: true,
+            message,
+        });
+    }
+    info!("[导入渠道] ✓ 已导入 {} 个渠道", results.len());
+    Ok(results)
+}
 
-            let api_key = provider_config
-                .get("apiKey")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
+/// 清空渠道配置 - 从 openclaw.json 中删除指定渠道的配置
+#[command]
+pub async fn clear_channel_config(channel_id: String) -> Result<String, String> {
+    info!("[清空渠道配置] 清空渠道配置: {}", channel_id);
+    let _config_guard = lock_config_writes().await?;
 
-            let api_key_masked = api_key.as_ref().map(|key| {
-                if key.len() > 8 {
-                    format!("{}...{}", &key[..4], &key[key.len() - 4..])
-                } else {
-                    "****".to_string()
-                }
-            });
+    let mut config = load_openclaw_config_raw()?;
+    let env_path = platform::get_env_file_path();
 
-            // 解析模型列表
-            let models_array = provider_config.get("models").and_then(|v| v.as_array());
-            info!(
-                "[AI 配置] Provider {} 的 models 数组: {:?}",
-                provider_name,
-                models_array.map(|a| a.len())
-            );
+    // 从 channels 对象中删除该渠道
+    if let Some(channels) = config.get_mut("channels").and_then(|v| v.as_object_mut()) {
+        channels.remove(&channel_id);
+        info!("[清空渠道配置] 已从 channels 中删除: {}", channel_id);
+    }
 
-            let models: Vec<ConfiguredModel> = models_array
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|m| {
-                            let id = m.get("id")?.as_str()?.to_string();
-                            let name = m
-                                .get("name")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or(&id)
-                                .to_string();
-                            let full_id = format!("{}/{}", provider_name, id);
-                            let is_primary = primary_model.as_ref() == Some(&full_id);
-
-                            info!("[AI 配置] 解析模型: {} (is_primary: {})", full_id, is_primary);
-
-                            Some(ConfiguredModel {
-                                full_id,
-                                id,
-                                name,
-                                api_type: m.get("api").and_then(|v| v.as_str()).map(|s| s.to_string()),
-                                context_window: m
-                                    .get("contextWindow")
-                                    .and_then(|v| v.as_u64())
-                                    .map(|n| n as u32),
-                                max_tokens: m
-                                    .get("maxTokens")
-                                    .and_then(|v| v.as_u64())
-                                    .map(|n| n as u32),
-                                is_primary,
-                            })
-                        })
-                        .collect()
-                })
-                .unwrap_or_default();
+    // 从 plugins.allow 数组中删除
+    if let Some(allow_arr) = config.pointer_mut("/plugins/allow").and_then(|v| v.as_array_mut()) {
+        allow_arr.retain(|v| v.as_str() != Some(&channel_id));
+        info!("[清空渠道配置] 已从 plugins.allow 中删除: {}", channel_id);
+    }
 
-            info!(
-                "[AI 配置] Provider {} 解析完成: {} 个模型",
-                provider_name,
-                models.len()
-            );
+    // 从 plugins.entries 中删除
+    if let Some(entries) = config.pointer_mut("/plugins/entries").and_then(|v| v.as_object_mut()) {
+        entries.remove(&channel_id);
+        info!("[清空渠道配置] 已从 plugins.entries 中删除: {}", channel_id);
+    }
 
-            configured_providers.push(ConfiguredProvider {
-                name: provider_name.clone(),
-                base_url,
-                api_key_masked,
-                has_api_key: api_key.is_some(),
-                models,
-            });
+    // 清除该渠道相关 bindings，避免遗留 accountId -> agentId 映射
+    let existing_bindings = config.get("bindings").cloned().unwrap_or(json!([]));
+    let mut all_pairs = parse_account_bindings(&existing_bindings);
+    all_pairs.retain(|(binding_channel, _), _| binding_channel != &channel_id);
+    config["bindings"] = merge_bindings_payload_by_shape(&existing_bindings, &all_pairs);
+
+    // 清除相关的环境变量
+    let env_keys = channel_test_fields().into_iter().map(|field| {
+        format!(
+            "OPENCLAW_{}_{}",
+            channel_id.to_uppercase(),
+            field.to_uppercase()
+        )
+    });
+    for env_key in env_keys {
+        let _ = file::remove_env_value(&env_path, &env_key);
+    }
+
+    // 保存配置
+    match save_openclaw_config(&config) {
+        Ok(_) => {
+            info!("[清空渠道配置] ✓ {} 配置已清空", channel_id);
+            Ok(format!("{} 配置已清空", channel_id))
+        }
+        Err(e) => {
+            error!("[清空渠道配置] ✗ 清空失败: {}", e);
+            Err(e)
         }
-    } else {
-        info!("[AI 配置] 未找到 providers 配置或格式不正确");
     }
+}
+
+/// 批量切换渠道启用状态：同步 channels.<id>.enabled、plugins.allow 与 plugins.entries
+/// - 仅修改启用标记，accounts / bindings 等配置保持不变，重新启用即可恢复
+/// - 未配置的渠道直接报错，避免凭空生成空渠道
+fn apply_channels_enabled(
+    config: &mut Value,
+    channel_ids: &[String],
+    enabled: bool,
+) -> Result<(), String> {
+    for channel_id in channel_ids {
+        if config
+            .pointer(&format!("/channels/{}", channel_id))
+            .and_then(|v| v.as_object())
+            .is_none()
+        {
+            return Err(format!("渠道 {} 未配置", channel_id));
+        }
+    }
+
+    for channel_id in channel_ids {
+        config["channels"][channel_id]["enabled"] = json!(enabled);
+        set_plugin_enabled(config, channel_id, enabled);
+    }
+
+    Ok(())
+}
 
+/// 启用/禁用单个渠道（保留渠道配置，仅切换 enabled 与插件启用状态）
+#[command]
+pub async fn set_channel_enabled(channel_id: String, enabled: bool) -> Result<String, String> {
+    let _config_guard = lock_config_writes().await?;
     info!(
-        "[AI 配置] ✓ 最终结果 - 主模型: {:?}, {} 个 Provider, {} 个可用模型",
-        primary_model,
-        configured_providers.len(),
-        available_models.len()
+        "[切换渠道] {} 渠道: {}",
+        if enabled { "启用" } else { "禁用" },
+        channel_id
     );
 
-    let typed: OpenClawConfig = serde_json::from_value(normalized)
-        .map_err(|e| format!("AI 配置结构无效: {}", e))?;
+    let mut config = load_openclaw_config_raw()?;
+    apply_channels_enabled(&mut config, std::slice::from_ref(&channel_id), enabled)?;
+    save_openclaw_config(&config)?;
 
-    Ok(AIConfigOverview {
-        primary_model,
-        configured_providers,
-        available_models,
-        agents_list: typed.agents.list,
-        bindings: typed.bindings,
-    })
+    info!("[切换渠道] ✓ {} enabled={}", channel_id, enabled);
+    Ok(format!(
+        "已{}渠道 {}",
+        if enabled { "启用" } else { "禁用" },
+        channel_id
+    ))
 }
 
-/// 添加或更新 Provider
+/// 批量启用/禁用渠道（一次写入配置）
 #[command]
-pub async fn save_provider(
-    provider_name: String,
-    base_url: String,
-    api_key: Option<String>,
-    api_type: String,
-    models: Vec<ModelConfig>,
+pub async fn set_channels_enabled(
+    channel_ids: Vec<String>,
+    enabled: bool,
 ) -> Result<String, String> {
+    let _config_guard = lock_config_writes().await?;
     info!(
-        "[保存 Provider] 保存 Provider: {} ({} 个模型)",
-        provider_name,
-        models.len()
+        "[批量切换渠道] {} 渠道: {:?}",
+        if enabled { "启用" } else { "禁用" },
+        channel_ids
     );
 
     let mut config = load_openclaw_config_raw()?;
+    apply_channels_enabled(&mut config, &channel_ids, enabled)?;
 
-    // 确保路径存在
-    if config.get("models").is_none() {
-        config["models"] = json!({});
+    match save_openclaw_config(&config) {
+        Ok(_) => {
+            info!("[批量切换渠道] ✓ 已更新 {} 个渠道", channel_ids.len());
+            Ok(format!(
+                "已{} {} 个渠道",
+                if enabled { "启用" } else { "禁用" },
+                channel_ids.len()
+            ))
+        }
+        Err(e) => {
+            error!("[批量切换渠道] ✗ 保存失败: {}", e);
+            Err(e)
+        }
+    }
+}
+
+// ============ 插件管理 ============
+
+/// 已安装插件信息（解析自 openclaw plugins list）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub enabled: bool,
+    /// 原始输出行，便于前端展示与排查
+    pub raw: String,
+}
+
+/// 判断是否像版本号（如 0.1.2 / v1.0.0）
+fn looks_like_version(token: &str) -> bool {
+    let token = token.strip_prefix('v').unwrap_or(token);
+    token.chars().next().is_some_and(|c| c.is_ascii_digit()) && token.contains('.')
+}
+
+/// 拆分 "name@version"，兼容 @scope/name@version 形式
+fn split_name_version(token: &str) -> (String, Option<String>) {
+    match token.rfind('@') {
+        Some(idx) if idx > 0 && looks_like_version(&token[idx + 1..]) => {
+            (token[..idx].to_string(), Some(token[idx + 1..].to_string()))
+        }
+        _ => (token.to_string(), None),
     }
-    if config["models"].get("providers").is_none() {
-        config["models"]["providers"] = json!({});
+}
+
+/// 从 plugins.entries 推断启用状态（兼容 @scope/name 与短名两种 key）
+fn plugin_enabled_from_entries(entries: Option<&Value>, name: &str) -> Option<bool> {
+    let entries = entries?.as_object()?;
+    let short_name = name.rsplit('/').next().unwrap_or(name);
+    [name, short_name]
+        .iter()
+        .find_map(|key| entries.get(*key))
+        .and_then(|entry| entry.get("enabled"))
+        .and_then(|v| v.as_bool())
+}
+
+/// 解析 `openclaw plugins list` 输出
+/// - 支持 "name@version" 与 "name version" 两种常见格式，兼容表格竖线分隔
+/// - 启用状态优先取输出中的 enabled/disabled 标记，其次取 plugins.entries，默认启用
+fn parse_plugins_list(output: &str, entries: Option<&Value>) -> Vec<PluginInfo> {
+    let mut plugins: Vec<PluginInfo> = Vec::new();
+
+    for line in output.lines() {
+        let cleaned = line.replace(['│', '|'], " ");
+        let mut tokens = cleaned
+            .split_whitespace()
+            .filter(|t| !matches!(*t, "-" | "*" | "•" | "✓" | "✗" | "✔" | "✘"));
+        let Some(first) = tokens.next() else {
+            continue;
+        };
+
+        // 跳过标题行、表头与边框
+        let lower_first = first.to_lowercase();
+        if first.ends_with(':')
+            || matches!(lower_first.as_str(), "name" | "plugins" | "plugin" | "installed" | "no")
+            || !first.chars().next().is_some_and(|c| c.is_alphanumeric() || c == '@')
+        {
+            continue;
+        }
+
+        let (name, mut version) = split_name_version(first);
+        let rest: Vec<&str> = tokens.collect();
+        if version.is_none() {
+            version = rest
+                .iter()
+                .find(|t| looks_like_version(t))
+                .map(|t| t.strip_prefix('v').unwrap_or(t).to_string());
+        }
+
+        let lower_line = line.to_lowercase();
+        let enabled = if lower_line.contains("disabled") {
+            false
+        } else if lower_line.contains("enabled") || lower_line.contains("loaded") {
+            true
+        } else {
+            plugin_enabled_from_entries(entries, &name).unwrap_or(true)
+        };
+
+        if plugins.iter().any(|p| p.name == name) {
+            continue;
+        }
+        plugins.push(PluginInfo {
+            name,
+            version,
+            enabled,
+            raw: line.trim().to_string(),
+        });
     }
-    if config.get("agents").is_none() {
-        config["agents"] = json!({});
+
+    plugins
+}
+
+/// 切换插件启用状态：同步 plugins.allow 白名单与 plugins.entries.<id>.enabled
+/// - 顺带清理 allow 中的空字符串
+/// - entries 只修改 enabled，保留插件自身的其它配置
+fn set_plugin_enabled(config: &mut Value, plugin_id: &str, enabled: bool) {
+    if !config.get("plugins").is_some_and(|v| v.is_object()) {
+        config["plugins"] = json!({});
     }
-    if config["agents"].get("defaults").is_none() {
-        config["agents"]["defaults"] = json!({});
+    if !config["plugins"].get("allow").is_some_and(|v| v.is_array()) {
+        config["plugins"]["allow"] = json!([]);
     }
-    if config["agents"]["defaults"].get("models").is_none() {
-        config["agents"]["defaults"]["models"] = json!({});
+    if !config["plugins"].get("entries").is_some_and(|v| v.is_object()) {
+        config["plugins"]["entries"] = json!({});
     }
 
-    // 构建模型配置
-    let models_json: Vec<Value> = models
-        .iter()
-        .map(|m| {
-            let mut model_obj = json!({
-                "id": m.id,
-                "name": m.name,
-                "api": m.api.clone().unwrap_or(api_type.clone()),
-                "input": if m.input.is_empty() { vec!["text".to_string()] } else { m.input.clone() },
-            });
+    if let Some(allow_arr) = config["plugins"]["allow"].as_array_mut() {
+        allow_arr.retain(|v| v.as_str().map(|s| !s.trim().is_empty()).unwrap_or(true));
 
-            if let Some(cw) = m.context_window {
-                model_obj["contextWindow"] = json!(cw);
-            }
-            if let Some(mt) = m.max_tokens {
-                model_obj["maxTokens"] = json!(mt);
-            }
-            if let Some(r) = m.reasoning {
-                model_obj["reasoning"] = json!(r);
-            }
-            if let Some(cost) = &m.cost {
-                model_obj["cost"] = json!({
-                    "input": cost.input,
-                    "output": cost.output,
-                    "cacheRead": cost.cache_read,
-                    "cacheWrite": cost.cache_write,
-                });
-            } else {
-                model_obj["cost"] = json!({
-                    "input": 0,
-                    "output": 0,
-                    "cacheRead": 0,
-                    "cacheWrite": 0,
-                });
+        let plugin_id_val = json!(plugin_id);
+        if enabled {
+            if !allow_arr.contains(&plugin_id_val) {
+                allow_arr.push(plugin_id_val);
             }
+        } else {
+            allow_arr.retain(|v| v != &plugin_id_val);
+        }
+    }
 
-            model_obj
-        })
+    let entry = &mut config["plugins"]["entries"][plugin_id];
+    if !entry.is_object() {
+        *entry = json!({});
+    }
+    entry["enabled"] = json!(enabled);
+}
+
+/// 插件与渠道配置不一致的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginConsistencyKind {
+    /// channels 中已启用的渠道不在 plugins.allow 中（且未通过 entries 显式禁用）
+    ChannelNotAllowed,
+    /// plugins.allow 中的渠道插件没有对应的 channels 配置
+    AllowedWithoutChannel,
+    /// plugins.allow 中的空字符串
+    EmptyAllowEntry,
+    /// plugins.entries 中已启用的插件不在 plugins.allow 中
+    EntryNotAllowed,
+}
+
+/// 插件与渠道配置的不一致项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginConsistencyIssue {
+    pub kind: PluginConsistencyKind,
+    pub plugin_id: String,
+    pub message: String,
+}
+
+/// 检查 plugins.allow / plugins.entries 与 channels 是否一致
+/// allow 为空或缺失时视为未启用白名单，不检查「不在 allow 中」的两类问题
+fn find_plugin_consistency_issues(config: &Value) -> Vec<PluginConsistencyIssue> {
+    let allow: Vec<&Value> = config
+        .pointer("/plugins/allow")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().collect())
+        .unwrap_or_default();
+    let allowed: Vec<&str> = allow
+        .iter()
+        .filter_map(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty())
         .collect();
+    let allowlist_active = !allowed.is_empty();
+    let channels = config.get("channels").and_then(|v| v.as_object());
+    let entries = config.pointer("/plugins/entries").and_then(|v| v.as_object());
+    let entry_enabled = |id: &str| {
+        entries
+            .and_then(|e| e.get(id))
+            .map(|entry| entry.get("enabled").and_then(|v| v.as_bool()) != Some(false))
+    };
+    let issue = |kind, plugin_id: &str, message: String| PluginConsistencyIssue {
+        kind,
+        plugin_id: plugin_id.to_string(),
+        message,
+    };
 
-    // 构建 Provider 配置
-    let mut provider_config = json!({
-        "baseUrl": base_url,
-        "models": models_json,
-    });
+    let mut issues = Vec::new();
+    let empty_count = allow
+        .iter()
+        .filter(|v| v.as_str().is_some_and(|s| s.trim().is_empty()))
+        .count();
+    if empty_count > 0 {
+        issues.push(issue(
+            PluginConsistencyKind::EmptyAllowEntry,
+            "",
+            format!("plugins.allow 中有 {} 个空字符串", empty_count),
+        ));
+    }
 
-    // 处理 API Key：如果传入了新的非空 key，使用新的；否则保留原有的
-    if let Some(key) = api_key {
-        if !key.is_empty() {
-            // 使用新传入的 API Key
-            provider_config["apiKey"] = json!(key);
-            info!("[保存 Provider] 使用新的 API Key");
-        } else {
-            // 空字符串表示不更改，尝试保留原有的 API Key
-            if let Some(existing_key) = config
-                .pointer(&format!("/models/providers/{}/apiKey", provider_name))
-                .and_then(|v| v.as_str())
+    for (channel_id, channel) in channels.into_iter().flatten() {
+        let enabled = channel.get("enabled").and_then(|v| v.as_bool()) != Some(false);
+        // entries 中显式禁用说明是通过「禁用插件」有意为之
+        if allowlist_active
+            && enabled
+            && !allowed.contains(&channel_id.as_str())
+            && entry_enabled(channel_id) != Some(false)
+        {
+            issues.push(issue(
+                PluginConsistencyKind::ChannelNotAllowed,
+                channel_id,
+                format!("渠道 {} 已配置但不在 plugins.allow 中，Gateway 不会加载", channel_id),
+            ));
+        }
+    }
+
+    for plugin_id in &allowed {
+        let is_channel_plugin = CHANNEL_TYPES.iter().any(|(id, _, _)| id == plugin_id);
+        if is_channel_plugin && !channels.is_some_and(|c| c.contains_key(*plugin_id)) {
+            issues.push(issue(
+                PluginConsistencyKind::AllowedWithoutChannel,
+                plugin_id,
+                format!("plugins.allow 包含渠道插件 {}，但 channels 中没有其配置", plugin_id),
+            ));
+        }
+    }
+
+    if allowlist_active {
+        for plugin_id in entries.into_iter().flat_map(|e| e.keys()) {
+            let is_channel = channels.is_some_and(|c| c.contains_key(plugin_id));
+            if !is_channel
+                && entry_enabled(plugin_id) == Some(true)
+                && !allowed.contains(&plugin_id.as_str())
             {
-                provider_config["apiKey"] = json!(existing_key);
-                info!("[保存 Provider] 保留原有的 API Key");
+                issues.push(issue(
+                    PluginConsistencyKind::EntryNotAllowed,
+                    plugin_id,
+                    format!("plugins.entries 中已启用 {}，但它不在 plugins.allow 中", plugin_id),
+                ));
             }
         }
-    } else {
-        // None 表示不更改，尝试保留原有的 API Key
-        if let Some(existing_key) = config
-            .pointer(&format!("/models/providers/{}/apiKey", provider_name))
-            .and_then(|v| v.as_str())
-        {
-            provider_config["apiKey"] = json!(existing_key);
-            info!("[保存 Provider] 保留原有的 API Key");
+    }
+    issues
+}
+
+fn plugins_allow_mut(config: &mut Value) -> Option<&mut Vec<Value>> {
+    config.pointer_mut("/plugins/allow").and_then(|v| v.as_array_mut())
+}
+
+/// 修复插件一致性问题，返回已修复的问题
+/// - 未允许的渠道 / 已启用的 entries：加入 plugins.allow
+/// - 没有渠道配置的渠道插件：从 plugins.allow 与 plugins.entries 中移除（同清空渠道配置）
+/// - 空字符串：从 plugins.allow 中移除
+fn repair_plugin_consistency_issues(config: &mut Value) -> Vec<PluginConsistencyIssue> {
+    let issues = find_plugin_consistency_issues(config);
+    for issue in &issues {
+        match issue.kind {
+            PluginConsistencyKind::ChannelNotAllowed | PluginConsistencyKind::EntryNotAllowed => {
+                set_plugin_enabled(config, &issue.plugin_id, true);
+            }
+            PluginConsistencyKind::AllowedWithoutChannel => {
+                let plugin_id = issue.plugin_id.as_str();
+                if let Some(allow) = plugins_allow_mut(config) {
+                    allow.retain(|v| v.as_str() != Some(plugin_id));
+                }
+                let entries = config.pointer_mut("/plugins/entries");
+                if let Some(entries) = entries.and_then(|v| v.as_object_mut()) {
+                    entries.remove(plugin_id);
+                }
+            }
+            PluginConsistencyKind::EmptyAllowEntry => {
+                if let Some(allow) = plugins_allow_mut(config) {
+                    allow.retain(|v| !v.as_str().is_some_and(|s| s.trim().is_empty()));
+                }
+            }
         }
     }
+    issues
+}
 
-    // 保存 Provider 配置
-    config["models"]["providers"][&provider_name] = provider_config;
+/// 检查插件与渠道配置的一致性
+#[command]
+pub async fn audit_plugin_consistency() -> Result<Vec<PluginConsistencyIssue>, String> {
+    let config = load_openclaw_config_raw()?;
+    let issues = find_plugin_consistency_issues(&config);
+    info!("[插件管理] 插件一致性检查: {} 个问题", issues.len());
+    Ok(issues)
+}
 
-    // 将模型添加到 agents.defaults.models
-    for model in &models {
-        let full_id = format!("{}/{}", provider_name, model.id);
-        config["agents"]["defaults"]["models"][&full_id] = json!({});
+/// 修复插件与渠道配置的不一致，写入前自动备份；返回已修复的问题
+#[command]
+pub async fn repair_plugin_consistency() -> Result<Vec<PluginConsistencyIssue>, String> {
+    info!("[插件管理] 修复插件一致性...");
+    let _config_guard = lock_config_writes().await?;
+
+    let original = load_openclaw_config_raw()?;
+    let mut config = original.clone();
+    let repaired = repair_plugin_consistency_issues(&mut config);
+    if config == original {
+        info!("[插件管理] 插件配置一致，无需修复");
+        return Ok(repaired);
+    }
+
+    let backup_path = write_backup_snapshot(&original)?;
+    save_openclaw_config(&config)?;
+    info!(
+        "[插件管理] ✓ 已修复 {} 个插件一致性问题，备份: {}",
+        repaired.len(),
+        backup_path
+    );
+    Ok(repaired)
+}
+
+/// 启用/禁用插件：优先调用 openclaw CLI，再同步写入 plugins 配置
+async fn toggle_plugin(plugin_id: String, enabled: bool) -> Result<String, String> {
+    let _config_guard = lock_config_writes().await?;
+    let action = if enabled { "enable" } else { "disable" };
+    let label = if enabled { "启用" } else { "禁用" };
+    info!("[插件管理] {}插件: {}", label, plugin_id);
+
+    if plugin_id.trim().is_empty() {
+        return Err("插件 id 不能为空".to_string());
     }
 
-    // 更新元数据
-    let now = chrono::Utc::now().to_rfc3339();
-    if config.get("meta").is_none() {
-        config["meta"] = json!({});
+    // CLI 不可用或不支持该子命令时仅记录日志，以配置写入为准
+    match shell::run_openclaw(&["plugins", action, &plugin_id]) {
+        Ok(output) => debug!("[插件管理] plugins {} 输出: {}", action, output),
+        Err(e) => warn!("[插件管理] openclaw plugins {} 执行失败，仅修改配置: {}", action, e),
     }
-    config["meta"]["lastTouchedAt"] = json!(now);
 
+    let mut config = load_openclaw_config_raw()?;
+    set_plugin_enabled(&mut config, &plugin_id, enabled);
     save_openclaw_config(&config)?;
-    info!("[保存 Provider] ✓ Provider {} 保存成功", provider_name);
 
-    Ok(format!("Provider {} 已保存", provider_name))
+    info!("[插件管理] ✓ 插件 {} 已{}", plugin_id, label);
+    Ok(format!("插件 {} 已{}", plugin_id, label))
 }
 
-/// 删除 Provider
+/// 启用插件
 #[command]
-pub async fn delete_provider(provider_name: String) -> Result<String, String> {
-    info!("[删除 Provider] 删除 Provider: {}", provider_name);
+pub async fn enable_plugin(plugin_id: String) -> Result<String, String> {
+    toggle_plugin(plugin_id, true).await
+}
 
-    let mut config = load_openclaw_config_raw()?;
+/// 禁用插件（不修改渠道账号配置）
+#[command]
+pub async fn disable_plugin(plugin_id: String) -> Result<String, String> {
+    toggle_plugin(plugin_id, false).await
+}
 
-    // 删除 Provider 配置
-    if let Some(providers) = config
-        .pointer_mut("/models/providers")
-        .and_then(|v| v.as_object_mut())
-    {
-        providers.remove(&provider_name);
-    }
+/// 获取已安装插件列表
+#[command]
+pub async fn list_plugins() -> Result<Vec<PluginInfo>, String> {
+    info!("[插件管理] 获取插件列表...");
+    let output =
+        shell::run_openclaw_async(&["plugins", "list"], std::time::Duration::from_secs(30)).await?;
+    debug!("[插件管理] plugins list 输出: {}", output);
+
+    let config = load_openclaw_config().ok();
+    let entries = config.as_ref().and_then(|c| c.pointer("/plugins/entries"));
+    let plugins = parse_plugins_list(&output, entries);
+    info!("[插件管理] ✓ 共解析到 {} 个插件", plugins.len());
+    Ok(plugins)
+}
 
-    // 删除相关模型
-    if let Some(models) = config
-        .pointer_mut("/agents/defaults/models")
-        .and_then(|v| v.as_object_mut())
-    {
-        let keys_to_remove: Vec<String> = models
-            .keys()
-            .filter(|k| k.starts_with(&format!("{}/", provider_name)))
-            .cloned()
-            .collect();
+// ============ 渠道插件检查 ============
+
+/// 需要单独安装插件包的渠道（其它渠道由 openclaw 内置）
+const CHANNEL_PLUGIN_PACKAGES: &[(&str, &str)] = &[("feishu", "@m1heng-clawd/feishu")];
+
+/// 渠道所需插件包名，内置渠道返回 None
+fn channel_plugin_package(channel_id: &str) -> Option<&'static str> {
+    CHANNEL_PLUGIN_PACKAGES
+        .iter()
+        .find(|(id, _)| *id == channel_id)
+        .map(|(_, package)| *package)
+}
+
+/// 渠道插件安装状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginInstallState {
+    /// 渠道由 openclaw 内置，无需插件
+    Builtin,
+    Installed,
+    Missing,
+    /// 获取插件列表失败，无法判断是否已安装
+    Unknown,
+}
+
+/// 渠道插件可用状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelPluginStatus {
+    pub channel_id: String,
+    /// 所需插件包名（内置渠道为 None）
+    pub package: Option<String>,
+    pub builtin: bool,
+    pub installed: bool,
+    pub state: PluginInstallState,
+    pub version: Option<String>,
+    /// 缺失时的安装命令
+    pub install_command: Option<String>,
+}
 
-        for key in keys_to_remove {
-            models.remove(&key);
+impl ChannelPluginStatus {
+    /// 插件缺失或状态未知时给用户的提示（内置或已安装时为 None）
+    fn notice(&self) -> Option<String> {
+        let package = self.package.as_deref().unwrap_or_default();
+        match self.state {
+            PluginInstallState::Missing => Some(format!(
+                "未检测到插件 {}，网关将无法加载该渠道，请先安装: {}",
+                package,
+                self.install_command.as_deref().unwrap_or_default()
+            )),
+            PluginInstallState::Unknown => {
+                Some(format!("无法获取插件列表，请确认已安装插件 {}", package))
+            }
+            PluginInstallState::Builtin | PluginInstallState::Installed => None,
         }
     }
+}
 
-    // 如果主模型属于该 Provider，清除主模型
-    if let Some(primary) = config
-        .pointer("/agents/defaults/model/primary")
-        .and_then(|v| v.as_str())
-    {
-        if primary.starts_with(&format!("{}/", provider_name)) {
-            config["agents"]["defaults"]["model"]["primary"] = json!(null);
-        }
+/// 根据已安装插件列表判断渠道插件是否可用（兼容 @scope/name 与短名）
+/// plugins 为 None 表示插件列表获取失败，需插件的渠道返回 Unknown
+fn channel_plugin_status(channel_id: &str, plugins: Option<&[PluginInfo]>) -> ChannelPluginStatus {
+    let Some(package) = channel_plugin_package(channel_id) else {
+        return ChannelPluginStatus {
+            channel_id: channel_id.to_string(),
+            package: None,
+            builtin: true,
+            installed: true,
+            state: PluginInstallState::Builtin,
+            version: None,
+            install_command: None,
+        };
+    };
+    let short_name = package.rsplit('/').next().unwrap_or(package);
+    let plugin = plugins.and_then(|plugins| {
+        plugins
+            .iter()
+            .find(|p| p.name == package || p.name.rsplit('/').next() == Some(short_name))
+    });
+    let state = match (plugins, plugin) {
+        (None, _) => PluginInstallState::Unknown,
+        (Some(_), Some(_)) => PluginInstallState::Installed,
+        (Some(_), None) => PluginInstallState::Missing,
+    };
+    ChannelPluginStatus {
+        channel_id: channel_id.to_string(),
+        package: Some(package.to_string()),
+        builtin: false,
+        installed: plugin.is_some(),
+        state,
+        version: plugin.and_then(|p| p.version.clone()),
+        install_command: (state == PluginInstallState::Missing)
+            .then(|| format!("openclaw plugins install {}", package)),
     }
+}
 
-    save_openclaw_config(&config)?;
-    info!("[删除 Provider] ✓ Provider {} 已删除", provider_name);
+/// 检查渠道所需插件是否已安装（启用渠道前调用，避免网关运行时加载失败）
+#[command]
+pub async fn check_channel_plugin(channel_id: String) -> Result<ChannelPluginStatus, String> {
+    info!("[渠道插件] 检查渠道 {} 的插件...", channel_id);
+    let plugins = if channel_plugin_package(&channel_id).is_some() {
+        list_plugins()
+            .await
+            .map_err(|e| warn!("[渠道插件] 获取插件列表失败，插件状态未知: {}", e))
+            .ok()
+    } else {
+        Some(Vec::new())
+    };
+    let status = channel_plugin_status(&channel_id, plugins.as_deref());
+    if status.state == PluginInstallState::Missing {
+        info!(
+            "[渠道插件] ✗ 渠道 {} 缺少插件 {}",
+            channel_id,
+            status.package.as_deref().unwrap_or_default()
+        );
+    }
+    Ok(status)
+}
 
-    Ok(format!("Provider {} 已删除", provider_name))
+// ============ 飞书插件管理 ============
+
+/// 飞书插件状态
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeishuPluginStatus {
+    pub installed: bool,
+    /// 获取插件列表失败时为 Unknown，不能据此判断插件缺失
+    pub state: PluginInstallState,
+    pub version: Option<String>,
+    pub plugin_name: Option<String>,
 }
 
-/// 设置主模型
+/// 检查飞书插件是否已安装（与渠道插件检查共用包名与匹配规则）
 #[command]
-pub async fn set_primary_model(model_id: String) -> Result<String, String> {
-    info!("[设置主模型] 设置主模型: {}", model_id);
+pub async fn check_feishu_plugin() -> Result<FeishuPluginStatus, String> {
+    info!("[飞书插件] 检查飞书插件安装状态...");
+    let status = check_channel_plugin("feishu".to_string()).await?;
+    match status.state {
+        PluginInstallState::Installed => info!("[飞书插件] ✓ 飞书插件已安装"),
+        PluginInstallState::Unknown => warn!("[飞书插件] 无法获取插件列表，安装状态未知"),
+        _ => info!("[飞书插件] ✗ 飞书插件未安装"),
+    }
+    Ok(FeishuPluginStatus {
+        installed: status.installed,
+        state: status.state,
+        version: status.version,
+        plugin_name: status.installed.then_some(status.package).flatten(),
+    })
+}
 
-    let mut config = load_openclaw_config_raw()?;
+/// 安装飞书插件
+#[command]
+pub async fn install_feishu_plugin() -> Result<String, String> {
+    info!("[飞书插件] 开始安装飞书插件...");
+    let package = channel_plugin_package("feishu").unwrap_or_default();
 
-    // 确保路径存在
-    if config.get("agents").is_none() {
-        config["agents"] = json!({});
-    }
-    if config["agents"].get("defaults").is_none() {
-        config["agents"]["defaults"] = json!({});
+    // 先检查是否已安装
+    let status = check_feishu_plugin().await?;
+    if status.installed {
+        info!("[飞书插件] 飞书插件已安装，跳过");
+        return Ok(format!("飞书插件已安装: {}", status.plugin_name.unwrap_or_default()));
     }
-    if config["agents"]["defaults"].get("model").is_none() {
-        config["agents"]["defaults"]["model"] = json!({});
+
+    info!("[飞书插件] 执行 openclaw plugins install {} ...", package);
+    match shell::run_openclaw(&["plugins", "install", package]) {
+        Ok(output) => {
+            info!("[飞书插件] 安装输出: {}", output);
+
+            // 验证安装结果
+            let verify_status = check_feishu_plugin().await?;
+            match verify_status.state {
+                PluginInstallState::Installed => {
+                    info!("[飞书插件] ✓ 飞书插件安装成功");
+                    Ok(format!(
+                        "飞书插件安装成功: {}",
+                        verify_status.plugin_name.unwrap_or_default()
+                    ))
+                }
+                PluginInstallState::Unknown => {
+                    warn!("[飞书插件] 安装命令执行成功，但无法获取插件列表确认");
+                    Ok(format!("安装命令已执行，但无法确认插件 {} 是否可用", package))
+                }
+                _ => {
+                    warn!("[飞书插件] 安装命令执行成功但插件未找到");
+                    Err("安装命令执行成功但插件未找到，请检查 openclaw 版本".to_string())
+                }
+            }
+        }
+        Err(e) => {
+            error!("[飞书插件] ✗ 安装失败: {}", e);
+            Err(format!(
+                "安装飞书插件失败: {}\n\n请手动执行: openclaw plugins install {}",
+                e, package
+            ))
+        }
     }
+}
 
-    // 设置主模型
-    config["agents"]["defaults"]["model"]["primary"] = json!(model_id);
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{
+        acquire_config_file_lock, add_available_model, apply_agent_model, apply_channel_config,
+        apply_channels_enabled, apply_config_change, apply_config_file_change, build_config_bundle,
+        build_config_diff_summary, build_dashboard_url, channel_plugin_status, clear_channel_config,
+        clone_provider, collect_channel_account_issues,
+        convert_bindings_shape, dashboard_host_for_bind, decode_config_bundle, delete_provider,
+        dropped_channel_bindings, find_orphaned_bindings, find_plugin_consistency_issues,
+        gateway_tls_enabled, gateway_token_verdict, get_agent_defaults, get_ai_config,
+        get_channels_config, get_config, get_dashboard_url, get_effective_config, get_env_keys,
+        get_gateway_settings, get_or_create_gateway_token, import_channels, insert_agent,
+        load_env_file_vars, load_openclaw_config_raw, mask_secret, normalize_and_validate_config,
+        normalize_base_url, open_config_file, parse_account_bindings,
+        parse_account_bindings_with_duplicates, parse_configured_model,
+        parse_openclaw_config_content, parse_plugins_list, parse_session_login_status,
+        provider_requires_api_key, read_config_bundle, remove_agent, remove_env_key,
+        remove_orphaned_bindings, remove_provider, remove_stale_lock,
+        repair_plugin_consistency_issues, replace_agent, replace_config_vars, resolve_bind_mode,
+        rotate_gateway_token, save_channel_config, save_config, save_gateway_settings,
+        save_openclaw_config, save_openclaw_config_edit, save_provider, set_channel_enabled,
+        set_max_concurrent, set_plugin_enabled, set_primary_model, typed_config_from_value,
+        update_model_cost, validate_gateway_mode, validate_gateway_network_fields, AgentEntry,
+        BindingsShape, ChannelConfig, ConfigChangeStatus, ConfigFileFingerprint, ConfigFileLock,
+        ConfigVarSource, ConfigWatchDebouncer, GatewayConfig, ModelConfig, ModelCostConfig,
+        PluginConsistencyKind, PluginInfo, PluginInstallState, BASE64, CONFIG_CONFLICT_ERROR,
+        CONFIG_FILE_MISSING_ERROR, MASK_MAX_PREFIX,
+    };
+    use crate::models::GatewayAuthConfig;
+    use crate::utils::{file as file_utils, platform as platform_utils, shell};
+    use base64::Engine as _;
+    use serde_json::{json, Value};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Mutex, OnceLock};
+    use std::time::{SystemTime, UNIX_EPOCH};
 
-    save_openclaw_config(&config)?;
-    info!("[设置主模型] ✓ 主模型已设置为: {}", model_id);
+    static TEST_ENV_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
 
-    Ok(format!("主模型已设置为 {}", model_id))
-}
+    pub(crate) fn test_env_lock() -> std::sync::MutexGuard<'static, ()> {
+        TEST_ENV_LOCK
+            .get_or_init(|| Mutex::new(()))
+            .lock()
+            .expect("测试环境锁不应中毒")
+    }
 
-/// 添加模型到可用列表
-#[command]
-pub async fn add_available_model(model_id: String) -> Result<String, String> {
-    info!("[添加模型] 添加模型到可用列表: {}", model_id);
+    pub(crate) struct EnvGuard {
+        key: String,
+        previous: Option<String>,
+    }
 
-    let mut config = load_openclaw_config_raw()?;
+    impl EnvGuard {
+        pub(crate) fn set(key: &str, value: &str) -> Self {
+            let previous = std::env::var(key).ok();
+            unsafe {
+                std::env::set_var(key, value);
+            }
+            Self {
+                key: key.to_string(),
+                previous,
+            }
+        }
 
-    // 确保路径存在
-    if config.get("agents").is_none() {
-        config["agents"] = json!({});
+        pub(crate) fn remove(key: &str) -> Self {
+            let previous = std::env::var(key).ok();
+            unsafe {
+                std::env::remove_var(key);
+            }
+            Self {
+                key: key.to_string(),
+                previous,
+            }
+        }
     }
-    if config["agents"].get("defaults").is_none() {
-        config["agents"]["defaults"] = json!({});
+
+    impl Drop for EnvGuard {
+        fn drop(&mut self) {
+            if let Some(prev) = &self.previous {
+                unsafe {
+                    std::env::set_var(&self.key, prev);
+                }
+            } else {
+                unsafe {
+                    std::env::remove_var(&self.key);
+                }
+            }
+        }
     }
-    if config["agents"]["defaults"].get("models").is_none() {
-        config["agents"]["defaults"]["models"] = json!({});
+
+    /// 创建测试独占的临时目录，进程号、时间戳与计数器保证并行测试之间不冲突
+    pub(crate) fn unique_temp_dir(name: &str) -> PathBuf {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "openclaw-{}-test-{}-{}-{}",
+            name,
+            std::process::id(),
+            nanos,
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).expect("应可创建临时目录");
+        dir
+    }
+
+    pub(crate) struct TempHomeGuard {
+        previous_home: Option<String>,
+        pub(crate) temp_home_dir: PathBuf,
     }
 
-    // 添加模型
-    config["agents"]["defaults"]["models"][&model_id] = json!({});
+    impl TempHomeGuard {
+        pub(crate) fn new() -> Self {
+            let previous_home = std::env::var("HOME").ok();
+            let temp_home_dir = unique_temp_dir("config-home");
+            fs::create_dir_all(temp_home_dir.join(".openclaw"))
+                .expect("应可创建临时 home 目录");
+
+            unsafe {
+                std::env::set_var("HOME", temp_home_dir.to_string_lossy().to_string());
+            }
+
+            Self {
+                previous_home,
+                temp_home_dir,
+            }
+        }
 
-    save_openclaw_config(&config)?;
-    info!("[添加模型] ✓ 模型 {} 已添加", model_id);
+        fn write_openclaw_env(&self, content: &str) {
+            let env_path = self.temp_home_dir.join(".openclaw").join("env");
+            fs::write(env_path, content).expect("应可写入临时 env 文件");
+        }
+    }
 
-    Ok(format!("模型 {} 已添加", model_id))
-}
+    impl Drop for TempHomeGuard {
+        fn drop(&mut self) {
+            if let Some(prev) = &self.previous_home {
+                unsafe {
+                    std::env::set_var("HOME", prev);
+                }
+            } else {
+                unsafe {
+                    std::env::remove_var("HOME");
+                }
+            }
+            let _ = fs::remove_dir_all(&self.temp_home_dir);
+        }
+    }
 
-/// 从可用列表移除模型
-#[command]
-pub async fn remove_available_model(model_id: String) -> Result<String, String> {
-    info!("[移除模型] 从可用列表移除模型: {}", model_id);
+    fn apply_replacement(mut value: Value) -> Result<Value, String> {
+        let env_file_vars = load_env_file_vars();
+        replace_config_vars(&mut value, &env_file_vars, "")?;
+        Ok(value)
+    }
 
-    let mut config = load_openclaw_config_raw()?;
+    #[test]
+    fn parse_pure_json_config() {
+        let content = r#"{"gateway":{"auth":{"token":"test-token"}}}"#;
+        let parsed = parse_openclaw_config_content(content).expect("纯 JSON 配置应可读取");
 
-    if let Some(models) = config
-        .pointer_mut("/agents/defaults/models")
-        .and_then(|v| v.as_object_mut())
-    {
-        models.remove(&model_id);
+        assert_eq!(
+            parsed
+                .pointer("/gateway/auth/token")
+                .and_then(|v| v.as_str()),
+            Some("test-token")
+        );
     }
 
-    save_openclaw_config(&config)?;
-    info!("[移除模型] ✓ 模型 {} 已移除", model_id);
+    #[test]
+    fn parse_json5_with_comments_and_trailing_comma() {
+        let content = r#"
+        {
+          // JSON5 注释
+          gateway: {
+            auth: {
+              token: "json5-token",
+            },
+          },
+        }
+        "#;
 
-    Ok(format!("模型 {} 已移除", model_id))
-}
+        let parsed = parse_openclaw_config_content(content).expect("JSON5 配置应可读取");
 
-// ============ 旧版兼容 ============
+        assert_eq!(
+            parsed
+                .pointer("/gateway/auth/token")
+                .and_then(|v| v.as_str()),
+            Some("json5-token")
+        );
+    }
 
-/// 获取所有支持的 AI Provider（旧版兼容）
-#[command]
-pub async fn get_ai_providers() -> Result<Vec<crate::models::AIProviderOption>, String> {
-    info!("[AI Provider] 获取支持的 AI Provider 列表（旧版）...");
+    #[test]
+    fn parse_invalid_config_should_return_clear_error() {
+        let content = "{ gateway: { auth: { token: } } }";
+        let err = parse_openclaw_config_content(content).expect_err("非法配置应返回错误");
 
-    let official = get_official_providers().await?;
-    let providers: Vec<crate::models::AIProviderOption> = official
-        .into_iter()
-        .map(|p| crate::models::AIProviderOption {
-            id: p.id,
-            name: p.name,
-            icon: p.icon,
-            default_base_url: p.default_base_url,
-            requires_api_key: p.requires_api_key,
-            models: p
-                .suggested_models
-                .into_iter()
-                .map(|m| crate::models::AIModelOption {
-                    id: m.id,
-                    name: m.name,
-                    description: m.description,
-                    recommended: m.recommended,
-                })
-                .collect(),
-        })
-        .collect();
+        assert!(
+            err.contains("JSON/JSON5 解析失败"),
+            "错误信息应包含 JSON/JSON5 解析失败，实际: {}",
+            err
+        );
+    }
 
-    Ok(providers)
-}
+    #[test]
+    fn parse_json5_should_preserve_core_config_fields() {
+        let content = r#"
+        {
+          agents: {
+            defaults: {
+              model: { primary: "anthropic/claude-opus-4-5-20251101" },
+            },
+          },
+          gateway: {
+            auth: {
+              token: "gateway-token",
+            },
+          },
+          channels: {
+            telegram: {
+              accounts: [
+                {
+                  name: "main",
+                  token: "tg-token",
+                },
+              ],
+            },
+          },
+        }
+        "#;
 
-// ============ 渠道配置 ============
+        let parsed = parse_openclaw_config_content(content).expect("回归字段应可正确解析");
 
-fn parse_account_bindings(bindings: &Value) -> HashMap<(String, String), String> {
-    let mut result = HashMap::new();
+        assert_eq!(
+            parsed
+                .pointer("/agents/defaults/model/primary")
+                .and_then(|v| v.as_str()),
+            Some("anthropic/claude-opus-4-5-20251101")
+        );
+        assert_eq!(
+            parsed
+                .pointer("/gateway/auth/token")
+                .and_then(|v| v.as_str()),
+            Some("gateway-token")
+        );
+        assert_eq!(
+            parsed
+                .pointer("/channels/telegram/accounts/0/token")
+                .and_then(|v| v.as_str()),
+            Some("tg-token")
+        );
+    }
 
-    if let Ok(entries) = serde_json::from_value::<Vec<BindingEntry>>(bindings.clone()) {
-        for entry in entries {
-            let Some(agent_id) = entry.agent_id else {
-                continue;
-            };
-            let Some(m) = entry.r#match else {
-                continue;
-            };
-            let Some(channel) = m.channel else {
-                continue;
-            };
-            let Some(account_id) = m.account_id else {
-                continue;
-            };
+    #[test]
+    fn replace_vars_in_json_config_should_work() {
+        let _env_lock = test_env_lock();
+        let _process_guard = EnvGuard::set("OPENCLAW_TEST_JSON_TOKEN", "json-token-from-process");
+        let _env_guard = EnvGuard::remove("OPENCLAW_TEST_JSON_SUFFIX");
 
-            result.insert((channel, account_id), agent_id);
-        }
+        let home_guard = TempHomeGuard::new();
+        home_guard.write_openclaw_env("export OPENCLAW_TEST_JSON_SUFFIX=-from-env\n");
 
-        if !result.is_empty() {
-            return result;
-        }
-    }
+        let parsed = parse_openclaw_config_content(
+            r#"{"gateway":{"auth":{"token":"${OPENCLAW_TEST_JSON_TOKEN}${OPENCLAW_TEST_JSON_SUFFIX}"}}}"#,
+        )
+        .expect("JSON 配置应可解析");
 
-    if let Some(arr) = bindings.as_array() {
-        for item in arr {
-            let Some(agent_id) = item.get("agentId").and_then(|v| v.as_str()) else {
-                continue;
-            };
-            let Some(m) = item.get("match").and_then(|v| v.as_object()) else {
-                continue;
-            };
-            let Some(channel) = m.get("channel").and_then(|v| v.as_str()) else {
-                continue;
-            };
-            let Some(account_id) = m.get("accountId").and_then(|v| v.as_str()) else {
-                continue;
-            };
-            result.insert(
-                (channel.to_string(), account_id.to_string()),
-                agent_id.to_string(),
-            );
-        }
-        return result;
+        let replaced = apply_replacement(parsed).expect("JSON 配置变量应可替换");
+        assert_eq!(
+            replaced
+                .pointer("/gateway/auth/token")
+                .and_then(|v| v.as_str()),
+            Some("json-token-from-process-from-env")
+        );
     }
 
-    if let Some(obj) = bindings.as_object() {
-        // 扁平格式：{"telegram/default":"main"}
-        for (key, value) in obj {
-            if let Some(agent_id) = value.as_str() {
-                if let Some((channel, account_id)) = key.split_once('/') {
-                    result.insert(
-                        (channel.to_string(), account_id.to_string()),
-                        agent_id.to_string(),
-                    );
-                    continue;
-                }
-                if let Some((channel, account_id)) = key.split_once(':') {
-                    result.insert(
-                        (channel.to_string(), account_id.to_string()),
-                        agent_id.to_string(),
-                    );
-                    continue;
-                }
-                if let Some((channel, account_id)) = key.split_once('.') {
-                    result.insert(
-                        (channel.to_string(), account_id.to_string()),
-                        agent_id.to_string(),
-                    );
-                    continue;
-                }
-            }
+    #[test]
+    fn replace_vars_in_json5_config_should_work() {
+        let _env_lock = test_env_lock();
+        let _process_guard = EnvGuard::set("OPENCLAW_TEST_JSON5_TOKEN", "json5-token-from-process");
+        let _env_guard = EnvGuard::remove("OPENCLAW_TEST_JSON5_SUFFIX");
 
-            // 分组格式：{"telegram":{"default":"main"}}
-            if let Some(accounts_obj) = value.as_object() {
-                for (account_id, nested) in accounts_obj {
-                    if let Some(agent_id) = nested.as_str() {
-                        result.insert(
-                            (key.to_string(), account_id.to_string()),
-                            agent_id.to_string(),
-                        );
-                        continue;
-                    }
+        let home_guard = TempHomeGuard::new();
+        home_guard.write_openclaw_env("export OPENCLAW_TEST_JSON5_SUFFIX=-json5-env\n");
 
-                    if let Some(nested_obj) = nested.as_object() {
-                        if let Some(agent_id) = nested_obj.get("agentId").and_then(|v| v.as_str()) {
-                            result.insert(
-                                (key.to_string(), account_id.to_string()),
-                                agent_id.to_string(),
-                            );
-                        }
-                    }
-                }
+        let parsed = parse_openclaw_config_content(
+            r#"
+            {
+              // JSON5 注释
+              gateway: {
+                auth: {
+                  token: "${OPENCLAW_TEST_JSON5_TOKEN}${OPENCLAW_TEST_JSON5_SUFFIX}",
+                },
+              },
             }
-        }
+            "#,
+        )
+        .expect("JSON5 配置应可解析");
+
+        let replaced = apply_replacement(parsed).expect("JSON5 配置变量应可替换");
+        assert_eq!(
+            replaced
+                .pointer("/gateway/auth/token")
+                .and_then(|v| v.as_str()),
+            Some("json5-token-from-process-json5-env")
+        );
     }
 
-    result
-}
+    #[test]
+    fn replace_multiple_placeholders_in_single_string_should_work() {
+        let _env_lock = test_env_lock();
+        let _api_guard = EnvGuard::set("OPENCLAW_MULTI_A", "A");
+        let _token_guard = EnvGuard::set("OPENCLAW_MULTI_B", "B");
+        let _secret_guard = EnvGuard::set("OPENCLAW_MULTI_C", "C");
 
-fn merge_bindings_payload_by_shape(
-    original_bindings: &Value,
-    all_pairs: &HashMap<(String, String), String>,
-) -> Value {
-    // 默认与数组格式都写回官方数组结构
-    if original_bindings.is_array() || !original_bindings.is_object() {
-        let mut entries = Vec::new();
-        for ((channel, account_id), agent_id) in all_pairs {
-            entries.push(json!({
-                "agentId": agent_id,
-                "match": {
-                    "channel": channel,
-                    "accountId": account_id,
-                }
-            }));
-        }
-        return Value::Array(entries);
+        let parsed = parse_openclaw_config_content(
+            r#"{"models":{"providers":{"demo":{"apiKey":"prefix-${OPENCLAW_MULTI_A}-${OPENCLAW_MULTI_B}-${OPENCLAW_MULTI_C}-suffix"}}}}"#,
+        )
+        .expect("配置应可解析");
+
+        let replaced = apply_replacement(parsed).expect("多占位符应可替换");
+        assert_eq!(
+            replaced
+                .pointer("/models/providers/demo/apiKey")
+                .and_then(|v| v.as_str()),
+            Some("prefix-A-B-C-suffix")
+        );
     }
 
-    let Some(obj) = original_bindings.as_object() else {
-        return Value::Array(vec![]);
-    };
+    #[test]
+    fn missing_variable_should_return_error_with_path_and_name() {
+        let _env_lock = test_env_lock();
+        let _guard = EnvGuard::remove("OPENCLAW_MISSING_VAR_TEST");
 
-    // 判断是否扁平对象
-    let is_flat = obj.values().all(|v| v.is_string());
-    if is_flat {
-        let mut flat = serde_json::Map::new();
-        for ((channel, account_id), agent_id) in all_pairs {
-            flat.insert(format!("{}/{}", channel, account_id), json!(agent_id));
-        }
-        return Value::Object(flat);
-    }
+        let parsed = parse_openclaw_config_content(
+            r#"{"models":{"providers":{"anthropic":{"apiKey":"${OPENCLAW_MISSING_VAR_TEST}"}}}}"#,
+        )
+        .expect("配置应可解析");
 
-    // 分组对象
-    let mut grouped: HashMap<String, serde_json::Map<String, Value>> = HashMap::new();
-    for ((channel, account_id), agent_id) in all_pairs {
-        grouped
-            .entry(channel.clone())
-            .or_default()
-            .insert(account_id.clone(), json!(agent_id));
+        let err = apply_replacement(parsed).expect_err("缺失变量应返回错误");
+        assert!(
+            err.contains("/models/providers/anthropic/apiKey"),
+            "错误信息应包含配置路径，实际: {}",
+            err
+        );
+        assert!(
+            err.contains("OPENCLAW_MISSING_VAR_TEST"),
+            "错误信息应包含缺失变量名，实际: {}",
+            err
+        );
     }
 
-    let mut grouped_obj = serde_json::Map::new();
-    for (channel, accounts) in grouped {
-        grouped_obj.insert(channel, Value::Object(accounts));
-    }
-    Value::Object(grouped_obj)
-}
+    #[test]
+    fn replacement_should_not_mutate_raw_placeholder_text_for_writeback() {
+        let _env_lock = test_env_lock();
+        let _process_guard = EnvGuard::set("OPENCLAW_WRITEBACK_VAR", "super-secret-value");
 
-/// 获取渠道配置 - 从 openclaw.json 和 env 文件读取
-#[command]
-pub async fn get_channels_config() -> Result<Vec<ChannelConfig>, String> {
-    info!("[渠道配置] 获取渠道配置列表...");
+        let raw = parse_openclaw_config_content(
+            r#"{"models":{"providers":{"anthropic":{"baseUrl":"https://api.anthropic.com","apiKey":"${OPENCLAW_WRITEBACK_VAR}"}}}}"#,
+        )
+        .expect("配置应可解析");
 
-    let config = load_openclaw_config()?;
-    let channels_obj = config.get("channels").cloned().unwrap_or(json!({}));
-    let bindings_obj = config.get("bindings").cloned().unwrap_or(json!([]));
-    let account_bindings = parse_account_bindings(&bindings_obj);
-    let env_path = platform::get_env_file_path();
-    debug!("[渠道配置] 环境文件路径: {}", env_path);
+        let replaced = apply_replacement(raw.clone()).expect("读取期应可替换");
+        assert_eq!(
+            replaced
+                .pointer("/models/providers/anthropic/apiKey")
+                .and_then(|v| v.as_str()),
+            Some("super-secret-value")
+        );
+        assert_eq!(
+            raw.pointer("/models/providers/anthropic/apiKey")
+                .and_then(|v| v.as_str()),
+            Some("${OPENCLAW_WRITEBACK_VAR}")
+        );
 
-    let mut channels = Vec::new();
+        let serialized = serde_json::to_string_pretty(&raw).expect("原始配置应可序列化");
+        assert!(
+            serialized.contains("${OPENCLAW_WRITEBACK_VAR}"),
+            "写回内容应保留占位符"
+        );
+        assert!(
+            !serialized.contains("super-secret-value"),
+            "写回内容不应包含替换后的敏感值"
+        );
 
-    // 支持的渠道类型列表及其测试字段
-    let channel_types = vec![
-        ("telegram", "telegram", vec!["userId"]),
-        ("discord", "discord", vec!["testChannelId"]),
-        ("slack", "slack", vec!["testChannelId"]),
-        ("feishu", "feishu", vec!["testChatId"]),
-        ("whatsapp", "whatsapp", vec![]),
-        ("imessage", "imessage", vec![]),
-        ("wechat", "wechat", vec![]),
-        ("dingtalk", "dingtalk", vec![]),
-    ];
-
-    for (channel_id, channel_type, test_fields) in channel_types {
-        let channel_config = channels_obj.get(channel_id);
+        let home_guard = TempHomeGuard::new();
+        let config_path = platform_utils::get_config_file_path();
+        file_utils::write_file(
+            &config_path,
+            r#"{"models":{"providers":{"anthropic":{"baseUrl":"https://api.anthropic.com","apiKey":"${OPENCLAW_WRITEBACK_VAR}"}}}}"#,
+        )
+        .expect("应可写入测试配置");
 
-        let mut accounts = channel_config
-            .and_then(|c| c.get("accounts"))
-            .and_then(|v| v.as_object())
-            .map(|obj| {
-                obj.iter()
-                    .map(|(k, v)| (k.clone(), v.clone()))
-                    .collect::<HashMap<String, Value>>()
-            })
-            .unwrap_or_default();
+        save_openclaw_config(&raw).expect("写回原始配置应成功");
+        let saved = file_utils::read_file(&config_path).expect("应可读取写回结果");
+        assert!(saved.contains("${OPENCLAW_WRITEBACK_VAR}"));
+        assert!(!saved.contains("super-secret-value"));
 
-        // bindings 存在但 accounts 不存在时，自动补齐空账号节点，便于前端直接编辑
-        for ((binding_channel, account_id), agent_id) in &account_bindings {
-            if binding_channel == channel_id {
-                let entry = accounts.entry(account_id.clone()).or_insert_with(|| json!({}));
-                if let Some(obj) = entry.as_object_mut() {
-                    // bindings 为权威来源，始终写入 agentId，避免账号内遗留旧值
-                    obj.insert("agentId".to_string(), json!(agent_id));
+        let _ = home_guard;
+    }
+
+    #[test]
+    fn normalize_config_defaults_when_agents_list_and_bindings_missing() {
+        let config = json!({
+            "agents": {
+                "defaults": {
+                    "model": {
+                        "primary": "anthropic/claude-opus-4-5-20251101"
+                    }
                 }
             }
-        }
+        });
 
-        let enabled = channel_config
-            .and_then(|c| c.get("enabled"))
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
+        let normalized = normalize_and_validate_config(&config)
+            .expect("仅 defaults 配置应可通过结构化校验");
 
-        // 将渠道配置转换为 HashMap（兼容旧版前端平铺字段）
-        let mut config_map: HashMap<String, Value> = if let Some(cfg) = channel_config {
-            if let Some(obj) = cfg.as_object() {
-                obj.iter()
-                    .filter(|(k, _)| *k != "enabled" && *k != "accounts")
-                    .map(|(k, v)| (k.clone(), v.clone()))
-                    .collect()
-            } else {
-                HashMap::new()
-            }
-        } else {
-            HashMap::new()
-        };
+        assert!(normalized.pointer("/agents/list").is_none());
+        assert!(normalized.get("bindings").is_none());
+    }
 
-        // 从 env 文件读取测试字段
-        for field in test_fields {
-            let env_key = format!(
-                "OPENCLAW_{}_{}",
-                channel_id.to_uppercase(),
-                field.to_uppercase()
-            );
-            if let Some(value) = file::read_env_value(&env_path, &env_key) {
-                config_map.insert(field.to_string(), json!(value));
+    #[test]
+    fn normalize_config_accepts_full_agents_list_and_bindings() {
+        let config = json!({
+            "agents": {
+                "defaults": {
+                    "model": { "primary": "anthropic/claude-opus-4-5-20251101" }
+                },
+                "list": [
+                    {
+                        "id": "main",
+                        "name": "主助手",
+                        "default": true,
+                        "workspace": "/tmp/main"
+                    }
+                ]
+            },
+            "bindings": [
+                {
+                    "agentId": "main",
+                    "match": {
+                        "channel": "telegram",
+                        "accountId": "default"
+                    }
+                }
+            ]
+        });
+
+        let normalized = normalize_and_validate_config(&config)
+            .expect("完整 agents.list + bindings 配置应可通过结构化校验");
+
+        assert_eq!(
+            normalized
+                .pointer("/agents/list/0/id")
+                .and_then(|v| v.as_str()),
+            Some("main")
+        );
+        assert_eq!(
+            normalized
+                .pointer("/bindings/0/match/channel")
+                .and_then(|v| v.as_str()),
+            Some("telegram")
+        );
+    }
+
+    #[test]
+    fn normalize_config_rejects_invalid_agents_list_or_bindings_type() {
+        let invalid_agents_list = json!({
+            "agents": {
+                "defaults": { "model": { "primary": "x/y" } },
+                "list": { "id": "main" }
             }
-        }
+        });
+        let err = normalize_and_validate_config(&invalid_agents_list)
+            .expect_err("agents.list 非数组应返回错误");
+        assert!(
+            err.contains("agents.list 结构无效"),
+            "错误信息应明确指向 agents.list，实际: {}",
+            err
+        );
 
-        let has_accounts = !accounts.is_empty();
-        let has_config = !config_map.is_empty() || enabled || has_accounts;
+        let invalid_bindings = json!({
+            "agents": { "defaults": { "model": { "primary": "x/y" } } },
+            "bindings": 123
+        });
+        let err = normalize_and_validate_config(&invalid_bindings)
+            .expect_err("bindings 非数组/对象应返回错误");
+        assert!(
+            err.contains("bindings 结构无效"),
+            "错误信息应明确指向 bindings，实际: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn config_diff_ignores_semantically_equivalent_optional_values() {
+        let before = json!({
+            "agents": {
+                "list": [
+                    {
+                        "id": "main",
+                        "name": "主助手",
+                        "workspace": "/tmp/main"
+                    }
+                ]
+            }
+        });
 
-        channels.push(ChannelConfig {
-            id: channel_id.to_string(),
-            channel_type: channel_type.to_string(),
-            enabled: has_config,
-            config: config_map,
-            accounts: if accounts.is_empty() { None } else { Some(accounts) },
+        let after = json!({
+            "agents": {
+                "list": [
+                    {
+                        "id": "main",
+                        "name": "主助手",
+                        "workspace": "/tmp/main",
+                        "default": false,
+                        "model": {},
+                        "tools": null,
+                        "sandbox": null
+                    }
+                ]
+            },
+            "bindings": []
         });
-    }
 
-    info!("[渠道配置] ✓ 返回 {} 个渠道配置", channels.len());
-    for ch in &channels {
-        debug!("[渠道配置] - {}: enabled={}", ch.id, ch.enabled);
+        let diff = build_config_diff_summary(&before, &after);
+        assert_eq!(diff.added, 0, "语义等价字段不应计入新增");
+        assert_eq!(diff.modified, 0, "语义等价字段不应计入修改");
+        assert_eq!(diff.removed, 0, "语义等价字段不应计入删除");
+        assert!(diff.changes.is_empty(), "语义等价字段不应产生差异项");
     }
-    Ok(channels)
-}
 
-/// 保存渠道配置 - 保存到 openclaw.json
-#[command]
-pub async fn save_channel_config(channel: ChannelConfig) -> Result<String, String> {
-    info!(
-        "[保存渠道配置] 保存渠道配置: {} ({})",
-        channel.id, channel.channel_type
-    );
+    #[test]
+    fn config_diff_reports_fine_grained_agent_field_changes() {
+        let before = json!({
+            "agents": {
+                "list": [
+                    {
+                        "id": "agent-1",
+                        "name": "旧名称1"
+                    },
+                    {
+                        "id": "agent-2",
+                        "name": "旧名称2"
+                    }
+                ]
+            }
+        });
 
-    let mut config = load_openclaw_config_raw()?;
-    let env_path = platform::get_env_file_path();
-    debug!("[保存渠道配置] 环境文件路径: {}", env_path);
+        let after = json!({
+            "agents": {
+                "list": [
+                    {
+                        "id": "agent-1",
+                        "name": "新名称1"
+                    },
+                    {
+                        "id": "agent-2",
+                        "name": "新名称2"
+                    }
+                ]
+            }
+        });
 
-    // 确保 channels 对象存在
-    if config.get("channels").is_none() {
-        config["channels"] = json!({});
-    }
+        let diff = build_config_diff_summary(&before, &after);
+        assert_eq!(diff.added, 0);
+        assert_eq!(diff.removed, 0);
+        assert_eq!(diff.modified, 2);
+        assert_eq!(diff.changes.len(), 2);
 
-    // 确保 plugins 对象存在
-    if config.get("plugins").is_none() {
-        config["plugins"] = json!({
-            "allow": [],
-            "entries": {}
-        });
-    }
-    if config["plugins"].get("allow").is_none() {
-        config["plugins"]["allow"] = json!([]);
-    }
-    if config["plugins"].get("entries").is_none() {
-        config["plugins"]["entries"] = json!({});
+        assert!(diff
+            .changes
+            .iter()
+            .any(|item| item.path == "/agents/list/0/name" && item.kind == "modified"));
+        assert!(diff
+            .changes
+            .iter()
+            .any(|item| item.path == "/agents/list/1/name" && item.kind == "modified"));
+        assert!(!diff
+            .changes
+            .iter()
+            .any(|item| item.path == "/agents" && item.kind == "modified"));
     }
+    #[test]
+    fn agent_model_override_is_per_agent_and_keeps_defaults() {
+        let mut config = json!({
+            "agents": {
+                "defaults": {
+                    "model": { "primary": "anthropic/claude-opus-4-5-20251101" },
+                    "models": {
+                        "anthropic/claude-opus-4-5-20251101": {},
+                        "openai/gpt-4o": {},
+                        "openai/gpt-4o-mini": {}
+                    }
+                },
+                "list": [
+                    { "id": "agent-a", "name": "A" },
+                    { "id": "agent-b", "name": "B" }
+                ]
+            }
+        });
 
-    // 这些字段只用于测试，不保存到 openclaw.json，而是保存到 env 文件
-    let test_only_fields = vec!["userId", "testChatId", "testChannelId"];
+        apply_agent_model(&mut config, "agent-a", Some("anthropic/claude-opus-4-5-20251101"), &[])
+            .expect("agent-a 应可设置模型");
+        apply_agent_model(
+            &mut config,
+            "agent-b",
+            Some("openai/gpt-4o"),
+            &["openai/gpt-4o-mini".to_string()],
+        )
+        .expect("agent-b 应可设置模型");
 
-    // 构建渠道配置
-    let mut channel_obj = json!({
-        "enabled": true
-    });
+        assert_eq!(
+            config.pointer("/agents/list/0/model/primary").and_then(|v| v.as_str()),
+            Some("anthropic/claude-opus-4-5-20251101")
+        );
+        assert!(config.pointer("/agents/list/0/model/fallbacks").is_none());
+        assert_eq!(
+            config.pointer("/agents/list/1/model/primary").and_then(|v| v.as_str()),
+            Some("openai/gpt-4o")
+        );
+        assert_eq!(
+            config.pointer("/agents/list/1/model/fallbacks/0").and_then(|v| v.as_str()),
+            Some("openai/gpt-4o-mini")
+        );
+        assert_eq!(
+            config.pointer("/agents/defaults/model"),
+            Some(&json!({ "primary": "anthropic/claude-opus-4-5-20251101" })),
+            "agents.defaults.model 不应被修改"
+        );
+        normalize_and_validate_config(&config).expect("写入后的配置应通过结构化校验");
 
-    // 添加渠道特定配置
-    for (key, value) in &channel.config {
-        if test_only_fields.contains(&key.as_str()) {
-            // 保存到 env 文件
-            let env_key = format!(
-                "OPENCLAW_{}_{}",
-                channel.id.to_uppercase(),
-                key.to_uppercase()
-            );
-            if let Some(val_str) = value.as_str() {
-                let _ = file::set_env_value(&env_path, &env_key, val_str);
-            }
-        } else {
-            // 保存到 openclaw.json
-            channel_obj[key] = value.clone();
-        }
-    }
+        let err = apply_agent_model(&mut config, "agent-a", Some("unknown/model"), &[])
+            .expect_err("不在可用列表中的模型应被拒绝");
+        assert!(err.contains("unknown/model"), "实际: {}", err);
 
-    // 保留/写入 accounts 多账号配置（兼容 channels.<provider>.accounts）
-    if let Some(accounts) = &channel.accounts {
-        let accounts_obj = accounts
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect::<serde_json::Map<String, Value>>();
-        if !accounts_obj.is_empty() {
-            channel_obj["accounts"] = Value::Object(accounts_obj);
-        }
-    } else if let Some(existing_accounts) = config
-        .pointer(&format!("/channels/{}/accounts", channel.id))
-        .cloned()
-    {
-        channel_obj["accounts"] = existing_accounts;
+        apply_agent_model(&mut config, "agent-b", None, &[]).expect("清空模型应成功");
+        assert!(config.pointer("/agents/list/1/model").is_none());
     }
 
-    // 更新 channels 配置
-    config["channels"][&channel.id] = channel_obj;
-
-    // 更新 plugins.allow 数组 - 确保渠道在白名单中，并清理空字符串
-    if let Some(allow_arr) = config["plugins"]["allow"].as_array_mut() {
-        allow_arr.retain(|v| v.as_str().map(|s| !s.trim().is_empty()).unwrap_or(true));
+    fn typed_model(model: Value) -> ModelConfig {
+        serde_json::from_value(model).expect("模型应可解析")
+    }
 
-        let channel_id_val = json!(&channel.id);
-        if !allow_arr.contains(&channel_id_val) {
-            allow_arr.push(channel_id_val);
+    #[test]
+    fn configured_model_parses_cost_when_present() {
+        let with_cost = json!({
+            "id": "gpt-4o",
+            "name": "GPT-4o",
+            "cost": { "input": 2.5, "output": 10, "cacheRead": 1.25, "cacheWrite": 0 }
+        });
+        let without_cost = json!({ "id": "llama3" });
+
+        let parsed =
+            parse_configured_model("openai", &typed_model(with_cost), Some("openai/gpt-4o"));
+        assert!(parsed.is_primary);
+        let cost = parsed.cost.expect("存在 cost 时应返回成本信息");
+        assert_eq!(cost.input, 2.5);
+        assert_eq!(cost.output, 10.0);
+        assert_eq!(cost.cache_read, 1.25);
+        assert_eq!(cost.cache_write, 0.0);
+
+        let parsed = parse_configured_model("ollama", &typed_model(without_cost), None);
+        assert_eq!(parsed.name, "llama3");
+        assert!(parsed.cost.is_none(), "缺少 cost 时应为 None");
+    }
+    #[test]
+    fn configured_model_parses_reasoning_flag() {
+        let cases = [
+            (json!({ "id": "deepseek-reasoner", "reasoning": true }), Some(true)),
+            (json!({ "id": "deepseek-chat", "reasoning": false }), Some(false)),
+            (json!({ "id": "gpt-4o" }), None),
+        ];
+
+        for (model, expected) in cases {
+            let parsed = parse_configured_model("demo", &typed_model(model), None);
+            assert_eq!(parsed.reasoning, expected, "模型 {} 的 reasoning 解析不符", parsed.id);
         }
     }
 
-    // 更新 plugins.entries - 确保插件已启用
-    config["plugins"]["entries"][&channel.id] = json!({
-        "enabled": true
-    });
-
-    // 同步更新 bindings：只替换当前 channel 的账号映射，其它渠道保持不变
-    let existing_bindings = config.get("bindings").cloned().unwrap_or(json!([]));
-    let mut all_pairs = parse_account_bindings(&existing_bindings);
-
-    all_pairs.retain(|(binding_channel, _), _| binding_channel != &channel.id);
-
-    if let Some(accounts) = &channel.accounts {
-        for (account_id, account_cfg) in accounts {
-            if let Some(obj) = account_cfg.as_object() {
-                if let Some(agent_id) = obj.get("agentId").and_then(|v| v.as_str()) {
-                    if !agent_id.trim().is_empty() {
-                        all_pairs.insert(
-                            (channel.id.clone(), account_id.clone()),
-                            agent_id.to_string(),
-                        );
-                    }
+    #[test]
+    fn provider_commands_edit_config_in_place() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        // 字段类型与强类型模型不符（port 为字符串）也不应阻塞 Provider 修改
+        let original = json!({
+            "models": {
+                "providers": {
+                    "ollama": {
+                        "baseUrl": "http://127.0.0.1:11434/v1",
+                        "models": [{ "id": "llava", "input": "image", "compat": { "x": 1 } }]
+                    },
+                    "anthropic": {
+                        "models": [{
+                            "id": "claude",
+                            "cost": { "input": 3, "output": 15, "tiers": [{ "above": 200000 }] }
+                        }]
+                    },
+                    "empty": { "baseUrl": "https://empty.example.com", "models": [] }
                 }
-            }
+            },
+            "agents": { "defaults": { "models": { "ollama/llava": { "alias": "eye" } } } },
+            "plugins": { "allow": [] },
+            "channels": {},
+            "gateway": { "port": "18789" },
+            "customTopLevel": [1, 2]
+        });
+        let config_path = platform_utils::get_config_file_path();
+        fs::write(&config_path, serde_json::to_string_pretty(&original).unwrap()).unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        runtime
+            .block_on(save_provider(
+                "openai".to_string(),
+                "https://api.openai.com/v1".to_string(),
+                Some("sk-test".to_string()),
+                "openai-completions".to_string(),
+                vec![typed_model(json!({ "id": "gpt-4o" }))],
+            ))
+            .expect("应可保存 Provider");
+        runtime
+            .block_on(set_primary_model("openai/gpt-4o".to_string()))
+            .expect("应可设置主模型");
+        runtime
+            .block_on(clone_provider("openai".to_string(), "openai-team".to_string()))
+            .expect("应可复制 Provider");
+        runtime
+            .block_on(delete_provider("openai-team".to_string()))
+            .expect("应可删除 Provider");
+
+        let saved = load_openclaw_config_raw().expect("应可读取保存后的配置");
+        for key in ["plugins", "channels", "gateway", "customTopLevel"] {
+            assert_eq!(saved.get(key), original.get(key), "{} 应原样保留", key);
         }
-    }
-
-    config["bindings"] = merge_bindings_payload_by_shape(&existing_bindings, &all_pairs);
-
-    // 保存配置
-    info!("[保存渠道配置] 写入配置文件...");
-    match save_openclaw_config(&config) {
-        Ok(_) => {
-            info!(
-                "[保存渠道配置] ✓ {} 配置保存成功",
-                channel.channel_type
+        for name in ["ollama", "anthropic", "empty"] {
+            assert_eq!(
+                saved["models"]["providers"][name], original["models"]["providers"][name],
+                "{} 应原样保留（包括整数成本与空模型列表）",
+                name
             );
-            Ok(format!("{} 配置已保存", channel.channel_type))
-        }
-        Err(e) => {
-            error!("[保存渠道配置] ✗ 保存失败: {}", e);
-            Err(e)
         }
+        let alias = saved.pointer("/agents/defaults/models/ollama~1llava/alias");
+        assert_eq!(alias, Some(&json!("eye")));
+        assert_eq!(saved.pointer("/agents/defaults/model/primary"), Some(&json!("openai/gpt-4o")));
+        assert_eq!(saved.pointer("/models/providers/openai/apiKey"), Some(&json!("sk-test")));
+        assert!(saved.pointer("/models/providers/openai-team").is_none());
     }
-}
 
-/// 清空渠道配置 - 从 openclaw.json 中删除指定渠道的配置
-#[command]
-pub async fn clear_channel_config(channel_id: String) -> Result<String, String> {
-    info!("[清空渠道配置] 清空渠道配置: {}", channel_id);
+    #[test]
+    fn typed_round_trip_preserves_unknown_keys() {
+        let original = json!({
+            "agents": {
+                "defaults": {
+                    "model": { "primary": "openai/gpt-4o", "customModelFlag": true },
+                    "models": { "openai/gpt-4o": { "alias": "4o" } },
+                    "workspace": "~/.openclaw/workspace"
+                },
+                "list": [{ "id": "main", "identity": { "emoji": "🦞" } }]
+            },
+            "models": {
+                "mode": "merge",
+                "providers": {
+                    "openai": {
+                        "baseUrl": "https://api.openai.com/v1",
+                        "apiKey": "${OPENAI_API_KEY}",
+                        "headers": { "X-Trace": "1" },
+                        "models": [{
+                            "id": "gpt-4o",
+                            "name": "GPT-4o",
+                            "input": ["text"],
+                            "compat": { "x": 1 }
+                        }]
+                    }
+                }
+            },
+            "gateway": { "port": 18789, "controlUi": { "enabled": true } },
+            "plugins": { "load": { "paths": ["/opt/plugins"] } },
+            "tools": { "profile": "coding" },
+            "discovery": { "mdns": { "mode": "minimal" } },
+            "customTopLevel": { "keep": ["me"] }
+        });
 
-    let mut config = load_openclaw_config_raw()?;
-    let env_path = platform::get_env_file_path();
+        let mut typed = typed_config_from_value(original.clone()).expect("应可解析为强类型配置");
+        assert_eq!(serde_json::to_value(&typed).expect("应可序列化"), original);
 
-    // 从 channels 对象中删除该渠道
-    if let Some(channels) = config.get_mut("channels").and_then(|v| v.as_object_mut()) {
-        channels.remove(&channel_id);
-        info!("[清空渠道配置] 已从 channels 中删除: {}", channel_id);
+        typed.agents.defaults.model.primary = Some("openai/gpt-4o-mini".to_string());
+        let saved = serde_json::to_value(&typed).expect("应可序列化");
+        assert_eq!(
+            saved.pointer("/agents/defaults/model/primary"),
+            Some(&json!("openai/gpt-4o-mini"))
+        );
+        assert_eq!(saved.get("tools"), original.get("tools"));
+        assert_eq!(saved.get("discovery"), original.get("discovery"));
+        assert_eq!(saved.get("customTopLevel"), original.get("customTopLevel"));
+        assert_eq!(
+            saved.pointer("/models/providers/openai/headers"),
+            Some(&json!({ "X-Trace": "1" }))
+        );
     }
 
-    // 从 plugins.allow 数组中删除
-    if let Some(allow_arr) = config.pointer_mut("/plugins/allow").and_then(|v| v.as_array_mut()) {
-        allow_arr.retain(|v| v.as_str() != Some(&channel_id));
-        info!("[清空渠道配置] 已从 plugins.allow 中删除: {}", channel_id);
-    }
+    #[test]
+    fn deleting_primary_provider_removes_primary_key() {
+        let original = json!({
+            "agents": {
+                "defaults": {
+                    "model": { "primary": "openai/gpt-4o" },
+                    "models": { "openai/gpt-4o": {}, "anthropic/claude-sonnet-4-5": {} }
+                }
+            },
+            "models": {
+                "providers": {
+                    "openai": {
+                        "baseUrl": "https://api.openai.com/v1",
+                        "models": [{ "id": "gpt-4o", "name": "GPT-4o" }]
+                    },
+                    "anthropic": {
+                        "baseUrl": "https://api.anthropic.com",
+                        "models": [{ "id": "claude-sonnet-4-5", "name": "Claude Sonnet 4.5" }]
+                    }
+                }
+            }
+        });
 
-    // 从 plugins.entries 中删除
-    if let Some(entries) = config.pointer_mut("/plugins/entries").and_then(|v| v.as_object_mut()) {
-        entries.remove(&channel_id);
-        info!("[清空渠道配置] 已从 plugins.entries 中删除: {}", channel_id);
-    }
+        let mut saved = original;
+        remove_provider(&mut saved, "openai");
 
-    // 清除该渠道相关 bindings，避免遗留 accountId -> agentId 映射
-    let existing_bindings = config.get("bindings").cloned().unwrap_or(json!([]));
-    let mut all_pairs = parse_account_bindings(&existing_bindings);
-    all_pairs.retain(|(binding_channel, _), _| binding_channel != &channel_id);
-    config["bindings"] = merge_bindings_payload_by_shape(&existing_bindings, &all_pairs);
+        let model = saved.pointer("/agents/defaults/model");
+        assert!(
+            model.map_or(true, |m| m.get("primary").is_none()),
+            "primary 应被移除而不是写成 null: {:?}",
+            model
+        );
+        assert!(saved.pointer("/models/providers/openai").is_none());
+        assert!(saved.pointer("/agents/defaults/models/openai~1gpt-4o").is_none());
+        assert!(saved.pointer("/models/providers/anthropic").is_some());
+        assert!(
+            saved
+                .pointer("/models/providers/anthropic/models/0")
+                .is_some_and(|m| m.get("cost").is_none()),
+            "未配置 cost 的模型不应写出 cost 字段"
+        );
+
+        // model 为字符串简写时整体移除
+        let mut shorthand = json!({
+            "agents": { "defaults": { "model": "openai/gpt-4o" } },
+            "models": { "providers": { "openai": { "models": [] } } }
+        });
+        remove_provider(&mut shorthand, "openai");
+        assert_eq!(
+            shorthand,
+            json!({ "agents": { "defaults": {} }, "models": { "providers": {} } })
+        );
+    }
 
-    // 清除相关的环境变量
-    let env_prefixes = vec![
-        format!("OPENCLAW_{}_USERID", channel_id.to_uppercase()),
-        format!("OPENCLAW_{}_TESTCHATID", channel_id.to_uppercase()),
-        format!("OPENCLAW_{}_TESTCHANNELID", channel_id.to_uppercase()),
-    ];
-    for env_key in env_prefixes {
-        let _ = file::remove_env_value(&env_path, &env_key);
+    #[test]
+    fn configured_model_parses_input_modalities() {
+        let cases = [
+            (json!({ "id": "gpt-4o", "input": ["text", "image"] }), vec!["text", "image"]),
+            (json!({ "id": "llama3" }), vec!["text"]),
+            (json!({ "id": "vision", "input": "image" }), vec!["image"]),
+        ];
+
+        for (model, expected) in cases {
+            let parsed = parse_configured_model("demo", &typed_model(model), None);
+            assert_eq!(parsed.inputs, expected, "模型 {} 的 input 解析不符", parsed.id);
+        }
     }
 
-    // 保存配置
-    match save_openclaw_config(&config) {
-        Ok(_) => {
-            info!("[清空渠道配置] ✓ {} 配置已清空", channel_id);
-            Ok(format!("{} 配置已清空", channel_id))
+    #[test]
+    fn import_channels_validates_all_then_saves_once() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({ "bindings": [] })).expect("应可写入测试配置");
+        let channel = |id: &str, config: Value, accounts: Option<Value>| ChannelConfig {
+            id: id.to_string(),
+            channel_type: id.to_string(),
+            enabled: true,
+            configured: true,
+            config: serde_json::from_value(config).unwrap(),
+            accounts: accounts.map(|a| serde_json::from_value(a).unwrap()),
+            login_status: None,
+        };
+        let telegram = channel("telegram", json!({ "botToken": "123:abc", "userId": "42" }), None);
+        let discord = channel(
+            "discord",
+            json!({}),
+            Some(json!({ "ops": { "botToken": "d-token", "agentId": "main" } })),
+        );
+        let whatsapp = channel("whatsapp", json!({ "dmPolicy": "pairing" }), None);
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+
+        // 任一渠道缺少必填字段时整体不写入
+        let bad_slack = channel("slack", json!({ "botToken": " " }), None);
+        let results = runtime
+            .block_on(import_channels(vec![telegram.clone(), bad_slack]))
+            .expect("校验失败应返回结果");
+        assert!(results.iter().all(|r| !r.success));
+        assert_eq!(results[1].message, "缺少必填字段: botToken");
+        assert!(load_openclaw_config_raw().unwrap().get("channels").is_none());
+
+        let results = runtime
+            .block_on(import_channels(vec![telegram, discord, whatsapp]))
+            .expect("应可批量导入");
+        assert!(results.iter().all(|r| r.success), "{:?}", results);
+
+        let config = load_openclaw_config_raw().unwrap();
+        assert_eq!(config.pointer("/channels/telegram/botToken"), Some(&json!("123:abc")));
+        assert!(config.pointer("/channels/telegram/userId").is_none(), "测试字段写入 env");
+        assert_eq!(
+            config.pointer("/channels/discord/accounts/ops/botToken"),
+            Some(&json!("d-token"))
+        );
+        assert_eq!(config.pointer("/channels/whatsapp/enabled"), Some(&json!(true)));
+        assert_eq!(
+            config.pointer("/plugins/allow"),
+            Some(&json!(["telegram", "discord", "whatsapp"]))
+        );
+        assert_eq!(config.pointer("/bindings/0/agentId"), Some(&json!("main")));
+        assert_eq!(config.pointer("/bindings/0/match/accountId"), Some(&json!("ops")));
+        assert_eq!(
+            file_utils::read_env_value(
+                &platform_utils::get_env_file_path(),
+                "OPENCLAW_TELEGRAM_USERID"
+            )
+            .as_deref(),
+            Some("42")
+        );
+    }
+
+    #[test]
+    fn max_concurrent_is_validated_and_keeps_other_defaults() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        let defaults = json!({
+            "model": { "primary": "openai/gpt-4o", "fallbacks": ["anthropic/claude"] },
+            "workspace": "~/.openclaw/workspace",
+            "compaction": { "mode": "safeguard" },
+            "contextPruning": { "mode": "cache-ttl", "ttl": "1h" },
+            "heartbeat": { "every": "30m" },
+            "subagents": { "maxConcurrent": 8 }
+        });
+        save_openclaw_config(&json!({ "agents": { "defaults": defaults.clone() } }))
+            .expect("应可写入测试配置");
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let settings = runtime.block_on(get_agent_defaults()).expect("应可读取默认配置");
+        assert_eq!(settings.primary_model.as_deref(), Some("openai/gpt-4o"));
+        assert_eq!(settings.fallbacks, ["anthropic/claude"]);
+        assert_eq!(settings.max_concurrent, None);
+        assert_eq!(settings.workspace.as_deref(), Some("~/.openclaw/workspace"));
+
+        for bad in [0, 65, 1000] {
+            let err = runtime
+                .block_on(set_max_concurrent(bad))
+                .expect_err("超出范围应被拒绝");
+            assert!(err.contains("1-64"), "实际: {}", err);
         }
-        Err(e) => {
-            error!("[清空渠道配置] ✗ 清空失败: {}", e);
-            Err(e)
+        assert!(load_openclaw_config_raw()
+            .unwrap()
+            .pointer("/agents/defaults/maxConcurrent")
+            .is_none());
+
+        for valid in [1, 64, 4] {
+            runtime
+                .block_on(set_max_concurrent(valid))
+                .expect("范围内的值应可保存");
         }
+        let settings = runtime.block_on(get_agent_defaults()).expect("应可读取默认配置");
+        assert_eq!(settings.max_concurrent, Some(4));
+
+        let mut expected = defaults;
+        expected["maxConcurrent"] = json!(4);
+        let saved = load_openclaw_config_raw().unwrap();
+        assert_eq!(saved.pointer("/agents/defaults"), Some(&expected));
+
+        // 简写的 model 同样可以读取，其它部分的类型问题不影响读取与修改
+        let mut shorthand = load_openclaw_config_raw().unwrap();
+        shorthand["agents"]["defaults"]["model"] = json!("openai/gpt-4o-mini");
+        shorthand["gateway"] = json!({ "port": "18789" });
+        save_openclaw_config_edit(&shorthand).expect("应可写入测试配置");
+        let settings = runtime.block_on(get_agent_defaults()).expect("应可读取默认配置");
+        assert_eq!(settings.primary_model.as_deref(), Some("openai/gpt-4o-mini"));
+        assert!(settings.fallbacks.is_empty());
+        runtime.block_on(set_max_concurrent(6)).expect("无关字段类型不应阻止修改并发数");
+        let saved = load_openclaw_config_raw().unwrap();
+        assert_eq!(saved.pointer("/gateway/port"), Some(&json!("18789")));
+
+        // 其它字段原样保留：占位符不展开，单值 input 与整数成本不被规范化
+        let mut with_placeholder = saved;
+        let provider = json!({
+            "baseUrl": "${CUSTOM_BASE_URL}",
+            "apiKey": "${OPENCLAW_TEST_UNSET_KEY}",
+            "models": [{ "id": "llava", "input": "image", "cost": { "input": 3 } }]
+        });
+        with_placeholder["models"] = json!({ "providers": { "custom": provider.clone() } });
+        save_openclaw_config_edit(&with_placeholder).expect("应可写入测试配置");
+        runtime.block_on(set_max_concurrent(8)).expect("占位符不应阻止修改并发数");
+        let saved = load_openclaw_config_raw().unwrap();
+        assert_eq!(saved.pointer("/agents/defaults/maxConcurrent"), Some(&json!(8)));
+        assert_eq!(saved.pointer("/models/providers/custom"), Some(&provider));
     }
-}
 
-// ============ 飞书插件管理 ============
+    #[test]
+    fn channel_plugin_status_maps_feishu_to_its_package() {
+        let plugin = |name: &str| PluginInfo {
+            name: name.to_string(),
+            version: Some("0.1.2".to_string()),
+            enabled: true,
+            raw: name.to_string(),
+        };
 
-/// 飞书插件状态
-#[derive(Debug, Serialize, Deserialize)]
-pub struct FeishuPluginStatus {
-    pub installed: bool,
-    pub version: Option<String>,
-    pub plugin_name: Option<String>,
-}
+        let missing = channel_plugin_status("feishu", Some(&[plugin("@openclaw/voice-call")]));
+        assert_eq!(missing.package.as_deref(), Some("@m1heng-clawd/feishu"));
+        assert!(!missing.builtin && !missing.installed);
+        assert_eq!(missing.state, PluginInstallState::Missing);
+        assert_eq!(
+            missing.install_command.as_deref(),
+            Some("openclaw plugins install @m1heng-clawd/feishu")
+        );
 
-/// 检查飞书插件是否已安装
-#[command]
-pub async fn check_feishu_plugin() -> Result<FeishuPluginStatus, String> {
-    info!("[飞书插件] 检查飞书插件安装状态...");
-    
-    // 执行 openclaw plugins list 命令
-    match shell::run_openclaw(&["plugins", "list"]) {
-        Ok(output) => {
-            debug!("[飞书插件] plugins list 输出: {}", output);
-            
-            // 查找包含 feishu 的行（不区分大小写）
-            let lines: Vec<&str> = output.lines().collect();
-            let feishu_line = lines.iter().find(|line| {
-                line.to_lowercase().contains("feishu")
-            });
-            
-            if let Some(line) = feishu_line {
-                info!("[飞书插件] ✓ 飞书插件已安装: {}", line);
-                
-                // 尝试解析版本号（通常格式为 "name@version" 或 "name version"）
-                let version = if line.contains('@') {
-                    line.split('@').last().map(|s| s.trim().to_string())
-                } else {
-                    // 尝试匹配版本号模式 (如 0.1.2)
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    parts.iter()
-                        .find(|p| p.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false))
-                        .map(|s| s.to_string())
-                };
-                
-                Ok(FeishuPluginStatus {
-                    installed: true,
-                    version,
-                    plugin_name: Some(line.trim().to_string()),
-                })
-            } else {
-                info!("[飞书插件] ✗ 飞书插件未安装");
-                Ok(FeishuPluginStatus {
-                    installed: false,
-                    version: None,
-                    plugin_name: None,
-                })
-            }
-        }
-        Err(e) => {
-            warn!("[飞书插件] 检查插件列表失败: {}", e);
-            // 如果命令失败，假设插件未安装
-            Ok(FeishuPluginStatus {
-                installed: false,
-                version: None,
-                plugin_name: None,
-            })
+        for name in ["@m1heng-clawd/feishu", "feishu"] {
+            let installed = channel_plugin_status("feishu", Some(&[plugin(name)]));
+            assert!(installed.installed, "{} 应视为已安装", name);
+            assert_eq!(installed.state, PluginInstallState::Installed);
+            assert_eq!(installed.version.as_deref(), Some("0.1.2"));
+            assert!(installed.install_command.is_none());
         }
+
+        let telegram = channel_plugin_status("telegram", Some(&[]));
+        assert!(telegram.builtin && telegram.installed);
+        assert!(telegram.package.is_none());
+        assert!(channel_plugin_status("telegram", None).installed);
+
+        // 插件列表获取失败时报告未知，而不是缺失
+        let unknown = channel_plugin_status("feishu", None);
+        assert_eq!(unknown.state, PluginInstallState::Unknown);
+        assert!(!unknown.installed && unknown.install_command.is_none());
+        assert!(unknown.notice().is_some_and(|n| n.contains("无法获取插件列表")));
+        assert!(missing.notice().is_some_and(|n| n.contains("openclaw plugins install")));
     }
-}
 
-/// 安装飞书插件
-#[command]
-pub async fn install_feishu_plugin() -> Result<String, String> {
-    info!("[飞书插件] 开始安装飞书插件...");
-    
-    // 先检查是否已安装
-    let status = check_feishu_plugin().await?;
-    if status.installed {
-        info!("[飞书插件] 飞书插件已安装，跳过");
-        return Ok(format!("飞书插件已安装: {}", status.plugin_name.unwrap_or_default()));
+    #[test]
+    fn channels_enabled_toggle_keeps_accounts_and_bindings() {
+        let mut config = json!({
+            "channels": {
+                "telegram": {
+                    "enabled": true,
+                    "accounts": { "bot-a": { "botToken": "123:abc", "agentId": "main" } }
+                },
+                "discord": { "enabled": true, "token": "discord-token" }
+            },
+            "plugins": {
+                "allow": ["telegram", "discord"],
+                "entries": {
+                    "telegram": { "enabled": true },
+                    "discord": { "enabled": true, "config": { "intents": 1 } }
+                }
+            },
+            "bindings": [
+                { "agentId": "main", "match": { "channel": "telegram", "accountId": "bot-a" } }
+            ]
+        });
+        let ids = vec!["telegram".to_string(), "discord".to_string()];
+
+        apply_channels_enabled(&mut config, &ids, false).expect("禁用应成功");
+        assert_eq!(config.pointer("/channels/telegram/enabled"), Some(&json!(false)));
+        assert_eq!(
+            config.pointer("/channels/telegram/accounts/bot-a/botToken"),
+            Some(&json!("123:abc"))
+        );
+        assert_eq!(config.pointer("/channels/discord/token"), Some(&json!("discord-token")));
+        assert_eq!(config.pointer("/plugins/allow"), Some(&json!([])));
+        assert_eq!(config.pointer("/plugins/entries/discord/enabled"), Some(&json!(false)));
+        assert_eq!(
+            config.pointer("/plugins/entries/discord/config"),
+            Some(&json!({ "intents": 1 }))
+        );
+        assert_eq!(config.pointer("/bindings/0/match/accountId"), Some(&json!("bot-a")));
+
+        apply_channels_enabled(&mut config, &ids[..1], true).expect("启用应成功");
+        assert_eq!(config.pointer("/channels/telegram/enabled"), Some(&json!(true)));
+        assert_eq!(config.pointer("/plugins/allow"), Some(&json!(["telegram"])));
+        assert_eq!(config.pointer("/plugins/entries/telegram/enabled"), Some(&json!(true)));
+        assert_eq!(config.pointer("/channels/discord/enabled"), Some(&json!(false)));
+
+        let err = apply_channels_enabled(&mut config, &["slack".to_string()], true)
+            .expect_err("未配置的渠道应报错");
+        assert!(err.contains("slack"));
     }
-    
-    // 安装飞书插件
-    // 注意：使用 @m1heng-clawd/feishu 包名
-    info!("[飞书插件] 执行 openclaw plugins install @m1heng-clawd/feishu ...");
-    match shell::run_openclaw(&["plugins", "install", "@m1heng-clawd/feishu"]) {
-        Ok(output) => {
-            info!("[飞书插件] 安装输出: {}", output);
-            
-            // 验证安装结果
-            let verify_status = check_feishu_plugin().await?;
-            if verify_status.installed {
-                info!("[飞书插件] ✓ 飞书插件安装成功");
-                Ok(format!("飞书插件安装成功: {}", verify_status.plugin_name.unwrap_or_default()))
-            } else {
-                warn!("[飞书插件] 安装命令执行成功但插件未找到");
-                Err("安装命令执行成功但插件未找到，请检查 openclaw 版本".to_string())
+
+    #[test]
+    fn configured_channels_without_enabled_flag_are_enabled() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "channels": {
+                "telegram": { "botToken": "123:abc" },
+                "discord": { "enabled": false, "botToken": "d-token" },
+                "slack": {}
             }
-        }
-        Err(e) => {
-            error!("[飞书插件] ✗ 安装失败: {}", e);
-            Err(format!("安装飞书插件失败: {}\n\n请手动执行: openclaw plugins install @m1heng-clawd/feishu", e))
-        }
+        }))
+        .expect("应可写入测试配置");
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let channels = runtime.block_on(get_channels_config()).expect("应可读取渠道配置");
+        let enabled = |id: &str| channels.iter().find(|c| c.id == id).map(|c| c.enabled);
+        assert_eq!(enabled("telegram"), Some(true));
+        assert_eq!(enabled("discord"), Some(false));
+        assert_eq!(enabled("slack"), Some(false));
+        assert_eq!(enabled("feishu"), Some(false));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{
-        build_config_diff_summary, load_env_file_vars, normalize_and_validate_config,
-        parse_openclaw_config_content,
-        replace_config_vars, save_openclaw_config,
-    };
-    use crate::utils::{file as file_utils, platform as platform_utils};
-    use serde_json::{json, Value};
-    use std::fs;
-    use std::path::PathBuf;
-    use std::sync::{Mutex, OnceLock};
-    use std::time::{SystemTime, UNIX_EPOCH};
+    #[test]
+    fn matrix_channel_is_surfaced_and_round_trips() {
+        let _env_lock = test_env_lock();
+        let home_guard = TempHomeGuard::new();
+        let config_path = platform_utils::get_config_file_path();
+        file_utils::write_file(
+            &config_path,
+            r#"{"channels":{"matrix":{"enabled":true,"homeserver":"https://matrix.org","accessToken":"mx-token"}}}"#,
+        )
+        .expect("应可写入测试配置");
 
-    static TEST_ENV_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let channels = runtime.block_on(get_channels_config()).expect("应可读取渠道配置");
+        let mut matrix = channels
+            .into_iter()
+            .find(|c| c.id == "matrix")
+            .expect("channels.matrix 应出现在渠道列表中");
+        assert!(matrix.enabled);
+        assert_eq!(matrix.config.get("homeserver"), Some(&json!("https://matrix.org")));
 
-    fn test_env_lock() -> std::sync::MutexGuard<'static, ()> {
-        TEST_ENV_LOCK
-            .get_or_init(|| Mutex::new(()))
-            .lock()
-            .expect("测试环境锁不应中毒")
+        matrix.config.insert("testRoomId".to_string(), json!("!room:matrix.org"));
+        runtime
+            .block_on(save_channel_config(matrix))
+            .expect("应可保存 matrix 配置");
+
+        let saved = file_utils::read_file(&config_path).expect("应可读取写回结果");
+        assert!(saved.contains("mx-token"));
+        assert!(!saved.contains("testRoomId"), "测试字段不应写入 openclaw.json");
+        let env_path = platform_utils::get_env_file_path();
+        assert_eq!(
+            file_utils::read_env_value(&env_path, "OPENCLAW_MATRIX_TESTROOMID").as_deref(),
+            Some("!room:matrix.org")
+        );
+
+        let channels = runtime.block_on(get_channels_config()).expect("应可再次读取渠道配置");
+        let matrix = channels.iter().find(|c| c.id == "matrix").expect("matrix 应仍存在");
+        assert_eq!(matrix.config.get("testRoomId"), Some(&json!("!room:matrix.org")));
+        assert_eq!(matrix.config.get("accessToken"), Some(&json!("mx-token")));
+        assert!(channels.iter().any(|c| c.id == "signal"));
+
+        runtime
+            .block_on(clear_channel_config("matrix".to_string()))
+            .expect("应可清空 matrix 配置");
+        assert!(file_utils::read_env_value(&env_path, "OPENCLAW_MATRIX_TESTROOMID").is_none());
+
+        let _ = home_guard;
     }
 
-    struct EnvGuard {
-        key: String,
-        previous: Option<String>,
+    #[test]
+    fn plugins_list_parses_common_formats() {
+        let output = "Installed plugins:\n\
+            - @m1heng-clawd/feishu@0.1.2\n\
+            - matrix 1.4.0 (disabled)\n\
+            - voice-call v2.0.1 enabled\n\
+            - local-tools\n";
+        let entries = json!({ "local-tools": { "enabled": false } });
+
+        let plugins = parse_plugins_list(output, Some(&entries));
+        let summary: Vec<(&str, Option<&str>, bool)> = plugins
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_deref(), p.enabled))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("@m1heng-clawd/feishu", Some("0.1.2"), true),
+                ("matrix", Some("1.4.0"), false),
+                ("voice-call", Some("2.0.1"), true),
+                ("local-tools", None, false),
+            ]
+        );
     }
 
-    impl EnvGuard {
-        fn set(key: &str, value: &str) -> Self {
-            let previous = std::env::var(key).ok();
-            unsafe {
-                std::env::set_var(key, value);
-            }
-            Self {
-                key: key.to_string(),
-                previous,
-            }
-        }
+    #[test]
+    fn agent_crud_preserves_extra_and_cascades_bindings() {
+        let mut config = json!({
+            "agents": {
+                "list": [
+                    { "id": "main", "name": "主助手", "identity": { "emoji": "🦞" } }
+                ]
+            },
+            "channels": {
+                "telegram": { "accounts": { "bot-b": { "botToken": "t", "agentId": "helper" } } }
+            },
+            "bindings": [
+                { "agentId": "main", "match": { "channel": "telegram", "accountId": "bot-a" } },
+                { "agentId": "helper", "match": { "channel": "telegram", "accountId": "bot-b" } },
+                { "agentId": "helper", "match": { "channel": "discord" } }
+            ]
+        });
 
-        fn remove(key: &str) -> Self {
-            let previous = std::env::var(key).ok();
-            unsafe {
-                std::env::remove_var(key);
-            }
-            Self {
-                key: key.to_string(),
-                previous,
-            }
-        }
+        let helper: AgentEntry =
+            serde_json::from_value(json!({ "id": "helper", "name": "助理" })).expect("应可解析");
+        insert_agent(&mut config, helper.clone()).expect("新增 Agent 应成功");
+        assert_eq!(config.pointer("/agents/list/1/id"), Some(&json!("helper")));
+        assert!(insert_agent(&mut config, helper).is_err(), "重复 id 应报错");
+
+        let update: AgentEntry =
+            serde_json::from_value(json!({ "name": "主助手 v2", "workspace": "/tmp/main" }))
+                .expect("应可解析");
+        replace_agent(&mut config, "main", update).expect("更新 Agent 应成功");
+        assert_eq!(config.pointer("/agents/list/0/name"), Some(&json!("主助手 v2")));
+        assert_eq!(config.pointer("/agents/list/0/workspace"), Some(&json!("/tmp/main")));
+        assert_eq!(
+            config.pointer("/agents/list/0/identity/emoji"),
+            Some(&json!("🦞")),
+            "更新时应保留 extra 字段"
+        );
+
+        remove_agent(&mut config, "helper").expect("删除 Agent 应成功");
+        let list = config.pointer("/agents/list").and_then(|v| v.as_array()).expect("list");
+        assert_eq!(list.len(), 1);
+        let bindings = config.get("bindings").and_then(|v| v.as_array()).expect("bindings");
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].get("agentId"), Some(&json!("main")));
+        assert!(config.pointer("/channels/telegram/accounts/bot-b/agentId").is_none());
+        assert_eq!(config.pointer("/channels/telegram/accounts/bot-b/botToken"), Some(&json!("t")));
+
+        let mut grouped = json!({
+            "agents": { "list": [{ "id": "main" }, { "id": "helper" }] },
+            "bindings": { "telegram": { "bot-a": "main", "bot-b": "helper" } }
+        });
+        remove_agent(&mut grouped, "helper").expect("删除 Agent 应成功");
+        assert_eq!(grouped.get("bindings"), Some(&json!({ "telegram": { "bot-a": "main" } })));
     }
 
-    impl Drop for EnvGuard {
-        fn drop(&mut self) {
-            if let Some(prev) = &self.previous {
-                unsafe {
-                    std::env::set_var(&self.key, prev);
-                }
-            } else {
-                unsafe {
-                    std::env::remove_var(&self.key);
+    #[test]
+    fn disabling_plugin_only_flips_enabled_flags() {
+        let mut config = json!({
+            "channels": {
+                "feishu": {
+                    "enabled": true,
+                    "appId": "cli_xxx",
+                    "accounts": { "main": { "appSecret": "secret" } }
                 }
+            },
+            "plugins": {
+                "allow": ["", "feishu", "voice-call"],
+                "entries": { "feishu": { "enabled": true, "config": { "region": "cn" } } }
             }
-        }
+        });
+        let channels_before = config.get("channels").cloned();
+
+        set_plugin_enabled(&mut config, "feishu", false);
+        assert_eq!(config.get("channels").cloned(), channels_before, "渠道配置不应被修改");
+        assert_eq!(config.pointer("/plugins/allow"), Some(&json!(["voice-call"])));
+        assert_eq!(config.pointer("/plugins/entries/feishu/enabled"), Some(&json!(false)));
+        assert_eq!(
+            config.pointer("/plugins/entries/feishu/config"),
+            Some(&json!({ "region": "cn" }))
+        );
+
+        set_plugin_enabled(&mut config, "feishu", true);
+        set_plugin_enabled(&mut config, "feishu", true);
+        assert_eq!(config.pointer("/plugins/allow"), Some(&json!(["voice-call", "feishu"])));
+        assert_eq!(config.pointer("/plugins/entries/feishu/enabled"), Some(&json!(true)));
+        assert_eq!(config.get("channels").cloned(), channels_before);
     }
 
-    struct TempHomeGuard {
-        previous_home: Option<String>,
-        temp_home_dir: PathBuf,
+    #[test]
+    fn plugin_consistency_audit_flags_and_repairs_each_kind() {
+        let mut config = json!({
+            "channels": {
+                "telegram": { "enabled": true, "botToken": "x" },
+                "discord": { "enabled": true },
+                "slack": { "enabled": false },
+                "feishu": { "enabled": true }
+            },
+            "plugins": {
+                "allow": ["", "discord", "whatsapp", "voice-call", "  "],
+                "entries": {
+                    "discord": { "enabled": true },
+                    "feishu": { "enabled": false },
+                    "memory-lancedb": { "enabled": true, "config": { "dim": 3 } },
+                    "voice-call": { "enabled": true },
+                    "old-plugin": { "enabled": false }
+                }
+            }
+        });
+
+        let issues = find_plugin_consistency_issues(&config);
+        let found: Vec<(PluginConsistencyKind, &str)> =
+            issues.iter().map(|i| (i.kind, i.plugin_id.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (PluginConsistencyKind::EmptyAllowEntry, ""),
+                (PluginConsistencyKind::ChannelNotAllowed, "telegram"),
+                (PluginConsistencyKind::AllowedWithoutChannel, "whatsapp"),
+                (PluginConsistencyKind::EntryNotAllowed, "memory-lancedb"),
+            ],
+            "slack 已禁用、feishu 被显式禁用插件、voice-call 非渠道插件，均不应报告"
+        );
+        assert!(issues[0].message.contains('2'));
+
+        let repaired = repair_plugin_consistency_issues(&mut config);
+        assert_eq!(repaired, issues);
+        assert_eq!(
+            config.pointer("/plugins/allow"),
+            Some(&json!(["discord", "voice-call", "telegram", "memory-lancedb"]))
+        );
+        assert_eq!(config.pointer("/plugins/entries/telegram/enabled"), Some(&json!(true)));
+        assert_eq!(
+            config.pointer("/plugins/entries/memory-lancedb/config"),
+            Some(&json!({ "dim": 3 }))
+        );
+        assert!(config.pointer("/plugins/entries/whatsapp").is_none());
+        assert!(find_plugin_consistency_issues(&config).is_empty());
+
+        // 未启用白名单时不报告「不在 allow 中」
+        let no_allowlist = json!({
+            "channels": { "telegram": { "enabled": true } },
+            "plugins": { "entries": { "memory-lancedb": { "enabled": true } } }
+        });
+        assert!(find_plugin_consistency_issues(&no_allowlist).is_empty());
     }
 
-    impl TempHomeGuard {
-        fn new() -> Self {
-            let previous_home = std::env::var("HOME").ok();
-            let unique = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_nanos();
-            let temp_home_dir = std::env::temp_dir().join(format!("openclaw-config-test-home-{}", unique));
-            fs::create_dir_all(temp_home_dir.join(".openclaw"))
-                .expect("应可创建临时 home 目录");
+    #[test]
+    fn duplicate_account_bindings_keep_first_and_are_reported() {
+        let array_bindings = json!([
+            { "agentId": "main", "match": { "channel": "telegram", "accountId": "bot-a" } },
+            { "agentId": "helper", "match": { "channel": "telegram", "accountId": "bot-a" } },
+            { "agentId": "helper", "match": { "channel": "telegram", "accountId": "bot-b" } }
+        ]);
+        let (pairs, duplicates) = parse_account_bindings_with_duplicates(&array_bindings);
+        assert_eq!(
+            pairs.get(&("telegram".to_string(), "bot-a".to_string())),
+            Some(&"main".to_string())
+        );
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].ignored_agent_id, "helper");
+
+        // "telegram/bot-a" 与 "telegram:bot-a" 指向同一账号
+        let flat_bindings = json!({ "telegram/bot-a": "main", "telegram:bot-a": "helper" });
+        let (pairs, duplicates) = parse_account_bindings_with_duplicates(&flat_bindings);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].account_id, "bot-a");
+    }
 
-            unsafe {
-                std::env::set_var("HOME", temp_home_dir.to_string_lossy().to_string());
-            }
+    #[test]
+    fn channel_account_issues_flag_case_collisions_and_missing_accounts() {
+        let config = json!({
+            "channels": {
+                "telegram": { "accounts": { "Bot-A": {}, "bot-a": {} } }
+            },
+            "bindings": [
+                { "agentId": "main", "match": { "channel": "telegram", "accountId": "bot-a" } },
+                { "agentId": "main", "match": { "channel": "telegram", "accountId": "ghost" } },
+                { "agentId": "helper", "match": { "channel": "telegram", "accountId": "ghost" } }
+            ]
+        });
 
-            Self {
-                previous_home,
-                temp_home_dir,
-            }
-        }
+        let issues = collect_channel_account_issues(&config);
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "channels.telegram.accounts.bot-a",
+                "bindings.telegram.ghost",
+                "bindings.telegram.ghost",
+            ]
+        );
+        assert!(issues[1].message.contains("重复绑定"));
+        assert!(issues[2].message.contains("不存在"));
+    }
 
-        fn write_openclaw_env(&self, content: &str) {
-            let env_path = self.temp_home_dir.join(".openclaw").join("env");
-            fs::write(env_path, content).expect("应可写入临时 env 文件");
-        }
+    #[test]
+    fn saving_a_channel_reports_dropped_bindings() {
+        let before = json!({
+            "channels": { "telegram": { "accounts": { "bot-a": {}, "bot-b": {} } } },
+            "bindings": [
+                { "agentId": "main", "match": { "channel": "telegram", "accountId": "bot-a" } },
+                { "agentId": "helper", "match": { "channel": "telegram", "accountId": "bot-b" } },
+                { "agentId": "other", "match": { "channel": "telegram", "accountId": "bot-a" } },
+                { "agentId": "main", "match": { "channel": "discord", "accountId": "default" } }
+            ]
+        });
+        let channel = ChannelConfig {
+            id: "telegram".to_string(),
+            channel_type: "telegram".to_string(),
+            enabled: true,
+            configured: true,
+            config: Default::default(),
+            accounts: Some(
+                serde_json::from_value(json!({ "bot-a": { "agentId": "main" }, "bot-b": {} }))
+                    .unwrap(),
+            ),
+            login_status: None,
+        };
+        let mut after = before.clone();
+        apply_channel_config(&mut after, &channel);
+
+        let issues = dropped_channel_bindings(&before, &after, "telegram");
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["bindings.telegram.bot-a", "bindings.telegram.bot-b"]);
+        assert!(issues[0].message.contains("丢弃 other"), "{}", issues[0].message);
+        assert!(issues[1].message.contains("helper"), "{}", issues[1].message);
+
+        // 其它渠道的绑定不受影响，也不会被报告
+        assert_eq!(parse_account_bindings(&after["bindings"]).len(), 2);
+        assert!(dropped_channel_bindings(&after, &after, "telegram").is_empty());
     }
 
-    impl Drop for TempHomeGuard {
-        fn drop(&mut self) {
-            if let Some(prev) = &self.previous_home {
-                unsafe {
-                    std::env::set_var("HOME", prev);
-                }
-            } else {
-                unsafe {
-                    std::env::remove_var("HOME");
-                }
+    #[test]
+    fn bindings_convert_between_all_shapes() {
+        let array = json!([
+            { "agentId": "main", "match": { "channel": "discord", "accountId": "default" } },
+            { "agentId": "helper", "match": { "channel": "telegram", "accountId": "bot-a" } }
+        ]);
+        let flat = json!({ "discord/default": "main", "telegram/bot-a": "helper" });
+        let grouped = json!({
+            "discord": { "default": "main" },
+            "telegram": { "bot-a": "helper" }
+        });
+        let shapes = [
+            (BindingsShape::Array, &array),
+            (BindingsShape::Flat, &flat),
+            (BindingsShape::Grouped, &grouped),
+        ];
+
+        for (source_shape, source) in &shapes {
+            for (target_shape, expected) in &shapes {
+                let converted =
+                    convert_bindings_shape(source, *target_shape).expect("迁移应成功");
+                assert_eq!(
+                    &converted, *expected,
+                    "{:?} -> {:?} 结果不符",
+                    source_shape, target_shape
+                );
+                assert_eq!(BindingsShape::detect(&converted), *target_shape);
+
+                let back = convert_bindings_shape(&converted, *source_shape).expect("回迁应成功");
+                assert_eq!(&back, *source);
             }
-            let _ = fs::remove_dir_all(&self.temp_home_dir);
         }
+
+        let with_peer = json!([
+            { "agentId": "main", "match": { "channel": "telegram", "accountId": "bot-a" } },
+            {
+                "agentId": "vip",
+                "match": { "channel": "telegram", "peer": { "kind": "dm", "id": "1" } }
+            }
+        ]);
+        assert!(convert_bindings_shape(&with_peer, BindingsShape::Flat).is_err());
+        let kept = convert_bindings_shape(&with_peer, BindingsShape::Array).expect("数组迁移应成功");
+        assert_eq!(kept, with_peer);
+
+        // 数组内迁移保持原有顺序：peer 路由在前、账号路由不被重新排序
+        let ordered = json!([
+            {
+                "agentId": "vip",
+                "match": { "channel": "telegram", "peer": { "kind": "dm", "id": "1" } }
+            },
+            { "agentId": "zeta", "match": { "channel": "telegram", "accountId": "bot-b" } },
+            { "agentId": "main", "match": { "channel": "discord", "accountId": "default" } }
+        ]);
+        let kept = convert_bindings_shape(&ordered, BindingsShape::Array).expect("数组迁移应成功");
+        assert_eq!(kept, ordered);
+        assert!(BindingsShape::parse("tree").is_err());
     }
 
-    fn apply_replacement(mut value: Value) -> Result<Value, String> {
-        let env_file_vars = load_env_file_vars();
-        replace_config_vars(&mut value, &env_file_vars, "")?;
-        Ok(value)
+    #[test]
+    fn provider_base_url_is_normalized() {
+        assert!(normalize_base_url("api.openai.com/v1", true).is_err(), "缺少协议头应拒绝");
+        assert!(normalize_base_url("ftp://example.com", false).is_err());
+
+        let (url, warning) =
+            normalize_base_url(" https://api.openai.com/v1// ", true).expect("合法地址");
+        assert_eq!(url, "https://api.openai.com/v1");
+        assert!(warning.is_none());
+
+        let (url, warning) = normalize_base_url("http://localhost:11434/v1/", true).expect("本机地址");
+        assert_eq!(url, "http://localhost:11434/v1");
+        assert!(warning.is_none(), "本机 http 不应告警");
+        let (_, warning) = normalize_base_url("http://127.0.0.1:8000", true).expect("本机地址");
+        assert!(warning.is_none());
+
+        let (url, warning) = normalize_base_url("http://llm.example.com/v1", true).expect("可保存");
+        assert_eq!(url, "http://llm.example.com/v1");
+        assert!(warning.is_some(), "远程 http 且需要 API Key 时应告警");
+        let (_, warning) = normalize_base_url("http://llm.example.com/v1", false).expect("可保存");
+        assert!(warning.is_none());
+
+        let (url, _) = normalize_base_url("${CUSTOM_BASE_URL}", true).expect("占位符原样保留");
+        assert_eq!(url, "${CUSTOM_BASE_URL}");
+
+        // 是否需要 API Key 取决于 Provider 定义，与是否已保存 key 无关
+        assert!(!provider_requires_api_key("ollama"));
+        assert!(provider_requires_api_key("openai"));
+        assert!(provider_requires_api_key("my-proxy"), "自定义 Provider 按需要 API Key 处理");
     }
 
     #[test]
-    fn parse_pure_json_config() {
-        let content = r#"{"gateway":{"auth":{"token":"test-token"}}}"#;
-        let parsed = parse_openclaw_config_content(content).expect("纯 JSON 配置应可读取");
+    fn orphaned_bindings_are_detected_and_removed() {
+        let mut config = json!({
+            "agents": { "list": [{ "id": "main" }] },
+            "bindings": [
+                { "agentId": "main", "match": { "channel": "telegram", "accountId": "bot-a" } },
+                { "agentId": "ghost", "match": { "channel": "telegram", "accountId": "bot-b" } },
+                { "agentId": "ghost", "match": { "channel": "discord", "guildId": "g-1" } }
+            ]
+        });
+
+        let orphans = find_orphaned_bindings(&config);
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].agent_id, "ghost");
+        assert_eq!(orphans[0].account_id, "bot-b");
 
+        let removed = remove_orphaned_bindings(&mut config);
+        assert_eq!(removed, orphans);
+        // 非账号路由不在孤立报告中，修复时也不应被删除
         assert_eq!(
-            parsed
-                .pointer("/gateway/auth/token")
-                .and_then(|v| v.as_str()),
-            Some("test-token")
+            config.get("bindings"),
+            Some(&json!([
+                { "agentId": "main", "match": { "channel": "telegram", "accountId": "bot-a" } },
+                { "agentId": "ghost", "match": { "channel": "discord", "guildId": "g-1" } }
+            ]))
         );
+        assert!(find_orphaned_bindings(&config).is_empty());
+
+        let mut flat = json!({
+            "agents": { "list": [{ "id": "main" }] },
+            "bindings": { "telegram/bot-a": "main", "telegram/bot-b": "ghost" }
+        });
+        remove_orphaned_bindings(&mut flat);
+        assert_eq!(flat.get("bindings"), Some(&json!({ "telegram/bot-a": "main" })));
     }
 
     #[test]
-    fn parse_json5_with_comments_and_trailing_comma() {
-        let content = r#"
-        {
-          // JSON5 注释
-          gateway: {
-            auth: {
-              token: "json5-token",
+    fn config_bundle_round_trip_and_redaction() {
+        let config = json!({
+            "models": {
+                "providers": {
+                    "openai": {
+                        "baseUrl": "https://api.openai.com/v1",
+                        "apiKey": "sk-live-secret",
+                        "models": [{ "id": "gpt-4o", "name": "GPT-4o", "maxTokens": 4096 }]
+                    },
+                    "anthropic": {
+                        "baseUrl": "https://api.anthropic.com",
+                        "apiKey": "${ANTHROPIC_API_KEY}",
+                        "models": []
+                    }
+                }
             },
-          },
+            "gateway": { "auth": { "token": "gateway-secret" } }
+        });
+        let env = "export ANTHROPIC_API_KEY=sk-ant-secret\nexport OPENCLAW_TELEGRAM_USERID=42\n";
+
+        let full = build_config_bundle(&config, Some(env), true).expect("应可导出");
+        // 经 IPC 以 base64 传输，解码后应与原始数据一致
+        let encoded = BASE64.encode(&full);
+        assert_eq!(decode_config_bundle(&format!("{}\n", encoded)).unwrap(), full);
+        assert!(decode_config_bundle("not base64!").is_err());
+        let (manifest, restored, restored_env) = read_config_bundle(&full).expect("应可导入");
+        assert!(manifest.includes_secrets);
+        assert_eq!(restored, config, "完整导出应原样保留 providers");
+        assert_eq!(restored_env.as_deref(), Some(env));
+
+        let redacted = build_config_bundle(&config, Some(env), false).expect("应可导出");
+        let (manifest, restored, restored_env) = read_config_bundle(&redacted).expect("应可导入");
+        assert!(!manifest.includes_secrets);
+        assert!(restored.pointer("/models/providers/openai/apiKey").is_none());
+        assert!(restored.pointer("/gateway/auth/token").is_none());
+        assert_eq!(
+            restored.pointer("/models/providers/anthropic/apiKey"),
+            Some(&json!("${ANTHROPIC_API_KEY}")),
+            "占位符不是明文密钥，应保留"
+        );
+        assert_eq!(
+            restored.pointer("/models/providers/openai/models/0/maxTokens"),
+            Some(&json!(4096))
+        );
+        let restored_env = restored_env.expect("应包含 env");
+        assert!(!restored_env.contains("sk-ant-secret"));
+        assert!(restored_env.contains("OPENCLAW_TELEGRAM_USERID=42"));
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let manifest =
+            br#"{"version":99,"createdAt":"","managerVersion":"","includesSecrets":true}"#;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o600);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "manifest.json", &manifest[..])
+            .expect("应可写入");
+        let incompatible = builder.into_inner().expect("应可生成");
+        let err = read_config_bundle(&incompatible).expect_err("版本不兼容应拒绝");
+        assert!(err.contains("版本不兼容"));
+    }
+
+    #[test]
+    fn secrets_are_masked_adaptively() {
+        let revealed = |masked: &str| masked.replace("...", "").replace('*', "").chars().count();
+        let alphabet = "abcdefghijklmnopqrstuvwxyz0123456789ABCD";
+
+        for (len, expected) in [(4, 0), (8, 0), (12, 2), (40, 5)] {
+            let key: String = alphabet.chars().take(len).collect();
+            let masked = mask_secret(&key);
+            assert_eq!(revealed(&masked), expected, "长度 {} 的密钥: {}", len, masked);
+            assert!(!masked.contains(&key[MASK_MAX_PREFIX.min(len)..]), "不应泄露中间部分");
         }
-        "#;
 
-        let parsed = parse_openclaw_config_content(content).expect("JSON5 配置应可读取");
+        assert_eq!(mask_secret("sk-12345"), "****");
+        assert_eq!(mask_secret("sk-abcdefghij"), "s...j");
+        assert_eq!(mask_secret("sk-ant-REDACTED"), "sk-...uv");
+        assert_eq!(mask_secret("密钥密钥密钥密钥密钥密钥"), "密...钥");
+    }
 
-        assert_eq!(
-            parsed
-                .pointer("/gateway/auth/token")
-                .and_then(|v| v.as_str()),
-            Some("json5-token")
-        );
+    #[test]
+    fn channel_enabled_is_reported_separately_from_configured() {
+        let _env_lock = test_env_lock();
+        let _home = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "channels": {
+                "telegram": { "enabled": false, "botToken": "123:abc", "dmPolicy": "pairing" }
+            },
+            "plugins": { "allow": [], "entries": { "telegram": { "enabled": false } } }
+        }))
+        .expect("写入测试配置");
+
+        let runtime = tokio::runtime::Runtime::new().expect("创建运行时");
+        let find_telegram = |channels: Vec<crate::models::ChannelConfig>| {
+            channels.into_iter().find(|c| c.id == "telegram").expect("应返回 telegram")
+        };
+
+        let telegram = find_telegram(runtime.block_on(get_channels_config()).unwrap());
+        assert!(telegram.configured, "已有配置的渠道应标记为 configured");
+        assert!(!telegram.enabled, "enabled 应反映 channels.telegram.enabled");
+
+        runtime
+            .block_on(set_channel_enabled("telegram".to_string(), true))
+            .expect("启用应成功");
+        let telegram = find_telegram(runtime.block_on(get_channels_config()).unwrap());
+        assert!(telegram.enabled && telegram.configured);
+        assert_eq!(telegram.config.get("botToken"), Some(&json!("123:abc")));
+
+        let saved = load_openclaw_config_raw().unwrap();
+        assert_eq!(saved.pointer("/plugins/allow"), Some(&json!(["telegram"])));
+        assert_eq!(saved.pointer("/plugins/entries/telegram/enabled"), Some(&json!(true)));
+
+        let discord = runtime
+            .block_on(get_channels_config())
+            .unwrap()
+            .into_iter()
+            .find(|c| c.id == "discord")
+            .expect("应返回 discord");
+        assert!(!discord.enabled && !discord.configured);
     }
 
     #[test]
-    fn parse_invalid_config_should_return_clear_error() {
-        let content = "{ gateway: { auth: { token: } } }";
-        let err = parse_openclaw_config_content(content).expect_err("非法配置应返回错误");
+    fn session_channel_login_status_is_parsed() {
+        let output = "Gateway reachable.\n\
+                      - Telegram default: enabled, configured, mode:polling, token:config\n\
+                      - WhatsApp default: enabled, configured, linked, running\n\
+                      - iMessage default: enabled, configured, not linked\n";
+        assert_eq!(parse_session_login_status(output, "whatsapp"), "linked");
+        assert_eq!(parse_session_login_status(output, "imessage"), "unlinked");
+        assert_eq!(parse_session_login_status(output, "signal"), "unknown");
+
+        let unlinked = "- WhatsApp default: enabled, configured, unlinked";
+        assert_eq!(parse_session_login_status(unlinked, "whatsapp"), "unlinked");
+        let pending = "- WhatsApp default: enabled, configured";
+        assert_eq!(parse_session_login_status(pending, "whatsapp"), "unknown");
+    }
 
-        assert!(
-            err.contains("JSON/JSON5 解析失败"),
-            "错误信息应包含 JSON/JSON5 解析失败，实际: {}",
-            err
+    #[test]
+    fn dashboard_url_uses_gateway_bind_address() {
+        assert_eq!(
+            build_dashboard_url(Some("0.0.0.0"), None, 18789, false, "tok"),
+            "http://localhost:18789?token=tok"
+        );
+        assert_eq!(
+            build_dashboard_url(Some("::"), None, 18789, false, "tok"),
+            "http://localhost:18789?token=tok"
+        );
+        assert_eq!(
+            build_dashboard_url(None, None, 18789, false, "tok"),
+            "http://localhost:18789?token=tok"
+        );
+        assert_eq!(
+            build_dashboard_url(Some("192.168.1.20"), None, 18789, false, "tok"),
+            "http://192.168.1.20:18789?token=tok"
+        );
+        assert_eq!(
+            build_dashboard_url(Some("fd00::5"), None, 18789, false, "tok"),
+            "http://[fd00::5]:18789?token=tok"
         );
     }
 
     #[test]
-    fn parse_json5_should_preserve_core_config_fields() {
-        let content = r#"
-        {
-          agents: {
-            defaults: {
-              model: { primary: "anthropic/claude-opus-4-5-20251101" },
-            },
-          },
-          gateway: {
-            auth: {
-              token: "gateway-token",
-            },
-          },
-          channels: {
-            telegram: {
-              accounts: [
-                {
-                  name: "main",
-                  token: "tg-token",
-                },
-              ],
-            },
-          },
+    fn dashboard_url_resolves_tailnet_and_custom_binds() {
+        let tailnet = || Some("100.64.0.7".to_string());
+        let bind = resolve_bind_mode(Some("tailnet"), None, tailnet, || None);
+        assert_eq!(
+            build_dashboard_url(bind.as_deref(), None, 18789, false, "tok"),
+            "http://100.64.0.7:18789?token=tok"
+        );
+        let bind = resolve_bind_mode(Some("custom"), Some(" 10.0.0.5 "), || None, || None);
+        assert_eq!(bind.as_deref(), Some("10.0.0.5"));
+        let bind = resolve_bind_mode(Some("lan"), Some("10.0.0.5"), tailnet, || None);
+        assert_eq!(bind.as_deref(), Some("lan"));
+        let bind = resolve_bind_mode(Some("loopback"), None, tailnet, || None);
+        assert_eq!(bind.as_deref(), Some("loopback"));
+
+        // lan/auto 与通配地址指向本机局域网 IP，localhost 在其它设备上无法访问
+        let lan = || Some("192.168.1.20".to_string());
+        for mode in ["lan", "auto", "0.0.0.0", "::"] {
+            let bind = resolve_bind_mode(Some(mode), None, tailnet, lan);
+            assert_eq!(
+                build_dashboard_url(bind.as_deref(), None, 18789, false, "tok"),
+                "http://192.168.1.20:18789?token=tok",
+                "{}",
+                mode
+            );
         }
-        "#;
 
-        let parsed = parse_openclaw_config_content(content).expect("回归字段应可正确解析");
+        // custom 地址带端口时只取主机部分
+        let cases = [
+            ("10.0.0.5:18800", "http://10.0.0.5:18789?token=tok"),
+            ("gw.lan:18800", "http://gw.lan:18789?token=tok"),
+            ("[fd00::5]:18800", "http://[fd00::5]:18789?token=tok"),
+            ("fd00::5", "http://[fd00::5]:18789?token=tok"),
+        ];
+        for (custom, expected) in cases {
+            let bind = resolve_bind_mode(Some("custom"), Some(custom), || None, || None);
+            assert_eq!(build_dashboard_url(bind.as_deref(), None, 18789, false, "tok"), expected);
+        }
+
+        // 无法解析时回退到 loopback，而不是把模式名当作主机名
+        for (mode, custom) in [("tailnet", None), ("custom", None), ("custom", Some(""))] {
+            let bind = resolve_bind_mode(Some(mode), custom, || None, || None);
+            assert_eq!(
+                build_dashboard_url(bind.as_deref(), None, 18789, false, "tok"),
+                "http://localhost:18789?token=tok"
+            );
+        }
+        assert_eq!(dashboard_host_for_bind(Some("tailnet")), "localhost");
+    }
 
+    #[test]
+    fn dashboard_url_prefers_external_host_override() {
         assert_eq!(
-            parsed
-                .pointer("/agents/defaults/model/primary")
-                .and_then(|v| v.as_str()),
-            Some("anthropic/claude-opus-4-5-20251101")
+            build_dashboard_url(Some("0.0.0.0"), Some("gw.example.com"), 18789, false, "tok"),
+            "http://gw.example.com:18789?token=tok"
         );
         assert_eq!(
-            parsed
-                .pointer("/gateway/auth/token")
-                .and_then(|v| v.as_str()),
-            Some("gateway-token")
+            build_dashboard_url(
+                Some("0.0.0.0"),
+                Some("https://claw.example.com/"),
+                18789,
+                false,
+                "tok"
+            ),
+            "https://claw.example.com?token=tok"
         );
+    }
+
+    #[test]
+    fn dashboard_url_uses_https_when_gateway_tls_enabled() {
+        assert!(gateway_tls_enabled(&json!({ "gateway": { "tls": { "enabled": true } } })));
+        assert!(!gateway_tls_enabled(&json!({ "gateway": { "tls": { "enabled": false } } })));
+        assert!(!gateway_tls_enabled(&json!({ "gateway": { "port": 18789 } })));
         assert_eq!(
-            parsed
-                .pointer("/channels/telegram/accounts/0/token")
-                .and_then(|v| v.as_str()),
-            Some("tg-token")
+            build_dashboard_url(Some("192.168.1.20"), None, 18789, true, "tok"),
+            "https://192.168.1.20:18789?token=tok"
+        );
+        assert_eq!(
+            build_dashboard_url(Some("loopback"), None, 18789, true, "tok"),
+            "https://localhost:18789?token=tok"
         );
     }
 
     #[test]
-    fn replace_vars_in_json_config_should_work() {
+    fn dashboard_url_reads_bind_and_tls_from_config() {
         let _env_lock = test_env_lock();
-        let _process_guard = EnvGuard::set("OPENCLAW_TEST_JSON_TOKEN", "json-token-from-process");
-        let _env_guard = EnvGuard::remove("OPENCLAW_TEST_JSON_SUFFIX");
-
-        let home_guard = TempHomeGuard::new();
-        home_guard.write_openclaw_env("export OPENCLAW_TEST_JSON_SUFFIX=-from-env\n");
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "gateway": {
+                "port": 18800,
+                "bind": "192.168.1.20",
+                "tls": { "enabled": true },
+                "auth": { "mode": "token", "token": "lan-token" }
+            }
+        }))
+        .expect("应可写入测试配置");
 
-        let parsed = parse_openclaw_config_content(
-            r#"{"gateway":{"auth":{"token":"${OPENCLAW_TEST_JSON_TOKEN}${OPENCLAW_TEST_JSON_SUFFIX}"}}}"#,
-        )
-        .expect("JSON 配置应可解析");
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let url = runtime.block_on(get_dashboard_url(None)).expect("应可生成 URL");
+        assert_eq!(url, "https://192.168.1.20:18800?token=lan-token");
+    }
 
-        let replaced = apply_replacement(parsed).expect("JSON 配置变量应可替换");
+    #[test]
+    fn rotate_gateway_token_replaces_token_in_dashboard_url() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "gateway": { "port": 18789, "auth": { "mode": "token", "token": "old-token" } }
+        }))
+        .unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let new_token = runtime.block_on(rotate_gateway_token(None)).unwrap();
+        assert_ne!(new_token, "old-token");
+        assert_eq!(new_token.len(), 48);
+
+        let config = load_openclaw_config_raw().unwrap();
+        assert_eq!(config.pointer("/gateway/auth/token"), Some(&json!(new_token)));
+        assert_eq!(config.pointer("/gateway/port"), Some(&json!(18789)));
+
+        let url = runtime.block_on(get_dashboard_url(None)).unwrap();
+        assert_eq!(url, format!("http://localhost:18789?token={}", new_token));
+        assert!(!url.ends_with("token=old-token"));
+
+        let again = runtime.block_on(rotate_gateway_token(None)).unwrap();
+        assert_ne!(again, new_token);
         assert_eq!(
-            replaced
-                .pointer("/gateway/auth/token")
-                .and_then(|v| v.as_str()),
-            Some("json-token-from-process-from-env")
+            load_openclaw_config_raw().unwrap().pointer("/gateway/mode"),
+            Some(&json!("local"))
         );
+
+        // 远程模式下轮换 token 不会改回 local
+        let mut config = load_openclaw_config_raw().unwrap();
+        config["gateway"]["mode"] = json!("remote");
+        save_openclaw_config(&config).unwrap();
+        let remote_token = runtime.block_on(rotate_gateway_token(None)).unwrap();
+        let config = load_openclaw_config_raw().unwrap();
+        assert_eq!(config.pointer("/gateway/mode"), Some(&json!("remote")));
+        assert_eq!(config.pointer("/gateway/auth/token"), Some(&json!(remote_token)));
     }
 
     #[test]
-    fn replace_vars_in_json5_config_should_work() {
+    fn ai_config_overview_includes_agents_and_bindings() {
         let _env_lock = test_env_lock();
-        let _process_guard = EnvGuard::set("OPENCLAW_TEST_JSON5_TOKEN", "json5-token-from-process");
-        let _env_guard = EnvGuard::remove("OPENCLAW_TEST_JSON5_SUFFIX");
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "models": {
+                "providers": {
+                    "anthropic": {
+                        "baseUrl": "https://api.anthropic.com",
+                        "apiKey": "sk-ant-test-key",
+                        "models": [{ "id": "claude-sonnet-4-5", "name": "Sonnet" }]
+                    }
+                }
+            },
+            "agents": {
+                "defaults": { "model": { "primary": "anthropic/claude-sonnet-4-5" } },
+                "list": [
+                    { "id": "main", "default": true },
+                    { "id": "work", "workspace": "~/work" }
+                ]
+            },
+            "bindings": [{ "agentId": "work", "match": { "channel": "telegram" } }]
+        }))
+        .expect("应可写入测试配置");
 
-        let home_guard = TempHomeGuard::new();
-        home_guard.write_openclaw_env("export OPENCLAW_TEST_JSON5_SUFFIX=-json5-env\n");
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let overview = runtime.block_on(get_ai_config()).expect("应可读取 AI 配置概览");
+        assert_eq!(overview.configured_providers.len(), 1);
+        let agent_ids: Vec<_> =
+            overview.agents_list.iter().filter_map(|a| a.id.as_deref()).collect();
+        assert_eq!(agent_ids, vec!["main", "work"]);
+        let bindings = overview.bindings.expect("应返回 bindings").into_value();
+        assert_eq!(bindings.pointer("/0/agentId"), Some(&json!("work")));
+
+        // 缺少 agents.list / bindings 的旧配置返回空默认值
+        save_openclaw_config(&json!({ "gateway": { "port": 18789 } })).expect("应可写入测试配置");
+        let overview = runtime.block_on(get_ai_config()).expect("应可读取 AI 配置概览");
+        assert!(overview.agents_list.is_empty());
+        assert!(overview.bindings.is_none());
+    }
 
-        let parsed = parse_openclaw_config_content(
-            r#"
-            {
-              // JSON5 注释
-              gateway: {
-                auth: {
-                  token: "${OPENCLAW_TEST_JSON5_TOKEN}${OPENCLAW_TEST_JSON5_SUFFIX}",
-                },
-              },
+    #[test]
+    fn saved_provider_reads_back_reasoning_and_input_types() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        let model = |id: &str, reasoning: Option<bool>, input: &[&str]| ModelConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            api: None,
+            input: input.iter().map(|s| s.to_string()).collect(),
+            context_window: None,
+            max_tokens: None,
+            reasoning,
+            cost: None,
+            extra: Default::default(),
+        };
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        runtime
+            .block_on(save_provider(
+                "deepseek".to_string(),
+                "https://api.deepseek.com/v1".to_string(),
+                Some("sk-test".to_string()),
+                "openai-completions".to_string(),
+                vec![
+                    model("deepseek-reasoner", Some(true), &["text", "image"]),
+                    model("deepseek-chat", None, &[]),
+                ],
+            ))
+            .expect("应可保存 Provider");
+
+        let overview = runtime.block_on(get_ai_config()).expect("应可读取 AI 配置概览");
+        let models = &overview.configured_providers[0].models;
+        assert_eq!(models[0].id, "deepseek-reasoner");
+        assert_eq!(models[0].reasoning, Some(true));
+        assert_eq!(models[0].inputs, ["text", "image"]);
+        assert_eq!(models[1].reasoning, None);
+        assert_eq!(models[1].inputs, ["text"]);
+    }
+
+    #[test]
+    fn model_cost_is_read_and_updated_per_model() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "models": {
+                "providers": {
+                    "openai": {
+                        "baseUrl": "https://api.openai.com/v1",
+                        "models": [
+                            {
+                                "id": "gpt-4o",
+                                "name": "GPT-4o",
+                                "contextWindow": 128000,
+                                "cost": {
+                                    "input": 2.5,
+                                    "output": 10,
+                                    "cacheRead": 1.25,
+                                    "cacheWrite": 0
+                                }
+                            },
+                            {
+                                "id": "gpt-4o-mini",
+                                "name": "GPT-4o mini",
+                                "cost": { "input": 0.15 }
+                            }
+                        ]
+                    }
+                }
             }
-            "#,
-        )
-        .expect("JSON5 配置应可解析");
+        }))
+        .expect("应可写入测试配置");
 
-        let replaced = apply_replacement(parsed).expect("JSON5 配置变量应可替换");
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let overview = runtime.block_on(get_ai_config()).expect("应可读取 AI 配置概览");
+        let models = &overview.configured_providers[0].models;
         assert_eq!(
-            replaced
-                .pointer("/gateway/auth/token")
-                .and_then(|v| v.as_str()),
-            Some("json5-token-from-process-json5-env")
+            models[0].cost,
+            Some(ModelCostConfig {
+                input: 2.5,
+                output: 10.0,
+                cache_read: 1.25,
+                ..Default::default()
+            })
+        );
+
+        let cost =
+            ModelCostConfig { input: 3.0, output: 12.0, cache_write: 0.5, ..Default::default() };
+        runtime
+            .block_on(update_model_cost("openai/gpt-4o".to_string(), cost.clone()))
+            .expect("应可更新模型成本");
+        let overview = runtime.block_on(get_ai_config()).expect("应可读取 AI 配置概览");
+        let models = &overview.configured_providers[0].models;
+        assert_eq!(models[0].cost, Some(cost));
+        assert_eq!(models[0].context_window, Some(128000));
+        assert_eq!(models[0].name, "GPT-4o");
+        assert_eq!(models[1].cost.as_ref().map(|c| c.input), Some(0.15));
+
+        let saved = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(
+            saved.pointer("/models/providers/openai/models/1/cost"),
+            Some(&json!({ "input": 0.15 }))
         );
+
+        let missing = ModelCostConfig::default();
+        assert!(runtime
+            .block_on(update_model_cost("openai/gpt-5".to_string(), missing.clone()))
+            .is_err());
+        assert!(runtime.block_on(update_model_cost("gpt-4o".to_string(), missing)).is_err());
     }
 
     #[test]
-    fn replace_multiple_placeholders_in_single_string_should_work() {
+    fn concurrent_provider_saves_are_not_lost() {
         let _env_lock = test_env_lock();
-        let _api_guard = EnvGuard::set("OPENCLAW_MULTI_A", "A");
-        let _token_guard = EnvGuard::set("OPENCLAW_MULTI_B", "B");
-        let _secret_guard = EnvGuard::set("OPENCLAW_MULTI_C", "C");
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({ "gateway": { "port": 18789 } })).expect("应可写入测试配置");
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(4)
+            .enable_all()
+            .build()
+            .expect("应可创建异步运行时");
+        let names: Vec<String> = (0..8).map(|i| format!("provider-{}", i)).collect();
+        runtime.block_on(async {
+            let tasks: Vec<_> = names
+                .iter()
+                .cloned()
+                .map(|name| {
+                    tokio::spawn(save_provider(
+                        name,
+                        "https://api.example.com/v1".to_string(),
+                        Some("sk-test".to_string()),
+                        "openai-completions".to_string(),
+                        vec![],
+                    ))
+                })
+                .collect();
+            for task in tasks {
+                task.await.expect("任务不应 panic").expect("保存 Provider 应成功");
+            }
+        });
 
-        let parsed = parse_openclaw_config_content(
-            r#"{"models":{"providers":{"demo":{"apiKey":"prefix-${OPENCLAW_MULTI_A}-${OPENCLAW_MULTI_B}-${OPENCLAW_MULTI_C}-suffix"}}}}"#,
-        )
-        .expect("配置应可解析");
+        let saved = load_openclaw_config_raw().expect("应可读取配置");
+        for name in &names {
+            assert!(
+                saved.pointer(&format!("/models/providers/{}", name)).is_some(),
+                "并发保存后 {} 丢失",
+                name
+            );
+        }
+    }
 
-        let replaced = apply_replacement(parsed).expect("多占位符应可替换");
-        assert_eq!(
-            replaced
-                .pointer("/models/providers/demo/apiKey")
-                .and_then(|v| v.as_str()),
-            Some("prefix-A-B-C-suffix")
-        );
+    #[test]
+    fn config_file_lock_serializes_racing_writers() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({ "meta": { "counter": 0 } })).expect("应可写入测试配置");
+        let lock_path = PathBuf::from(platform_utils::get_config_lock_path());
+
+        let writers: Vec<_> = (0..2)
+            .map(|_| {
+                let lock_path = lock_path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        let _lock = acquire_config_file_lock(
+                            &lock_path,
+                            std::time::Duration::from_secs(10),
+                            std::time::Duration::from_secs(30),
+                        )
+                        .expect("应可获取锁文件");
+                        let mut config = load_openclaw_config_raw().expect("应可读取配置");
+                        let counter = config.pointer("/meta/counter").and_then(|v| v.as_u64());
+                        std::thread::sleep(std::time::Duration::from_millis(2));
+                        config["meta"]["counter"] = json!(counter.unwrap_or(0) + 1);
+                        save_openclaw_config(&config).expect("应可保存配置");
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().expect("写入线程不应 panic");
+        }
+
+        let saved = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(saved.pointer("/meta/counter"), Some(&json!(20)));
+        assert!(!lock_path.exists(), "释放后应删除锁文件");
     }
 
     #[test]
-    fn missing_variable_should_return_error_with_path_and_name() {
+    fn config_file_lock_times_out_and_clears_stale_locks() {
         let _env_lock = test_env_lock();
-        let _guard = EnvGuard::remove("OPENCLAW_MISSING_VAR_TEST");
+        let _home_guard = TempHomeGuard::new();
+        let lock_path = PathBuf::from(platform_utils::get_config_lock_path());
 
-        let parsed = parse_openclaw_config_content(
-            r#"{"models":{"providers":{"anthropic":{"apiKey":"${OPENCLAW_MISSING_VAR_TEST}"}}}}"#,
+        let held = acquire_config_file_lock(
+            &lock_path,
+            std::time::Duration::from_secs(1),
+            std::time::Duration::from_secs(30),
         )
-        .expect("配置应可解析");
+        .expect("应可获取锁文件");
+        let err = acquire_config_file_lock(
+            &lock_path,
+            std::time::Duration::from_millis(100),
+            std::time::Duration::from_secs(30),
+        )
+        .err()
+        .expect("锁被占用时应超时");
+        assert!(err.contains("正被其他进程修改"), "{}", err);
+
+        // 持有者异常退出遗留的锁文件在过期后被清理
+        std::mem::forget(held);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let lock = acquire_config_file_lock(
+            &lock_path,
+            std::time::Duration::from_millis(100),
+            std::time::Duration::from_millis(10),
+        )
+        .expect("过期锁应被清理");
+        let leftovers: Vec<_> = fs::read_dir(lock_path.parent().unwrap())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".stale-"))
+            .collect();
+        assert!(leftovers.is_empty(), "清理后不应留下改名的旧锁文件");
+
+        // 原持有者恢复后释放锁时，不应删除别人已重新获取的锁文件
+        let stale_holder =
+            ConfigFileLock { path: lock_path.clone(), token: "0 old\n".to_string() };
+        drop(stale_holder);
+        assert!(lock_path.exists(), "只能删除自己持有的锁文件");
+
+        // 判定过期后锁已被他人替换：改名核对不一致时原样放回
+        remove_stale_lock(&lock_path, "0 old\n");
+        assert!(lock_path.exists(), "不应清理他人新建的锁文件");
+        drop(lock);
+        assert!(!lock_path.exists(), "持有者释放后应删除锁文件");
+    }
+
+    #[test]
+    fn gateway_token_verdict_flags_default_token_mismatch() {
+        let verdict = gateway_token_verdict("config-token", Some(200), None);
+        assert!(verdict.accepted && !verdict.uses_default_token);
+
+        // Gateway 以内置默认 token 启动：配置 token 被拒，默认 token 被接受
+        let verdict = gateway_token_verdict("config-token", Some(401), Some(404));
+        assert!(!verdict.accepted);
+        assert!(verdict.uses_default_token);
+        assert!(verdict.message.contains("默认 Token"));
+
+        // 两个 token 都被拒绝：单纯不匹配
+        let verdict = gateway_token_verdict("config-token", Some(401), Some(401));
+        assert!(!verdict.accepted && !verdict.uses_default_token);
+
+        // 配置本身就是默认 token
+        let verdict = gateway_token_verdict(shell::DEFAULT_GATEWAY_TOKEN, Some(200), None);
+        assert!(verdict.accepted && verdict.uses_default_token);
+
+        let verdict = gateway_token_verdict("config-token", None, None);
+        assert!(!verdict.gateway_reachable && !verdict.accepted);
+    }
+
+    #[test]
+    fn save_config_rejects_stale_version() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({ "gateway": { "port": 18789 } })).expect("应可写入测试配置");
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let loaded = runtime.block_on(get_config()).expect("应可读取配置");
+
+        // 其他窗口或进程修改了配置文件
+        save_openclaw_config(&json!({ "gateway": { "port": 19001 } })).expect("应可写入测试配置");
+
+        let err = runtime
+            .block_on(save_config(loaded.config.clone(), Some(loaded.version.clone())))
+            .expect_err("版本过期时应拒绝保存");
+        assert!(err.starts_with(CONFIG_CONFLICT_ERROR), "{}", err);
+        let on_disk = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(on_disk.pointer("/gateway/port"), Some(&json!(19001)));
+
+        // 重新加载后使用最新版本号可以保存
+        let reloaded = runtime.block_on(get_config()).expect("应可读取配置");
+        assert_ne!(reloaded.version, loaded.version);
+        let mut next = reloaded.config.clone();
+        next["gateway"]["port"] = json!(19002);
+        runtime
+            .block_on(save_config(next, Some(reloaded.version)))
+            .expect("最新版本号应可保存");
+        let on_disk = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(on_disk.pointer("/gateway/port"), Some(&json!(19002)));
+
+        // 设置页的应用路径同样校验版本，并返回新版本供下一次应用
+        let input = json!({ "gateway": { "port": 19002 }, "agents": { "list": [] } });
+        let err = runtime
+            .block_on(apply_config_change(input.clone(), Some(loaded.version.clone())))
+            .expect_err("版本过期时应拒绝应用");
+        assert!(err.starts_with(CONFIG_CONFLICT_ERROR), "{}", err);
+        let latest = runtime.block_on(get_config()).expect("应可读取配置");
+        let applied = runtime
+            .block_on(apply_config_change(input, Some(latest.version)))
+            .expect("最新版本号应可应用");
+        let after = runtime.block_on(get_config()).expect("应可读取配置");
+        assert_eq!(applied.version, after.version);
+    }
+
+    #[test]
+    fn config_watch_debounces_rapid_writes() {
+        let fingerprint = |exists: bool, modified_ms: u64, len: u64| ConfigFileFingerprint {
+            exists,
+            modified_ms: exists.then_some(modified_ms),
+            len,
+        };
+        let debounce = std::time::Duration::from_millis(300);
+        let start = std::time::Instant::now();
+        let at = |ms: u64| start + std::time::Duration::from_millis(ms);
+        let mut debouncer = ConfigWatchDebouncer::new(fingerprint(true, 1, 10));
+
+        assert!(!debouncer.observe(fingerprint(true, 1, 10), at(0), debounce));
+        // 连续写入：每次指纹变化都重新计时
+        assert!(!debouncer.observe(fingerprint(true, 2, 20), at(100), debounce));
+        assert!(!debouncer.observe(fingerprint(true, 3, 30), at(200), debounce));
+        assert!(!debouncer.observe(fingerprint(true, 3, 30), at(400), debounce));
+        assert!(debouncer.observe(fingerprint(true, 3, 30), at(600), debounce));
+        assert!(!debouncer.observe(fingerprint(true, 3, 30), at(1200), debounce));
+
+        // 删除后重新创建
+        assert!(!debouncer.observe(fingerprint(false, 0, 0), at(1300), debounce));
+        assert!(debouncer.observe(fingerprint(false, 0, 0), at(1700), debounce));
+        assert!(!debouncer.observe(fingerprint(true, 4, 30), at(1800), debounce));
+        assert!(debouncer.observe(fingerprint(true, 4, 30), at(2200), debounce));
+    }
+
+    #[test]
+    fn config_watch_ignores_own_writes() {
+        let previous = ConfigChangeStatus {
+            exists: true,
+            version: "v1".to_string(),
+            change_count: 2,
+            ..Default::default()
+        };
+        let on_disk = |version: &str| ConfigChangeStatus {
+            exists: true,
+            version: version.to_string(),
+            ..Default::default()
+        };
+
+        let (status, external) = apply_config_file_change(&previous, on_disk("v2"), Some("v2"));
+        assert!(!external);
+        assert_eq!((status.version.as_str(), status.change_count), ("v2", 2));
+
+        let (status, external) = apply_config_file_change(&status, on_disk("v3"), Some("v2"));
+        assert!(external);
+        assert_eq!(status.change_count, 3);
 
-        let err = apply_replacement(parsed).expect_err("缺失变量应返回错误");
-        assert!(
-            err.contains("/models/providers/anthropic/apiKey"),
-            "错误信息应包含配置路径，实际: {}",
-            err
-        );
-        assert!(
-            err.contains("OPENCLAW_MISSING_VAR_TEST"),
-            "错误信息应包含缺失变量名，实际: {}",
-            err
-        );
+        // 仅修改时间变化（内容相同）不算修改
+        let (_, external) = apply_config_file_change(&status, on_disk("v3"), None);
+        assert!(!external);
     }
 
     #[test]
-    fn replacement_should_not_mutate_raw_placeholder_text_for_writeback() {
+    fn openclaw_child_env_uses_configured_gateway_token() {
         let _env_lock = test_env_lock();
-        let _process_guard = EnvGuard::set("OPENCLAW_WRITEBACK_VAR", "super-secret-value");
+        let _home_guard = TempHomeGuard::new();
+        let token_env = |cmd: &std::process::Command| {
+            cmd.get_envs()
+                .find(|(key, _)| *key == "OPENCLAW_GATEWAY_TOKEN")
+                .and_then(|(_, value)| value)
+                .map(|value| value.to_string_lossy().to_string())
+        };
 
-        let raw = parse_openclaw_config_content(
-            r#"{"models":{"providers":{"anthropic":{"baseUrl":"https://api.anthropic.com","apiKey":"${OPENCLAW_WRITEBACK_VAR}"}}}}"#,
-        )
-        .expect("配置应可解析");
+        let configured = json!({ "gateway": { "auth": { "token": "configured-token-123" } } });
+        save_openclaw_config(&configured).expect("应可写入测试配置");
+        let mut cmd = std::process::Command::new("openclaw");
+        shell::apply_gateway_token_env(&mut cmd);
+        assert_eq!(token_env(&cmd).as_deref(), Some("configured-token-123"));
+
+        // 未配置 token 时不注入，也不写配置（只读命令不应生成 token）
+        let without_token = json!({ "gateway": { "port": 18789 } });
+        save_openclaw_config(&without_token).expect("应可写入测试配置");
+        let mut cmd = std::process::Command::new("openclaw");
+        shell::apply_gateway_token_env(&mut cmd);
+        assert_eq!(token_env(&cmd), None);
+        assert_eq!(load_openclaw_config_raw().expect("应可读取配置"), without_token);
+
+        // 启动 gateway 前生成 token，之后的子进程注入该 token
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let created = runtime
+            .block_on(get_or_create_gateway_token())
+            .expect("应可生成 token");
+        assert_ne!(created, shell::DEFAULT_GATEWAY_TOKEN);
+        let mut cmd = std::process::Command::new("openclaw");
+        shell::apply_gateway_token_env(&mut cmd);
+        assert_eq!(token_env(&cmd), Some(created.clone()));
+        let saved = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(saved.pointer("/gateway/auth/token"), Some(&json!(created)));
+    }
 
-        let replaced = apply_replacement(raw.clone()).expect("读取期应可替换");
-        assert_eq!(
-            replaced
-                .pointer("/models/providers/anthropic/apiKey")
-                .and_then(|v| v.as_str()),
-            Some("super-secret-value")
-        );
-        assert_eq!(
-            raw.pointer("/models/providers/anthropic/apiKey")
-                .and_then(|v| v.as_str()),
-            Some("${OPENCLAW_WRITEBACK_VAR}")
-        );
+    #[test]
+    fn env_keys_are_listed_masked_and_removable() {
+        let _env_lock = test_env_lock();
+        let home_guard = TempHomeGuard::new();
+        home_guard.write_openclaw_env(concat!(
+            "# comment\n",
+            "export OPENAI_API_KEY=\"sk-openai-1234567890\"\n",
+            "ANTHROPIC_API_KEY=sk-ant-abcdefghij\n",
+        ));
 
-        let serialized = serde_json::to_string_pretty(&raw).expect("原始配置应可序列化");
-        assert!(
-            serialized.contains("${OPENCLAW_WRITEBACK_VAR}"),
-            "写回内容应保留占位符"
-        );
-        assert!(
-            !serialized.contains("super-secret-value"),
-            "写回内容不应包含替换后的敏感值"
-        );
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let entries = runtime.block_on(get_env_keys()).expect("应可列出环境变量");
+        let keys: Vec<_> = entries.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["ANTHROPIC_API_KEY", "OPENAI_API_KEY"]);
+        assert!(entries.iter().all(|e| !e.masked_value.contains("1234567890")));
+
+        runtime
+            .block_on(remove_env_key("ANTHROPIC_API_KEY".to_string()))
+            .expect("应可删除环境变量");
+        let entries = runtime.block_on(get_env_keys()).expect("应可列出环境变量");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "OPENAI_API_KEY");
+        assert!(runtime.block_on(remove_env_key("MISSING".to_string())).is_err());
+    }
 
+    #[test]
+    fn effective_config_reports_variable_sources() {
+        let _env_lock = test_env_lock();
         let home_guard = TempHomeGuard::new();
+        home_guard.write_openclaw_env("export OPENCLAW_TEST_EFFECTIVE_KEY=sk-from-env-file\n");
+        std::env::remove_var("OPENCLAW_TEST_EFFECTIVE_KEY");
+        std::env::remove_var("OPENCLAW_TEST_EFFECTIVE_MISSING");
+        std::env::set_var("OPENCLAW_TEST_EFFECTIVE_PROCESS", "from-process");
         let config_path = platform_utils::get_config_file_path();
         file_utils::write_file(
             &config_path,
-            r#"{"models":{"providers":{"anthropic":{"baseUrl":"https://api.anthropic.com","apiKey":"${OPENCLAW_WRITEBACK_VAR}"}}}}"#,
+            r#"{
+              "models": { "providers": { "openai": {
+                "baseUrl": "https://${OPENCLAW_TEST_EFFECTIVE_PROCESS}.example.com",
+                "apiKey": "${OPENCLAW_TEST_EFFECTIVE_KEY}"
+              } } },
+              "gateway": { "auth": { "token": "${OPENCLAW_TEST_EFFECTIVE_MISSING}" } }
+            }"#,
         )
         .expect("应可写入测试配置");
 
-        save_openclaw_config(&raw).expect("写回原始配置应成功");
-        let saved = file_utils::read_file(&config_path).expect("应可读取写回结果");
-        assert!(saved.contains("${OPENCLAW_WRITEBACK_VAR}"));
-        assert!(!saved.contains("super-secret-value"));
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let effective = runtime.block_on(get_effective_config()).expect("缺失变量不应导致失败");
+        std::env::remove_var("OPENCLAW_TEST_EFFECTIVE_PROCESS");
 
-        let _ = home_guard;
+        let source_of = |name: &str| {
+            effective
+                .variables
+                .iter()
+                .find(|v| v.name == name)
+                .map(|v| (v.path.clone(), v.source.clone()))
+        };
+        assert_eq!(
+            source_of("OPENCLAW_TEST_EFFECTIVE_KEY"),
+            Some(("/models/providers/openai/apiKey".to_string(), ConfigVarSource::EnvFile))
+        );
+        assert_eq!(
+            source_of("OPENCLAW_TEST_EFFECTIVE_PROCESS").map(|(_, source)| source),
+            Some(ConfigVarSource::ProcessEnv)
+        );
+        assert_eq!(
+            source_of("OPENCLAW_TEST_EFFECTIVE_MISSING").map(|(_, source)| source),
+            Some(ConfigVarSource::Unresolved)
+        );
+
+        let config = &effective.config;
+        assert_eq!(
+            config.pointer("/models/providers/openai/apiKey"),
+            Some(&json!("sk-from-env-file"))
+        );
+        assert_eq!(
+            config.pointer("/gateway/auth/token"),
+            Some(&json!("${OPENCLAW_TEST_EFFECTIVE_MISSING}"))
+        );
+        assert_eq!(config.pointer("/gateway/port"), Some(&json!(18789)));
+        assert_eq!(effective.defaults_applied, vec!["/gateway/port".to_string()]);
     }
 
     #[test]
-    fn normalize_config_defaults_when_agents_list_and_bindings_missing() {
-        let config = json!({
-            "agents": {
-                "defaults": {
-                    "model": {
-                        "primary": "anthropic/claude-opus-4-5-20251101"
-                    }
-                }
-            }
-        });
+    fn gateway_network_fields_are_validated() {
+        let valid = json!({ "gateway": {
+            "port": 18789,
+            "bind": "127.0.0.1",
+            "trustedProxies": ["10.0.0.0/8", "192.168.1.10", "fd00::/8"]
+        } });
+        assert!(validate_gateway_network_fields(&valid).is_ok());
+        for mode in [None, Some("local"), Some("remote")] {
+            assert!(validate_gateway_mode(mode).is_ok(), "{:?}", mode);
+        }
+        let err = validate_gateway_mode(Some("cloud")).expect_err("未知模式应被拒绝");
+        assert!(err.contains("local/remote"), "{}", err);
+        let binds = ["loopback", "lan", "0.0.0.0", "::", "localhost", "gw.local:8080", "[::1]:80"];
+        for bind in binds {
+            let config = json!({ "gateway": { "bind": bind } });
+            assert!(validate_gateway_network_fields(&config).is_ok(), "{}", bind);
+        }
 
-        let normalized = normalize_and_validate_config(&config)
-            .expect("仅 defaults 配置应可通过结构化校验");
+        let err = validate_gateway_network_fields(&json!({ "gateway": { "bind": "999.1.1.1" } }))
+            .expect_err("非法 IP 应被拒绝");
+        assert!(err.contains("gateway.bind"), "{}", err);
+        let err = validate_gateway_network_fields(&json!({ "gateway": { "bind": "host:99999" } }))
+            .expect_err("非法端口应被拒绝");
+        assert!(err.contains("gateway.bind"), "{}", err);
+
+        let config = json!({ "gateway": { "trustedProxies": "10.0.0.1" } });
+        let err = validate_gateway_network_fields(&config).expect_err("非数组应被拒绝");
+        assert!(err.contains("gateway.trustedProxies 必须是"), "{}", err);
+        let config = json!({ "gateway": { "trustedProxies": ["10.0.0.0/33", 5] } });
+        let err = validate_gateway_network_fields(&config).expect_err("非法 CIDR 应被拒绝");
+        assert!(err.contains("trustedProxies[0]") && err.contains("trustedProxies[1]"), "{}", err);
+
+        for port in [json!(0), json!(70000), json!("18789")] {
+            let config = json!({ "gateway": { "port": port } });
+            let err = validate_gateway_network_fields(&config).expect_err("非法端口应被拒绝");
+            assert!(err.contains("gateway.port"), "{}", err);
+        }
+    }
 
-        assert!(normalized.pointer("/agents/list").is_none());
-        assert!(normalized.get("bindings").is_none());
+    #[test]
+    fn save_config_rejects_invalid_gateway_fields() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let err = runtime
+            .block_on(save_config(json!({ "gateway": { "bind": "not a host" } }), None))
+            .expect_err("非法 bind 应拒绝保存");
+        assert!(err.contains("gateway.bind"), "{}", err);
     }
 
     #[test]
-    fn normalize_config_accepts_full_agents_list_and_bindings() {
-        let config = json!({
-            "agents": {
-                "defaults": {
-                    "model": { "primary": "anthropic/claude-opus-4-5-20251101" }
-                },
-                "list": [
-                    {
-                        "id": "main",
-                        "name": "主助手",
-                        "default": true,
-                        "workspace": "/tmp/main"
-                    }
-                ]
+    fn gateway_settings_only_touch_gateway_subtree() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "gateway": {
+                "port": 18789,
+                "mode": "local",
+                "auth": { "mode": "token", "token": "${GATEWAY_TOKEN}" }
             },
-            "bindings": [
-                {
-                    "agentId": "main",
-                    "match": {
-                        "channel": "telegram",
-                        "accountId": "default"
-                    }
-                }
-            ]
+            "agents": { "list": [{ "id": "main" }] }
+        }))
+        .expect("应可写入测试配置");
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let mut settings = runtime.block_on(get_gateway_settings()).expect("应可读取 gateway 配置");
+        assert_eq!(settings.port, Some(18789));
+        assert_eq!(
+            settings.auth.as_ref().and_then(|a| a.token.as_deref()),
+            Some("${GATEWAY_TOKEN}")
+        );
+
+        let update = GatewayConfig {
+            port: Some(19000),
+            bind: Some("lan".to_string()),
+            trusted_proxies: Some(vec!["10.0.0.0/8".to_string()]),
+            ..Default::default()
+        };
+        runtime
+            .block_on(save_gateway_settings(update))
+            .expect("应可保存 gateway 配置");
+
+        let saved = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(saved.pointer("/gateway/port"), Some(&json!(19000)));
+        assert_eq!(saved.pointer("/gateway/bind"), Some(&json!("lan")));
+        assert_eq!(saved.pointer("/gateway/mode"), Some(&json!("local")));
+        assert_eq!(saved.pointer("/gateway/auth/token"), Some(&json!("${GATEWAY_TOKEN}")));
+        assert_eq!(saved.pointer("/agents/list/0/id"), Some(&json!("main")));
+
+        // 显式提供 auth 时才覆盖
+        settings.auth = Some(GatewayAuthConfig {
+            mode: Some("token".to_string()),
+            token: Some("new-token".to_string()),
+            ..Default::default()
         });
+        runtime
+            .block_on(save_gateway_settings(settings))
+            .expect("应可保存 gateway 配置");
+        let saved = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(saved.pointer("/gateway/auth/token"), Some(&json!("new-token")));
+
+        let invalid = GatewayConfig {
+            bind: Some("bad host".to_string()),
+            ..Default::default()
+        };
+        assert!(runtime.block_on(save_gateway_settings(invalid)).is_err());
+    }
 
-        let normalized = normalize_and_validate_config(&config)
-            .expect("完整 agents.list + bindings 配置应可通过结构化校验");
+    #[test]
+    fn gateway_settings_round_trip_trusted_proxies_and_reload() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "gateway": {
+                "mode": "local",
+                "port": 18789,
+                "bind": "loopback",
+                "trustedProxies": ["10.0.0.0/8", "192.168.1.10"],
+                "reload": { "mode": "hybrid" }
+            }
+        }))
+        .expect("应可写入测试配置");
 
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let mut settings = runtime.block_on(get_gateway_settings()).expect("应可读取 gateway 配置");
         assert_eq!(
-            normalized
-                .pointer("/agents/list/0/id")
-                .and_then(|v| v.as_str()),
-            Some("main")
+            settings.trusted_proxies,
+            Some(vec!["10.0.0.0/8".to_string(), "192.168.1.10".to_string()])
         );
+        assert_eq!(settings.reload, Some(json!({ "mode": "hybrid" })));
+
+        settings.trusted_proxies = Some(vec!["172.16.0.0/12".to_string(), "fd00::/8".to_string()]);
+        runtime
+            .block_on(save_gateway_settings(settings.clone()))
+            .expect("应可保存 gateway 配置");
+
+        let saved = load_openclaw_config_raw().expect("应可读取配置");
         assert_eq!(
-            normalized
-                .pointer("/bindings/0/match/channel")
-                .and_then(|v| v.as_str()),
-            Some("telegram")
+            saved.pointer("/gateway/trustedProxies"),
+            Some(&json!(["172.16.0.0/12", "fd00::/8"]))
         );
+        assert_eq!(saved.pointer("/gateway/reload/mode"), Some(&json!("hybrid")));
+        let reloaded = runtime.block_on(get_gateway_settings()).expect("应可读取 gateway 配置");
+        assert_eq!(reloaded, settings);
     }
 
     #[test]
-    fn normalize_config_rejects_invalid_agents_list_or_bindings_type() {
-        let invalid_agents_list = json!({
-            "agents": {
-                "defaults": { "model": { "primary": "x/y" } },
-                "list": { "id": "main" }
+    fn save_gateway_settings_rejects_invalid_values_and_reports_restart() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "gateway": {
+                "mode": "local",
+                "port": 18789,
+                "auth": { "mode": "token", "token": "keep-me" }
             }
-        });
-        let err = normalize_and_validate_config(&invalid_agents_list)
-            .expect_err("agents.list 非数组应返回错误");
-        assert!(
-            err.contains("agents.list 结构无效"),
-            "错误信息应明确指向 agents.list，实际: {}",
-            err
-        );
+        }))
+        .expect("应可写入测试配置");
 
-        let invalid_bindings = json!({
-            "agents": { "defaults": { "model": { "primary": "x/y" } } },
-            "bindings": 123
-        });
-        let err = normalize_and_validate_config(&invalid_bindings)
-            .expect_err("bindings 非数组/对象应返回错误");
-        assert!(
-            err.contains("bindings 结构无效"),
-            "错误信息应明确指向 bindings，实际: {}",
-            err
-        );
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let invalid_port = GatewayConfig { port: Some(0), ..Default::default() };
+        let err = runtime.block_on(save_gateway_settings(invalid_port)).unwrap_err();
+        assert!(err.contains("gateway.port"), "{}", err);
+        let invalid_mode = GatewayConfig {
+            mode: Some("remote-ish".to_string()),
+            ..Default::default()
+        };
+        let err = runtime.block_on(save_gateway_settings(invalid_mode)).unwrap_err();
+        assert!(err.contains("gateway.mode"), "{}", err);
+        let saved = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(saved.pointer("/gateway/port"), Some(&json!(18789)));
+
+        let change = GatewayConfig { port: Some(19001), ..Default::default() };
+        let result = runtime.block_on(save_gateway_settings(change.clone())).expect("应可保存");
+        assert!(result.restart_required);
+        let saved = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(saved.pointer("/gateway/port"), Some(&json!(19001)));
+        assert_eq!(saved.pointer("/gateway/auth/token"), Some(&json!("keep-me")));
+
+        let result = runtime.block_on(save_gateway_settings(change)).expect("应可保存");
+        assert!(!result.restart_required);
+        let reload = GatewayConfig {
+            reload: Some(json!({ "mode": "hot" })),
+            ..Default::default()
+        };
+        let result = runtime.block_on(save_gateway_settings(reload)).expect("应可保存");
+        assert!(!result.restart_required);
     }
 
     #[test]
-    fn config_diff_ignores_semantically_equivalent_optional_values() {
-        let before = json!({
-            "agents": {
-                "list": [
-                    {
-                        "id": "main",
-                        "name": "主助手",
-                        "workspace": "/tmp/main"
+    fn model_ids_are_validated_before_being_written() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "models": {
+                "providers": {
+                    "anthropic": {
+                        "baseUrl": "https://api.anthropic.com",
+                        "models": [{ "id": "claude-opus-4-5", "name": "Claude Opus 4.5" }]
                     }
-                ]
+                }
             }
-        });
-
-        let after = json!({
-            "agents": {
-                "list": [
-                    {
-                        "id": "main",
-                        "name": "主助手",
-                        "workspace": "/tmp/main",
-                        "default": false,
-                        "model": {},
-                        "tools": null,
-                        "sandbox": null
-                    }
-                ]
-            },
-            "bindings": []
-        });
+        }))
+        .expect("应可写入测试配置");
 
-        let diff = build_config_diff_summary(&before, &after);
-        assert_eq!(diff.added, 0, "语义等价字段不应计入新增");
-        assert_eq!(diff.modified, 0, "语义等价字段不应计入修改");
-        assert_eq!(diff.removed, 0, "语义等价字段不应计入删除");
-        assert!(diff.changes.is_empty(), "语义等价字段不应产生差异项");
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let err = runtime
+            .block_on(set_primary_model("claude-opus".to_string()))
+            .expect_err("缺少 provider 前缀应被拒绝");
+        assert!(err.contains("provider/model"), "{}", err);
+        let err = runtime
+            .block_on(add_available_model("/claude-opus".to_string()))
+            .expect_err("provider 为空应被拒绝");
+        assert!(err.contains("provider/model"), "{}", err);
+        let err = runtime
+            .block_on(add_available_model("openai/gpt-4o".to_string()))
+            .expect_err("未配置的 provider 应被拒绝");
+        assert!(err.contains("openai"), "{}", err);
+        let err = runtime
+            .block_on(set_primary_model("anthropic/claude-haiku".to_string()))
+            .expect_err("不在模型列表中的模型应被拒绝");
+        assert!(err.contains("claude-haiku"), "{}", err);
+        let config = load_openclaw_config_raw().expect("应可读取配置");
+        assert!(config.pointer("/agents/defaults").is_none());
+
+        runtime
+            .block_on(set_primary_model("anthropic/claude-opus-4-5".to_string()))
+            .expect("已配置的模型应可设为主模型");
+        runtime
+            .block_on(add_available_model("anthropic/claude-sonnet-4-5".to_string()))
+            .expect("已配置 provider 下的模型应可加入可用列表");
+        runtime
+            .block_on(set_primary_model("anthropic/claude-sonnet-4-5".to_string()))
+            .expect("agents.defaults.models 中的模型应可设为主模型");
+
+        let config = load_openclaw_config_raw().expect("应可读取配置");
+        assert_eq!(
+            config.pointer("/agents/defaults/model/primary"),
+            Some(&json!("anthropic/claude-sonnet-4-5"))
+        );
+        assert!(config
+            .pointer("/agents/defaults/models/anthropic~1claude-sonnet-4-5")
+            .is_some());
     }
 
     #[test]
-    fn config_diff_reports_fine_grained_agent_field_changes() {
-        let before = json!({
-            "agents": {
-                "list": [
-                    {
-                        "id": "agent-1",
-                        "name": "旧名称1"
-                    },
-                    {
-                        "id": "agent-2",
-                        "name": "旧名称2"
+    fn clone_provider_copies_models_under_new_prefix_and_blanks_key() {
+        let _env_lock = test_env_lock();
+        let _home_guard = TempHomeGuard::new();
+        save_openclaw_config(&json!({
+            "models": {
+                "providers": {
+                    "openrouter": {
+                        "baseUrl": "https://openrouter.ai/api/v1",
+                        "apiKey": "sk-or-personal",
+                        "headers": { "HTTP-Referer": "https://example.com" },
+                        "models": [
+                            { "id": "anthropic/claude-sonnet-4", "name": "Claude Sonnet 4" },
+                            { "id": "openai/gpt-4o", "name": "GPT-4o" }
+                        ]
                     }
-                ]
-            }
-        });
-
-        let after = json!({
+                }
+            },
             "agents": {
-                "list": [
-                    {
-                        "id": "agent-1",
-                        "name": "新名称1"
-                    },
-                    {
-                        "id": "agent-2",
-                        "name": "新名称2"
+                "defaults": {
+                    "model": { "primary": "openrouter/openai/gpt-4o" },
+                    "models": {
+                        "openrouter/anthropic/claude-sonnet-4": {
+                            "alias": "sonnet",
+                            "params": { "temperature": 0.2 }
+                        },
+                        "openrouter/openai/gpt-4o": {}
                     }
-                ]
+                }
             }
-        });
+        }))
+        .expect("应可写入测试配置");
 
-        let diff = build_config_diff_summary(&before, &after);
-        assert_eq!(diff.added, 0);
-        assert_eq!(diff.removed, 0);
-        assert_eq!(diff.modified, 2);
-        assert_eq!(diff.changes.len(), 2);
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let err = runtime
+            .block_on(clone_provider("openrouter".to_string(), "team/billing".to_string()))
+            .expect_err("包含 / 的名称应被拒绝");
+        assert!(err.contains('/'), "{}", err);
+        let err = runtime
+            .block_on(clone_provider("openrouter".to_string(), "openrouter".to_string()))
+            .expect_err("已存在的名称应被拒绝");
+        assert!(err.contains("已存在"), "{}", err);
+        runtime
+            .block_on(clone_provider("openrouter".to_string(), "openrouter-team".to_string()))
+            .expect("应可复制 Provider");
+
+        let config = load_openclaw_config_raw().expect("应可读取配置");
+        let source = &config["models"]["providers"]["openrouter"];
+        let clone = &config["models"]["providers"]["openrouter-team"];
+        assert_eq!(source["apiKey"], "sk-or-personal");
+        assert!(clone.get("apiKey").is_none());
+        assert_eq!(clone["baseUrl"], source["baseUrl"]);
+        assert_eq!(clone["headers"], source["headers"]);
+        assert_eq!(clone["models"], source["models"]);
+
+        let models = &config["agents"]["defaults"]["models"];
+        assert_eq!(
+            models["openrouter-team/anthropic/claude-sonnet-4"],
+            json!({ "params": { "temperature": 0.2 } })
+        );
+        assert_eq!(models["openrouter-team/openai/gpt-4o"], json!({}));
+        assert_eq!(models["openrouter/anthropic/claude-sonnet-4"]["alias"], "sonnet");
+        assert_eq!(
+            config.pointer("/agents/defaults/model/primary"),
+            Some(&json!("openrouter/openai/gpt-4o"))
+        );
+    }
 
-        assert!(diff
-            .changes
-            .iter()
-            .any(|item| item.path == "/agents/list/0/name" && item.kind == "modified"));
-        assert!(diff
-            .changes
-            .iter()
-            .any(|item| item.path == "/agents/list/1/name" && item.kind == "modified"));
-        assert!(!diff
-            .changes
-            .iter()
-            .any(|item| item.path == "/agents" && item.kind == "modified"));
+    #[test]
+    fn open_config_file_requires_opt_in_to_create() {
+        let _env_lock = test_env_lock();
+        let home_guard = TempHomeGuard::new();
+        let config_file = home_guard.temp_home_dir.join(".openclaw").join("openclaw.json");
+
+        // 文件不存在且未要求创建时不打开编辑器，返回可识别的错误
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let missing = runtime
+            .block_on(open_config_file(None))
+            .expect_err("配置文件不存在时应报错");
+        assert!(missing.starts_with(CONFIG_FILE_MISSING_ERROR));
+        assert!(!config_file.exists());
     }
 }
@@ -0,0 +1,265 @@
+use crate::utils::platform;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::command;
+
+/// 凭证目录中的一项（文件或目录，如 whatsapp/、oauth.json）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialEntry {
+    pub name: String,
+    /// 所属渠道（目录名或去掉扩展名的文件名）
+    pub channel: String,
+    pub is_dir: bool,
+    pub file_count: u64,
+    pub total_bytes: u64,
+    /// 最后修改时间（Unix 秒，目录取其中最新的文件）
+    pub modified: u64,
+}
+
+/// 凭证目录概览（~/.openclaw/credentials）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialsInfo {
+    pub root: String,
+    pub total_bytes: u64,
+    pub entries: Vec<CredentialEntry>,
+}
+
+/// 清除渠道凭证的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialsClearResult {
+    /// 未确认时只列出将被删除的内容，不做删除
+    pub dry_run: bool,
+    pub channel: String,
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+/// 渠道名只允许字母、数字、"-"、"_"，防止路径穿越
+fn is_valid_channel_name(channel: &str) -> bool {
+    !channel.is_empty()
+        && channel.len() <= 64
+        && channel
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// 统计文件数、字节数与最新修改时间；不跟随符号链接
+fn measure(path: &Path) -> (u64, u64, SystemTime) {
+    let mut file_count = 0;
+    let mut total_bytes = 0;
+    let mut latest = UNIX_EPOCH;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(metadata) = std::fs::symlink_metadata(&current) else {
+            continue;
+        };
+        latest = latest.max(metadata.modified().unwrap_or(UNIX_EPOCH));
+        if metadata.file_type().is_dir() {
+            if let Ok(entries) = std::fs::read_dir(&current) {
+                pending.extend(entries.flatten().map(|entry| entry.path()));
+            }
+        } else {
+            file_count += 1;
+            total_bytes += metadata.len();
+        }
+    }
+    (file_count, total_bytes, latest)
+}
+
+/// 条目所属渠道：目录取目录名，文件取去掉扩展名的文件名
+fn entry_channel(name: &str, is_dir: bool) -> String {
+    if is_dir {
+        return name.to_string();
+    }
+    match name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// 列出凭证目录下的各项（按名称排序），目录不存在时返回空列表
+fn scan_credentials(credentials_dir: &Path) -> Result<CredentialsInfo, String> {
+    let mut entries = Vec::new();
+    if credentials_dir.is_dir() {
+        let dir = std::fs::read_dir(credentials_dir)
+            .map_err(|e| format!("读取凭证目录失败: {}", e))?;
+        for entry in dir.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let is_dir = std::fs::symlink_metadata(entry.path())
+                .map(|m| m.file_type().is_dir())
+                .unwrap_or(false);
+            let (file_count, total_bytes, modified) = measure(&entry.path());
+            entries.push(CredentialEntry {
+                channel: entry_channel(&name, is_dir),
+                name,
+                is_dir,
+                file_count,
+                total_bytes,
+                modified: unix_secs(modified),
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(CredentialsInfo {
+        root: credentials_dir.display().to_string(),
+        total_bytes: entries.iter().map(|e| e.total_bytes).sum(),
+        entries,
+    })
+}
+
+/// 删除某渠道的凭证（与渠道同名的目录或文件，如 whatsapp/、whatsapp.json）
+/// 只删除凭证目录的直接子项；符号链接只删除链接本身；dry_run 时只统计
+fn remove_channel_credentials(
+    credentials_dir: &Path,
+    channel: &str,
+    dry_run: bool,
+) -> Result<CredentialsClearResult, String> {
+    if !is_valid_channel_name(channel) {
+        return Err(format!("无效的渠道名: {}", channel));
+    }
+    let mut result = CredentialsClearResult {
+        dry_run,
+        channel: channel.to_string(),
+        removed: Vec::new(),
+        bytes_freed: 0,
+    };
+    if !credentials_dir.is_dir() {
+        return Ok(result);
+    }
+    let root = credentials_dir
+        .canonicalize()
+        .map_err(|e| format!("解析凭证目录失败: {}", e))?;
+
+    let targets: Vec<CredentialEntry> = scan_credentials(credentials_dir)?
+        .entries
+        .into_iter()
+        .filter(|entry| entry.channel == channel)
+        .collect();
+    for entry in targets {
+        let path = root.join(&entry.name);
+        // 防御性校验：目标必须是凭证目录的直接子项
+        if path.parent() != Some(root.as_path()) {
+            warn!("[凭证管理] 跳过凭证目录外的路径: {}", path.display());
+            continue;
+        }
+        if !dry_run {
+            let removed = if entry.is_dir {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_file(&path)
+            };
+            removed.map_err(|e| format!("删除 {} 失败: {}", path.display(), e))?;
+        }
+        result.bytes_freed += entry.total_bytes;
+        result.removed.push(entry.name);
+    }
+    Ok(result)
+}
+
+fn get_credentials_dir() -> PathBuf {
+    PathBuf::from(platform::get_config_dir()).join("credentials")
+}
+
+/// 列出已保存的渠道凭证（~/.openclaw/credentials）
+#[command]
+pub async fn list_credentials() -> Result<CredentialsInfo, String> {
+    scan_credentials(&get_credentials_dir())
+}
+
+/// 清除指定渠道的凭证（如强制 WhatsApp 重新扫码登录）
+/// confirm 不为 true 时只返回将被删除的内容，不做删除
+#[command]
+pub async fn clear_credentials(
+    channel: String,
+    confirm: Option<bool>,
+) -> Result<CredentialsClearResult, String> {
+    let dry_run = !confirm.unwrap_or(false);
+    info!(
+        "[凭证管理] 清除渠道 {} 的凭证{}...",
+        channel,
+        if dry_run { "（预览）" } else { "" }
+    );
+    let result = remove_channel_credentials(&get_credentials_dir(), channel.trim(), dry_run)?;
+    if result.removed.is_empty() {
+        info!("[凭证管理] 渠道 {} 没有已保存的凭证", result.channel);
+    } else {
+        info!(
+            "[凭证管理] {}: {}（{} 字节）",
+            if dry_run { "将删除" } else { "已删除" },
+            result.removed.join(", "),
+            result.bytes_freed
+        );
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remove_channel_credentials, scan_credentials};
+    use crate::commands::config::tests::unique_temp_dir;
+
+    #[test]
+    fn clearing_a_channel_only_touches_its_credentials() {
+        let dir = unique_temp_dir("credentials");
+        let credentials = dir.join("credentials");
+        std::fs::create_dir_all(credentials.join("whatsapp/default")).unwrap();
+        std::fs::write(credentials.join("whatsapp/default/creds.json"), "{\"me\":1}").unwrap();
+        std::fs::write(credentials.join("whatsapp/default/app-state.json"), "[]").unwrap();
+        std::fs::write(credentials.join("oauth.json"), "{}").unwrap();
+        std::fs::write(credentials.join("whatsapp-allowFrom.json"), "[]").unwrap();
+        std::fs::write(dir.join("openclaw.json"), "{}").unwrap();
+
+        let info = scan_credentials(&credentials).unwrap();
+        let names: Vec<&str> = info.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["oauth.json", "whatsapp", "whatsapp-allowFrom.json"]);
+        let whatsapp = &info.entries[1];
+        assert!(whatsapp.is_dir);
+        assert_eq!((whatsapp.file_count, whatsapp.total_bytes), (2, 10));
+        assert!(whatsapp.modified > 0);
+        assert_eq!(info.entries[0].channel, "oauth");
+        assert_eq!(info.total_bytes, 14);
+
+        // 未确认时不删除
+        let preview = remove_channel_credentials(&credentials, "whatsapp", true).unwrap();
+        assert_eq!(preview.removed, ["whatsapp"]);
+        assert_eq!(preview.bytes_freed, 10);
+        assert!(credentials.join("whatsapp/default/creds.json").exists());
+
+        for bad in ["../", "..", "", "a/b", "whatsapp/../../openclaw.json"] {
+            assert!(remove_channel_credentials(&credentials, bad, false).is_err(), "{}", bad);
+        }
+
+        let cleared = remove_channel_credentials(&credentials, "whatsapp", false).unwrap();
+        assert!(!cleared.dry_run);
+        assert!(!credentials.join("whatsapp").exists());
+        assert!(credentials.join("whatsapp-allowFrom.json").exists());
+        assert!(credentials.join("oauth.json").exists());
+        assert!(dir.join("openclaw.json").exists());
+
+        let none = remove_channel_credentials(&credentials, "telegram", false).unwrap();
+        assert!(none.removed.is_empty());
+
+        // 指向凭证目录外的符号链接只删除链接本身
+        #[cfg(unix)]
+        {
+            std::fs::create_dir_all(dir.join("outside")).unwrap();
+            std::fs::write(dir.join("outside/keep.txt"), "keep").unwrap();
+            std::os::unix::fs::symlink(dir.join("outside"), credentials.join("signal")).unwrap();
+            let linked = remove_channel_credentials(&credentials, "signal", false).unwrap();
+            assert_eq!(linked.removed, ["signal"]);
+            assert!(!credentials.join("signal").exists());
+            assert!(dir.join("outside/keep.txt").exists());
+        }
+        let missing = dir.join("missing");
+        assert!(scan_credentials(&missing).unwrap().entries.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
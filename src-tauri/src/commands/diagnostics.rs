@@ -1,4 +1,8 @@
-use crate::models::{AITestResult, ChannelTestResult, DiagnosticResult, SystemInfo};
+use super::{config, process};
+use crate::models::{
+    AITestResult, ChannelTestResult, DiagnosticResult, ManagerPaths, PathEntry, PortOwner,
+    SystemInfo,
+};
 use crate::utils::{platform, shell};
 use tauri::command;
 use log::{info, warn, error, debug};
@@ -81,6 +85,380 @@ fn extract_json_from_output(output: &str) -> Option<String> {
     }
 }
 
+/// 检查 Gateway 认证配置：gateway.mode、gateway.auth.mode 与 gateway.auth.token
+fn check_gateway_auth(config: &serde_json::Value) -> Vec<DiagnosticResult> {
+    let mut results = Vec::new();
+
+    let gateway_mode = config
+        .pointer("/gateway/mode")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty());
+    results.push(DiagnosticResult {
+        name: "Gateway 模式".to_string(),
+        passed: gateway_mode.is_some(),
+        message: match gateway_mode {
+            Some(mode) => format!("gateway.mode = {}", mode),
+            None => "未设置 gateway.mode".to_string(),
+        },
+        suggestion: if gateway_mode.is_some() {
+            None
+        } else {
+            Some(
+                "在 openclaw.json 中设置 gateway.mode 为 \"local\"，或生成 Gateway Token 自动补齐"
+                    .to_string(),
+            )
+        },
+    });
+
+    let has_token = config
+        .pointer("/gateway/auth/token")
+        .and_then(|v| v.as_str())
+        .is_some_and(|s| !s.trim().is_empty());
+    let auth_mode = config.pointer("/gateway/auth/mode").and_then(|v| v.as_str());
+    let token_mode = auth_mode == Some("token");
+    let passed = has_token && token_mode;
+    results.push(DiagnosticResult {
+        name: "Gateway 认证".to_string(),
+        passed,
+        message: if passed {
+            "已配置 Gateway Token 认证".to_string()
+        } else if !has_token {
+            "未配置 gateway.auth.token，Dashboard 链接将无法通过认证".to_string()
+        } else {
+            format!(
+                "gateway.auth.mode 为 {}，应为 token",
+                auth_mode.unwrap_or("未设置")
+            )
+        },
+        suggestion: if passed {
+            None
+        } else {
+            Some(
+                "调用 get_or_create_gateway_token（打开 Dashboard 时会自动执行）生成 Token 并设置认证模式"
+                    .to_string(),
+            )
+        },
+    });
+
+    results
+}
+
+/// 根据端口占用情况生成诊断结果
+/// gateway_responding 表示 openclaw health 已确认 Gateway 在该端口正常响应
+fn port_conflict_result(
+    port: u16,
+    in_use: bool,
+    gateway_responding: bool,
+    owner: Option<PortOwner>,
+) -> DiagnosticResult {
+    let name = "Gateway 端口".to_string();
+    let change_port_hint = "或在 openclaw.json 中修改 gateway.port 后重启服务";
+
+    match owner {
+        None if !in_use => DiagnosticResult {
+            name,
+            passed: true,
+            message: format!("端口 {} 空闲，可正常启动 Gateway", port),
+            suggestion: None,
+        },
+        None => DiagnosticResult {
+            name,
+            passed: gateway_responding,
+            message: format!("端口 {} 已被占用，无法识别占用进程", port),
+            suggestion: if gateway_responding {
+                None
+            } else {
+                Some(format!("请检查占用端口 {} 的程序，{}", port, change_port_hint))
+            },
+        },
+        Some(owner) => {
+            let pid = owner.pid;
+            let process_name = match &owner.cmdline {
+                Some(cmdline) => format!("{} - {}", owner.name, cmdline),
+                None => owner.name.clone(),
+            };
+            let is_gateway =
+                gateway_responding || process_name.to_lowercase().contains("openclaw");
+            if is_gateway {
+                DiagnosticResult {
+                    name,
+                    passed: true,
+                    message: format!(
+                        "端口 {} 由 OpenClaw Gateway 占用 (PID {}: {})",
+                        port, pid, process_name
+                    ),
+                    suggestion: None,
+                }
+            } else {
+                DiagnosticResult {
+                    name,
+                    passed: false,
+                    message: format!(
+                        "端口 {} 被其他进程占用 (PID {}: {})，Gateway 将无法启动",
+                        port, pid, process_name
+                    ),
+                    suggestion: Some(format!(
+                        "结束进程 {} (PID {})，{}",
+                        owner.name, pid, change_port_hint
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// 需要限制权限的敏感路径及期望权限：配置目录 700，env 文件 600
+#[cfg(unix)]
+fn secret_permission_targets() -> Vec<(std::path::PathBuf, u32)> {
+    vec![
+        (std::path::PathBuf::from(platform::get_config_dir()), 0o700),
+        (std::path::PathBuf::from(platform::get_env_file_path()), 0o600),
+    ]
+}
+
+/// 读取各路径的当前权限（不存在的路径跳过），返回 (路径, 当前权限, 期望权限)
+#[cfg(unix)]
+fn read_permissions(targets: &[(std::path::PathBuf, u32)]) -> Vec<(std::path::PathBuf, u32, u32)> {
+    use std::os::unix::fs::PermissionsExt;
+    targets
+        .iter()
+        .filter_map(|(path, expected)| {
+            let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o777;
+            Some((path.clone(), mode, *expected))
+        })
+        .collect()
+}
+
+/// 组或其他用户可访问即视为过宽
+#[cfg(unix)]
+fn is_loose_mode(mode: u32) -> bool {
+    mode & 0o077 != 0
+}
+
+/// 配置目录与 env 文件权限检查（密钥不应被其他用户读取）
+#[cfg(unix)]
+fn config_permissions_result(targets: &[(std::path::PathBuf, u32)]) -> DiagnosticResult {
+    let modes = read_permissions(targets);
+    let loose: Vec<_> = modes.iter().filter(|(_, mode, _)| is_loose_mode(*mode)).collect();
+    let describe = |(path, mode, _): &(std::path::PathBuf, u32, u32)| {
+        format!("{} 权限 {:o}", path.display(), mode)
+    };
+    DiagnosticResult {
+        name: "配置目录权限".to_string(),
+        passed: loose.is_empty(),
+        message: if loose.is_empty() {
+            modes.iter().map(describe).collect::<Vec<_>>().join("，")
+        } else {
+            format!(
+                "权限过宽，其他用户可读取密钥: {}",
+                loose.iter().map(|m| describe(m)).collect::<Vec<_>>().join("，")
+            )
+        },
+        suggestion: if loose.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "执行「修复配置目录权限」，或运行: {}",
+                loose
+                    .iter()
+                    .map(|(path, _, expected)| format!("chmod {:o} {}", expected, path.display()))
+                    .collect::<Vec<_>>()
+                    .join(" && ")
+            ))
+        },
+    }
+}
+
+/// 将权限过宽的路径收紧为期望权限，返回修改记录
+#[cfg(unix)]
+fn tighten_permissions(targets: &[(std::path::PathBuf, u32)]) -> Result<Vec<String>, String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut fixed = Vec::new();
+    for (path, mode, expected) in read_permissions(targets) {
+        if !is_loose_mode(mode) {
+            continue;
+        }
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(expected))
+            .map_err(|e| format!("修改 {} 权限失败: {}", path.display(), e))?;
+        fixed.push(format!("{}: {:o} -> {:o}", path.display(), mode, expected));
+    }
+    Ok(fixed)
+}
+
+/// openclaw doctor 单项检查的状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DoctorCheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// 将 doctor JSON 中的状态字段映射为检查状态
+fn doctor_status_from_str(status: &str) -> Option<DoctorCheckStatus> {
+    match status.trim().to_lowercase().as_str() {
+        "ok" | "pass" | "passed" | "success" | "healthy" => Some(DoctorCheckStatus::Pass),
+        "warn" | "warning" | "skipped" => Some(DoctorCheckStatus::Warn),
+        "fail" | "failed" | "error" | "critical" => Some(DoctorCheckStatus::Fail),
+        _ => None,
+    }
+}
+
+/// 构造单项 doctor 检查结果，未通过时附带修复建议
+fn doctor_check_result(
+    status: DoctorCheckStatus,
+    name: &str,
+    message: &str,
+    suggestion: Option<String>,
+) -> DiagnosticResult {
+    let passed = status == DoctorCheckStatus::Pass;
+    let suggestion = if passed {
+        None
+    } else {
+        suggestion.or_else(|| Some("可运行 openclaw doctor --fix 尝试自动修复".to_string()))
+    };
+    DiagnosticResult {
+        name: format!("Doctor: {}", name),
+        passed,
+        message: match status {
+            DoctorCheckStatus::Warn => format!("警告: {}", message),
+            _ => message.to_string(),
+        },
+        suggestion,
+    }
+}
+
+/// 解析 openclaw doctor --json 输出（检查项数组，或包含 checks 数组的对象）
+fn parse_doctor_json(value: &serde_json::Value) -> Option<Vec<DiagnosticResult>> {
+    let checks = value
+        .as_array()
+        .or_else(|| value.get("checks").and_then(|v| v.as_array()))?;
+    let text = |item: &serde_json::Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| item.get(*key).and_then(|v| v.as_str()))
+            .map(str::to_string)
+    };
+
+    let results: Vec<DiagnosticResult> = checks
+        .iter()
+        .filter_map(|item| {
+            let name = text(item, &["name", "title", "id", "check"])?;
+            let status = ["ok", "passed"]
+                .iter()
+                .find_map(|key| item.get(*key).and_then(|v| v.as_bool()))
+                .map(|ok| if ok { DoctorCheckStatus::Pass } else { DoctorCheckStatus::Fail })
+                .or_else(|| {
+                    text(item, &["status", "level"]).and_then(|s| doctor_status_from_str(&s))
+                })?;
+            let message =
+                text(item, &["message", "detail", "summary"]).unwrap_or_else(|| name.clone());
+            let suggestion = text(item, &["fix", "suggestion", "hint"]);
+            Some(doctor_check_result(status, &name, &message, suggestion))
+        })
+        .collect();
+
+    if results.is_empty() {
+        None
+    } else {
+        Some(results)
+    }
+}
+
+/// doctor 文本输出中表示检查状态的行首标记
+const DOCTOR_LINE_MARKERS: &[(&str, DoctorCheckStatus)] = &[
+    ("✓", DoctorCheckStatus::Pass),
+    ("✔", DoctorCheckStatus::Pass),
+    ("[ok]", DoctorCheckStatus::Pass),
+    ("⚠", DoctorCheckStatus::Warn),
+    ("[warn]", DoctorCheckStatus::Warn),
+    ("✗", DoctorCheckStatus::Fail),
+    ("✘", DoctorCheckStatus::Fail),
+    ("✖", DoctorCheckStatus::Fail),
+    ("×", DoctorCheckStatus::Fail),
+    ("[fail]", DoctorCheckStatus::Fail),
+    ("[error]", DoctorCheckStatus::Fail),
+];
+
+/// 解析 doctor 文本输出的一行：去掉 clack 边框后按状态标记识别，"名称: 详情" 拆分名称
+fn parse_doctor_line(line: &str) -> Option<DiagnosticResult> {
+    let line = line
+        .trim_start_matches(|c: char| c.is_whitespace() || "│┃|◇◆●○└┌├─".contains(c))
+        .trim_end();
+    let lower = line.to_lowercase();
+    let (marker, status) = DOCTOR_LINE_MARKERS
+        .iter()
+        .find(|(marker, _)| lower.starts_with(marker))?;
+    let rest = line[marker.len()..].trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let (name, message) = match rest.split_once(": ") {
+        Some((name, detail)) if name.chars().count() <= 40 => (name.trim(), detail.trim()),
+        _ => (rest, rest),
+    };
+    Some(doctor_check_result(*status, name, message, None))
+}
+
+/// 解析 openclaw doctor 输出为逐项检查结果，无法识别任何检查项时返回 None
+fn parse_doctor_output(output: &str) -> Option<Vec<DiagnosticResult>> {
+    if let Some(json_str) = extract_json_from_output(output) {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(&json_str) {
+            if let Some(results) = parse_doctor_json(&value) {
+                return Some(results);
+            }
+        }
+    }
+
+    let results: Vec<DiagnosticResult> = strip_ansi_codes(output)
+        .lines()
+        .filter_map(parse_doctor_line)
+        .collect();
+    if results.is_empty() {
+        None
+    } else {
+        Some(results)
+    }
+}
+
+/// 将 doctor 执行结果转换为诊断结果：优先逐项解析，无法解析时整体作为一项
+/// 命令执行失败时总会包含一项未通过的结果，即使解析出的检查项全部通过
+fn doctor_results(doctor_result: Result<String, String>) -> Vec<DiagnosticResult> {
+    let (output, failed) = match &doctor_result {
+        Ok(output) => (output, false),
+        Err(output) => (output, true),
+    };
+    let Some(mut results) = parse_doctor_output(output) else {
+        return vec![DiagnosticResult {
+            name: "OpenClaw Doctor".to_string(),
+            passed: !failed && !output.contains("invalid"),
+            message: output.clone(),
+            suggestion: None,
+        }];
+    };
+
+    if failed {
+        results.push(DiagnosticResult {
+            name: "OpenClaw Doctor".to_string(),
+            passed: false,
+            message: "openclaw doctor 执行失败".to_string(),
+            suggestion: Some("请在终端运行 openclaw doctor 查看完整输出".to_string()),
+        });
+    }
+    results
+}
+
+/// 运行 openclaw doctor：优先使用 --json，旧版本不支持该参数时回退为文本输出
+async fn run_openclaw_doctor() -> Result<String, String> {
+    let timeout = std::time::Duration::from_secs(60);
+    match shell::run_openclaw_async(&["doctor", "--json"], timeout).await {
+        Err(e) if e.contains("unknown option") => {
+            debug!("[诊断] openclaw doctor 不支持 --json，使用文本输出");
+            shell::run_openclaw_async(&["doctor"], timeout).await
+        }
+        result => result,
+    }
+}
+
 /// 运行诊断
 #[command]
 pub async fn run_doctor() -> Result<Vec<DiagnosticResult>, String> {
@@ -139,6 +517,75 @@ pub async fn run_doctor() -> Result<Vec<DiagnosticResult>, String> {
         },
     });
     
+    // 检查 Gateway 认证配置
+    let config_value = if config_exists {
+        match config::load_openclaw_config() {
+            Ok(cfg) => Some(cfg),
+            Err(e) => {
+                warn!("[诊断] 读取配置失败，跳过 Gateway 认证检查: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(cfg) = &config_value {
+        results.extend(check_gateway_auth(cfg));
+    }
+    
+    // 检查 Gateway 端口是否被其他进程占用
+    let gateway_port = config_value
+        .as_ref()
+        .and_then(|cfg| cfg.pointer("/gateway/port"))
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u16::try_from(v).ok())
+        .unwrap_or(config::DEFAULT_GATEWAY_PORT);
+    info!("[诊断] 检查端口 {} 占用情况...", gateway_port);
+    let port_in_use = process::check_port_in_use(gateway_port).await.unwrap_or(false);
+    // 默认端口下 check_port_in_use 通过 openclaw health 判断，成功即说明是 Gateway 在监听
+    let gateway_responding = port_in_use && gateway_port == config::DEFAULT_GATEWAY_PORT;
+    let port_owner = process::find_port_owner(gateway_port);
+    results.push(port_conflict_result(
+        gateway_port,
+        port_in_use,
+        gateway_responding,
+        port_owner,
+    ));
+    
+    // 检查孤立 bindings（指向不存在的 Agent）
+    if config_exists {
+        match config::check_orphaned_bindings() {
+            Ok(orphans) => {
+                info!("[诊断] 孤立 bindings: {} 条", orphans.len());
+                results.push(DiagnosticResult {
+                    name: "Bindings 路由".to_string(),
+                    passed: orphans.is_empty(),
+                    message: if orphans.is_empty() {
+                        "所有 bindings 均指向有效的 Agent".to_string()
+                    } else {
+                        format!(
+                            "以下 bindings 指向不存在的 Agent: {}",
+                            orphans
+                                .iter()
+                                .map(|o| format!("{}/{} -> {}", o.channel, o.account_id, o.agent_id))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        )
+                    },
+                    suggestion: if orphans.is_empty() {
+                        None
+                    } else {
+                        Some(
+                            "执行「修复孤立 bindings」移除无效路由，或在 agents.list 中补回对应 Agent"
+                                .to_string(),
+                        )
+                    },
+                });
+            }
+            Err(e) => warn!("[诊断] 检查 bindings 失败: {}", e),
+        }
+    }
+    
     // 检查环境变量文件
     let env_path = platform::get_env_file_path();
     let env_exists = std::path::Path::new(&env_path).exists();
@@ -157,20 +604,37 @@ pub async fn run_doctor() -> Result<Vec<DiagnosticResult>, String> {
         },
     });
     
+    // 检查配置目录与 env 文件权限（Windows 不适用）
+    #[cfg(unix)]
+    results.push(config_permissions_result(&secret_permission_targets()));
+
     // 运行 openclaw doctor
     if openclaw_installed {
-        let doctor_result = shell::run_openclaw(&["doctor"]);
-        results.push(DiagnosticResult {
-            name: "OpenClaw Doctor".to_string(),
-            passed: doctor_result.is_ok() && !doctor_result.as_ref().unwrap().contains("invalid"),
-            message: doctor_result.unwrap_or_else(|e| e),
-            suggestion: None,
-        });
+        results.extend(doctor_results(run_openclaw_doctor().await));
     }
     
     Ok(results)
 }
 
+/// 将配置目录与 env 文件权限收紧为 700 / 600，返回修改记录；Windows 上不做任何操作
+#[command]
+pub async fn fix_config_permissions() -> Result<Vec<String>, String> {
+    #[cfg(unix)]
+    {
+        let fixed = tighten_permissions(&secret_permission_targets())?;
+        if fixed.is_empty() {
+            info!("[诊断] 配置目录权限正常，无需修复");
+        } else {
+            info!("[诊断] ✓ 已修复权限: {}", fixed.join("; "));
+        }
+        Ok(fixed)
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(Vec::new())
+    }
+}
+
 /// 测试 AI 连接
 #[command]
 pub async fn test_ai_connection() -> Result<AITestResult, String> {
@@ -226,6 +690,167 @@ pub async fn test_ai_connection() -> Result<AITestResult, String> {
     }
 }
 
+/// Anthropic Messages 协议的 API 类型标识
+const ANTHROPIC_MESSAGES_API: &str = "anthropic-messages";
+/// 请求 Anthropic 兼容接口时携带的 anthropic-version
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// 测试 Provider 连接的 HTTP 请求
+#[derive(Debug, PartialEq)]
+struct ProviderProbeRequest {
+    url: String,
+    headers: Vec<(String, String)>,
+    /// 有请求体时使用 POST（对话请求），否则 GET（模型列表）
+    body: Option<serde_json::Value>,
+}
+
+/// Provider 实际使用的 API 类型：优先 provider.api，其次第一个模型的 api，默认 openai-completions
+fn provider_api_type(provider: &serde_json::Value) -> String {
+    provider
+        .get("api")
+        .and_then(|v| v.as_str())
+        .or_else(|| provider.pointer("/models/0/api").and_then(|v| v.as_str()))
+        .unwrap_or("openai-completions")
+        .to_string()
+}
+
+/// 按 API 类型构造连接测试请求
+/// anthropic-messages 使用 /v1/models、/v1/messages 与 x-api-key + anthropic-version 请求头，
+/// 其余按 OpenAI 兼容接口使用 /models、/chat/completions 与 Bearer 认证；
+/// 指定 model 时发送最小对话请求，否则只请求模型列表
+fn build_provider_probe_request(
+    api_type: &str,
+    base_url: &str,
+    api_key: Option<&str>,
+    model: Option<&str>,
+) -> ProviderProbeRequest {
+    let base = base_url.trim().trim_end_matches('/');
+    let mut headers = Vec::new();
+    let messages = serde_json::json!([{ "role": "user", "content": "ping" }]);
+
+    if api_type == ANTHROPIC_MESSAGES_API {
+        let base = if base.ends_with("/v1") {
+            base.to_string()
+        } else {
+            format!("{}/v1", base)
+        };
+        if let Some(key) = api_key {
+            headers.push(("x-api-key".to_string(), key.to_string()));
+        }
+        headers.push(("anthropic-version".to_string(), ANTHROPIC_VERSION.to_string()));
+        match model {
+            Some(model) => ProviderProbeRequest {
+                url: format!("{}/messages", base),
+                headers,
+                body: Some(serde_json::json!({
+                    "model": model,
+                    "max_tokens": 1,
+                    "messages": messages,
+                })),
+            },
+            None => ProviderProbeRequest {
+                url: format!("{}/models", base),
+                headers,
+                body: None,
+            },
+        }
+    } else {
+        if let Some(key) = api_key {
+            headers.push(("Authorization".to_string(), format!("Bearer {}", key)));
+        }
+        match model {
+            Some(model) => ProviderProbeRequest {
+                url: format!("{}/chat/completions", base),
+                headers,
+                body: Some(serde_json::json!({
+                    "model": model,
+                    "max_tokens": 1,
+                    "messages": messages,
+                })),
+            },
+            None => ProviderProbeRequest {
+                url: format!("{}/models", base),
+                headers,
+                body: None,
+            },
+        }
+    }
+}
+
+/// 直接请求 Provider API 测试连接（按 API 类型区分 Anthropic 与 OpenAI 兼容接口）
+/// model_id 为空时使用该 Provider 的第一个模型；Provider 未配置模型时只请求模型列表
+#[command]
+pub async fn test_provider_connection(
+    provider_name: String,
+    model_id: Option<String>,
+) -> Result<AITestResult, String> {
+    info!("[AI测试] 测试 Provider 连接: {}", provider_name);
+    let config = config::load_openclaw_config()?;
+    let provider = config
+        .pointer("/models/providers")
+        .and_then(|providers| providers.get(&provider_name))
+        .ok_or_else(|| format!("Provider 不存在: {}", provider_name))?;
+    let base_url = provider
+        .get("baseUrl")
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.trim().is_empty())
+        .ok_or_else(|| format!("Provider {} 未配置 baseUrl", provider_name))?;
+    let api_key = provider
+        .get("apiKey")
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.trim().is_empty());
+    let model = model_id.or_else(|| {
+        provider
+            .pointer("/models/0/id")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    });
+
+    let api_type = provider_api_type(provider);
+    let request = build_provider_probe_request(&api_type, base_url, api_key, model.as_deref());
+    info!("[AI测试] {} 接口 ({}): {}", provider_name, api_type, request.url);
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let mut builder = match &request.body {
+        Some(body) => client.post(&request.url).json(body),
+        None => client.get(&request.url),
+    };
+    for (key, value) in &request.headers {
+        builder = builder.header(key, value);
+    }
+
+    let start = std::time::Instant::now();
+    let outcome = builder.send().await;
+    let latency = start.elapsed().as_millis() as u64;
+    let model = model.unwrap_or_else(|| "-".to_string());
+
+    let (success, text) = match outcome {
+        Ok(response) => {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            (status.is_success(), format!("HTTP {}: {}", status, text))
+        }
+        Err(e) => (false, format!("请求失败: {}", e)),
+    };
+    if success {
+        info!("[AI测试] ✓ {} 连接成功 ({}ms)", provider_name, latency);
+    } else {
+        warn!("[AI测试] ✗ {} 连接失败: {}", provider_name, text);
+    }
+
+    Ok(AITestResult {
+        success,
+        provider: provider_name,
+        model,
+        response: if success { Some(text.clone()) } else { None },
+        error: if success { None } else { Some(text) },
+        latency_ms: Some(latency),
+    })
+}
+
 /// 获取渠道测试目标
 fn get_channel_test_target(channel_type: &str) -> Option<String> {
     let env_path = platform::get_env_file_path();
@@ -281,6 +906,37 @@ fn parse_channel_status_text(output: &str, channel_type: &str) -> Option<(bool,
     None
 }
 
+/// 判断 openclaw message send 的输出是否表示发送成功
+fn message_send_succeeded(output: &str) -> bool {
+    if let Some(json_str) = extract_json_from_output(output) {
+        info!("[渠道测试] 提取到 JSON: {}", json_str);
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&json_str) {
+            // 检查各种成功标志
+            let has_ok = json.get("ok").and_then(|v| v.as_bool()).unwrap_or(false);
+            let has_success = json.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
+            let has_message_id = json.get("messageId").is_some();
+            let has_payload_ok = json.get("payload").and_then(|p| p.get("ok")).and_then(|v| v.as_bool()).unwrap_or(false);
+            let has_payload_message_id = json.get("payload").and_then(|p| p.get("messageId")).is_some();
+            let has_payload_result_message_id = json.get("payload")
+                .and_then(|p| p.get("result"))
+                .and_then(|r| r.get("messageId"))
+                .is_some();
+            
+            info!("[渠道测试] 判断条件: ok={}, success={}, messageId={}, payload.ok={}, payload.messageId={}, payload.result.messageId={}",
+                has_ok, has_success, has_message_id, has_payload_ok, has_payload_message_id, has_payload_result_message_id);
+            
+            has_ok || has_success || has_message_id || has_payload_ok || has_payload_message_id || has_payload_result_message_id
+        } else {
+            info!("[渠道测试] JSON 解析失败");
+            false
+        }
+    } else {
+        info!("[渠道测试] 未提取到 JSON，检查关键词");
+        // 如果没有 JSON，检查是否有错误关键词
+        !output.to_lowercase().contains("error") && !output.to_lowercase().contains("failed")
+    }
+}
+
 /// 测试渠道连接（检查状态并发送测试消息）
 #[command]
 pub async fn test_channel(channel_type: String) -> Result<ChannelTestResult, String> {
@@ -403,33 +1059,7 @@ pub async fn test_channel(channel_type: String) -> Result<ChannelTestResult, Str
                 info!("[渠道测试] 发送命令输出长度: {}", output.len());
                 
                 // 检查发送是否成功
-                let send_ok = if let Some(json_str) = extract_json_from_output(&output) {
-                    info!("[渠道测试] 提取到 JSON: {}", json_str);
-                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&json_str) {
-                        // 检查各种成功标志
-                        let has_ok = json.get("ok").and_then(|v| v.as_bool()).unwrap_or(false);
-                        let has_success = json.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
-                        let has_message_id = json.get("messageId").is_some();
-                        let has_payload_ok = json.get("payload").and_then(|p| p.get("ok")).and_then(|v| v.as_bool()).unwrap_or(false);
-                        let has_payload_message_id = json.get("payload").and_then(|p| p.get("messageId")).is_some();
-                        let has_payload_result_message_id = json.get("payload")
-                            .and_then(|p| p.get("result"))
-                            .and_then(|r| r.get("messageId"))
-                            .is_some();
-                        
-                        info!("[渠道测试] 判断条件: ok={}, success={}, messageId={}, payload.ok={}, payload.messageId={}, payload.result.messageId={}",
-                            has_ok, has_success, has_message_id, has_payload_ok, has_payload_message_id, has_payload_result_message_id);
-                        
-                        has_ok || has_success || has_message_id || has_payload_ok || has_payload_message_id || has_payload_result_message_id
-                    } else {
-                        info!("[渠道测试] JSON 解析失败");
-                        false
-                    }
-                } else {
-                    info!("[渠道测试] 未提取到 JSON，检查关键词");
-                    // 如果没有 JSON，检查是否有错误关键词
-                    !output.to_lowercase().contains("error") && !output.to_lowercase().contains("failed")
-                };
+                let send_ok = message_send_succeeded(&output);
                 
                 if send_ok {
                     info!("[渠道测试] ✓ {} 测试消息发送成功", channel_type);
@@ -479,16 +1109,376 @@ pub async fn test_channel(channel_type: String) -> Result<ChannelTestResult, Str
     }
 }
 
-/// 发送测试消息到渠道
+/// 校验并规范化手动指定的消息目标
+/// - telegram: 数字 chat id（群组可为负数）或 @username
+/// - discord: 频道/用户 ID，支持 <#id>、<@id> 提及格式，裸 ID 视为频道
+/// - slack: 频道/用户 ID 或 #频道名，支持 <#C123|name> 提及格式
+/// - feishu: chat_id (oc_) 或 open_id (ou_) / union_id (on_)
+fn normalize_message_target(channel_type: &str, target: &str) -> Result<String, String> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err("发送目标不能为空".to_string());
+    }
+    if target.chars().any(|c| c.is_whitespace()) {
+        return Err(format!("发送目标不能包含空白字符: {}", target));
+    }
+
+    let is_id_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+
+    match channel_type.to_lowercase().as_str() {
+        "telegram" => {
+            let digits = target.strip_prefix('-').unwrap_or(target);
+            let is_chat_id = !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+            let is_username = target
+                .strip_prefix('@')
+                .is_some_and(|name| !name.is_empty() && name.chars().all(is_id_char));
+            if is_chat_id || is_username {
+                Ok(target.to_string())
+            } else {
+                Err(format!("Telegram 目标应为数字 chat id 或 @username: {}", target))
+            }
+        }
+        "discord" => {
+            let mention = target.strip_prefix('<').and_then(|t| t.strip_suffix('>'));
+            let (kind, id) = if let Some(id) = mention.and_then(|m| m.strip_prefix('#')) {
+                ("channel", id)
+            } else if let Some(id) = mention.and_then(|m| m.strip_prefix('@')) {
+                ("user", id.trim_start_matches('!'))
+            } else if let Some(id) = target.strip_prefix("channel:") {
+                ("channel", id)
+            } else if let Some(id) = target.strip_prefix("user:") {
+                ("user", id)
+            } else {
+                ("channel", target)
+            };
+            if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) {
+                Ok(format!("{}:{}", kind, id))
+            } else {
+                Err(format!("Discord 目标应为数字频道/用户 ID: {}", target))
+            }
+        }
+        "slack" => {
+            if let Some(inner) = target.strip_prefix("<#").and_then(|t| t.strip_suffix('>')) {
+                let id = inner.split('|').next().unwrap_or_default();
+                if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Ok(format!("channel:{}", id));
+                }
+            } else if let Some(name) = target.strip_prefix('#') {
+                if !name.is_empty() && name.chars().all(is_id_char) {
+                    return Ok(target.to_string());
+                }
+            } else {
+                let id = target
+                    .strip_prefix("channel:")
+                    .or_else(|| target.strip_prefix("user:"))
+                    .unwrap_or(target);
+                if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Ok(target.to_string());
+                }
+            }
+            Err(format!("Slack 目标应为频道/用户 ID 或 #频道名: {}", target))
+        }
+        "feishu" => {
+            let valid_prefix = ["oc_", "ou_", "on_"].iter().any(|p| target.starts_with(p));
+            if valid_prefix && target.chars().all(is_id_char) {
+                Ok(target.to_string())
+            } else {
+                Err(format!(
+                    "飞书目标应为 chat_id (oc_...) 或 open_id (ou_...): {}",
+                    target
+                ))
+            }
+        }
+        other => Err(format!(
+            "不支持向 {} 发送测试消息，仅支持 telegram、discord、slack、feishu",
+            other
+        )),
+    }
+}
+
+/// 通过渠道原生 API 发送消息的 HTTP 请求
+#[derive(Debug, PartialEq)]
+struct NativeSendRequest {
+    url: String,
+    /// Authorization 请求头
+    authorization: Option<String>,
+    body: serde_json::Value,
+}
+
+/// 读取渠道凭证：优先 channels.<id>.<key>，其次 default 账号，再按账号 ID 顺序取第一个
+fn channel_credential(config: &serde_json::Value, channel: &str, key: &str) -> Option<String> {
+    let channel_config = config.pointer(&format!("/channels/{}", channel))?;
+    let non_empty = |v: Option<&serde_json::Value>| {
+        v.and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty() && !s.starts_with("${"))
+    };
+
+    if let Some(value) = non_empty(channel_config.get(key)) {
+        return Some(value);
+    }
+
+    let accounts = channel_config.get("accounts").and_then(|v| v.as_object())?;
+    if let Some(value) = non_empty(accounts.get("default").and_then(|a| a.get(key))) {
+        return Some(value);
+    }
+    let mut account_ids: Vec<&String> = accounts.keys().collect();
+    account_ids.sort();
+    account_ids
+        .into_iter()
+        .find_map(|id| non_empty(accounts.get(id).and_then(|a| a.get(key))))
+}
+
+/// 原生 API 测试消息的最大字符数（取各渠道单条消息上限中最小的 Discord 2000 字符）
+const MAX_NATIVE_MESSAGE_CHARS: usize = 2000;
+
+/// 飞书开放平台站点（channels.feishu.domain）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeishuDomain {
+    /// 国内飞书 open.feishu.cn
+    Feishu,
+    /// 海外 Lark open.larksuite.com
+    Lark,
+}
+
+impl FeishuDomain {
+    /// 读取配置中的 domain；未配置或无法识别时使用国内飞书
+    fn from_config(config: &serde_json::Value) -> Self {
+        match config.pointer("/channels/feishu/domain").and_then(|v| v.as_str()) {
+            Some(domain) if domain.trim().eq_ignore_ascii_case("lark") => FeishuDomain::Lark,
+            _ => FeishuDomain::Feishu,
+        }
+    }
+
+    fn api_base(self) -> &'static str {
+        match self {
+            FeishuDomain::Feishu => "https://open.feishu.cn",
+            FeishuDomain::Lark => "https://open.larksuite.com",
+        }
+    }
+}
+
+/// 截断超长消息（按字符计，保留省略号）
+fn bound_message(message: &str) -> String {
+    if message.chars().count() <= MAX_NATIVE_MESSAGE_CHARS {
+        return message.to_string();
+    }
+    let mut bounded: String = message.chars().take(MAX_NATIVE_MESSAGE_CHARS - 1).collect();
+    bounded.push('…');
+    bounded
+}
+
+/// 构造原生 API 发送请求；credential 为 bot token（飞书为 tenant_access_token）
+/// 请求体统一由 serde_json 生成，任意字符都会被正确转义；超长消息会被截断
+/// 原生 API 不便直接发送的目标（如 Discord 私信）返回 None，由 openclaw CLI 处理
+fn build_native_send_request(
+    channel: &str,
+    target: &str,
+    message: &str,
+    credential: &str,
+    feishu_domain: FeishuDomain,
+) -> Option<NativeSendRequest> {
+    let message = bound_message(message);
+    match channel {
+        "telegram" => Some(NativeSendRequest {
+            url: format!("https://api.telegram.org/bot{}/sendMessage", credential),
+            authorization: None,
+            body: serde_json::json!({ "chat_id": target, "text": message }),
+        }),
+        "discord" => {
+            let channel_id = target.strip_prefix("channel:")?;
+            Some(NativeSendRequest {
+                url: format!("https://discord.com/api/v10/channels/{}/messages", channel_id),
+                authorization: Some(format!("Bot {}", credential)),
+                body: serde_json::json!({ "content": message }),
+            })
+        }
+        "slack" => {
+            let channel_id = target
+                .strip_prefix("channel:")
+                .or_else(|| target.strip_prefix("user:"))
+                .unwrap_or(target);
+            Some(NativeSendRequest {
+                url: "https://slack.com/api/chat.postMessage".to_string(),
+                authorization: Some(format!("Bearer {}", credential)),
+                body: serde_json::json!({ "channel": channel_id, "text": message }),
+            })
+        }
+        "feishu" => {
+            let receive_id_type = if target.starts_with("oc_") {
+                "chat_id"
+            } else if target.starts_with("on_") {
+                "union_id"
+            } else {
+                "open_id"
+            };
+            Some(NativeSendRequest {
+                url: format!(
+                    "{}/open-apis/im/v1/messages?receive_id_type={}",
+                    feishu_domain.api_base(),
+                    receive_id_type
+                ),
+                authorization: Some(format!("Bearer {}", credential)),
+                body: serde_json::json!({
+                    "receive_id": target,
+                    "msg_type": "text",
+                    "content": serde_json::json!({ "text": message }).to_string(),
+                }),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// 判断原生 API 响应是否表示发送成功
+fn native_send_succeeded(channel: &str, status_ok: bool, body: &serde_json::Value) -> bool {
+    if !status_ok {
+        return false;
+    }
+    match channel {
+        "telegram" | "slack" => body.get("ok").and_then(|v| v.as_bool()).unwrap_or(false),
+        "discord" => body.get("id").is_some(),
+        "feishu" => body.get("code").and_then(|v| v.as_i64()) == Some(0),
+        _ => false,
+    }
+}
+
+/// 获取原生 API 凭证（飞书需先用 appId/appSecret 换取 tenant_access_token）
+async fn resolve_native_credential(
+    client: &reqwest::Client,
+    channel: &str,
+    config: &serde_json::Value,
+) -> Result<Option<String>, String> {
+    match channel {
+        "telegram" | "discord" | "slack" => Ok(channel_credential(config, channel, "botToken")),
+        "feishu" => {
+            let (Some(app_id), Some(app_secret)) = (
+                channel_credential(config, "feishu", "appId"),
+                channel_credential(config, "feishu", "appSecret"),
+            ) else {
+                return Ok(None);
+            };
+            let url = format!(
+                "{}/open-apis/auth/v3/tenant_access_token/internal",
+                FeishuDomain::from_config(config).api_base()
+            );
+            let response: serde_json::Value = client
+                .post(&url)
+                .json(&serde_json::json!({ "app_id": app_id, "app_secret": app_secret }))
+                .send()
+                .await
+                .map_err(|e| format!("获取飞书 tenant_access_token 失败: {}", e.without_url()))?
+                .json()
+                .await
+                .map_err(|e| {
+                    format!("解析飞书 tenant_access_token 响应失败: {}", e.without_url())
+                })?;
+            response
+                .get("tenant_access_token")
+                .and_then(|v| v.as_str())
+                .map(|token| Some(token.to_string()))
+                .ok_or_else(|| format!("获取飞书 tenant_access_token 失败: {}", response))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// 通过渠道原生 API 发送消息；缺少凭证或目标不支持时返回 Ok(None)
+async fn send_native_message(
+    channel: &str,
+    target: &str,
+    message: &str,
+) -> Result<Option<ChannelTestResult>, String> {
+    let config = config::load_openclaw_config()?;
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+
+    let Some(credential) = resolve_native_credential(&client, channel, &config).await? else {
+        debug!("[渠道测试] {} 未找到原生 API 凭证", channel);
+        return Ok(None);
+    };
+    let feishu_domain = FeishuDomain::from_config(&config);
+    let Some(request) =
+        build_native_send_request(channel, target, message, &credential, feishu_domain)
+    else {
+        return Ok(None);
+    };
+
+    info!("[渠道测试] 通过 {} 原生 API 发送测试消息...", channel);
+    let mut builder = client.post(&request.url).json(&request.body);
+    if let Some(authorization) = &request.authorization {
+        builder = builder.header("Authorization", authorization);
+    }
+    // Telegram 的 bot token 在 URL 路径中，错误信息不能带出 URL
+    let response = builder
+        .send()
+        .await
+        .map_err(|e| format!("请求 {} API 失败: {}", channel, e.without_url()))?;
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    let body: serde_json::Value = serde_json::from_str(&text).unwrap_or(serde_json::Value::Null);
+
+    let success = native_send_succeeded(channel, status.is_success(), &body);
+    Ok(Some(ChannelTestResult {
+        success,
+        channel: channel.to_string(),
+        message: if success {
+            format!("消息已发送到 {}", target)
+        } else {
+            "消息发送失败".to_string()
+        },
+        error: if success {
+            None
+        } else {
+            Some(format!("HTTP {}: {}", status, text))
+        },
+    }))
+}
+
+/// 发送测试消息到手动指定的目标（不依赖 env 中保存的测试目标）
+/// 优先使用渠道原生 API 直接发送，缺少凭证或目标不支持时回退到 openclaw message send
 #[command]
 pub async fn send_test_message(channel_type: String, target: String) -> Result<ChannelTestResult, String> {
+    info!("[渠道测试] 发送测试消息: {} -> {}", channel_type, target);
+    let channel_lower = channel_type.to_lowercase();
+    
+    let target = match normalize_message_target(&channel_lower, &target) {
+        Ok(target) => target,
+        Err(e) => {
+            info!("[渠道测试] ✗ 目标无效: {}", e);
+            return Ok(ChannelTestResult {
+                success: false,
+                channel: channel_type,
+                message: "发送目标无效".to_string(),
+                error: Some(e),
+            });
+        }
+    };
+    
     let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
     let message = format!("🤖 OpenClaw 测试消息\n\n✅ 连接成功！\n⏰ {}", timestamp);
     
-    // 使用 openclaw message send 命令发送测试消息
+    match send_native_message(&channel_lower, &target, &message).await {
+        Ok(Some(mut result)) => {
+            if result.success {
+                info!("[渠道测试] ✓ {} 原生 API 发送成功", channel_type);
+            } else {
+                info!("[渠道测试] ✗ {} 原生 API 发送失败: {:?}", channel_type, result.error);
+            }
+            result.channel = channel_type;
+            return Ok(result);
+        }
+        Ok(None) => info!("[渠道测试] 无法使用原生 API，回退到 openclaw CLI"),
+        Err(e) => warn!("[渠道测试] 原生 API 发送出错，回退到 openclaw CLI: {}", e),
+    }
+    
+    // 使用 openclaw message send 命令发送测试消息（凭证由 openclaw 按渠道配置读取）
+    info!("[渠道测试] 执行: openclaw message send --channel {} --target {} ...", channel_lower, target);
     let send_result = shell::run_openclaw(&[
         "message", "send",
-        "--channel", &channel_type,
+        "--channel", &channel_lower,
         "--target", &target,
         "--message", &message,
         "--json"
@@ -496,33 +1486,33 @@ pub async fn send_test_message(channel_type: String, target: String) -> Result<C
     
     match send_result {
         Ok(output) => {
-            // 尝试从混合输出中提取并解析 JSON 结果
-            let success = if let Some(json_str) = extract_json_from_output(&output) {
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&json_str) {
-                    json.get("success").and_then(|v| v.as_bool()).unwrap_or(false)
-                        || json.get("ok").and_then(|v| v.as_bool()).unwrap_or(false)
-                        || json.get("messageId").is_some()
-                } else {
-                    false
-                }
+            let success = message_send_succeeded(&output);
+            if success {
+                info!("[渠道测试] ✓ {} 测试消息已发送到 {}", channel_type, target);
             } else {
-                // 非 JSON 输出，检查是否包含错误关键词
-                !output.to_lowercase().contains("error") && !output.to_lowercase().contains("failed")
-            };
+                info!("[渠道测试] ✗ {} 测试消息发送失败", channel_type);
+            }
             
             Ok(ChannelTestResult {
                 success,
                 channel: channel_type,
-                message: if success { "消息已发送".to_string() } else { "消息发送失败".to_string() },
+                message: if success {
+                    format!("消息已发送到 {}", target)
+                } else {
+                    "消息发送失败".to_string()
+                },
                 error: if success { None } else { Some(output) },
             })
         }
-        Err(e) => Ok(ChannelTestResult {
-            success: false,
-            channel: channel_type,
-            message: "发送失败".to_string(),
-            error: Some(e),
-        }),
+        Err(e) => {
+            info!("[渠道测试] ✗ {} 发送命令执行失败: {}", channel_type, e);
+            Ok(ChannelTestResult {
+                success: false,
+                channel: channel_type,
+                message: "发送失败".to_string(),
+                error: Some(e),
+            })
+        }
     }
 }
 
@@ -547,7 +1537,9 @@ pub async fn get_system_info() -> Result<SystemInfo, String> {
     
     let openclaw_installed = shell::get_openclaw_path().is_some();
     let openclaw_version = if openclaw_installed {
-        shell::run_openclaw(&["--version"]).ok()
+        shell::run_openclaw_async(&["--version"], std::time::Duration::from_secs(15))
+            .await
+            .ok()
     } else {
         None
     };
@@ -565,6 +1557,22 @@ pub async fn get_system_info() -> Result<SystemInfo, String> {
     })
 }
 
+/// 获取配置、环境变量、日志等文件的实际路径，便于用户手动编辑或排查问题
+#[command]
+pub async fn get_paths() -> Result<ManagerPaths, String> {
+    let entry = |path: String| PathEntry {
+        exists: std::path::Path::new(&path).exists(),
+        path,
+    };
+    Ok(ManagerPaths {
+        config_file: entry(platform::get_config_file_path()),
+        env_file: entry(platform::get_env_file_path()),
+        config_dir: entry(platform::get_config_dir()),
+        log_file: entry(platform::get_log_file_path()),
+        auth_config: entry(platform::get_web_auth_config_path()),
+    })
+}
+
 /// 启动渠道登录（如 WhatsApp 扫码）
 #[command]
 pub async fn start_channel_login(channel_type: String) -> Result<String, String> {
@@ -731,3 +1739,390 @@ read -p "按回车键关闭..."
         _ => Err(format!("不支持 {} 的登录向导", channel_type)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_native_send_request, build_provider_probe_request, channel_credential,
+        check_gateway_auth, doctor_results, native_send_succeeded, normalize_message_target,
+        parse_doctor_output, port_conflict_result, provider_api_type, FeishuDomain,
+        MAX_NATIVE_MESSAGE_CHARS,
+    };
+    use crate::commands::config::tests::{
+        test_env_lock, unique_temp_dir, EnvGuard, TempHomeGuard,
+    };
+    use crate::models::PortOwner;
+    use crate::utils::platform;
+    use serde_json::json;
+
+    #[test]
+    fn get_paths_match_platform_helpers() {
+        use super::get_paths;
+
+        let _env_lock = test_env_lock();
+        let home_guard = TempHomeGuard::new();
+        let config_dir = home_guard.temp_home_dir.join("custom-openclaw");
+        let _config_dir = EnvGuard::set("OPENCLAW_CONFIG_DIR", &config_dir.to_string_lossy());
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(config_dir.join("openclaw.json"), "{}").unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let paths = runtime.block_on(get_paths()).expect("应可获取路径");
+        assert_eq!(paths.config_dir.path, platform::get_config_dir());
+        assert_eq!(paths.config_file.path, platform::get_config_file_path());
+        assert_eq!(paths.env_file.path, platform::get_env_file_path());
+        assert_eq!(paths.log_file.path, platform::get_log_file_path());
+        assert_eq!(paths.auth_config.path, platform::get_web_auth_config_path());
+        assert!(paths.config_file.path.starts_with(&*config_dir.to_string_lossy()));
+        assert!(paths.config_dir.exists && paths.config_file.exists);
+        assert!(!paths.env_file.exists);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn loose_config_permissions_are_reported_and_tightened() {
+        use super::{config_permissions_result, tighten_permissions};
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = unique_temp_dir("perm");
+        let config_dir = dir.join(".openclaw");
+        let env_file = config_dir.join("env");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(&env_file, "export OPENAI_API_KEY=sk-test\n").unwrap();
+        let set_mode = |path: &std::path::Path, mode: u32| {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+        };
+        let mode_of = |path: &std::path::Path| {
+            std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+        };
+        set_mode(&config_dir, 0o755);
+        set_mode(&env_file, 0o644);
+        let targets = vec![
+            (config_dir.clone(), 0o700),
+            (env_file.clone(), 0o600),
+            (dir.join("missing"), 0o600),
+        ];
+
+        let before = config_permissions_result(&targets);
+        assert!(!before.passed);
+        assert!(before.message.contains("755"), "{}", before.message);
+        assert!(before.message.contains("644"), "{}", before.message);
+        assert!(before.suggestion.unwrap().contains("chmod 700"));
+
+        let fixed = tighten_permissions(&targets).unwrap();
+        assert_eq!(fixed.len(), 2);
+        assert_eq!(mode_of(&config_dir), 0o700);
+        assert_eq!(mode_of(&env_file), 0o600);
+
+        let after = config_permissions_result(&targets);
+        assert!(after.passed, "{}", after.message);
+        assert!(after.message.contains("700"));
+        assert!(tighten_permissions(&targets).unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn owner(pid: u32, name: &str, cmdline: Option<&str>) -> Option<PortOwner> {
+        Some(PortOwner {
+            pid,
+            name: name.to_string(),
+            cmdline: cmdline.map(|c| c.to_string()),
+        })
+    }
+
+    #[test]
+    fn gateway_auth_check_reports_missing_token_and_mode() {
+        let configured = json!({
+            "gateway": { "mode": "local", "auth": { "mode": "token", "token": "abc123" } }
+        });
+        let results = check_gateway_auth(&configured);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.passed), "完整配置应全部通过: {:?}", results);
+
+        let missing = json!({ "gateway": { "port": 18789 } });
+        let results = check_gateway_auth(&missing);
+        assert!(results.iter().all(|r| !r.passed));
+        assert!(results.iter().all(|r| r.suggestion.is_some()));
+        assert!(results[1].message.contains("gateway.auth.token"));
+
+        let wrong_mode = json!({
+            "gateway": { "mode": "local", "auth": { "mode": "password", "token": "abc123" } }
+        });
+        let results = check_gateway_auth(&wrong_mode);
+        assert!(results[0].passed);
+        assert!(!results[1].passed);
+        assert!(results[1].message.contains("password"));
+    }
+
+    #[test]
+    fn port_conflict_check_distinguishes_gateway_from_other_processes() {
+        let free = port_conflict_result(18789, false, false, None);
+        assert!(free.passed);
+        assert!(free.suggestion.is_none());
+
+        let cmdline = Some("node /usr/bin/openclaw gateway --port 18790");
+        let own = port_conflict_result(18790, true, false, owner(42, "node", cmdline));
+        assert!(own.passed, "OpenClaw 自身占用不应报冲突: {}", own.message);
+
+        let healthy = port_conflict_result(18789, true, true, owner(42, "node.exe", None));
+        assert!(healthy.passed);
+
+        let conflict = port_conflict_result(18789, true, false, owner(7, "nginx", None));
+        assert!(!conflict.passed);
+        assert!(conflict.message.contains("nginx") && conflict.message.contains("PID 7"));
+        assert!(conflict.suggestion.unwrap().contains("gateway.port"));
+    }
+
+    #[test]
+    fn message_target_is_normalized_per_channel() {
+        assert_eq!(normalize_message_target("telegram", " 123456 ").unwrap(), "123456");
+        assert_eq!(normalize_message_target("telegram", "-100123").unwrap(), "-100123");
+        assert_eq!(normalize_message_target("Telegram", "@my_bot").unwrap(), "@my_bot");
+        assert!(normalize_message_target("telegram", "abc").is_err());
+
+        assert_eq!(normalize_message_target("discord", "987").unwrap(), "channel:987");
+        assert_eq!(normalize_message_target("discord", "<#987>").unwrap(), "channel:987");
+        assert_eq!(normalize_message_target("discord", "<@!42>").unwrap(), "user:42");
+        assert_eq!(normalize_message_target("discord", "user:42").unwrap(), "user:42");
+        assert!(normalize_message_target("discord", "general").is_err());
+
+        assert_eq!(normalize_message_target("slack", "C0123ABC").unwrap(), "C0123ABC");
+        let mention = normalize_message_target("slack", "<#C0123|general>").unwrap();
+        assert_eq!(mention, "channel:C0123");
+        assert_eq!(normalize_message_target("slack", "#general").unwrap(), "#general");
+        assert!(normalize_message_target("slack", "#").is_err());
+
+        assert_eq!(normalize_message_target("feishu", "oc_abc123").unwrap(), "oc_abc123");
+        assert_eq!(normalize_message_target("feishu", "ou_abc123").unwrap(), "ou_abc123");
+        assert!(normalize_message_target("feishu", "abc123").is_err());
+
+        assert!(normalize_message_target("telegram", "   ").is_err());
+        assert!(normalize_message_target("telegram", "12 34").is_err());
+        assert!(normalize_message_target("whatsapp", "+8613800000000").is_err());
+    }
+
+    #[test]
+    fn native_send_uses_channel_credentials_and_targets() {
+        let config = json!({
+            "channels": {
+                "telegram": { "botToken": "123:abc" },
+                "discord": {
+                    "accounts": { "zeta": { "botToken": "z" }, "alpha": { "botToken": "a" } }
+                },
+                "slack": { "botToken": "${SLACK_TOKEN}" }
+            }
+        });
+        assert_eq!(channel_credential(&config, "telegram", "botToken"), Some("123:abc".into()));
+        assert_eq!(channel_credential(&config, "discord", "botToken"), Some("a".into()));
+        assert_eq!(channel_credential(&config, "slack", "botToken"), None, "未替换的占位符不可用");
+        assert_eq!(channel_credential(&config, "feishu", "appId"), None);
+
+        let base = FeishuDomain::Feishu;
+        let telegram =
+            build_native_send_request("telegram", "-100", "hi", "123:abc", base).unwrap();
+        assert_eq!(telegram.url, "https://api.telegram.org/bot123:abc/sendMessage");
+        assert_eq!(telegram.body, json!({ "chat_id": "-100", "text": "hi" }));
+
+        let discord =
+            build_native_send_request("discord", "channel:42", "hi", "tok", base).unwrap();
+        assert_eq!(discord.url, "https://discord.com/api/v10/channels/42/messages");
+        assert_eq!(discord.authorization.as_deref(), Some("Bot tok"));
+        assert!(build_native_send_request("discord", "user:42", "hi", "tok", base).is_none());
+
+        let slack = build_native_send_request("slack", "channel:C1", "hi", "xoxb", base).unwrap();
+        assert_eq!(slack.body, json!({ "channel": "C1", "text": "hi" }));
+
+        let feishu = build_native_send_request("feishu", "ou_1", "hi", "t-1", base).unwrap();
+        assert!(feishu.url.ends_with("/open-apis/im/v1/messages?receive_id_type=open_id"));
+        assert_eq!(feishu.body["content"], json!("{\"text\":\"hi\"}"));
+
+        let lark = build_native_send_request("feishu", "oc_1", "hi", "t-1", FeishuDomain::Lark);
+        assert!(lark.unwrap().url.starts_with("https://open.larksuite.com/open-apis/im/v1/"));
+        let config = json!({ "channels": { "feishu": { "domain": "Lark" } } });
+        assert_eq!(FeishuDomain::from_config(&config), FeishuDomain::Lark);
+        assert_eq!(FeishuDomain::from_config(&json!({})), FeishuDomain::Feishu);
+
+        assert!(native_send_succeeded("telegram", true, &json!({ "ok": true })));
+        assert!(!native_send_succeeded("slack", true, &json!({ "ok": false })));
+        assert!(native_send_succeeded("feishu", true, &json!({ "code": 0 })));
+        assert!(!native_send_succeeded("discord", false, &json!({ "id": "1" })));
+    }
+
+    #[test]
+    fn feishu_body_escapes_any_message_and_bounds_length() {
+        let message = "say \"hi\" \\ path C:\\tmp\nline2\t\u{1}</script> 🤖";
+        let domain = FeishuDomain::Feishu;
+        let request = build_native_send_request("feishu", "oc_1", message, "t", domain).unwrap();
+        // 请求体序列化后仍是合法 JSON，content 字段本身也是合法 JSON 且还原出原文
+        let wire = serde_json::to_string(&request.body).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&wire).unwrap();
+        let content: serde_json::Value =
+            serde_json::from_str(body["content"].as_str().unwrap()).unwrap();
+        assert_eq!(content, json!({ "text": message }));
+
+        let long = "飞".repeat(MAX_NATIVE_MESSAGE_CHARS + 10);
+        let request = build_native_send_request("feishu", "oc_1", &long, "t", domain).unwrap();
+        let content: serde_json::Value =
+            serde_json::from_str(request.body["content"].as_str().unwrap()).unwrap();
+        let text = content["text"].as_str().unwrap();
+        assert_eq!(text.chars().count(), MAX_NATIVE_MESSAGE_CHARS);
+        assert!(text.ends_with('…'));
+    }
+
+    const DOCTOR_PASS_OUTPUT: &str = "\
+┌  OpenClaw doctor
+│
+◇  Config
+│  ✓ Config: ~/.openclaw/openclaw.json is valid
+│  ✓ Gateway auth: token configured
+│
+◇  Gateway
+│  ✓ Gateway: reachable on 127.0.0.1:18789
+│
+└  Doctor complete.
+";
+
+    const DOCTOR_FAIL_OUTPUT: &str = "\
+┌  OpenClaw doctor
+│
+◇  Config
+│  \u{1b}[32m✓\u{1b}[0m Config: ~/.openclaw/openclaw.json is valid
+│  \u{1b}[33m⚠\u{1b}[0m Sessions: 3 orphaned transcript files
+│
+◇  Gateway
+│  \u{1b}[31m✗\u{1b}[0m Gateway: not reachable on 127.0.0.1:18789
+│  ✗ Channel telegram: botToken missing
+│
+└  Doctor found 2 problems.
+";
+
+    #[test]
+    fn doctor_plain_output_is_parsed_per_check() {
+        let passed = parse_doctor_output(DOCTOR_PASS_OUTPUT).expect("应能解析通过的输出");
+        assert_eq!(passed.len(), 3);
+        assert!(passed.iter().all(|r| r.passed && r.suggestion.is_none()));
+        assert_eq!(passed[1].name, "Doctor: Gateway auth");
+        assert_eq!(passed[1].message, "token configured");
+
+        let failed = parse_doctor_output(DOCTOR_FAIL_OUTPUT).expect("应能解析失败的输出");
+        let statuses: Vec<bool> = failed.iter().map(|r| r.passed).collect();
+        assert_eq!(statuses, vec![true, false, false, false]);
+        assert_eq!(failed[1].message, "警告: 3 orphaned transcript files");
+        assert_eq!(failed[2].name, "Doctor: Gateway");
+        assert_eq!(failed[3].name, "Doctor: Channel telegram");
+        assert!(failed[3].suggestion.is_some());
+    }
+
+    #[test]
+    fn doctor_json_output_is_parsed_per_check() {
+        let output = r#"{
+  "ok": false,
+  "checks": [
+    { "id": "config", "status": "ok", "message": "config valid" },
+    { "id": "gateway", "ok": false, "message": "not reachable", "fix": "openclaw gateway start" },
+    { "id": "sessions", "level": "warn", "message": "orphaned transcripts" }
+  ]
+}"#;
+        let results = parse_doctor_output(output).expect("应能解析 JSON 输出");
+        assert_eq!(results.len(), 3);
+        assert!(results[0].passed);
+        assert!(!results[1].passed);
+        assert_eq!(results[1].suggestion.as_deref(), Some("openclaw gateway start"));
+        assert!(!results[2].passed);
+    }
+
+    #[test]
+    fn unparseable_doctor_output_falls_back_to_single_result() {
+        let results = doctor_results(Ok("Everything looks fine".to_string()));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "OpenClaw Doctor");
+        assert!(results[0].passed);
+
+        let results = doctor_results(Err("config invalid".to_string()));
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].passed);
+
+        // 命令失败但输出可解析时仍按检查项展示，并追加一项失败结果
+        let results = doctor_results(Err(DOCTOR_FAIL_OUTPUT.to_string()));
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[4].name, "OpenClaw Doctor");
+        assert!(!results[4].passed);
+
+        // 检查项全部通过但命令失败时同样不能显示为全部通过
+        let results = doctor_results(Err("✓ Config: valid".to_string()));
+        assert_eq!(results.len(), 2);
+        assert!(results[0].passed);
+        assert!(!results[1].passed);
+        let results = doctor_results(Ok("✓ Config: valid".to_string()));
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn anthropic_provider_probe_uses_messages_api_headers() {
+        let provider = json!({
+            "baseUrl": "https://api.minimax.io/anthropic",
+            "models": [{ "id": "minimax-m2.1", "api": "anthropic-messages" }]
+        });
+        assert_eq!(provider_api_type(&provider), "anthropic-messages");
+
+        let request = build_provider_probe_request(
+            "anthropic-messages",
+            "https://api.minimax.io/anthropic/",
+            Some("sk-test"),
+            Some("minimax-m2.1"),
+        );
+        assert_eq!(request.url, "https://api.minimax.io/anthropic/v1/messages");
+        assert_eq!(
+            request.headers,
+            vec![
+                ("x-api-key".to_string(), "sk-test".to_string()),
+                ("anthropic-version".to_string(), "2023-06-01".to_string()),
+            ]
+        );
+        let body = request.body.expect("对话请求应有请求体");
+        assert_eq!(body["model"], "minimax-m2.1");
+        assert_eq!(body["max_tokens"], 1);
+
+        let request = build_provider_probe_request(
+            "anthropic-messages",
+            "https://api.anthropic.com/v1",
+            None,
+            None,
+        );
+        assert_eq!(request.url, "https://api.anthropic.com/v1/models");
+        assert!(request.body.is_none());
+        assert!(!request.headers.iter().any(|(k, _)| k == "Authorization"));
+    }
+
+    #[test]
+    fn openai_provider_probe_uses_bearer_and_completions_path() {
+        let provider = json!({
+            "baseUrl": "https://api.openai.com/v1",
+            "models": [{ "id": "gpt-4o" }]
+        });
+        assert_eq!(provider_api_type(&provider), "openai-completions");
+
+        let request = build_provider_probe_request(
+            "openai-completions",
+            "https://api.openai.com/v1",
+            Some("sk-test"),
+            Some("gpt-4o"),
+        );
+        assert_eq!(request.url, "https://api.openai.com/v1/chat/completions");
+        assert_eq!(
+            request.headers,
+            vec![("Authorization".to_string(), "Bearer sk-test".to_string())]
+        );
+        assert_eq!(request.body.expect("对话请求应有请求体")["model"], "gpt-4o");
+
+        let request = build_provider_probe_request(
+            "openai-completions",
+            "https://api.deepseek.com",
+            None,
+            None,
+        );
+        assert_eq!(request.url, "https://api.deepseek.com/models");
+        assert!(request.headers.is_empty());
+        assert!(request.body.is_none());
+    }
+}
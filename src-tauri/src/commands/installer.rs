@@ -24,6 +24,21 @@ pub struct EnvironmentStatus {
     pub os: String,
 }
 
+/// Node.js 安装信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeInstall {
+    /// node 可执行文件路径
+    pub path: String,
+    /// 版本号 (如 "v22.12.0")
+    pub version: String,
+    /// 安装来源 (nvm/fnm/volta/asdf/mise/homebrew/scoop/chocolatey/system)
+    pub source: String,
+    /// 是否满足版本要求 (>=22)
+    pub meets_requirement: bool,
+    /// 是否为用户选择的 Node.js
+    pub preferred: bool,
+}
+
 /// 安装进度
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallProgress {
@@ -33,6 +48,40 @@ pub struct InstallProgress {
     pub error: Option<String>,
 }
 
+/// OpenClaw 的安装方式，决定更新与卸载使用的工具
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallMethod {
+    Npm,
+    Brew,
+}
+
+/// Manager 记录的安装状态（~/.openclaw/manager-install.json）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallState {
+    method: InstallMethod,
+    /// npm 安装时 openclaw 所在的 Node.js 来源（nvm/fnm/volta/asdf/mise/homebrew/system 等）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    node_source: Option<String>,
+    /// 安装在 Node 版本管理器下时对应的 npm 路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    npm_path: Option<String>,
+}
+
+/// 更新与卸载使用的工具
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PackageTool {
+    Brew,
+    /// PATH 中的 npm
+    Npm,
+    /// Node 版本管理器下的 npm（完整路径）
+    ScopedNpm(String),
+}
+
+/// 全局包与 node 版本绑定的 Node 版本管理器
+const VERSION_MANAGER_SOURCES: &[&str] = &["nvm", "fnm", "volta", "asdf", "mise"];
+
 /// 安装结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallResult {
@@ -59,7 +108,7 @@ pub async fn check_environment() -> Result<EnvironmentStatus, String> {
     
     // 检查 OpenClaw
     info!("[环境检查] 检查 OpenClaw...");
-    let openclaw_version = get_openclaw_version();
+    let openclaw_version = get_openclaw_version().await;
     let openclaw_installed = openclaw_version.is_some();
     info!("[环境检查] OpenClaw: installed={}, version={:?}", 
         openclaw_installed, openclaw_version);
@@ -87,6 +136,15 @@ pub async fn check_environment() -> Result<EnvironmentStatus, String> {
 /// 获取 Node.js 版本
 /// 检测多个可能的安装路径，因为 GUI 应用不继承用户 shell 的 PATH
 fn get_node_version() -> Option<String> {
+    // 用户选择的 Node.js 优先
+    if let Some(path) = platform::get_preferred_node_path() {
+        if let Some(version) = probe_node_version(&path) {
+            info!("[环境检查] 使用已选择的 Node.js {}: {}", path, version);
+            return Some(version);
+        }
+        warn!("[环境检查] 已选择的 Node.js 无法执行: {}", path);
+    }
+    
     if platform::is_windows() {
         // Windows: 先尝试直接调用（如果 PATH 已更新）
         if let Ok(v) = shell::run_cmd_output("node --version") {
@@ -260,10 +318,189 @@ fn get_windows_node_paths() -> Vec<String> {
     paths
 }
 
+/// 执行指定路径的 node --version，返回版本号（直接启动进程，不经过 shell）
+fn probe_node_version(path: &str) -> Option<String> {
+    shell::run_command_output(path, &["--version"])
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| v.starts_with('v'))
+}
+
+/// 校验用户选择的 Node.js 路径：必须是名为 node/node.exe 的文件，返回规范化后的绝对路径
+fn validate_node_binary_path(path: &str) -> Result<String, String> {
+    let canonical = std::fs::canonicalize(path)
+        .map_err(|e| format!("Node.js 路径无效: {} ({})", path, e))?;
+    if !canonical.is_file() {
+        return Err(format!("Node.js 路径不是文件: {}", path));
+    }
+    let file_name = canonical
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let is_node = if platform::is_windows() {
+        file_name.eq_ignore_ascii_case("node.exe") || file_name.eq_ignore_ascii_case("node")
+    } else {
+        file_name == "node"
+    };
+    if !is_node {
+        return Err(format!("所选文件不是 Node.js 可执行文件: {}", file_name));
+    }
+    let canonical = canonical.display().to_string();
+    // Windows 下 canonicalize 会返回 \\?\ 前缀的扩展路径，去掉以便写入 PATH
+    Ok(canonical
+        .strip_prefix(r"\\?\")
+        .map(str::to_string)
+        .unwrap_or(canonical))
+}
+
+/// 根据路径判断 Node.js 的安装来源
+fn classify_node_source(path: &str) -> &'static str {
+    let normalized = path.replace('\\', "/").to_lowercase();
+    let rules: &[(&[&str], &str)] = &[
+        (&["/.nvm/", "/nvm/", "nvm4w"], "nvm"),
+        (&["/.fnm/", "/fnm/"], "fnm"),
+        (&["/.volta/", "/volta/"], "volta"),
+        (&["/.asdf/"], "asdf"),
+        (&["/mise/"], "mise"),
+        (&["/homebrew/", "/cellar/", "/linuxbrew/"], "homebrew"),
+        (&["/scoop/"], "scoop"),
+        (&["/chocolatey/"], "chocolatey"),
+    ];
+    rules
+        .iter()
+        .find(|(patterns, _)| patterns.iter().any(|p| normalized.contains(p)))
+        .map(|(_, source)| *source)
+        .unwrap_or("system")
+}
+
+/// 去除重复的 Node.js 安装：解析后的真实路径相同，或来源与版本都相同（如同一版本的多个 shim）
+/// 输入为 (真实路径, 安装信息)，保留首次出现的条目
+fn dedupe_node_installs(candidates: Vec<(String, NodeInstall)>) -> Vec<NodeInstall> {
+    let mut seen_paths = std::collections::HashSet::new();
+    let mut seen_versions = std::collections::HashSet::new();
+    let mut installs = Vec::new();
+    for (real_path, install) in candidates {
+        if !seen_paths.insert(real_path) {
+            continue;
+        }
+        if !seen_versions.insert((install.source.clone(), install.version.clone())) {
+            continue;
+        }
+        installs.push(install);
+    }
+    installs
+}
+
+/// 收集所有可能的 node 可执行文件路径（PATH 中的全部 node + 常见安装路径 + nvm 已安装版本）
+fn collect_node_candidates() -> Vec<String> {
+    let mut candidates = Vec::new();
+    
+    if let Some(preferred) = platform::get_preferred_node_path() {
+        candidates.push(preferred);
+    }
+    
+    let path_lookup = if platform::is_windows() {
+        shell::run_cmd_output("where node")
+    } else {
+        shell::run_bash_output("which -a node 2>/dev/null")
+    };
+    if let Ok(output) = path_lookup {
+        candidates.extend(output.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()));
+    }
+    
+    if platform::is_windows() {
+        candidates.extend(get_windows_node_paths());
+    } else {
+        candidates.extend(get_unix_node_paths());
+        
+        // nvm 已安装的全部版本
+        if let Some(home) = dirs::home_dir() {
+            let versions_dir = home.join(".nvm").join("versions").join("node");
+            if let Ok(entries) = std::fs::read_dir(versions_dir) {
+                let mut nvm_nodes: Vec<String> = entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path().join("bin").join("node").display().to_string())
+                    .collect();
+                nvm_nodes.sort();
+                candidates.extend(nvm_nodes);
+            }
+        }
+    }
+    
+    candidates
+}
+
+/// 列出本机所有 Node.js 安装
+#[command]
+pub async fn list_node_installations() -> Result<Vec<NodeInstall>, String> {
+    info!("[环境检查] 扫描 Node.js 安装...");
+    let preferred = platform::get_preferred_node_path();
+    
+    let mut probed = Vec::new();
+    for path in collect_node_candidates() {
+        if !std::path::Path::new(&path).is_file() {
+            continue;
+        }
+        let real_path = std::fs::canonicalize(&path)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| path.clone());
+        // 真实路径已探测过则跳过，避免重复执行 node
+        if probed.iter().any(|(seen, _): &(String, NodeInstall)| *seen == real_path) {
+            continue;
+        }
+        let Some(version) = probe_node_version(&path) else {
+            continue;
+        };
+        debug!("[环境检查] 发现 Node.js: {} ({})", path, version);
+        probed.push((
+            real_path.clone(),
+            NodeInstall {
+                source: classify_node_source(&real_path).to_string(),
+                meets_requirement: check_node_version_requirement(&Some(version.clone())),
+                preferred: preferred.as_deref() == Some(path.as_str()),
+                path,
+                version,
+            },
+        ));
+    }
+    
+    let installs = dedupe_node_installs(probed);
+    info!("[环境检查] 共发现 {} 个 Node.js 安装", installs.len());
+    Ok(installs)
+}
+
+/// 选择安装/服务命令使用的 Node.js（传入 None 恢复自动检测）
+#[command]
+pub async fn set_preferred_node_path(path: Option<String>) -> Result<String, String> {
+    let marker_path = platform::get_preferred_node_marker_path();
+    
+    let Some(path) = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) else {
+        if std::path::Path::new(&marker_path).exists() {
+            std::fs::remove_file(&marker_path).map_err(|e| format!("清除 Node.js 选择失败: {}", e))?;
+        }
+        info!("[环境检查] 已恢复自动检测 Node.js");
+        return Ok("已恢复自动检测 Node.js".to_string());
+    };
+    
+    let path = validate_node_binary_path(&path)?;
+    let version = probe_node_version(&path).ok_or_else(|| format!("无法执行 Node.js: {}", path))?;
+    if !check_node_version_requirement(&Some(version.clone())) {
+        return Err(format!("Node.js 版本过低: {}，需要 22 或更高版本", version));
+    }
+    
+    if let Some(parent) = std::path::Path::new(&marker_path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    std::fs::write(&marker_path, &path).map_err(|e| format!("保存 Node.js 选择失败: {}", e))?;
+    info!("[环境检查] ✓ 已选择 Node.js {} ({})", path, version);
+    Ok(format!("已选择 Node.js {} ({})", version, path))
+}
+
 /// 获取 OpenClaw 版本
-fn get_openclaw_version() -> Option<String> {
-    // 使用 run_openclaw 统一处理各平台
-    shell::run_openclaw(&["--version"])
+async fn get_openclaw_version() -> Option<String> {
+    // 使用 run_openclaw_async 统一处理各平台，并避免 openclaw 卡住时阻塞
+    shell::run_openclaw_async(&["--version"], std::time::Duration::from_secs(15))
+        .await
         .ok()
         .map(|v| v.trim().to_string())
 }
@@ -290,6 +527,15 @@ pub async fn install_nodejs() -> Result<InstallResult, String> {
     let os = platform::get_os();
     info!("[安装Node.js] 检测到操作系统: {}", os);
     
+    if let Some(result) = install_disk_preflight() {
+        return Ok(result);
+    }
+    if os == "windows" {
+        if let Some(result) = registry_preflight(NODEJS_DIST_URL, "nodejs.org").await {
+            return Ok(result);
+        }
+    }
+    
     let result = match os.as_str() {
         "windows" => {
             info!("[安装Node.js] 使用 Windows 安装方式...");
@@ -371,13 +617,18 @@ if ($nodeVersion) {
 }
 "#;
     
-    match shell::run_powershell_output(script) {
+    let (result, attempts) =
+        run_with_network_retry("安装Node.js", || shell::run_powershell_output(script)).await;
+    match result {
         Ok(output) => {
             // 验证安装
             if get_node_version().is_some() {
                 Ok(InstallResult {
                     success: true,
-                    message: "Node.js 安装成功！请重启应用以使环境变量生效。".to_string(),
+                    message: with_attempts(
+                        "Node.js 安装成功！请重启应用以使环境变量生效。".to_string(),
+                        attempts,
+                    ),
                     error: None,
                 })
             } else {
@@ -390,7 +641,7 @@ if ($nodeVersion) {
         }
         Err(e) => Ok(InstallResult {
             success: false,
-            message: "Node.js 安装失败".to_string(),
+            message: with_attempts("Node.js 安装失败".to_string(), attempts),
             error: Some(e),
         }),
     }
@@ -421,15 +672,17 @@ brew link --overwrite node@22
 node --version
 "#;
     
-    match shell::run_bash_output(script) {
+    let (result, attempts) =
+        run_with_network_retry("安装Node.js", || shell::run_bash_output(script)).await;
+    match result {
         Ok(output) => Ok(InstallResult {
             success: true,
-            message: format!("Node.js 安装成功！{}", output),
+            message: with_attempts(format!("Node.js 安装成功！{}", output), attempts),
             error: None,
         }),
         Err(e) => Ok(InstallResult {
             success: false,
-            message: "Node.js 安装失败".to_string(),
+            message: with_attempts("Node.js 安装失败".to_string(), attempts),
             error: Some(e),
         }),
     }
@@ -464,32 +717,47 @@ fi
 node --version
 "#;
     
-    match shell::run_bash_output(script) {
+    let (result, attempts) =
+        run_with_network_retry("安装Node.js", || shell::run_bash_output(script)).await;
+    match result {
         Ok(output) => Ok(InstallResult {
             success: true,
-            message: format!("Node.js 安装成功！{}", output),
+            message: with_attempts(format!("Node.js 安装成功！{}", output), attempts),
             error: None,
         }),
         Err(e) => Ok(InstallResult {
             success: false,
-            message: "Node.js 安装失败".to_string(),
+            message: with_attempts("Node.js 安装失败".to_string(), attempts),
             error: Some(e),
         }),
     }
 }
 
 /// 安装 OpenClaw
+/// prefer_brew=true 且 macOS 上有可用的 Homebrew formula 时使用 brew 安装，否则使用 npm
 #[command]
-pub async fn install_openclaw() -> Result<InstallResult, String> {
+pub async fn install_openclaw(prefer_brew: Option<bool>) -> Result<InstallResult, String> {
     info!("[安装OpenClaw] 开始安装 OpenClaw...");
     let os = platform::get_os();
     info!("[安装OpenClaw] 检测到操作系统: {}", os);
     
+    if let Some(result) = install_disk_preflight() {
+        return Ok(result);
+    }
+    let use_brew = os == "macos" && prefer_brew.unwrap_or(false) && brew_formula_available();
+    if !use_brew {
+        let registry = configured_npm_registry();
+        if let Some(result) = registry_preflight(&registry, "npm registry").await {
+            return Ok(result);
+        }
+    }
+    
     let result = match os.as_str() {
         "windows" => {
             info!("[安装OpenClaw] 使用 Windows 安装方式...");
             install_openclaw_windows().await
         },
+        "macos" => install_openclaw_macos(use_brew).await,
         _ => {
             info!("[安装OpenClaw] 使用 Unix 安装方式 (npm)...");
             install_openclaw_unix().await
@@ -497,7 +765,12 @@ pub async fn install_openclaw() -> Result<InstallResult, String> {
     };
     
     match &result {
-        Ok(r) if r.success => info!("[安装OpenClaw] ✓ 安装成功"),
+        Ok(r) if r.success => {
+            info!("[安装OpenClaw] ✓ 安装成功");
+            let method = if use_brew { InstallMethod::Brew } else { InstallMethod::Npm };
+            let state = install_state_for(method, resolved_openclaw_path().as_deref());
+            record_install_state(Some(&state));
+        }
         Ok(r) => warn!("[安装OpenClaw] ✗ 安装失败: {}", r.message),
         Err(e) => error!("[安装OpenClaw] ✗ 安装错误: {}", e),
     }
@@ -505,6 +778,129 @@ pub async fn install_openclaw() -> Result<InstallResult, String> {
     result
 }
 
+/// macOS 安装 OpenClaw：use_brew 时使用 Homebrew，否则与其他 Unix 系统一样使用 npm
+async fn install_openclaw_macos(use_brew: bool) -> Result<InstallResult, String> {
+    if !use_brew {
+        info!("[安装OpenClaw] 使用 Unix 安装方式 (npm)...");
+        return install_openclaw_unix().await;
+    }
+
+    info!("[安装OpenClaw] 使用 Homebrew 安装...");
+    match shell::run_command_output("brew", &["install", "openclaw"]) {
+        Ok(output) => Ok(InstallResult {
+            success: true,
+            message: format!("OpenClaw 已通过 Homebrew 安装！{}", output),
+            error: None,
+        }),
+        Err(e) => Ok(InstallResult {
+            success: false,
+            message: "Homebrew 安装 OpenClaw 失败".to_string(),
+            error: Some(e),
+        }),
+    }
+}
+
+/// 是否可以通过 Homebrew 安装 openclaw（仅 macOS，需已安装 brew 且存在 openclaw formula）
+fn brew_formula_available() -> bool {
+    platform::is_macos()
+        && shell::command_exists("brew")
+        && shell::run_command_output("brew", &["info", "--formula", "openclaw"]).is_ok()
+}
+
+/// openclaw 可执行文件解析符号链接后的真实路径
+/// /opt/homebrew/bin/openclaw 可能是 brew formula 或 brew 安装的 node 下 npm 全局包的链接，需解析后判断
+fn resolved_openclaw_path() -> Option<String> {
+    shell::get_openclaw_path().map(|path| {
+        std::fs::canonicalize(&path)
+            .map(|p| p.display().to_string())
+            .unwrap_or(path)
+    })
+}
+
+/// 由 npm 全局包的真实路径推断所属的 npm：<prefix>/lib/node_modules/openclaw → <prefix>/bin/npm
+fn npm_for_openclaw_path(resolved_path: &str) -> Option<String> {
+    let normalized = resolved_path.replace('\\', "/");
+    let index = normalized.find("/lib/node_modules/openclaw")?;
+    Some(format!("{}/bin/npm", &normalized[..index]))
+}
+
+/// 生成安装状态：npm 安装时附带 openclaw 所在的 Node.js 来源，位于版本管理器下时记录对应的 npm
+fn install_state_for(method: InstallMethod, resolved_path: Option<&str>) -> InstallState {
+    let mut state = InstallState { method, node_source: None, npm_path: None };
+    if let (InstallMethod::Npm, Some(path)) = (method, resolved_path) {
+        let source = classify_node_source(path);
+        state.node_source = Some(source.to_string());
+        if VERSION_MANAGER_SOURCES.contains(&source) {
+            state.npm_path = npm_for_openclaw_path(path);
+        }
+    }
+    state
+}
+
+/// 选择更新与卸载使用的工具
+/// 有安装记录时以记录为准；否则按 openclaw 真实路径推断（位于 Homebrew Cellar 内视为 brew），未知时默认 npm
+/// 记录的 npm 已不存在（如对应的 node 版本已被删除）时退回 PATH 中的 npm
+fn select_package_tool(
+    state: Option<&InstallState>,
+    resolved_path: Option<&str>,
+    npm_exists: impl Fn(&str) -> bool,
+) -> PackageTool {
+    let state = match state {
+        Some(state) => state.clone(),
+        None => {
+            let method = match resolved_path {
+                Some(path) if path.contains("/Cellar/") => InstallMethod::Brew,
+                _ => InstallMethod::Npm,
+            };
+            install_state_for(method, resolved_path)
+        }
+    };
+    match (state.method, state.npm_path) {
+        (InstallMethod::Brew, _) => PackageTool::Brew,
+        (InstallMethod::Npm, Some(npm)) if npm_exists(&npm) => PackageTool::ScopedNpm(npm),
+        (InstallMethod::Npm, _) => PackageTool::Npm,
+    }
+}
+
+/// 检测更新与卸载当前 OpenClaw 应使用的工具
+fn detect_package_tool() -> PackageTool {
+    let state = std::fs::read_to_string(platform::get_install_state_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<InstallState>(&content).ok());
+    let tool = select_package_tool(state.as_ref(), resolved_openclaw_path().as_deref(), |npm| {
+        std::path::Path::new(npm).exists()
+    });
+    info!("[安装OpenClaw] 安装记录: {:?}，使用: {:?}", state, tool);
+    tool
+}
+
+/// 记录安装状态（None 表示清除记录）
+fn record_install_state(state: Option<&InstallState>) {
+    let state_path = platform::get_install_state_path();
+    let result = match state {
+        Some(state) => serde_json::to_string_pretty(state)
+            .map_err(std::io::Error::other)
+            .and_then(|content| crate::utils::file::write_file(&state_path, &content)),
+        None => match std::fs::remove_file(&state_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    };
+    if let Err(e) = result {
+        warn!("[安装OpenClaw] 记录安装方式失败: {}", e);
+    }
+}
+
+/// 版本管理器下的 npm 需要把其 bin 目录放在 PATH 最前，保证 npm 与 openclaw 使用同一个 node
+fn scoped_npm_path_export(npm: Option<&str>) -> String {
+    npm.and_then(|npm| std::path::Path::new(npm).parent())
+        .map(|dir| {
+            let dir = shell::shell_quote(&dir.display().to_string());
+            format!("export PATH={}:\"$PATH\"\n", dir)
+        })
+        .unwrap_or_default()
+}
+
 /// Windows 安装 OpenClaw
 async fn install_openclaw_windows() -> Result<InstallResult, String> {
     let script = r#"
@@ -531,12 +927,14 @@ if ($openclawVersion) {
 }
 "#;
     
-    match shell::run_powershell_output(script) {
+    let (result, attempts) =
+        run_with_network_retry("安装OpenClaw", || shell::run_powershell_output(script)).await;
+    match result {
         Ok(output) => {
-            if get_openclaw_version().is_some() {
+            if get_openclaw_version().await.is_some() {
                 Ok(InstallResult {
                     success: true,
-                    message: "OpenClaw 安装成功！".to_string(),
+                    message: with_attempts("OpenClaw 安装成功！".to_string(), attempts),
                     error: None,
                 })
             } else {
@@ -549,7 +947,7 @@ if ($openclawVersion) {
         }
         Err(e) => Ok(InstallResult {
             success: false,
-            message: "OpenClaw 安装失败".to_string(),
+            message: with_attempts("OpenClaw 安装失败".to_string(), attempts),
             error: Some(e),
         }),
     }
@@ -571,15 +969,17 @@ npm install -g openclaw@latest --unsafe-perm
 openclaw --version
 "#;
     
-    match shell::run_bash_output(script) {
+    let (result, attempts) =
+        run_with_network_retry("安装OpenClaw", || shell::run_bash_output(script)).await;
+    match result {
         Ok(output) => Ok(InstallResult {
             success: true,
-            message: format!("OpenClaw 安装成功！{}", output),
+            message: with_attempts(format!("OpenClaw 安装成功！{}", output), attempts),
             error: None,
         }),
         Err(e) => Ok(InstallResult {
             success: false,
-            message: "OpenClaw 安装失败".to_string(),
+            message: with_attempts("OpenClaw 安装失败".to_string(), attempts),
             error: Some(e),
         }),
     }
@@ -634,6 +1034,14 @@ pub async fn init_openclaw_config() -> Result<InstallResult, String> {
                 info!("[初始化配置] ✓ 权限设置成功");
             }
         }
+        // env 文件存放 API Key，权限收紧为 600
+        let env_path = platform::get_env_file_path();
+        if std::path::Path::new(&env_path).exists() {
+            let perms = std::fs::Permissions::from_mode(0o600);
+            if let Err(e) = std::fs::set_permissions(&env_path, perms) {
+                warn!("[初始化配置] 设置 env 文件权限失败: {}", e);
+            }
+        }
     }
     
     // 设置 gateway mode 为 local
@@ -875,7 +1283,7 @@ read -p "按回车键关闭..."
 
 /// 卸载 OpenClaw
 #[command]
-pub async fn uninstall_openclaw() -> Result<InstallResult, String> {
+pub async fn uninstall_openclaw(purge_config: Option<bool>) -> Result<InstallResult, String> {
     info!("[卸载OpenClaw] 开始卸载 OpenClaw...");
     let os = platform::get_os();
     info!("[卸载OpenClaw] 检测到操作系统: {}", os);
@@ -890,19 +1298,140 @@ pub async fn uninstall_openclaw() -> Result<InstallResult, String> {
             info!("[卸载OpenClaw] 使用 Windows 卸载方式...");
             uninstall_openclaw_windows().await
         },
-        _ => {
-            info!("[卸载OpenClaw] 使用 Unix 卸载方式 (npm)...");
-            uninstall_openclaw_unix().await
+        _ => match detect_package_tool() {
+            PackageTool::Brew => {
+                info!("[卸载OpenClaw] 使用 Homebrew 卸载...");
+                uninstall_openclaw_brew().await
+            }
+            PackageTool::Npm => {
+                info!("[卸载OpenClaw] 使用 Unix 卸载方式 (npm)...");
+                uninstall_openclaw_unix(None).await
+            }
+            PackageTool::ScopedNpm(npm) => {
+                info!("[卸载OpenClaw] 使用版本管理器下的 npm 卸载: {}", npm);
+                uninstall_openclaw_unix(Some(&npm)).await
+            }
         },
     };
     
     match &result {
-        Ok(r) if r.success => info!("[卸载OpenClaw] ✓ 卸载成功"),
+        Ok(r) if r.success => {
+            info!("[卸载OpenClaw] ✓ 卸载成功");
+            record_install_state(None);
+        }
         Ok(r) => warn!("[卸载OpenClaw] ✗ 卸载失败: {}", r.message),
         Err(e) => error!("[卸载OpenClaw] ✗ 卸载错误: {}", e),
     }
     
-    result
+    let config_dir = std::path::PathBuf::from(platform::get_config_dir());
+    // 备份放在配置目录之外，清空配置目录后仍可恢复
+    let backup_dir = config_dir
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(std::env::temp_dir);
+    result.map(|r| finish_uninstall(r, purge_config.unwrap_or(false), &config_dir, &backup_dir))
+}
+
+/// 清理配置目录的结果
+struct PurgeReport {
+    /// 备份文件路径
+    backup_path: String,
+    /// 已删除的条目（相对配置目录）
+    removed: Vec<String>,
+}
+
+/// 将整个配置目录打包为 tar 备份后清空目录内容
+fn purge_config_dir(
+    config_dir: &std::path::Path,
+    backup_dir: &std::path::Path,
+) -> Result<PurgeReport, String> {
+    std::fs::create_dir_all(backup_dir).map_err(|e| format!("创建备份目录失败: {}", e))?;
+    let backup_path = backup_dir.join(format!(
+        "openclaw-uninstall-backup-{}.tar",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    
+    // 备份包含 API Key 与凭证，仅允许当前用户读取
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let backup_file =
+        options.open(&backup_path).map_err(|e| format!("创建备份文件失败: {}", e))?;
+    let mut builder = tar::Builder::new(backup_file);
+    builder.follow_symlinks(false);
+    builder
+        .append_dir_all(".openclaw", config_dir)
+        .and_then(|_| builder.finish())
+        .map_err(|e| format!("备份配置目录失败: {}", e))?;
+    info!("[卸载OpenClaw] 配置目录已备份到: {}", backup_path.display());
+    
+    let mut entries: Vec<std::path::PathBuf> = std::fs::read_dir(config_dir)
+        .map_err(|e| format!("读取配置目录失败: {}", e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    
+    let mut removed = Vec::new();
+    for path in entries {
+        let result = if path.is_dir() && !path.is_symlink() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        result.map_err(|e| format!("删除 {} 失败: {}", path.display(), e))?;
+        if let Some(name) = path.file_name() {
+            removed.push(name.to_string_lossy().to_string());
+        }
+    }
+    
+    Ok(PurgeReport {
+        backup_path: backup_path.display().to_string(),
+        removed,
+    })
+}
+
+/// 卸载完成后的处理：仅在卸载成功且 purge=true 时备份并清空配置目录
+fn finish_uninstall(
+    result: InstallResult,
+    purge: bool,
+    config_dir: &std::path::Path,
+    backup_dir: &std::path::Path,
+) -> InstallResult {
+    if !purge || !result.success {
+        return result;
+    }
+    if !config_dir.is_dir() {
+        info!("[卸载OpenClaw] 配置目录不存在，无需清理");
+        return result;
+    }
+    
+    info!("[卸载OpenClaw] 清理配置目录: {}", config_dir.display());
+    match purge_config_dir(config_dir, backup_dir) {
+        Ok(report) => {
+            info!("[卸载OpenClaw] ✓ 已删除: {:?}", report.removed);
+            InstallResult {
+                success: true,
+                message: format!(
+                    "OpenClaw 已卸载，配置已清除（{}），备份: {}",
+                    report.removed.join(", "),
+                    report.backup_path
+                ),
+                error: None,
+            }
+        }
+        Err(e) => {
+            error!("[卸载OpenClaw] ✗ 清理配置目录失败: {}", e);
+            InstallResult {
+                success: false,
+                message: "OpenClaw 已卸载，但清理配置目录失败".to_string(),
+                error: Some(e),
+            }
+        }
+    }
 }
 
 /// Windows 卸载 OpenClaw
@@ -916,7 +1445,7 @@ async fn uninstall_openclaw_windows() -> Result<InstallResult, String> {
             
             // 验证卸载是否成功
             std::thread::sleep(std::time::Duration::from_millis(500));
-            if get_openclaw_version().is_none() {
+            if get_openclaw_version().await.is_none() {
                 Ok(InstallResult {
                     success: true,
                     message: "OpenClaw 已成功卸载！".to_string(),
@@ -941,9 +1470,25 @@ async fn uninstall_openclaw_windows() -> Result<InstallResult, String> {
     }
 }
 
-/// Unix 系统卸载 OpenClaw
-async fn uninstall_openclaw_unix() -> Result<InstallResult, String> {
-    let script = r#"
+/// 通过 Homebrew 卸载 OpenClaw
+async fn uninstall_openclaw_brew() -> Result<InstallResult, String> {
+    match shell::run_command_output("brew", &["uninstall", "openclaw"]) {
+        Ok(output) => Ok(InstallResult {
+            success: true,
+            message: format!("OpenClaw 已通过 Homebrew 卸载！{}", output),
+            error: None,
+        }),
+        Err(e) => Ok(InstallResult {
+            success: false,
+            message: "Homebrew 卸载 OpenClaw 失败".to_string(),
+            error: Some(e),
+        }),
+    }
+}
+
+/// Unix 系统卸载 OpenClaw（npm 为版本管理器下的 npm 路径时使用该 npm）
+async fn uninstall_openclaw_unix(npm: Option<&str>) -> Result<InstallResult, String> {
+    let script = scoped_npm_path_export(npm) + r#"
 echo "卸载 OpenClaw..."
 npm uninstall -g openclaw
 
@@ -957,7 +1502,7 @@ else
 fi
 "#;
     
-    match shell::run_bash_output(script) {
+    match shell::run_bash_output(&script) {
         Ok(output) => Ok(InstallResult {
             success: true,
             message: format!("OpenClaw 已成功卸载！{}", output),
@@ -990,7 +1535,7 @@ pub async fn check_openclaw_update() -> Result<UpdateInfo, String> {
     info!("[版本检查] 开始检查 OpenClaw 更新...");
     
     // 获取当前版本
-    let current_version = get_openclaw_version();
+    let current_version = get_openclaw_version().await;
     info!("[版本检查] 当前版本: {:?}", current_version);
     
     if current_version.is_none() {
@@ -1004,7 +1549,7 @@ pub async fn check_openclaw_update() -> Result<UpdateInfo, String> {
     }
     
     // 获取最新版本
-    let latest_version = get_latest_openclaw_version();
+    let latest_version = get_latest_openclaw_version().await;
     info!("[版本检查] 最新版本: {:?}", latest_version);
     
     if latest_version.is_none() {
@@ -1031,26 +1576,361 @@ pub async fn check_openclaw_update() -> Result<UpdateInfo, String> {
     })
 }
 
-/// 获取 npm registry 上的最新版本
-fn get_latest_openclaw_version() -> Option<String> {
-    // 使用 npm view 获取最新版本
-    let result = if platform::is_windows() {
-        shell::run_cmd_output("npm view openclaw version")
+/// 安装所需的最小可用磁盘空间（500MB）
+const MIN_INSTALL_FREE_BYTES: u64 = 500 * 1024 * 1024;
+
+/// 解析 `df -Pk <path>` 输出中的可用空间（字节）
+fn parse_df_available_bytes(output: &str) -> Option<u64> {
+    // Filesystem 1024-blocks Used Available Capacity Mounted on
+    output
+        .lines()
+        .nth(1)?
+        .split_whitespace()
+        .nth(3)?
+        .parse::<u64>()
+        .ok()
+        .map(|kb| kb * 1024)
+}
+
+/// 获取安装目标所在磁盘的可用空间（字节），无法获取时返回 None
+fn get_available_disk_bytes(path: &str) -> Option<u64> {
+    if platform::is_windows() {
+        let script = format!(
+            "([System.IO.DriveInfo]::new([System.IO.Path]::GetPathRoot('{}'))).AvailableFreeSpace",
+            path.replace('\'', "''")
+        );
+        shell::run_powershell_output(&script)
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
     } else {
-        shell::run_bash_output("npm view openclaw version 2>/dev/null")
+        shell::run_command_output("df", &["-Pk", path])
+            .ok()
+            .and_then(|output| parse_df_available_bytes(&output))
+    }
+}
+
+/// 根据可用空间判断是否允许安装，空间不足时返回提前结束的安装结果
+/// 无法获取可用空间时不阻止安装
+fn disk_space_check(available: Option<u64>, min_bytes: u64) -> Option<InstallResult> {
+    let available = available?;
+    if available >= min_bytes {
+        return None;
+    }
+    let to_mb = |bytes: u64| bytes / 1024 / 1024;
+    Some(InstallResult {
+        success: false,
+        message: format!(
+            "磁盘空间不足：可用 {}MB，至少需要 {}MB",
+            to_mb(available),
+            to_mb(min_bytes)
+        ),
+        error: Some("请清理磁盘空间后重试".to_string()),
+    })
+}
+
+/// 安装前检查用户目录所在磁盘的可用空间
+fn install_disk_preflight() -> Option<InstallResult> {
+    let target = dirs::home_dir()
+        .map(|home| home.display().to_string())
+        .unwrap_or_else(platform::get_config_dir);
+    let available = get_available_disk_bytes(&target);
+    info!("[安装检查] {} 可用空间: {:?} 字节", target, available);
+    disk_space_check(available, MIN_INSTALL_FREE_BYTES)
+}
+
+/// 默认 npm registry 地址
+const DEFAULT_NPM_REGISTRY: &str = "https://registry.npmjs.org";
+
+/// Node.js 官方下载站（winget 安装失败时的回退来源）
+const NODEJS_DIST_URL: &str = "https://nodejs.org/dist/";
+
+/// 安装前连通性检查的超时时间（秒）
+const REGISTRY_PREFLIGHT_TIMEOUT_SECS: u64 = 5;
+
+/// 规范化 registry 地址，空值时使用默认 registry
+fn npm_registry_base(registry_override: Option<&str>) -> String {
+    registry_override
+        .map(|r| r.trim().trim_end_matches('/'))
+        .filter(|r| !r.is_empty())
+        .unwrap_or(DEFAULT_NPM_REGISTRY)
+        .to_string()
+}
+
+/// 读取当前生效的 npm registry（OPENCLAW_NPM_REGISTRY 优先，其次为 npm 自身的 registry 环境变量）
+fn configured_npm_registry() -> String {
+    let registry = ["OPENCLAW_NPM_REGISTRY", "npm_config_registry", "NPM_CONFIG_REGISTRY"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|v| !v.trim().is_empty()));
+    npm_registry_base(registry.as_deref())
+}
+
+/// 构造查询 openclaw 最新版本的 registry URL，支持 OPENCLAW_NPM_REGISTRY 镜像覆盖
+fn npm_registry_latest_url(registry_override: Option<&str>) -> String {
+    format!("{}/openclaw/latest", npm_registry_base(registry_override))
+}
+
+/// npm 安装/更新的默认尝试次数（可通过 OPENCLAW_NPM_RETRY_ATTEMPTS 覆盖）
+const DEFAULT_NPM_RETRY_ATTEMPTS: u32 = 3;
+
+/// 重试的初始等待时间，之后每次翻倍
+const NPM_RETRY_BASE_DELAY_MS: u64 = 2000;
+
+/// 查询最新版本时的重试初始等待时间（界面等待结果，间隔较短）
+const VERSION_CHECK_RETRY_BASE_DELAY_MS: u64 = 500;
+
+/// 认证/权限类错误：重试不会成功
+const PERMANENT_ERROR_MARKERS: &[&str] = &[
+    "eacces",
+    "eperm",
+    "e401",
+    "e403",
+    "eneedauth",
+    "eotp",
+    "permission denied",
+    "unauthorized",
+    "forbidden",
+];
+
+/// 网络抖动或 registry 服务端错误：稍后重试可能成功（npm、curl 与 HTTP 客户端的错误输出）
+const TRANSIENT_ERROR_MARKERS: &[&str] = &[
+    "etimedout",
+    "esockettimedout",
+    "econnreset",
+    "econnrefused",
+    "eai_again",
+    "socket hang up",
+    "network timeout",
+    "e500",
+    "e502",
+    "e503",
+    "e504",
+    "500 internal server error",
+    "502 bad gateway",
+    "503 service unavailable",
+    "504 gateway timeout",
+    "could not resolve host",
+    "temporary failure in name resolution",
+    "failed to connect",
+    "connection timed out",
+    "operation timed out",
+    "error sending request",
+];
+
+/// 按 stderr 中的已知字符串判断是否为可重试的临时网络错误；认证/权限错误优先判定为不可重试
+fn is_transient_network_error(output: &str) -> bool {
+    let lower = output.to_lowercase();
+    if PERMANENT_ERROR_MARKERS.iter().any(|m| lower.contains(m)) {
+        return false;
+    }
+    TRANSIENT_ERROR_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+/// 读取 npm 安装/更新的最大尝试次数（至少 1 次）
+fn npm_retry_attempts() -> u32 {
+    std::env::var("OPENCLAW_NPM_RETRY_ATTEMPTS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_NPM_RETRY_ATTEMPTS)
+}
+
+/// 第 attempt 次失败后的等待时间（指数退避）
+fn backoff_delay(base_delay: std::time::Duration, attempt: u32) -> std::time::Duration {
+    base_delay * (1u32 << (attempt - 1).min(6))
+}
+
+/// 执行 f，遇到临时网络错误时按指数退避重试，最多尝试 attempts 次；权限等其他错误立即返回
+/// 返回最后一次的结果与实际尝试次数；最终因网络错误失败且配置了代理时在错误中注明代理
+async fn retry_with_backoff<T, F, Fut>(
+    label: &str,
+    attempts: u32,
+    base_delay: std::time::Duration,
+    mut f: F,
+) -> (Result<T, String>, u32)
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, String>>,
+{
+    let max_attempts = attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        let result = f().await;
+        match &result {
+            Err(e) if attempt < max_attempts && is_transient_network_error(e) => {
+                let delay = backoff_delay(base_delay, attempt);
+                warn!(
+                    "[{}] 第 {} 次尝试遇到临时网络错误，{}ms 后重试: {}",
+                    label,
+                    attempt,
+                    delay.as_millis(),
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) if is_transient_network_error(e) => {
+                let proxy = shell::get_proxy_settings();
+                return (Err(with_proxy_hint(e.clone(), &proxy)), attempt);
+            }
+            _ => return (result, attempt),
+        }
+    }
+}
+
+/// 执行需要联网的安装命令（npm、brew、curl 等），遇到临时网络错误时按指数退避重试
+async fn run_with_network_retry<F>(label: &str, mut run: F) -> (Result<String, String>, u32)
+where
+    F: FnMut() -> Result<String, String>,
+{
+    let base_delay = std::time::Duration::from_millis(NPM_RETRY_BASE_DELAY_MS);
+    retry_with_backoff(label, npm_retry_attempts(), base_delay, || {
+        let result = run();
+        async move { result }
+    })
+    .await
+}
+
+/// 多次尝试时在结果信息中注明尝试次数
+fn with_attempts(message: String, attempts: u32) -> String {
+    if attempts > 1 {
+        format!("{}（共尝试 {} 次）", message, attempts)
+    } else {
+        message
+    }
+}
+
+/// 创建访问 npm registry 的 HTTP 客户端，使用与 npm 命令相同的代理设置
+fn registry_client(
+    timeout_secs: u64,
+    proxy: &shell::ProxySettings,
+) -> Result<reqwest::Client, String> {
+    let mut builder =
+        reqwest::Client::builder().timeout(std::time::Duration::from_secs(timeout_secs));
+    let no_proxy = proxy.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string);
+    let invalid = |url: &str, e: reqwest::Error| {
+        format!("代理地址无效 ({}): {}", shell::redact_proxy_userinfo(url), e.without_url())
+    };
+    if let Some(url) = &proxy.http {
+        let http = reqwest::Proxy::http(url).map_err(|e| invalid(url, e))?;
+        builder = builder.proxy(http.no_proxy(no_proxy.clone()));
+    }
+    if let Some(url) = &proxy.https {
+        let https = reqwest::Proxy::https(url).map_err(|e| invalid(url, e))?;
+        builder = builder.proxy(https.no_proxy(no_proxy));
+    }
+    builder.build().map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+/// 配置了代理时在网络错误中注明代理地址，便于区分代理故障与 registry 故障
+fn with_proxy_hint(error: String, proxy: &shell::ProxySettings) -> String {
+    match proxy.display() {
+        Some(url) => format!("{}（经由代理 {}，请确认代理可用）", error, url),
+        None => error,
+    }
+}
+
+/// 安装前检查能否连接到指定地址，连接失败时返回提前结束的安装结果
+/// 收到 HTTP 响应即视为可达（网关类错误除外，通常是代理无法连到上游）；
+/// 代理取自 HTTP(S)_PROXY 环境变量或 ~/.openclaw/env
+async fn registry_preflight(url: &str, label: &str) -> Option<InstallResult> {
+    info!("[安装检查] 检查 {} 连通性: {}", label, url);
+    let proxy = shell::get_proxy_settings();
+    let client = match registry_client(REGISTRY_PREFLIGHT_TIMEOUT_SECS, &proxy) {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("[安装检查] {}，跳过连通性检查", e);
+            return None;
+        }
     };
     
-    match result {
-        Ok(version) => {
-            let v = version.trim().to_string();
-            if v.is_empty() {
-                None
-            } else {
-                Some(v)
-            }
+    let failure = match client.head(url).send().await {
+        Ok(response) if !matches!(response.status().as_u16(), 502..=504) => {
+            info!("[安装检查] ✓ {} 可达 ({})", label, response.status());
+            return None;
         }
+        Ok(response) => with_proxy_hint(format!("HTTP {}", response.status()), &proxy),
+        Err(e) => with_proxy_hint(e.to_string(), &proxy),
+    };
+    
+    warn!("[安装检查] ✗ 无法连接到 {}: {}", label, failure);
+    Some(InstallResult {
+        success: false,
+        message: format!("无法连接到 {}", label),
+        error: Some(format!(
+            "{} ({})，请检查网络或代理设置（HTTPS_PROXY），也可通过 OPENCLAW_NPM_REGISTRY 使用镜像",
+            failure, url
+        )),
+    })
+}
+
+/// 解析 registry 响应中的版本号（/openclaw/latest 的 version 或 /openclaw 的 dist-tags.latest）
+fn parse_registry_version(body: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    json.get("version")
+        .or_else(|| json.pointer("/dist-tags/latest"))
+        .and_then(|v| v.as_str())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// 直接请求 npm registry 获取最新版本（npm CLI 不可用时的回退）
+async fn fetch_latest_version_from_registry() -> Result<String, String> {
+    let registry = std::env::var("OPENCLAW_NPM_REGISTRY").ok();
+    let url = npm_registry_latest_url(registry.as_deref());
+    info!("[版本检查] 请求 npm registry: {}", url);
+
+    let proxy = shell::get_proxy_settings();
+    let client = registry_client(10, &proxy)?;
+    let response = client
+        .get(&url)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| with_proxy_hint(format!("请求 npm registry 失败: {}", e), &proxy))?;
+    if !response.status().is_success() {
+        return Err(format!("npm registry 返回错误状态: {}", response.status()));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("读取 npm registry 响应失败: {}", e))?;
+
+    parse_registry_version(&body).ok_or_else(|| "npm registry 响应中缺少版本号".to_string())
+}
+
+/// 获取 npm registry 上的最新版本
+async fn get_latest_openclaw_version() -> Option<String> {
+    let attempts = npm_retry_attempts();
+    let base_delay = std::time::Duration::from_millis(VERSION_CHECK_RETRY_BASE_DELAY_MS);
+    
+    // 优先使用 npm view 获取最新版本（保留 stderr 以便识别临时网络错误）
+    let (result, tries) = retry_with_backoff("版本检查", attempts, base_delay, || async {
+        if platform::is_windows() {
+            shell::run_cmd_output("npm view openclaw version")
+        } else {
+            shell::run_bash_output("npm view openclaw version")
+        }
+    })
+    .await;
+    
+    match result {
+        Ok(version) if !version.trim().is_empty() => return Some(version.trim().to_string()),
+        Ok(_) => warn!("[版本检查] npm view 未返回版本号，改为直接查询 registry"),
+        Err(e) => warn!(
+            "[版本检查] npm view 获取最新版本失败{}，改为直接查询 registry: {}",
+            with_attempts(String::new(), tries),
+            e
+        ),
+    }
+    
+    let (result, tries) = retry_with_backoff("版本检查", attempts, base_delay, || {
+        fetch_latest_version_from_registry()
+    })
+    .await;
+    match result {
+        Ok(version) => Some(version),
         Err(e) => {
-            warn!("[版本检查] 获取最新版本失败: {}", e);
+            warn!("[版本检查] 获取最新版本失败{}: {}", with_attempts(String::new(), tries), e);
             None
         }
     }
@@ -1104,9 +1984,19 @@ pub async fn update_openclaw() -> Result<InstallResult, String> {
             info!("[更新OpenClaw] 使用 Windows 更新方式...");
             update_openclaw_windows().await
         },
-        _ => {
-            info!("[更新OpenClaw] 使用 Unix 更新方式 (npm)...");
-            update_openclaw_unix().await
+        _ => match detect_package_tool() {
+            PackageTool::Brew => {
+                info!("[更新OpenClaw] 使用 Homebrew 更新...");
+                update_openclaw_brew().await
+            }
+            PackageTool::Npm => {
+                info!("[更新OpenClaw] 使用 Unix 更新方式 (npm)...");
+                update_openclaw_unix(None).await
+            }
+            PackageTool::ScopedNpm(npm) => {
+                info!("[更新OpenClaw] 使用版本管理器下的 npm 更新: {}", npm);
+                update_openclaw_unix(Some(&npm)).await
+            }
         },
     };
     
@@ -1123,16 +2013,23 @@ pub async fn update_openclaw() -> Result<InstallResult, String> {
 async fn update_openclaw_windows() -> Result<InstallResult, String> {
     info!("[更新OpenClaw] 执行 npm install -g openclaw@latest...");
     
-    match shell::run_cmd_output("npm install -g openclaw@latest") {
+    let (result, attempts) = run_with_network_retry("更新OpenClaw", || {
+        shell::run_cmd_output("npm install -g openclaw@latest")
+    })
+    .await;
+    match result {
         Ok(output) => {
             info!("[更新OpenClaw] npm 输出: {}", output);
             
             // 获取新版本
-            let new_version = get_openclaw_version();
+            let new_version = get_openclaw_version().await;
             
             Ok(InstallResult {
                 success: true,
-                message: format!("OpenClaw 已更新到 {}", new_version.unwrap_or("最新版本".to_string())),
+                message: with_attempts(
+                    format!("OpenClaw 已更新到 {}", new_version.unwrap_or("最新版本".to_string())),
+                    attempts,
+                ),
                 error: None,
             })
         }
@@ -1140,16 +2037,32 @@ async fn update_openclaw_windows() -> Result<InstallResult, String> {
             warn!("[更新OpenClaw] npm install 失败: {}", e);
             Ok(InstallResult {
                 success: false,
-                message: "OpenClaw 更新失败".to_string(),
+                message: with_attempts("OpenClaw 更新失败".to_string(), attempts),
                 error: Some(e),
             })
         }
     }
 }
 
-/// Unix 系统更新 OpenClaw
-async fn update_openclaw_unix() -> Result<InstallResult, String> {
-    let script = r#"
+/// 通过 Homebrew 更新 OpenClaw
+async fn update_openclaw_brew() -> Result<InstallResult, String> {
+    match shell::run_command_output("brew", &["upgrade", "openclaw"]) {
+        Ok(output) => Ok(InstallResult {
+            success: true,
+            message: format!("OpenClaw 已通过 Homebrew 更新！{}", output),
+            error: None,
+        }),
+        Err(e) => Ok(InstallResult {
+            success: false,
+            message: "Homebrew 更新 OpenClaw 失败".to_string(),
+            error: Some(e),
+        }),
+    }
+}
+
+/// Unix 系统更新 OpenClaw（npm 为版本管理器下的 npm 路径时使用该 npm）
+async fn update_openclaw_unix(npm: Option<&str>) -> Result<InstallResult, String> {
+    let script = scoped_npm_path_export(npm) + r#"
 echo "更新 OpenClaw..."
 npm install -g openclaw@latest
 
@@ -1157,16 +2070,341 @@ npm install -g openclaw@latest
 openclaw --version
 "#;
     
-    match shell::run_bash_output(script) {
+    let (result, attempts) =
+        run_with_network_retry("更新OpenClaw", || shell::run_bash_output(&script)).await;
+    match result {
         Ok(output) => Ok(InstallResult {
             success: true,
-            message: format!("OpenClaw 已更新！{}", output),
+            message: with_attempts(format!("OpenClaw 已更新！{}", output), attempts),
             error: None,
         }),
         Err(e) => Ok(InstallResult {
             success: false,
-            message: "OpenClaw 更新失败".to_string(),
+            message: with_attempts("OpenClaw 更新失败".to_string(), attempts),
             error: Some(e),
         }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        backoff_delay, classify_node_source, dedupe_node_installs, disk_space_check,
+        finish_uninstall, install_state_for, is_transient_network_error, npm_registry_latest_url,
+        parse_df_available_bytes, parse_registry_version, registry_preflight, retry_with_backoff,
+        select_package_tool, validate_node_binary_path, with_attempts, InstallMethod,
+        InstallResult, InstallState, NodeInstall, PackageTool, MIN_INSTALL_FREE_BYTES,
+        NPM_RETRY_BASE_DELAY_MS,
+    };
+    use crate::commands::config::tests::unique_temp_dir;
+
+    #[test]
+    fn registry_response_version_is_parsed() {
+        let latest = r#"{
+            "name": "openclaw",
+            "version": "2026.2.3",
+            "dist": { "tarball": "https://registry.npmjs.org/openclaw/-/openclaw-2026.2.3.tgz" }
+        }"#;
+        assert_eq!(parse_registry_version(latest), Some("2026.2.3".to_string()));
+
+        let packument = r#"{
+            "name": "openclaw",
+            "dist-tags": { "latest": "2026.1.30", "beta": "2026.2.0-beta.1" }
+        }"#;
+        assert_eq!(parse_registry_version(packument), Some("2026.1.30".to_string()));
+
+        assert_eq!(parse_registry_version(r#"{"error":"Not found"}"#), None);
+        assert_eq!(parse_registry_version("<html>502</html>"), None);
+    }
+
+    #[test]
+    fn registry_override_builds_latest_url() {
+        assert_eq!(
+            npm_registry_latest_url(None),
+            "https://registry.npmjs.org/openclaw/latest"
+        );
+        assert_eq!(
+            npm_registry_latest_url(Some("https://registry.npmmirror.com/")),
+            "https://registry.npmmirror.com/openclaw/latest"
+        );
+        assert_eq!(
+            npm_registry_latest_url(Some("  ")),
+            "https://registry.npmjs.org/openclaw/latest"
+        );
+    }
+
+    fn node(path: &str, version: &str, source: &str) -> NodeInstall {
+        NodeInstall {
+            path: path.to_string(),
+            version: version.to_string(),
+            source: source.to_string(),
+            meets_requirement: true,
+            preferred: false,
+        }
+    }
+
+    #[test]
+    fn node_source_is_classified_from_path() {
+        let nvm = "/home/me/.nvm/versions/node/v22.12.0/bin/node";
+        assert_eq!(classify_node_source(nvm), "nvm");
+        assert_eq!(classify_node_source("/opt/homebrew/bin/node"), "homebrew");
+        assert_eq!(classify_node_source("/usr/local/Cellar/node/23.1.0/bin/node"), "homebrew");
+        assert_eq!(classify_node_source("/usr/bin/node"), "system");
+        assert_eq!(classify_node_source("C:\\nvm4w\\nodejs\\node.exe"), "nvm");
+        assert_eq!(classify_node_source("C:\\Program Files\\nodejs\\node.exe"), "system");
+        assert_eq!(classify_node_source("/home/me/.volta/bin/node"), "volta");
+    }
+
+    #[test]
+    fn node_installs_are_deduplicated() {
+        let cellar = "/usr/local/Cellar/node/22.12.0/bin/node";
+        let installs = dedupe_node_installs(vec![
+            (cellar.to_string(), node("/usr/local/bin/node", "v22.12.0", "homebrew")),
+            // 指向同一真实路径的符号链接
+            (cellar.to_string(), node("/usr/local/opt/node/bin/node", "v22.12.0", "homebrew")),
+            // 同版本同来源的 shim
+            ("/a/.asdf/shims/node".to_string(), node("/a/.asdf/shims/node", "v20.1.0", "asdf")),
+            ("/a/.asdf/inst/node".to_string(), node("/a/.asdf/inst/node", "v20.1.0", "asdf")),
+            // 版本相同但来源不同，保留
+            ("/usr/bin/node".to_string(), node("/usr/bin/node", "v22.12.0", "system")),
+        ]);
+
+        let paths: Vec<&str> = installs.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["/usr/local/bin/node", "/a/.asdf/shims/node", "/usr/bin/node"]
+        );
+    }
+
+    #[test]
+    fn unreachable_registry_stops_install_early() {
+        // 绑定后立即释放端口，得到一个本机无人监听的地址
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .map(|addr| addr.port())
+            .expect("获取空闲端口");
+        let url = format!("http://127.0.0.1:{}/", port);
+
+        let runtime = tokio::runtime::Runtime::new().expect("创建运行时");
+        let result = runtime
+            .block_on(registry_preflight(&url, "npm registry"))
+            .expect("不可达时应提前返回安装结果");
+        assert!(!result.success);
+        assert_eq!(result.message, "无法连接到 npm registry");
+        assert!(result.error.unwrap().contains(&url));
+    }
+
+    #[test]
+    fn install_is_refused_when_disk_space_is_low() {
+        let mb = 1024 * 1024;
+        let low = disk_space_check(Some(120 * mb), MIN_INSTALL_FREE_BYTES).expect("空间不足应拒绝");
+        assert!(!low.success);
+        assert!(low.message.contains("120MB") && low.message.contains("500MB"));
+
+        assert!(disk_space_check(Some(MIN_INSTALL_FREE_BYTES), MIN_INSTALL_FREE_BYTES).is_none());
+        assert!(disk_space_check(Some(20 * 1024 * mb), MIN_INSTALL_FREE_BYTES).is_none());
+        assert!(disk_space_check(None, MIN_INSTALL_FREE_BYTES).is_none(), "未知空间不阻止安装");
+
+        let df = "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+                  /dev/disk3s5 971350180 812345678 159004502 84% /System/Volumes/Data\n";
+        assert_eq!(parse_df_available_bytes(df), Some(159004502 * 1024));
+        assert_eq!(parse_df_available_bytes("df: /nope: No such file"), None);
+    }
+
+    #[test]
+    fn preferred_node_path_must_be_an_existing_node_binary() {
+        let root = unique_temp_dir("node-path");
+        let node_name = if cfg!(windows) { "node.exe" } else { "node" };
+        let node = root.join(node_name);
+        let other = root.join("calc.exe");
+        std::fs::write(&node, "").unwrap();
+        std::fs::write(&other, "").unwrap();
+
+        let canonical = validate_node_binary_path(&node.display().to_string()).unwrap();
+        assert!(canonical.ends_with(node_name));
+        assert!(!canonical.starts_with(r"\\?\"));
+        assert!(std::path::Path::new(&canonical).is_absolute());
+
+        // 非 node 文件、目录以及注入式路径都会被拒绝
+        assert!(validate_node_binary_path(&other.display().to_string()).is_err());
+        assert!(validate_node_binary_path(&root.display().to_string()).is_err());
+        let injected = format!("{}\" & calc & \"", node.display());
+        assert!(validate_node_binary_path(&injected).is_err());
+        assert!(validate_node_binary_path(&root.join("missing").display().to_string()).is_err());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    fn uninstalled() -> InstallResult {
+        InstallResult {
+            success: true,
+            message: "OpenClaw 已成功卸载！".to_string(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn uninstall_purge_backs_up_and_empties_config_dir() {
+        let root = unique_temp_dir("uninstall");
+        let config_dir = root.join(".openclaw");
+        std::fs::create_dir_all(config_dir.join("credentials")).unwrap();
+        std::fs::write(config_dir.join("openclaw.json"), "{}").unwrap();
+        std::fs::write(config_dir.join("env"), "export KEY=1\n").unwrap();
+        std::fs::write(config_dir.join("credentials").join("wa.json"), "{}").unwrap();
+
+        // purge=false 保持现有行为
+        let kept = finish_uninstall(uninstalled(), false, &config_dir, &root);
+        assert_eq!(kept.message, "OpenClaw 已成功卸载！");
+        assert!(config_dir.join("openclaw.json").is_file());
+
+        // 卸载失败时即使 purge=true 也不清理
+        let mut failed = uninstalled();
+        failed.success = false;
+        finish_uninstall(failed, true, &config_dir, &root);
+        assert!(config_dir.join("env").is_file());
+
+        let purged = finish_uninstall(uninstalled(), true, &config_dir, &root);
+        assert!(purged.success, "{:?}", purged.error);
+        assert!(purged.message.contains("credentials") && purged.message.contains("env"));
+        assert_eq!(std::fs::read_dir(&config_dir).unwrap().count(), 0);
+
+        let backup = std::fs::read_dir(&root)
+            .unwrap()
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .find(|p| p.extension().is_some_and(|ext| ext == "tar"))
+            .expect("应生成备份文件");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&backup).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "备份文件应仅当前用户可读写");
+        }
+        let mut archive = tar::Archive::new(std::fs::File::open(&backup).unwrap());
+        let names: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path().unwrap().display().to_string())
+            .collect();
+        assert!(names.iter().any(|n| n.ends_with("credentials/wa.json")), "{:?}", names);
+        assert!(names.iter().any(|n| n.ends_with("openclaw.json")));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn npm_errors_are_classified_as_transient_or_permanent() {
+        let transient = [
+            "npm ERR! code ETIMEDOUT\nnpm ERR! network request to \
+             https://registry.npmjs.org/openclaw failed, reason: connect ETIMEDOUT",
+            "npm error code ECONNRESET\nnpm error network aborted",
+            "npm ERR! code E503\nnpm ERR! 503 Service Unavailable - GET \
+             https://registry.npmjs.org/openclaw",
+            "npm ERR! 502 Bad Gateway - GET https://registry.npmmirror.com/openclaw",
+            "npm error code EAI_AGAIN\nnpm error request failed, reason: getaddrinfo EAI_AGAIN",
+        ];
+        for output in transient {
+            assert!(is_transient_network_error(output), "应判定为临时错误: {}", output);
+        }
+
+        let permanent = [
+            "npm ERR! code EACCES\nnpm ERR! Error: EACCES: permission denied, \
+             mkdir '/usr/local/lib/node_modules/openclaw'",
+            "npm error code E401\nnpm error Unable to authenticate, need: Basic realm=\"npm\"",
+            "npm ERR! code E403\nnpm ERR! 403 Forbidden - GET https://npm.example.com/openclaw",
+            "npm ERR! code E404\nnpm ERR! 404 Not Found - GET https://registry.npmjs.org/openclw",
+            "npm ERR! code ENEEDAUTH\nnpm ERR! need auth This command requires you to log in",
+            "Command failed with exit code: Some(1)",
+        ];
+        for output in permanent {
+            assert!(!is_transient_network_error(output), "不应重试: {}", output);
+        }
+    }
+
+    #[test]
+    fn retry_with_backoff_retries_transient_errors_only() {
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let no_delay = std::time::Duration::ZERO;
+
+        let mut calls = 0;
+        let (result, attempts) = runtime.block_on(retry_with_backoff("测试", 3, no_delay, || {
+            calls += 1;
+            let outcome = if calls < 3 {
+                Err("npm error code ETIMEDOUT".to_string())
+            } else {
+                Ok(calls)
+            };
+            async move { outcome }
+        }));
+        assert_eq!((result, attempts), (Ok(3), 3));
+
+        let mut calls = 0;
+        let (result, attempts) = runtime.block_on(retry_with_backoff("测试", 3, no_delay, || {
+            calls += 1;
+            async { Err::<(), _>("npm ERR! code EACCES: permission denied".to_string()) }
+        }));
+        assert!(result.is_err());
+        assert_eq!((attempts, calls), (1, 1), "权限错误不应重试");
+
+        let (result, attempts) = runtime.block_on(retry_with_backoff("测试", 2, no_delay, || async {
+            Err::<(), _>("curl: (6) Could not resolve host: deb.nodesource.com".to_string())
+        }));
+        assert!(result.is_err());
+        assert_eq!(attempts, 2, "用尽次数后返回最后一次错误");
+    }
+
+    #[test]
+    fn npm_retry_backs_off_and_reports_attempts() {
+        let base = std::time::Duration::from_millis(NPM_RETRY_BASE_DELAY_MS);
+        assert_eq!(backoff_delay(base, 1).as_millis(), 2000);
+        assert_eq!(backoff_delay(base, 2).as_millis(), 4000);
+        assert_eq!(backoff_delay(base, 3).as_millis(), 8000);
+        assert_eq!(with_attempts("OpenClaw 更新失败".to_string(), 1), "OpenClaw 更新失败");
+        assert_eq!(
+            with_attempts("OpenClaw 更新失败".to_string(), 3),
+            "OpenClaw 更新失败（共尝试 3 次）"
+        );
+    }
+
+    #[test]
+    fn package_tool_prefers_recorded_state_then_resolved_path() {
+        let brew_formula = "/opt/homebrew/Cellar/openclaw/1.2.0/bin/openclaw";
+        let npm_on_brew_node = "/opt/homebrew/lib/node_modules/openclaw/openclaw.mjs";
+        let nvm_npm = "/home/u/.nvm/versions/node/v22.12.0/bin/npm";
+        let npm_on_nvm =
+            "/home/u/.nvm/versions/node/v22.12.0/lib/node_modules/openclaw/openclaw.mjs";
+        let any = |_: &str| true;
+        let select = |state: Option<&InstallState>, path: Option<&str>| {
+            select_package_tool(state, path, any)
+        };
+
+        // 无记录：按真实路径推断，未知时默认 npm
+        assert_eq!(select(None, Some(brew_formula)), PackageTool::Brew);
+        assert_eq!(select(None, Some(npm_on_brew_node)), PackageTool::Npm);
+        assert_eq!(select(None, None), PackageTool::Npm);
+        assert_eq!(select(None, Some(npm_on_nvm)), PackageTool::ScopedNpm(nvm_npm.to_string()));
+
+        // 有记录：以记录为准
+        let npm_state = install_state_for(InstallMethod::Npm, None);
+        let brew_state = install_state_for(InstallMethod::Brew, Some(brew_formula));
+        assert_eq!(select(Some(&npm_state), Some(brew_formula)), PackageTool::Npm);
+        assert_eq!(select(Some(&brew_state), Some(npm_on_brew_node)), PackageTool::Brew);
+
+        let nvm_state = install_state_for(InstallMethod::Npm, Some(npm_on_nvm));
+        assert_eq!(nvm_state.node_source.as_deref(), Some("nvm"));
+        assert_eq!(nvm_state.npm_path.as_deref(), Some(nvm_npm));
+        assert_eq!(select(Some(&nvm_state), None), PackageTool::ScopedNpm(nvm_npm.to_string()));
+        // 记录的 npm 已不存在时退回 PATH 中的 npm
+        assert_eq!(select_package_tool(Some(&nvm_state), None, |_| false), PackageTool::Npm);
+
+        // 状态文件往返，损坏的记录不会被采用
+        let content = serde_json::to_string(&nvm_state).unwrap();
+        assert!(content.contains("\"method\":\"npm\"") && content.contains("npmPath"));
+        assert_eq!(serde_json::from_str::<InstallState>(&content).unwrap(), nvm_state);
+        assert_eq!(
+            serde_json::to_string(&brew_state).unwrap(),
+            "{\"method\":\"brew\"}"
+        );
+        assert!(serde_json::from_str::<InstallState>("garbage").is_err());
+    }
+}
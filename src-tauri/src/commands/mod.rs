@@ -1,5 +1,156 @@
+pub mod audit;
+pub mod autostart;
 pub mod config;
+pub mod credentials;
 pub mod diagnostics;
 pub mod installer;
 pub mod process;
+pub mod profile;
 pub mod service;
+pub mod sessions;
+
+/// 前端可调用的命令表（单一来源）
+/// 以 `模块::函数` 列出全部命令并交给回调宏展开：main.rs 据此生成 generate_handler!，
+/// 命令名清单也由此生成；web_server 的 dispatch_command 由测试校验与之一致
+/// （web_server 只在测试中展开命令表，故放行未使用告警）
+#[allow(unused_macros)]
+macro_rules! command_table {
+    ($callback:ident) => {
+        $callback! {
+            // 服务管理
+            service::get_service_status,
+            service::start_service,
+            service::stop_service,
+            service::restart_service,
+            service::get_logs,
+            service::clear_logs,
+            // 开机自启
+            autostart::install_autostart,
+            autostart::uninstall_autostart,
+            autostart::is_autostart_enabled,
+            // 会话清理
+            sessions::get_sessions_info,
+            sessions::clear_old_sessions,
+            sessions::get_usage_stats,
+            // 渠道凭证
+            credentials::list_credentials,
+            credentials::clear_credentials,
+            // 进程管理
+            process::check_openclaw_installed,
+            process::get_openclaw_version,
+            process::get_node_version,
+            process::check_port_in_use,
+            process::check_port_owner,
+            process::list_gateway_processes,
+            process::kill_gateway_process,
+            process::run_openclaw_command,
+            config::get_config,
+            config::get_config_schema,
+            config::get_effective_config,
+            config::save_config,
+            config::preview_config_change,
+            config::apply_config_change,
+            config::list_config_backups,
+            config::rollback_config,
+            config::export_config_bundle,
+            config::import_config_bundle,
+            profile::list_profiles,
+            profile::current_profile,
+            profile::create_profile,
+            profile::switch_profile,
+            config::get_agents_list,
+            config::save_agents_list,
+            config::get_agent,
+            config::set_agent_model,
+            config::add_agent,
+            config::update_agent,
+            config::delete_agent,
+            config::get_bindings,
+            config::save_bindings,
+            config::migrate_bindings,
+            config::repair_orphaned_bindings,
+            config::get_env_value,
+            config::get_env_values,
+            config::get_env_keys,
+            config::remove_env_key,
+            config::save_env_value,
+            config::save_env_values,
+            config::get_ai_providers,
+            config::get_channels_config,
+            config::save_channel_config,
+            config::import_channels,
+            config::clear_channel_config,
+            config::validate_channel_accounts,
+            config::set_channel_enabled,
+            config::set_channels_enabled,
+            config::get_or_create_gateway_token,
+            config::rotate_gateway_token,
+            config::get_dashboard_url,
+            config::get_gateway_settings,
+            config::save_gateway_settings,
+            config::verify_gateway_token,
+            config::watch_config,
+            config::open_config_file,
+            config::open_config_dir,
+            // AI 配置管理
+            config::get_official_providers,
+            config::get_ai_config,
+            config::save_provider,
+            config::delete_provider,
+            config::clone_provider,
+            config::set_primary_model,
+            config::get_agent_defaults,
+            config::set_max_concurrent,
+            config::add_available_model,
+            config::remove_available_model,
+            config::update_model_cost,
+            // 飞书插件管理
+            config::list_plugins,
+            config::enable_plugin,
+            config::disable_plugin,
+            config::audit_plugin_consistency,
+            config::repair_plugin_consistency,
+            config::check_channel_plugin,
+            config::check_feishu_plugin,
+            config::install_feishu_plugin,
+            // 诊断测试
+            diagnostics::run_doctor,
+            diagnostics::fix_config_permissions,
+            diagnostics::test_ai_connection,
+            diagnostics::test_provider_connection,
+            diagnostics::test_channel,
+            diagnostics::send_test_message,
+            diagnostics::get_system_info,
+            diagnostics::get_paths,
+            diagnostics::start_channel_login,
+            // 安装器
+            installer::check_environment,
+            installer::install_nodejs,
+            installer::list_node_installations,
+            installer::set_preferred_node_path,
+            installer::install_openclaw,
+            installer::init_openclaw_config,
+            installer::open_install_terminal,
+            installer::uninstall_openclaw,
+            // 版本更新
+            installer::check_openclaw_update,
+            installer::update_openclaw,
+            // 审计日志
+            audit::get_audit_log,
+        }
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use command_table;
+
+/// 从命令表中取出命令名
+#[cfg(test)]
+macro_rules! command_names {
+    ($($module:ident::$command:ident),* $(,)?) => {
+        &[$(stringify!($command)),*]
+    };
+}
+
+/// 前端可调用的命令名清单，由命令表生成
+#[cfg(test)]
+pub(crate) const COMMAND_NAMES: &[&str] = command_table!(command_names);
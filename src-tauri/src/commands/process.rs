@@ -1,6 +1,20 @@
+use super::service;
+use crate::models::{GatewayProcess, OpenclawCommandResult, PortOwner};
 use crate::utils::shell;
 use tauri::command;
-use log::{info, debug};
+use log::{info, debug, warn};
+
+/// 允许透传执行的 openclaw 子命令（只读/诊断类，按前缀匹配）
+const ALLOWED_OPENCLAW_COMMANDS: &[&[&str]] = &[
+    &["status"],
+    &["doctor"],
+    &["plugins", "list"],
+    &["config", "get"],
+    &["channels", "status"],
+];
+
+/// 即使子命令在白名单内也禁止携带的参数（会修改配置或跳过确认）
+const FORBIDDEN_OPENCLAW_FLAGS: &[&str] = &["--fix", "--repair", "--force", "--yes", "-y"];
 
 /// 检查 OpenClaw 是否已安装
 #[command]
@@ -16,8 +30,8 @@ pub async fn check_openclaw_installed() -> Result<bool, String> {
 #[command]
 pub async fn get_openclaw_version() -> Result<Option<String>, String> {
     info!("[进程检查] 获取 OpenClaw 版本...");
-    // 使用 run_openclaw 来获取版本
-    match shell::run_openclaw(&["--version"]) {
+    // 使用 run_openclaw_async 获取版本，避免 openclaw 卡住时阻塞命令
+    match shell::run_openclaw_async(&["--version"], std::time::Duration::from_secs(15)).await {
         Ok(version) => {
             let v = version.trim().to_string();
             info!("[进程检查] OpenClaw 版本: {}", v);
@@ -30,6 +44,11 @@ pub async fn get_openclaw_version() -> Result<Option<String>, String> {
     }
 }
 
+/// 通过 openclaw health 探测 gateway 是否仍在提供服务
+pub(crate) fn gateway_health_ok() -> bool {
+    shell::run_openclaw(&["health", "--timeout", "2000"]).is_ok()
+}
+
 /// 检查端口是否被占用（通过尝试连接 openclaw gateway）
 #[command]
 pub async fn check_port_in_use(port: u16) -> Result<bool, String> {
@@ -39,9 +58,8 @@ pub async fn check_port_in_use(port: u16) -> Result<bool, String> {
     // 如果 port 是默认的 18789，直接使用 openclaw health
     if port == 18789 {
         debug!("[进程检查] 使用 openclaw health 检查端口 18789...");
-        let result = shell::run_openclaw(&["health", "--timeout", "2000"]);
         // 如果 health 命令成功，说明端口被 gateway 占用
-        let in_use = result.is_ok();
+        let in_use = gateway_health_ok();
         info!("[进程检查] 端口 18789 状态: {}", if in_use { "被占用" } else { "空闲" });
         return Ok(in_use);
     }
@@ -64,6 +82,303 @@ pub async fn check_port_in_use(port: u16) -> Result<bool, String> {
     }
 }
 
+/// 自动寻找空闲端口时最多尝试的端口数
+const FREE_PORT_ATTEMPTS: u16 = 20;
+
+/// 从 start 开始依次列出候选端口（不超过 u16 上限）
+fn candidate_ports(start: u16, attempts: u16) -> impl Iterator<Item = u16> {
+    (0..attempts).map_while(move |offset| start.checked_add(offset))
+}
+
+/// 从 start 开始依次探测，返回第一个空闲端口
+pub(crate) async fn find_free_port(start: u16) -> Option<u16> {
+    for port in candidate_ports(start, FREE_PORT_ATTEMPTS) {
+        // 探测失败时保守地视为占用
+        if !check_port_in_use(port).await.unwrap_or(true) && find_port_owner(port).is_none() {
+            info!("[进程检查] 找到空闲端口: {}", port);
+            return Some(port);
+        }
+    }
+    warn!("[进程检查] 从 {} 开始的 {} 个端口均被占用", start, FREE_PORT_ATTEMPTS);
+    None
+}
+
+/// 解析 tasklist /FO CSV /NH 的输出，返回映像名称
+/// 格式: "node.exe","1234","Console","1","50,000 K"
+fn parse_tasklist_name(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| line.starts_with('"'))
+        .and_then(|line| line.trim_start_matches('"').split('"').next())
+        .map(|name| name.to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// 根据 PID 获取进程名称和命令行
+fn get_process_info(pid: u32) -> (Option<String>, Option<String>) {
+    let pid_str = pid.to_string();
+    let non_empty = |s: String| if s.is_empty() { None } else { Some(s) };
+
+    if cfg!(windows) {
+        let filter = format!("PID eq {}", pid);
+        let name = shell::run_command_output("tasklist", &["/FI", &filter, "/FO", "CSV", "/NH"])
+            .ok()
+            .and_then(|output| parse_tasklist_name(&output));
+        let cmdline = shell::run_powershell_output(&format!(
+            "(Get-CimInstance Win32_Process -Filter \"ProcessId={}\").CommandLine",
+            pid
+        ))
+        .ok()
+        .and_then(non_empty);
+        (name, cmdline)
+    } else {
+        let name = shell::run_command_output("ps", &["-p", &pid_str, "-o", "comm="])
+            .ok()
+            .and_then(non_empty);
+        let cmdline = shell::run_command_output("ps", &["-p", &pid_str, "-o", "command="])
+            .ok()
+            .and_then(non_empty);
+        (name, cmdline)
+    }
+}
+
+/// 检查 PID 对应的进程是否仍然存活
+/// Unix 使用 kill -0（无权限发信号时进程同样存活），Windows 使用 Get-Process
+pub(crate) fn is_pid_alive(pid: u32) -> bool {
+    if pid == 0 {
+        return false;
+    }
+    if cfg!(windows) {
+        shell::run_powershell_output(&format!(
+            "if (Get-Process -Id {} -ErrorAction SilentlyContinue) {{ 'alive' }}",
+            pid
+        ))
+        .map(|output| output.trim() == "alive")
+        .unwrap_or(false)
+    } else {
+        match shell::run_command_output("kill", &["-0", &pid.to_string()]) {
+            Ok(_) => true,
+            Err(e) => e.contains("not permitted"),
+        }
+    }
+}
+
+/// 查找占用端口的进程（Unix 使用 lsof，Windows 使用 netstat + tasklist）
+pub(crate) fn find_port_owner(port: u16) -> Option<PortOwner> {
+    let pid = service::check_port_listening(port)?;
+    let (name, cmdline) = get_process_info(pid);
+    let name = name
+        .map(|n| n.rsplit(['/', '\\']).next().unwrap_or(&n).to_string())
+        .unwrap_or_else(|| "未知进程".to_string());
+    debug!("[进程检查] 端口 {} 被进程占用: {} (PID {})", port, name, pid);
+    Some(PortOwner { pid, name, cmdline })
+}
+
+/// 获取占用端口的进程信息，端口空闲时返回 None
+#[command]
+pub async fn check_port_owner(port: u16) -> Result<Option<PortOwner>, String> {
+    info!("[进程检查] 查询端口 {} 的占用进程...", port);
+    let owner = find_port_owner(port);
+    match &owner {
+        Some(o) => info!("[进程检查] 端口 {} 被 {} (PID {}) 占用", port, o.name, o.pid),
+        None => info!("[进程检查] 端口 {} 空闲", port),
+    }
+    Ok(owner)
+}
+
+/// pgrep 粗筛 gateway 候选进程的模式，结果还需经 is_gateway_cmdline 按 argv 结构确认
+const GATEWAY_PROCESS_PATTERN: &str = "openclaw.*gateway";
+
+/// 判断命令行是否为正在运行的 gateway：openclaw 可执行文件或入口脚本后紧跟 `gateway`，
+/// 且之后没有 stop/status/logs 等子命令（仅允许无子命令或 `run`）
+fn is_gateway_cmdline(cmdline: &str) -> bool {
+    let tokens: Vec<&str> = cmdline
+        .split_whitespace()
+        .map(|t| t.trim_matches(|c| c == '"' || c == '\''))
+        .collect();
+    let is_openclaw_entry = |token: &str| {
+        let name = token.rsplit(['/', '\\']).next().unwrap_or(token).to_ascii_lowercase();
+        let stem = [".exe", ".cmd", ".mjs", ".js"]
+            .iter()
+            .find_map(|ext| name.strip_suffix(ext))
+            .unwrap_or(&name);
+        stem == "openclaw"
+            || ((name.ends_with(".mjs") || name.ends_with(".js"))
+                && token.to_ascii_lowercase().contains("openclaw"))
+    };
+    let Some(entry) = tokens.iter().position(|t| is_openclaw_entry(t)) else {
+        return false;
+    };
+    if tokens.get(entry + 1) != Some(&"gateway") {
+        return false;
+    }
+    match tokens.get(entry + 2) {
+        None => true,
+        Some(next) => next.starts_with('-') || *next == "run",
+    }
+}
+
+/// 强制结束前等待进程响应 SIGTERM 的时间
+const KILL_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// 解析 pgrep 输出（每行一个 PID），去重并排除 exclude（当前进程）
+fn parse_pid_list(output: &str, exclude: u32) -> Vec<u32> {
+    let mut pids: Vec<u32> = output
+        .lines()
+        .filter_map(|line| line.trim().parse::<u32>().ok())
+        .filter(|pid| *pid != 0 && *pid != exclude)
+        .collect();
+    pids.sort_unstable();
+    pids.dedup();
+    pids
+}
+
+/// 解析 lsof -Fn 输出中的监听端口（形如 n*:18789、n127.0.0.1:18789、n[::1]:18789）
+fn parse_lsof_listen_ports(output: &str) -> Vec<u16> {
+    let mut ports: Vec<u16> = output
+        .lines()
+        .filter_map(|line| line.strip_prefix('n'))
+        .filter_map(|addr| addr.rsplit(':').next()?.parse::<u16>().ok())
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// 解析 netstat -ano 输出中属于 pid 的监听端口
+fn parse_netstat_listen_ports(output: &str, pid: u32) -> Vec<u16> {
+    let pid = pid.to_string();
+    let mut ports: Vec<u16> = output
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter(|cols| cols.len() >= 5 && cols[3] == "LISTENING" && cols[4] == pid)
+        .filter_map(|cols| cols[1].rsplit(':').next()?.parse::<u16>().ok())
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// 解析 WMI 查询输出（每行 PID|CreationDate|CommandLine）
+fn parse_wmi_gateway_lines(output: &str, exclude: u32) -> Vec<GatewayProcess> {
+    let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+    let mut processes: Vec<GatewayProcess> = output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.trim().splitn(3, '|');
+            let pid = parts.next()?.trim().parse::<u32>().ok()?;
+            Some(GatewayProcess {
+                pid,
+                ports: Vec::new(),
+                started_at: parts.next().and_then(non_empty),
+                cmdline: parts.next().and_then(non_empty),
+            })
+        })
+        .filter(|p| p.pid != 0 && p.pid != exclude)
+        .collect();
+    processes.sort_by_key(|p| p.pid);
+    processes.dedup_by_key(|p| p.pid);
+    processes
+}
+
+/// 查找所有 openclaw gateway 进程（Unix 使用 pgrep -f，Windows 使用 WMI 查询）
+pub(crate) fn find_gateway_processes() -> Vec<GatewayProcess> {
+    let current = std::process::id();
+    if cfg!(windows) {
+        let script = "Get-CimInstance Win32_Process | \
+            Where-Object { $_.CommandLine -like '*openclaw*gateway*' } | \
+            ForEach-Object { \"$($_.ProcessId)|$($_.CreationDate)|$($_.CommandLine)\" }";
+        let output = shell::run_powershell_output(script).unwrap_or_default();
+        let netstat =
+            shell::run_command_output("netstat", &["-ano", "-p", "TCP"]).unwrap_or_default();
+        parse_wmi_gateway_lines(&output, current)
+            .into_iter()
+            .filter(|p| p.cmdline.as_deref().is_some_and(is_gateway_cmdline))
+            .map(|mut p| {
+                p.ports = parse_netstat_listen_ports(&netstat, p.pid);
+                p
+            })
+            .collect()
+    } else {
+        // pgrep 未匹配到进程时退出码为 1，视为没有进程
+        let output = shell::run_command_output("pgrep", &["-f", GATEWAY_PROCESS_PATTERN])
+            .unwrap_or_default();
+        parse_pid_list(&output, current)
+            .into_iter()
+            .filter_map(|pid| {
+                let pid_str = pid.to_string();
+                let ps = |field: &str| {
+                    shell::run_command_output("ps", &["-p", &pid_str, "-o", field])
+                        .ok()
+                        .filter(|s| !s.trim().is_empty())
+                };
+                let cmdline = ps("command=").filter(|c| is_gateway_cmdline(c))?;
+                let ports = shell::run_command_output(
+                    "lsof",
+                    &["-nP", "-a", "-p", &pid_str, "-iTCP", "-sTCP:LISTEN", "-Fn"],
+                )
+                .map(|output| parse_lsof_listen_ports(&output))
+                .unwrap_or_default();
+                Some(GatewayProcess {
+                    pid,
+                    ports,
+                    started_at: ps("lstart="),
+                    cmdline: Some(cmdline),
+                })
+            })
+            .collect()
+    }
+}
+
+/// 结束进程：Unix 先发 SIGTERM，宽限期后仍存活再 SIGKILL；Windows 使用 taskkill /F
+/// 宽限期内异步等待，不阻塞运行时线程
+pub(crate) async fn terminate_process(pid: u32) -> Result<(), String> {
+    if cfg!(windows) {
+        shell::run_command_output("taskkill", &["/PID", &pid.to_string(), "/T", "/F"])?;
+    } else {
+        shell::run_command_output("kill", &["-TERM", &pid.to_string()])?;
+        let deadline = std::time::Instant::now() + KILL_GRACE_PERIOD;
+        while is_pid_alive(pid) && std::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+        if is_pid_alive(pid) {
+            warn!("[进程检查] PID {} 未响应 SIGTERM，强制结束", pid);
+            shell::run_command_output("kill", &["-KILL", &pid.to_string()])?;
+        }
+    }
+    if is_pid_alive(pid) {
+        Err(format!("进程 {} 仍在运行", pid))
+    } else {
+        Ok(())
+    }
+}
+
+/// 列出所有 openclaw gateway 进程（包括崩溃后残留、未监听端口的进程）
+#[command]
+pub async fn list_gateway_processes() -> Result<Vec<GatewayProcess>, String> {
+    info!("[进程检查] 查找 gateway 进程...");
+    let processes = find_gateway_processes();
+    info!(
+        "[进程检查] 找到 {} 个 gateway 进程: {:?}",
+        processes.len(),
+        processes.iter().map(|p| p.pid).collect::<Vec<_>>()
+    );
+    Ok(processes)
+}
+
+/// 结束指定的 gateway 进程；只允许结束 list_gateway_processes 能找到的进程
+#[command]
+pub async fn kill_gateway_process(pid: u32) -> Result<String, String> {
+    info!("[进程检查] 结束 gateway 进程 PID {}...", pid);
+    if !find_gateway_processes().iter().any(|p| p.pid == pid) {
+        return Err(format!("PID {} 不是 openclaw gateway 进程", pid));
+    }
+    terminate_process(pid).await.map_err(|e| format!("结束进程 {} 失败: {}", pid, e))?;
+    info!("[进程检查] ✓ 已结束 PID {}", pid);
+    Ok(format!("已结束 gateway 进程 {}", pid))
+}
+
 /// 获取 Node.js 版本
 #[command]
 pub async fn get_node_version() -> Result<Option<String>, String> {
@@ -84,3 +399,150 @@ pub async fn get_node_version() -> Result<Option<String>, String> {
         },
     }
 }
+
+/// 校验透传参数是否命中白名单
+fn validate_openclaw_args(args: &[String]) -> Result<(), String> {
+    let allowed = ALLOWED_OPENCLAW_COMMANDS.iter().any(|prefix| {
+        args.len() >= prefix.len() && prefix.iter().zip(args).all(|(p, a)| p == a)
+    });
+    if !allowed {
+        let allowed_list: Vec<String> = ALLOWED_OPENCLAW_COMMANDS
+            .iter()
+            .map(|prefix| prefix.join(" "))
+            .collect();
+        return Err(format!(
+            "不允许执行 openclaw {}，仅支持: {}",
+            args.join(" "),
+            allowed_list.join(", ")
+        ));
+    }
+
+    if let Some(flag) = args
+        .iter()
+        .find(|a| FORBIDDEN_OPENCLAW_FLAGS.contains(&a.split('=').next().unwrap_or(a)))
+    {
+        return Err(format!("不允许使用参数 {}", flag));
+    }
+
+    Ok(())
+}
+
+/// 透传执行 openclaw 子命令（仅限白名单内的只读命令）
+#[command]
+pub async fn run_openclaw_command(args: Vec<String>) -> Result<OpenclawCommandResult, String> {
+    info!("[命令透传] 执行: openclaw {}", args.join(" "));
+    if let Err(e) = validate_openclaw_args(&args) {
+        warn!("[命令透传] 已拒绝: {}", e);
+        return Err(e);
+    }
+
+    let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let output = shell::run_openclaw_output(&arg_refs)?;
+    let result = OpenclawCommandResult {
+        args,
+        success: output.status.success(),
+        exit_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    };
+    info!("[命令透传] 退出码: {:?}", result.exit_code);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        candidate_ports, is_gateway_cmdline, is_pid_alive, parse_lsof_listen_ports,
+        parse_netstat_listen_ports, parse_pid_list, parse_tasklist_name, parse_wmi_gateway_lines,
+        validate_openclaw_args,
+    };
+
+    fn args(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn openclaw_passthrough_only_allows_listed_commands() {
+        assert!(validate_openclaw_args(&args(&["status"])).is_ok());
+        assert!(validate_openclaw_args(&args(&["plugins", "list", "--json"])).is_ok());
+        assert!(validate_openclaw_args(&args(&["config", "get", "gateway.port"])).is_ok());
+
+        assert!(validate_openclaw_args(&args(&[])).is_err());
+        assert!(validate_openclaw_args(&args(&["plugins", "install", "x"])).is_err());
+        assert!(validate_openclaw_args(&args(&["config", "set", "a", "b"])).is_err());
+        assert!(validate_openclaw_args(&args(&["doctor", "--fix"])).is_err());
+        assert!(validate_openclaw_args(&args(&["plugins"])).is_err());
+    }
+
+    #[test]
+    fn tasklist_csv_output_yields_image_name() {
+        let output = "\r\n\"node.exe\",\"1234\",\"Console\",\"1\",\"50,000 K\"\r\n";
+        assert_eq!(parse_tasklist_name(output), Some("node.exe".to_string()));
+        let none = "INFO: No tasks are running which match the specified criteria.";
+        assert_eq!(parse_tasklist_name(none), None);
+    }
+
+    #[test]
+    fn gateway_process_listings_parse_multiple_pids() {
+        let pgrep = "4321\n1200\n\n4321\n  77  \nabc\n999\n";
+        assert_eq!(parse_pid_list(pgrep, 999), vec![77, 1200, 4321]);
+        assert!(parse_pid_list("", 1).is_empty());
+
+        let lsof = "p4321\nf23\nn*:18789\nf24\nn127.0.0.1:18791\nf25\nn[::1]:18789\n";
+        assert_eq!(parse_lsof_listen_ports(lsof), vec![18789, 18791]);
+
+        let netstat = "\
+  Proto  Local Address          Foreign Address        State           PID
+  TCP    0.0.0.0:18789          0.0.0.0:0              LISTENING       4321
+  TCP    127.0.0.1:18790        127.0.0.1:50000        ESTABLISHED     4321
+  TCP    [::]:18791             [::]:0                 LISTENING       4321
+  TCP    0.0.0.0:445            0.0.0.0:0              LISTENING       43210
+";
+        assert_eq!(parse_netstat_listen_ports(netstat, 4321), vec![18789, 18791]);
+
+        let wmi = "\
+4321|20240101120000.000000+480|node C:\\openclaw\\openclaw.mjs gateway --port 18789\r
+1200|20240101110000.000000+480|node openclaw.mjs gateway\r
+42||\r
+\r
+";
+        let processes = parse_wmi_gateway_lines(wmi, 42);
+        assert_eq!(processes.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![1200, 4321]);
+        assert_eq!(processes[0].started_at.as_deref(), Some("20240101110000.000000+480"));
+        let cmdline = processes[1].cmdline.as_deref().unwrap_or_default();
+        assert!(cmdline.ends_with("--port 18789"), "{}", cmdline);
+    }
+
+    #[test]
+    fn gateway_cmdline_requires_gateway_subcommand_after_openclaw_entry() {
+        assert!(is_gateway_cmdline("/usr/local/bin/openclaw gateway --port 18789"));
+        assert!(is_gateway_cmdline("node /usr/lib/node_modules/openclaw/openclaw.mjs gateway"));
+        assert!(is_gateway_cmdline("node C:\\openclaw\\openclaw.mjs gateway --port 18789"));
+        assert!(is_gateway_cmdline("cmd /c \"C:\\npm\\openclaw.cmd\" gateway run"));
+        assert!(is_gateway_cmdline("openclaw gateway"));
+
+        assert!(!is_gateway_cmdline("openclaw gateway stop"));
+        assert!(!is_gateway_cmdline("openclaw gateway status --json"));
+        assert!(!is_gateway_cmdline("node openclaw.mjs gateway logs"));
+        assert!(!is_gateway_cmdline("openclaw status gateway"));
+        assert!(!is_gateway_cmdline("vim /home/me/openclaw/gateway.md"));
+        assert!(!is_gateway_cmdline("tail -f /tmp/openclaw/logs/gateway.log"));
+        assert!(!is_gateway_cmdline("/opt/openclaw-manager --gateway"));
+    }
+
+    #[test]
+    fn free_port_candidates_stop_at_port_limit() {
+        let ports: Vec<u16> = candidate_ports(18789, 3).collect();
+        assert_eq!(ports, vec![18789, 18790, 18791]);
+        let ports: Vec<u16> = candidate_ports(65534, 5).collect();
+        assert_eq!(ports, vec![65534, 65535]);
+    }
+
+    #[test]
+    fn nonexistent_pid_is_not_alive() {
+        assert!(is_pid_alive(std::process::id()));
+        assert!(!is_pid_alive(0));
+        // 超过 Linux pid_max 上限（4194304），不可能存在
+        assert!(!is_pid_alive(u32::MAX - 1));
+    }
+}
@@ -0,0 +1,189 @@
+use crate::utils::platform;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::command;
+
+/// 配置档信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileInfo {
+    /// 配置档名称
+    pub name: String,
+    /// 是否为当前激活的配置档
+    pub active: bool,
+    /// 配置档目录
+    pub path: String,
+}
+
+/// 配置档内随之切换的文件
+const PROFILE_FILES: &[&str] = &["openclaw.json", "env"];
+
+/// 校验配置档名称，防止路径穿越
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    if platform::is_valid_profile_name(name) {
+        Ok(())
+    } else {
+        Err(format!(
+            "配置档名称无效: {}（仅支持字母、数字、- 和 _，最长 64 个字符）",
+            name
+        ))
+    }
+}
+
+/// 获取当前配置档名称
+fn active_profile_name() -> String {
+    platform::get_active_profile().unwrap_or_else(|| platform::DEFAULT_PROFILE.to_string())
+}
+
+/// 列出所有配置档（包含默认配置档）
+#[command]
+pub async fn list_profiles() -> Result<Vec<ProfileInfo>, String> {
+    info!("[配置档] 获取配置档列表...");
+    let active = active_profile_name();
+
+    let mut profiles = vec![ProfileInfo {
+        name: platform::DEFAULT_PROFILE.to_string(),
+        active: active == platform::DEFAULT_PROFILE,
+        path: platform::get_config_dir(),
+    }];
+
+    let profiles_dir = platform::get_profiles_dir();
+    if let Ok(entries) = fs::read_dir(&profiles_dir) {
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| platform::is_valid_profile_name(name))
+            .collect();
+        names.sort();
+
+        for name in names {
+            profiles.push(ProfileInfo {
+                active: active == name,
+                path: platform::get_profile_dir(&name),
+                name,
+            });
+        }
+    }
+
+    Ok(profiles)
+}
+
+/// 获取当前配置档
+#[command]
+pub async fn current_profile() -> Result<String, String> {
+    Ok(active_profile_name())
+}
+
+/// 新建配置档：复制当前配置档的 openclaw.json 与 env
+#[command]
+pub async fn create_profile(name: String) -> Result<ProfileInfo, String> {
+    info!("[配置档] 新建配置档: {}", name);
+    validate_profile_name(&name)?;
+    if name == platform::DEFAULT_PROFILE {
+        return Err(format!("{} 为保留名称", platform::DEFAULT_PROFILE));
+    }
+
+    let profile_dir = platform::get_profile_dir(&name);
+    if Path::new(&profile_dir).exists() {
+        return Err(format!("配置档已存在: {}", name));
+    }
+    fs::create_dir_all(&profile_dir).map_err(|e| format!("创建配置档目录失败: {}", e))?;
+
+    let source_dir = platform::get_active_config_dir();
+    for file_name in PROFILE_FILES {
+        let source = Path::new(&source_dir).join(file_name);
+        if source.is_file() {
+            fs::copy(&source, Path::new(&profile_dir).join(file_name))
+                .map_err(|e| format!("复制 {} 失败: {}", file_name, e))?;
+        }
+    }
+
+    info!("[配置档] ✓ 配置档 {} 已创建: {}", name, profile_dir);
+    Ok(ProfileInfo {
+        name,
+        active: false,
+        path: profile_dir,
+    })
+}
+
+/// 切换配置档（default 表示恢复使用配置目录下的默认文件）
+/// 切换后需重启服务才能让 Gateway 读取新配置
+#[command]
+pub async fn switch_profile(name: String) -> Result<String, String> {
+    info!("[配置档] 切换配置档: {}", name);
+    validate_profile_name(&name)?;
+
+    let marker_path = platform::get_active_profile_marker_path();
+    if name == platform::DEFAULT_PROFILE {
+        if Path::new(&marker_path).exists() {
+            fs::remove_file(&marker_path).map_err(|e| format!("切换配置档失败: {}", e))?;
+        }
+    } else {
+        if !Path::new(&platform::get_profile_dir(&name)).is_dir() {
+            return Err(format!("配置档不存在: {}", name));
+        }
+        fs::write(&marker_path, &name).map_err(|e| format!("切换配置档失败: {}", e))?;
+    }
+
+    info!("[配置档] ✓ 当前配置档: {}", name);
+    Ok(format!("已切换到配置档 {}，重启服务后生效", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_profile, current_profile, switch_profile};
+    use crate::commands::config::get_config;
+    use crate::commands::config::tests::{test_env_lock, TempHomeGuard};
+    use crate::utils::{file, platform};
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn config_paths_follow_active_profile() {
+        let _env_lock = test_env_lock();
+        let home_guard = TempHomeGuard::new();
+        let default_path = platform::get_config_file_path();
+        fs::write(&default_path, json!({ "gateway": { "port": 18789 } }).to_string())
+            .expect("应可写入默认配置");
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        for bad_name in ["../evil", "a/b", "", "..", "x y"] {
+            assert!(
+                runtime.block_on(create_profile(bad_name.to_string())).is_err(),
+                "非法名称 {:?} 应被拒绝",
+                bad_name
+            );
+        }
+
+        runtime
+            .block_on(create_profile("work".to_string()))
+            .expect("应可创建配置档");
+        runtime
+            .block_on(switch_profile("work".to_string()))
+            .expect("应可切换配置档");
+        assert_eq!(runtime.block_on(current_profile()).ok().as_deref(), Some("work"));
+
+        let work_path = platform::get_config_file_path();
+        assert_ne!(work_path, default_path);
+        assert!(work_path.contains("profiles"));
+        let copied = runtime.block_on(get_config()).expect("应可读取配置档配置").config;
+        assert_eq!(copied.pointer("/gateway/port"), Some(&json!(18789)));
+
+        fs::write(&work_path, json!({ "gateway": { "port": 19999 } }).to_string())
+            .expect("应可写入配置档");
+        let default_content = file::read_file(&default_path).expect("默认配置应存在");
+        assert!(default_content.contains("18789"), "写入应只影响当前配置档");
+
+        runtime
+            .block_on(switch_profile("default".to_string()))
+            .expect("应可切回默认配置档");
+        assert_eq!(platform::get_config_file_path(), default_path);
+        let restored = runtime.block_on(get_config()).expect("应可读取默认配置").config;
+        assert_eq!(restored.pointer("/gateway/port"), Some(&json!(18789)));
+
+        assert!(runtime.block_on(switch_profile("missing".to_string())).is_err());
+        let _ = home_guard;
+    }
+}
@@ -1,8 +1,10 @@
+use super::{config, process};
 use crate::models::ServiceStatus;
-use crate::utils::shell;
+use crate::utils::{file, platform, shell};
+use serde::{Deserialize, Serialize};
 use tauri::command;
 use std::process::Command;
-use log::{info, debug};
+use log::{info, warn, debug};
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
@@ -11,11 +13,9 @@ use std::os::windows::process::CommandExt;
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-const SERVICE_PORT: u16 = 18789;
-
 /// 检测端口是否有服务在监听，返回 PID
 /// 简单直接：端口被占用 = 服务运行中
-fn check_port_listening(port: u16) -> Option<u32> {
+pub(crate) fn check_port_listening(port: u16) -> Option<u32> {
     #[cfg(unix)]
     {
         let output = Command::new("lsof")
@@ -57,17 +57,54 @@ fn check_port_listening(port: u16) -> Option<u32> {
     }
 }
 
+/// 启动/重启前校验配置文件能否解析，force=true 时跳过
+fn ensure_config_valid(force: bool, action: &str) -> Result<(), String> {
+    if force {
+        info!("[服务] 已指定 force，跳过配置预检");
+        return Ok(());
+    }
+    config::check_config_parses().map_err(|e| {
+        warn!("[服务] 配置预检失败: {}", e);
+        format!("配置无效，已取消{}: {}", action, e)
+    })
+}
+
+/// 查找监听端口且仍然存活的进程 PID
+/// lsof/netstat 可能短暂返回已退出进程的 PID，此时重新探测一次，仍失效则视为未运行
+fn find_live_listener_pid(port: u16) -> Option<u32> {
+    let pid = check_port_listening(port)?;
+    if process::is_pid_alive(pid) {
+        return Some(pid);
+    }
+    warn!("[服务] 端口 {} 对应的 PID {} 已退出，重新探测", port, pid);
+    check_port_listening(port).filter(|&fresh| process::is_pid_alive(fresh))
+}
+
+/// 按大小轮转 gateway 日志；随状态轮询执行，gateway 长时间运行时日志也不会无限增长
+fn rotate_gateway_log_if_needed() {
+    let log_path = platform::get_log_file_path();
+    let max_bytes = platform::get_log_max_bytes();
+    match file::rotate_file_if_needed(&log_path, max_bytes, shell::LOG_ROTATE_KEEP) {
+        Ok(true) => info!("[服务] 日志超过 {} 字节，已轮转: {}", max_bytes, log_path),
+        Ok(false) => {}
+        Err(e) => warn!("[服务] 日志轮转失败: {}", e),
+    }
+}
+
 /// 获取服务状态（简单版：直接检查端口占用）
 #[command]
 pub async fn get_service_status() -> Result<ServiceStatus, String> {
-    // 简单直接：检查端口是否被占用
-    let pid = check_port_listening(SERVICE_PORT);
+    rotate_gateway_log_if_needed();
+
+    // 简单直接：检查端口是否被占用，并确认对应进程仍然存活
+    let port = config::get_gateway_port();
+    let pid = find_live_listener_pid(port);
     let running = pid.is_some();
     
     Ok(ServiceStatus {
         running,
         pid,
-        port: SERVICE_PORT,
+        port,
         uptime_seconds: None,
         memory_mb: None,
         cpu_percent: None,
@@ -75,15 +112,39 @@ pub async fn get_service_status() -> Result<ServiceStatus, String> {
 }
 
 /// 启动服务
+/// 默认严格模式：配置端口被其他进程占用时直接失败；
+/// auto_port=true 时自动寻找后续空闲端口，写回 gateway.port 后启动
 #[command]
-pub async fn start_service() -> Result<String, String> {
+pub async fn start_service(
+    force: Option<bool>,
+    auto_port: Option<bool>,
+) -> Result<String, String> {
     info!("[服务] 启动服务...");
+    ensure_config_valid(force.unwrap_or(false), "启动")?;
     
-    // 检查是否已经运行
-    let status = get_service_status().await?;
-    if status.running {
-        info!("[服务] 服务已在运行中");
-        return Err("服务已在运行中".to_string());
+    // 检查端口是否已被占用：被 OpenClaw 占用说明已在运行，否则为端口冲突
+    let configured_port = config::get_gateway_port();
+    let mut port = configured_port;
+    if let Some(owner) = process::find_port_owner(configured_port) {
+        let owner_desc = format!("{} {}", owner.name, owner.cmdline.as_deref().unwrap_or(""));
+        if owner_desc.to_lowercase().contains("openclaw") {
+            info!("[服务] 服务已在运行中");
+            return Err("服务已在运行中".to_string());
+        }
+        
+        if !auto_port.unwrap_or(false) {
+            info!("[服务] 端口 {} 被 {} (PID {}) 占用", configured_port, owner.name, owner.pid);
+            return Err(format!(
+                "端口 {} 已被 {} (PID {}) 占用，请结束该进程、修改 gateway.port 或启用自动切换端口",
+                configured_port, owner.name, owner.pid
+            ));
+        }
+        
+        port = process::find_free_port(configured_port.saturating_add(1))
+            .await
+            .ok_or_else(|| format!("端口 {} 被占用，且未找到可用的空闲端口", configured_port))?;
+        info!("[服务] 端口 {} 被占用，改用端口 {}", configured_port, port);
+        config::set_gateway_port(port).await?;
     }
     
     // 检查 openclaw 命令是否存在
@@ -94,18 +155,26 @@ pub async fn start_service() -> Result<String, String> {
     }
     info!("[服务] openclaw 路径: {:?}", openclaw_path);
     
+    // 确保配置中已有 Gateway Token，gateway 与 Dashboard 使用同一 Token
+    config::get_or_create_gateway_token().await?;
+
     // 直接后台启动 gateway（不等待 doctor，避免阻塞）
     info!("[服务] 后台启动 gateway...");
-    shell::spawn_openclaw_gateway()
+    shell::spawn_openclaw_gateway(port)
         .map_err(|e| format!("启动服务失败: {}", e))?;
+    let port_note = if port != configured_port {
+        format!("（端口 {} 被占用，已改用 {} 并写入 gateway.port）", configured_port, port)
+    } else {
+        String::new()
+    };
     
     // 轮询等待端口开始监听（最多 15 秒）
-    info!("[服务] 等待端口 {} 开始监听...", SERVICE_PORT);
+    info!("[服务] 等待端口 {} 开始监听...", port);
     for i in 1..=15 {
-        std::thread::sleep(std::time::Duration::from_secs(1));
-        if let Some(pid) = check_port_listening(SERVICE_PORT) {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        if let Some(pid) = check_port_listening(port) {
             info!("[服务] ✓ 启动成功 ({}秒), PID: {}", i, pid);
-            return Ok(format!("服务已启动，PID: {}", pid));
+            return Ok(format!("服务已启动，PID: {}{}", pid, port_note));
         }
         if i % 3 == 0 {
             debug!("[服务] 等待中... ({}秒)", i);
@@ -113,43 +182,134 @@ pub async fn start_service() -> Result<String, String> {
     }
     
     info!("[服务] 等待超时，端口仍未监听");
-    Err("服务启动超时（15秒），请检查 openclaw 日志".to_string())
+    let log_path = platform::get_log_file_path();
+    match file::read_last_lines(&log_path, 20) {
+        Ok(lines) if !lines.is_empty() => Err(format!(
+            "服务启动超时（15秒），最近日志 ({}):\n{}",
+            log_path,
+            lines.join("\n")
+        )),
+        _ => Err("服务启动超时（15秒），请检查 openclaw 日志".to_string()),
+    }
+}
+
+/// 优雅停止的默认等待时间（秒）
+const DEFAULT_STOP_TIMEOUT_SECS: u32 = 10;
+
+/// 在超时时间内轮询，直到 still_running 返回 false（轮询间隔使用异步 sleep，不阻塞运行时）
+/// 返回 true 表示已在超时前停止
+async fn wait_until_stopped<F: FnMut() -> bool>(
+    timeout: std::time::Duration,
+    interval: std::time::Duration,
+    mut still_running: F,
+) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        if !still_running() {
+            return true;
+        }
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(interval).await;
+    }
 }
 
 /// 停止服务
+/// 默认先执行 gateway stop 并等待 Gateway 处理完进行中的消息（最多 timeout_secs 秒），
+/// 超时仍在运行才强制停止；force=true 时跳过等待直接强制停止
+/// kill_all=true 时随后结束所有残留的 gateway 进程，否则只在结果中提示残留进程
 #[command]
-pub async fn stop_service() -> Result<String, String> {
-    info!("[服务] 停止服务...");
+pub async fn stop_service(
+    force: Option<bool>,
+    timeout_secs: Option<u32>,
+    kill_all: Option<bool>,
+) -> Result<String, String> {
+    let force = force.unwrap_or(false);
+    info!("[服务] 停止服务... (force={})", force);
     
     let _ = shell::run_openclaw(&["gateway", "stop"]);
-    std::thread::sleep(std::time::Duration::from_millis(500));
     
-    let status = get_service_status().await?;
-    if !status.running {
-        info!("[服务] ✓ 已停止");
-        return Ok("服务已停止".to_string());
-    }
+    let port = config::get_gateway_port();
+    let stopped = if force {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        !get_service_status().await?.running
+    } else {
+        let timeout = timeout_secs.unwrap_or(DEFAULT_STOP_TIMEOUT_SECS);
+        info!("[服务] 等待 Gateway 退出（最多 {} 秒）...", timeout);
+        // 优先使用 health 探测判断是否仍在提供服务，找不到 openclaw 时退回端口检查
+        let has_cli = shell::get_openclaw_path().is_some();
+        wait_until_stopped(
+            std::time::Duration::from_secs(timeout as u64),
+            std::time::Duration::from_millis(500),
+            || {
+                if has_cli {
+                    process::gateway_health_ok()
+                } else {
+                    check_port_listening(port).is_some()
+                }
+            },
+        )
+        .await
+    };
     
-    // 尝试强制停止
-    let _ = shell::run_openclaw(&["gateway", "stop", "--force"]);
-    std::thread::sleep(std::time::Duration::from_millis(500));
+    if !stopped {
+        // 尝试强制停止
+        info!("[服务] Gateway 仍在运行，强制停止...");
+        let _ = shell::run_openclaw(&["gateway", "stop", "--force"]);
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
     
+    let leftover_note = stop_leftover_gateways(kill_all.unwrap_or(false)).await;
     let status = get_service_status().await?;
     if status.running {
-        Err(format!("无法停止服务，PID: {:?}", status.pid))
+        Err(format!("无法停止服务，PID: {:?}{}", status.pid, leftover_note))
     } else {
         info!("[服务] ✓ 已停止");
-        Ok("服务已停止".to_string())
+        Ok(format!("服务已停止{}", leftover_note))
+    }
+}
+
+/// 处理 gateway stop 之后仍残留的 gateway 进程，返回附加到结果中的说明
+/// kill_all=true 时逐个结束，否则只列出 PID 供用户确认
+async fn stop_leftover_gateways(kill_all: bool) -> String {
+    let leftovers: Vec<u32> = process::find_gateway_processes().iter().map(|p| p.pid).collect();
+    if leftovers.is_empty() {
+        return String::new();
+    }
+    if !kill_all {
+        warn!("[服务] 仍有 gateway 进程残留: {:?}", leftovers);
+        return format!(
+            "，另有 {} 个 gateway 进程仍在运行（PID: {:?}），可选择结束全部实例",
+            leftovers.len(),
+            leftovers
+        );
+    }
+    
+    let mut failed = Vec::new();
+    for pid in &leftovers {
+        info!("[服务] 结束残留 gateway 进程 PID {}...", pid);
+        if let Err(e) = process::terminate_process(*pid).await {
+            warn!("[服务] 结束 PID {} 失败: {}", pid, e);
+            failed.push(*pid);
+        }
+    }
+    if failed.is_empty() {
+        format!("，并结束了 {} 个残留 gateway 进程", leftovers.len())
+    } else {
+        format!("，残留 gateway 进程未能全部结束（PID: {:?}）", failed)
     }
 }
 
 /// 重启服务
 #[command]
-pub async fn restart_service() -> Result<String, String> {
+pub async fn restart_service(force: Option<bool>) -> Result<String, String> {
     info!("[服务] 重启服务...");
+    // 配置无法解析时保留旧实例，避免停掉后无法再启动
+    ensure_config_valid(force.unwrap_or(false), "重启")?;
     
     let _ = shell::run_openclaw(&["gateway", "restart"]);
-    std::thread::sleep(std::time::Duration::from_secs(2));
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
     
     let status = get_service_status().await?;
     if status.running {
@@ -157,21 +317,146 @@ pub async fn restart_service() -> Result<String, String> {
         Ok(format!("服务已重启，PID: {:?}", status.pid))
     } else {
         // 手动停止再启动
-        let _ = stop_service().await;
-        std::thread::sleep(std::time::Duration::from_secs(1));
-        start_service().await
+        let _ = stop_service(Some(true), None, None).await;
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        start_service(Some(true), None).await
     }
 }
 
+/// 日志分页
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogPage {
+    pub lines: Vec<String>,
+    /// 本页第一行在日志文件中的字节偏移，作为下一次 before_offset 向前翻页；为 0 表示已到开头
+    pub start_offset: u64,
+    /// 日志文件总字节数
+    pub total_bytes: u64,
+    /// 是否还能以 start_offset 作为 before_offset 继续向前翻页
+    pub has_more: bool,
+}
+
 /// 获取日志
+/// gateway 输出日志文件存在时始终从文件分页读取（before_offset 为空表示从末尾开始）；
+/// 文件不存在时回退到 openclaw logs，CLI 输出与文件偏移无关，不支持翻页
 #[command]
-pub async fn get_logs(lines: Option<u32>) -> Result<Vec<String>, String> {
+pub async fn get_logs(lines: Option<u32>, before_offset: Option<u64>) -> Result<LogPage, String> {
     let n = lines.unwrap_or(100);
-    
-    match shell::run_openclaw(&["logs", "--lines", &n.to_string()]) {
-        Ok(output) => {
-            Ok(output.lines().map(|s| s.to_string()).collect())
-        }
-        Err(e) => Err(format!("读取日志失败: {}", e))
+    let log_path = platform::get_log_file_path();
+
+    if std::path::Path::new(&log_path).exists() {
+        return file::read_lines_page(&log_path, n as usize, before_offset)
+            .map(|page| LogPage {
+                lines: page.lines,
+                start_offset: page.start_offset,
+                total_bytes: page.total_bytes,
+                has_more: page.has_more,
+            })
+            .map_err(|e| format!("读取日志失败: {}", e));
+    }
+    if before_offset.is_some() {
+        return Err("日志文件不存在，无法翻页".to_string());
+    }
+
+    debug!("[服务] 日志文件不存在，使用 openclaw logs: {}", log_path);
+    let output = shell::run_openclaw(&["logs", "--lines", &n.to_string()])
+        .map_err(|e| format!("读取日志失败: {}", e))?;
+    let lines = output.lines().map(|s| s.to_string()).collect();
+    Ok(LogPage {
+        lines,
+        start_offset: 0,
+        total_bytes: output.len() as u64,
+        has_more: false,
+    })
+}
+
+/// 清空日志（截断而非删除，保证运行中的 gateway 文件句柄仍然有效）
+#[command]
+pub async fn clear_logs() -> Result<String, String> {
+    let log_path = platform::get_log_file_path();
+    info!("[服务] 清空日志: {}", log_path);
+
+    file::truncate_file(&log_path).map_err(|e| format!("清空日志失败: {}", e))?;
+    Ok("日志已清空".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_logs, restart_service, start_service, wait_until_stopped};
+    use crate::commands::config;
+    use crate::commands::config::tests::{test_env_lock, EnvGuard, TempHomeGuard};
+    use crate::utils::platform;
+    use std::time::Duration;
+
+    #[test]
+    fn logs_are_paged_from_the_log_file_when_it_exists() {
+        let _lock = test_env_lock();
+        let home = TempHomeGuard::new();
+        let config_dir = home.temp_home_dir.join(".openclaw");
+        let _dir = EnvGuard::set("OPENCLAW_CONFIG_DIR", &config_dir.display().to_string());
+        let content: String = (0..5).map(|i| format!("line-{}\n", i)).collect();
+        std::fs::write(platform::get_log_file_path(), &content).unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let last = runtime.block_on(get_logs(Some(2), None)).unwrap();
+        assert_eq!(last.lines, vec!["line-3", "line-4"]);
+        assert!(last.has_more);
+        assert_eq!(last.total_bytes, content.len() as u64);
+
+        let first = runtime.block_on(get_logs(Some(10), Some(last.start_offset))).unwrap();
+        assert_eq!(first.lines, vec!["line-0", "line-1", "line-2"]);
+        assert!(!first.has_more);
+    }
+
+    #[test]
+    fn stop_wait_returns_once_gateway_exits() {
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let mut polls = 0;
+        let stopped = runtime.block_on(wait_until_stopped(
+            Duration::from_secs(5),
+            Duration::from_millis(1),
+            || {
+                polls += 1;
+                polls < 3
+            },
+        ));
+        assert!(stopped);
+        assert_eq!(polls, 3);
+    }
+
+    #[test]
+    fn stop_wait_gives_up_after_timeout() {
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let mut polls = 0;
+        let stopped = runtime.block_on(wait_until_stopped(
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+            || {
+                polls += 1;
+                true
+            },
+        ));
+        assert!(!stopped);
+        assert!(polls >= 2, "超时前应多次探测: {}", polls);
+    }
+
+    #[test]
+    fn restart_is_refused_when_config_does_not_parse() {
+        let _env_lock = test_env_lock();
+        let _home = TempHomeGuard::new();
+
+        assert!(config::check_config_parses().is_ok(), "配置文件不存在时不应阻止启动");
+
+        let config_path = platform::get_config_file_path();
+        std::fs::write(&config_path, "{ \"gateway\": { \"port\": 18789, ").expect("写入损坏配置");
+        assert!(config::check_config_parses().is_err());
+
+        let runtime = tokio::runtime::Runtime::new().expect("创建运行时");
+        let err = runtime.block_on(restart_service(None)).expect_err("配置无效时应拒绝重启");
+        assert!(err.contains("配置无效，已取消重启"), "{}", err);
+        let err = runtime.block_on(start_service(None, None)).expect_err("配置无效时应拒绝启动");
+        assert!(err.contains("配置无效，已取消启动"), "{}", err);
+
+        std::fs::write(&config_path, "{ gateway: { port: 18789 }, }").expect("写入 JSON5 配置");
+        assert!(config::check_config_parses().is_ok());
     }
 }
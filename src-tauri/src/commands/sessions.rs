@@ -0,0 +1,598 @@
+use super::config;
+use crate::models::ModelCostConfig;
+use crate::utils::platform;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::command;
+
+/// 会话清理时永不删除的文件名：配置、环境变量与 openclaw 的会话索引
+const PROTECTED_FILE_NAMES: &[&str] = &["openclaw.json", "env", "sessions.json"];
+
+/// 路径中包含该目录名时一律跳过（渠道凭证等）
+const PROTECTED_DIR_NAMES: &[&str] = &["credentials"];
+
+/// 单个 Agent 的会话占用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentSessionsInfo {
+    pub agent: String,
+    pub file_count: u64,
+    pub total_bytes: u64,
+}
+
+/// 会话目录占用汇总（~/.openclaw/agents/*/sessions）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionsInfo {
+    pub root: String,
+    pub file_count: u64,
+    pub total_bytes: u64,
+    pub agents: Vec<AgentSessionsInfo>,
+}
+
+/// 会话清理结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionCleanupResult {
+    /// 未确认时只统计、不删除
+    pub dry_run: bool,
+    pub deleted_files: u64,
+    pub bytes_freed: u64,
+    /// 删除失败的文件及原因
+    pub errors: Vec<String>,
+}
+
+/// 单个模型的用量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelUsage {
+    /// provider/model（会话中缺少模型信息时为 unknown）
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub estimated_cost: f64,
+    /// 是否在配置中找到该模型的 cost（未找到时费用按 0 计）
+    pub priced: bool,
+}
+
+/// 用量统计（来自 ~/.openclaw/agents/*/sessions 下的会话记录）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub period: String,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    /// 按配置中的 cost（每百万 Token 单价）估算
+    pub estimated_cost: f64,
+    /// 按总 Token 数降序
+    pub by_model: Vec<ModelUsage>,
+    /// 无法读取或格式无法识别而跳过的文件数
+    pub skipped_files: u64,
+}
+
+/// 会话文件
+#[derive(Debug)]
+struct SessionFile {
+    path: PathBuf,
+    agent: String,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// 是否为受保护的文件（按文件名或所在目录判断）
+fn is_protected(path: &Path) -> bool {
+    let name_protected = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| PROTECTED_FILE_NAMES.contains(&n))
+        .unwrap_or(true);
+    name_protected
+        || path.components().any(|c| {
+            c.as_os_str()
+                .to_str()
+                .map(|s| PROTECTED_DIR_NAMES.contains(&s))
+                .unwrap_or(false)
+        })
+}
+
+/// 非符号链接的目录
+fn is_real_dir(path: &Path) -> bool {
+    std::fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_dir())
+        .unwrap_or(false)
+}
+
+/// 收集 config_dir/agents/*/sessions 下的会话文件
+/// 不跟随任何符号链接，并校验每个文件都位于 config_dir 内；受保护的文件不会被收集
+fn collect_session_files(config_dir: &Path) -> Result<Vec<SessionFile>, String> {
+    let agents_dir = config_dir.join("agents");
+    if !is_real_dir(&agents_dir) {
+        return Ok(Vec::new());
+    }
+    let root = config_dir
+        .canonicalize()
+        .map_err(|e| format!("解析配置目录失败: {}", e))?;
+
+    let mut files = Vec::new();
+    let agents = std::fs::read_dir(&agents_dir).map_err(|e| format!("读取 agents 目录失败: {}", e))?;
+    for agent_entry in agents.flatten() {
+        let agent = agent_entry.file_name().to_string_lossy().to_string();
+        let sessions_dir = agent_entry.path().join("sessions");
+        if !is_real_dir(&agent_entry.path()) || !is_real_dir(&sessions_dir) {
+            continue;
+        }
+
+        let mut pending = vec![sessions_dir];
+        while let Some(dir) = pending.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("[会话清理] 读取目录失败 {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let metadata = match std::fs::symlink_metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                };
+                let file_type = metadata.file_type();
+                if file_type.is_symlink() || is_protected(&path) {
+                    continue;
+                }
+                if file_type.is_dir() {
+                    pending.push(path);
+                } else if file_type.is_file() {
+                    // 防御性校验：目录本身被替换为指向外部的链接时不处理
+                    let inside = path
+                        .canonicalize()
+                        .map(|p| p.starts_with(&root))
+                        .unwrap_or(false);
+                    if !inside {
+                        continue;
+                    }
+                    files.push(SessionFile {
+                        path,
+                        agent: agent.clone(),
+                        size: metadata.len(),
+                        modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    });
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// 汇总会话文件占用（按 Agent 名排序）
+fn summarize_sessions(config_dir: &Path) -> Result<SessionsInfo, String> {
+    let files = collect_session_files(config_dir)?;
+    let mut agents: Vec<AgentSessionsInfo> = Vec::new();
+    for file in &files {
+        match agents.iter_mut().find(|a| a.agent == file.agent) {
+            Some(info) => {
+                info.file_count += 1;
+                info.total_bytes += file.size;
+            }
+            None => agents.push(AgentSessionsInfo {
+                agent: file.agent.clone(),
+                file_count: 1,
+                total_bytes: file.size,
+            }),
+        }
+    }
+    agents.sort_by(|a, b| a.agent.cmp(&b.agent));
+
+    Ok(SessionsInfo {
+        root: config_dir.join("agents").display().to_string(),
+        file_count: files.len() as u64,
+        total_bytes: files.iter().map(|f| f.size).sum(),
+        agents,
+    })
+}
+
+/// 删除修改时间早于 now - older_than 的会话文件；dry_run 时只统计
+fn remove_old_sessions(
+    config_dir: &Path,
+    older_than: Duration,
+    now: SystemTime,
+    dry_run: bool,
+) -> Result<SessionCleanupResult, String> {
+    let cutoff = now.checked_sub(older_than).unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut result = SessionCleanupResult {
+        dry_run,
+        deleted_files: 0,
+        bytes_freed: 0,
+        errors: Vec::new(),
+    };
+
+    for file in collect_session_files(config_dir)? {
+        if file.modified >= cutoff {
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = std::fs::remove_file(&file.path) {
+                result.errors.push(format!("{}: {}", file.path.display(), e));
+                continue;
+            }
+        }
+        result.deleted_files += 1;
+        result.bytes_freed += file.size;
+    }
+    Ok(result)
+}
+
+/// 统计周期
+fn usage_period_duration(period: &str) -> Result<Option<Duration>, String> {
+    let days = match period {
+        "day" => 1,
+        "week" => 7,
+        "month" => 30,
+        "all" => return Ok(None),
+        other => return Err(format!("不支持的统计周期: {}（可选 day/week/month/all）", other)),
+    };
+    Ok(Some(Duration::from_secs(days * 24 * 60 * 60)))
+}
+
+/// 读取 models.providers.*.models[].cost，键为 provider/model
+fn model_cost_table(config: &Value) -> HashMap<String, ModelCostConfig> {
+    let mut table = HashMap::new();
+    let Some(providers) = config.pointer("/models/providers").and_then(|v| v.as_object()) else {
+        return table;
+    };
+    for (provider, provider_config) in providers {
+        let models = provider_config.get("models").and_then(|v| v.as_array());
+        for model in models.into_iter().flatten() {
+            let (Some(id), Some(cost)) = (
+                model.get("id").and_then(|v| v.as_str()),
+                config::parse_model_cost(model.get("cost")),
+            ) else {
+                continue;
+            };
+            table.insert(format!("{}/{}", provider, id), cost);
+        }
+    }
+    table
+}
+
+/// 会话记录中的一次用量
+#[derive(Debug, Default, PartialEq)]
+struct UsageRecord {
+    model: String,
+    input: u64,
+    output: u64,
+    cache_read: u64,
+    cache_write: u64,
+}
+
+/// 解析记录时间（RFC 3339 字符串或毫秒时间戳）
+fn record_time(value: Option<&Value>) -> Option<SystemTime> {
+    match value? {
+        Value::String(s) => chrono::DateTime::parse_from_rfc3339(s).ok().map(SystemTime::from),
+        Value::Number(n) => Some(SystemTime::UNIX_EPOCH + Duration::from_millis(n.as_u64()?)),
+        _ => None,
+    }
+}
+
+/// 解析会话 JSONL 中的一行：用量位于 message.usage 或顶层 usage
+/// 兼容 input/output/cacheRead/cacheWrite 与 Anthropic、OpenAI 风格的字段名
+fn parse_usage_line(line: &Value) -> Option<(UsageRecord, Option<SystemTime>)> {
+    let message = line.get("message").filter(|m| m.is_object()).unwrap_or(line);
+    let usage = message.get("usage").or_else(|| line.get("usage"))?.as_object()?;
+    let tokens = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| usage.get(*key).and_then(|v| v.as_u64()))
+            .unwrap_or(0)
+    };
+    let text = |key: &str| {
+        message
+            .get(key)
+            .or_else(|| line.get(key))
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+    };
+    let model = match (text("provider"), text("model")) {
+        (Some(provider), Some(model)) if !model.contains('/') => format!("{}/{}", provider, model),
+        (_, Some(model)) => model.to_string(),
+        _ => "unknown".to_string(),
+    };
+    let record = UsageRecord {
+        model,
+        input: tokens(&["input", "input_tokens", "prompt_tokens"]),
+        output: tokens(&["output", "output_tokens", "completion_tokens"]),
+        cache_read: tokens(&["cacheRead", "cache_read_input_tokens"]),
+        cache_write: tokens(&["cacheWrite", "cache_creation_input_tokens"]),
+    };
+    let time =
+        record_time(line.get("timestamp")).or_else(|| record_time(message.get("timestamp")));
+    Some((record, time))
+}
+
+/// 汇总会话文件中的用量；cutoff 之前的记录不计入（记录没有时间时使用文件修改时间）
+fn compute_usage_stats(
+    config_dir: &Path,
+    costs: &HashMap<String, ModelCostConfig>,
+    period: &str,
+    cutoff: Option<SystemTime>,
+) -> Result<UsageStats, String> {
+    let mut by_model: HashMap<String, ModelUsage> = HashMap::new();
+    let mut skipped_files = 0;
+
+    for file in collect_session_files(config_dir)? {
+        if file.path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        if cutoff.is_some_and(|cutoff| file.modified < cutoff) {
+            continue;
+        }
+        let content = match std::fs::read_to_string(&file.path) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("[用量统计] 跳过无法读取的会话文件 {}: {}", file.path.display(), e);
+                skipped_files += 1;
+                continue;
+            }
+        };
+        let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+        let parsed: Vec<Value> =
+            lines.iter().filter_map(|l| serde_json::from_str(l).ok()).collect();
+        if !lines.is_empty() && parsed.is_empty() {
+            warn!("[用量统计] 跳过格式无法识别的会话文件: {}", file.path.display());
+            skipped_files += 1;
+            continue;
+        }
+
+        for (record, time) in parsed.iter().filter_map(parse_usage_line) {
+            if let Some(cutoff) = cutoff {
+                if time.unwrap_or(file.modified) < cutoff {
+                    continue;
+                }
+            }
+            let cost = costs.get(&record.model);
+            let entry = by_model.entry(record.model.clone()).or_insert_with(|| ModelUsage {
+                model: record.model.clone(),
+                input_tokens: 0,
+                output_tokens: 0,
+                cache_read_tokens: 0,
+                cache_write_tokens: 0,
+                estimated_cost: 0.0,
+                priced: cost.is_some(),
+            });
+            entry.input_tokens += record.input;
+            entry.output_tokens += record.output;
+            entry.cache_read_tokens += record.cache_read;
+            entry.cache_write_tokens += record.cache_write;
+            if let Some(cost) = cost {
+                // cost 为每百万 Token 的单价
+                entry.estimated_cost += (record.input as f64 * cost.input
+                    + record.output as f64 * cost.output
+                    + record.cache_read as f64 * cost.cache_read
+                    + record.cache_write as f64 * cost.cache_write)
+                    / 1_000_000.0;
+            }
+        }
+    }
+
+    let mut by_model: Vec<ModelUsage> = by_model.into_values().collect();
+    by_model.sort_by(|a, b| {
+        (b.input_tokens + b.output_tokens)
+            .cmp(&(a.input_tokens + a.output_tokens))
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    Ok(UsageStats {
+        period: period.to_string(),
+        total_input_tokens: by_model.iter().map(|m| m.input_tokens).sum(),
+        total_output_tokens: by_model.iter().map(|m| m.output_tokens).sum(),
+        estimated_cost: by_model.iter().map(|m| m.estimated_cost).sum(),
+        by_model,
+        skipped_files,
+    })
+}
+
+/// 获取会话文件占用（~/.openclaw/agents/*/sessions）
+#[command]
+pub async fn get_sessions_info() -> Result<SessionsInfo, String> {
+    let config_dir = PathBuf::from(platform::get_config_dir());
+    summarize_sessions(&config_dir)
+}
+
+/// 清理超过 older_than_days 天未修改的会话文件
+/// confirm 不为 true 时只返回将被删除的文件数与可释放空间，不做删除
+#[command]
+pub async fn clear_old_sessions(
+    older_than_days: u32,
+    confirm: Option<bool>,
+) -> Result<SessionCleanupResult, String> {
+    if older_than_days == 0 {
+        return Err("清理天数必须大于 0".to_string());
+    }
+    let dry_run = !confirm.unwrap_or(false);
+    info!(
+        "[会话清理] 清理 {} 天前的会话文件{}...",
+        older_than_days,
+        if dry_run { "（预览）" } else { "" }
+    );
+
+    let config_dir = PathBuf::from(platform::get_config_dir());
+    let older_than = Duration::from_secs(u64::from(older_than_days) * 24 * 60 * 60);
+    let result = remove_old_sessions(&config_dir, older_than, SystemTime::now(), dry_run)?;
+    info!(
+        "[会话清理] {} 个文件，{} 字节{}",
+        result.deleted_files,
+        result.bytes_freed,
+        if dry_run { "可释放" } else { "已释放" }
+    );
+    Ok(result)
+}
+
+/// 统计 Token 用量与估算费用
+/// period 可选 day / week / month / all（默认 all），费用按 models.providers 中各模型的 cost 估算
+#[command]
+pub async fn get_usage_stats(period: Option<String>) -> Result<UsageStats, String> {
+    let period = period.unwrap_or_else(|| "all".to_string());
+    let cutoff = usage_period_duration(&period)?
+        .map(|duration| SystemTime::now().checked_sub(duration).unwrap_or(SystemTime::UNIX_EPOCH));
+    info!("[用量统计] 统计 {} 的 Token 用量...", period);
+
+    let config_dir = PathBuf::from(platform::get_config_dir());
+    let costs = model_cost_table(&config::load_openclaw_config()?);
+    let stats = compute_usage_stats(&config_dir, &costs, &period, cutoff)?;
+    info!(
+        "[用量统计] 输入 {} / 输出 {} Token，估算费用 {:.4}，跳过 {} 个文件",
+        stats.total_input_tokens,
+        stats.total_output_tokens,
+        stats.estimated_cost,
+        stats.skipped_files
+    );
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        compute_usage_stats, model_cost_table, remove_old_sessions, summarize_sessions,
+        AgentSessionsInfo,
+    };
+    use crate::commands::config::tests::unique_temp_dir;
+    use serde_json::json;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    fn write_with_age(path: &Path, content: &str, age_days: u64) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age_days * 24 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn old_sessions_are_removed_without_touching_protected_files() {
+        let dir = unique_temp_dir("sessions");
+        let outside = dir.join("outside");
+        let config_dir = dir.join(".openclaw");
+        let sessions = config_dir.join("agents/main/sessions");
+
+        write_with_age(&sessions.join("old.jsonl"), "0123456789", 40);
+        write_with_age(&sessions.join("archive/older.jsonl"), "01234", 90);
+        write_with_age(&sessions.join("recent.jsonl"), "abc", 1);
+        write_with_age(&sessions.join("sessions.json"), "{}", 90);
+        write_with_age(&config_dir.join("agents/work/sessions/old.jsonl"), "xy", 60);
+        write_with_age(&config_dir.join("agents/work/sessions/credentials/token"), "secret", 90);
+        write_with_age(&config_dir.join("openclaw.json"), "{}", 90);
+        write_with_age(&config_dir.join("env"), "KEY=1", 90);
+        write_with_age(&outside.join("victim.jsonl"), "keep me", 90);
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(outside.join("victim.jsonl"), sessions.join("link.jsonl"))
+                .unwrap();
+            std::os::unix::fs::symlink(&outside, sessions.join("linked-dir")).unwrap();
+        }
+
+        let info = summarize_sessions(&config_dir).unwrap();
+        assert_eq!(info.file_count, 4);
+        assert_eq!(info.total_bytes, 20);
+        assert_eq!(
+            info.agents,
+            vec![
+                AgentSessionsInfo { agent: "main".to_string(), file_count: 3, total_bytes: 18 },
+                AgentSessionsInfo { agent: "work".to_string(), file_count: 1, total_bytes: 2 },
+            ]
+        );
+
+        let thirty_days = Duration::from_secs(30 * 24 * 60 * 60);
+        let now = SystemTime::now();
+        let preview = remove_old_sessions(&config_dir, thirty_days, now, true).unwrap();
+        assert!(preview.dry_run);
+        assert_eq!((preview.deleted_files, preview.bytes_freed), (3, 17));
+        assert!(sessions.join("old.jsonl").exists());
+
+        let result = remove_old_sessions(&config_dir, thirty_days, now, false).unwrap();
+        assert_eq!((result.deleted_files, result.bytes_freed), (3, 17));
+        assert!(result.errors.is_empty());
+        assert!(!sessions.join("old.jsonl").exists());
+        assert!(!sessions.join("archive/older.jsonl").exists());
+        assert!(sessions.join("recent.jsonl").exists());
+        assert!(sessions.join("sessions.json").exists());
+        assert!(config_dir.join("agents/work/sessions/credentials/token").exists());
+        assert!(config_dir.join("openclaw.json").exists());
+        assert!(config_dir.join("env").exists());
+        assert!(outside.join("victim.jsonl").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn usage_stats_aggregate_tokens_and_cost_per_model() {
+        let dir = unique_temp_dir("usage");
+        let config_dir = dir.join(".openclaw");
+        let main = config_dir.join("agents/main/sessions");
+        let work = config_dir.join("agents/work/sessions");
+        let recent = chrono::Utc::now().to_rfc3339();
+        let old = "2020-01-01T00:00:00Z";
+
+        let main_session = [
+            json!({ "type": "session", "id": "s1", "timestamp": recent }),
+            json!({ "type": "message", "timestamp": recent, "message": {
+                "role": "assistant", "provider": "anthropic", "model": "claude-sonnet",
+                "usage": { "input": 1000, "output": 500, "cacheRead": 2000, "cacheWrite": 0 }
+            }}),
+            json!({ "type": "message", "timestamp": old, "message": {
+                "role": "assistant", "provider": "anthropic", "model": "claude-sonnet",
+                "usage": { "input": 3000, "output": 1500 }
+            }}),
+        ];
+        let work_session = [json!({
+            "timestamp": recent, "model": "openai/gpt-4o",
+            "usage": { "prompt_tokens": 200, "completion_tokens": 100 }
+        })];
+        let to_jsonl = |lines: &[serde_json::Value]| {
+            lines.iter().map(|l| format!("{}\n", l)).collect::<String>() + "not json\n"
+        };
+        std::fs::create_dir_all(&main).unwrap();
+        std::fs::create_dir_all(&work).unwrap();
+        std::fs::write(main.join("s1.jsonl"), to_jsonl(&main_session)).unwrap();
+        std::fs::write(work.join("s2.jsonl"), to_jsonl(&work_session)).unwrap();
+        std::fs::write(work.join("broken.jsonl"), "<html>not a session</html>\n").unwrap();
+        std::fs::write(work.join("notes.txt"), "ignored").unwrap();
+
+        let config = json!({ "models": { "providers": {
+            "anthropic": { "models": [
+                { "id": "claude-sonnet", "cost": { "input": 3, "output": 15, "cacheRead": 0.3 } }
+            ] },
+            "openai": { "models": [{ "id": "gpt-4o" }] }
+        } } });
+        let costs = model_cost_table(&config);
+        assert_eq!(costs.len(), 1);
+
+        let all = compute_usage_stats(&config_dir, &costs, "all", None).unwrap();
+        assert_eq!((all.total_input_tokens, all.total_output_tokens), (4200, 2100));
+        assert_eq!(all.skipped_files, 1);
+        assert_eq!(all.by_model.len(), 2);
+        let claude = &all.by_model[0];
+        assert_eq!(claude.model, "anthropic/claude-sonnet");
+        assert_eq!(
+            (claude.input_tokens, claude.output_tokens, claude.cache_read_tokens),
+            (4000, 2000, 2000)
+        );
+        // 4000*3 + 2000*15 + 2000*0.3 = 42600 / 1e6
+        assert!((claude.estimated_cost - 0.0426).abs() < 1e-9);
+        let gpt = &all.by_model[1];
+        assert_eq!(gpt.model, "openai/gpt-4o");
+        assert_eq!((gpt.input_tokens, gpt.priced), (200, false));
+        assert!((all.estimated_cost - 0.0426).abs() < 1e-9);
+
+        let week_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(7 * 86400);
+        let week = compute_usage_stats(&config_dir, &costs, "week", Some(week_ago)).unwrap();
+        assert_eq!((week.total_input_tokens, week.total_output_tokens), (1200, 600));
+        assert!((week.by_model[0].estimated_cost - 0.0111).abs() < 1e-9);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
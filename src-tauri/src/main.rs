@@ -8,7 +8,34 @@ mod commands;
 mod models;
 mod utils;
 
-use commands::{config, diagnostics, installer, process, service};
+use commands::{
+    audit, autostart, config, credentials, diagnostics, installer, process, profile, service,
+    sessions,
+};
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::Emitter;
+
+/// 包装 Tauri 命令分发：修改类命令在执行前写入审计日志（用户记为 local）
+fn with_audit_log<F>(handler: F) -> impl Fn(Invoke) -> bool + Send + Sync + 'static
+where
+    F: Fn(Invoke) -> bool + Send + Sync + 'static,
+{
+    move |invoke: Invoke| {
+        let args = match invoke.message.payload() {
+            InvokeBody::Json(args) => args.clone(),
+            _ => serde_json::Value::Null,
+        };
+        audit::record_command(None, invoke.message.command(), &args, None);
+        handler(invoke)
+    }
+}
+
+/// 以命令表展开 Tauri 的命令分发
+macro_rules! tauri_handler {
+    ($($module:ident::$command:ident),* $(,)?) => {
+        tauri::generate_handler![$($module::$command),*]
+    };
+}
 
 fn main() {
     // 初始化日志 - 默认显示 info 级别日志
@@ -23,65 +50,15 @@ fn main() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_notification::init())
-        .invoke_handler(tauri::generate_handler![
-            // 服务管理
-            service::get_service_status,
-            service::start_service,
-            service::stop_service,
-            service::restart_service,
-            service::get_logs,
-            // 进程管理
-            process::check_openclaw_installed,
-            process::get_openclaw_version,
-            process::check_port_in_use,
-            config::get_config,
-            config::save_config,
-            config::preview_config_change,
-            config::apply_config_change,
-            config::list_config_backups,
-            config::rollback_config,
-            config::get_agents_list,
-
-            config::save_agents_list,
-            config::get_bindings,
-            config::save_bindings,
-            config::get_env_value,
-            config::save_env_value,
-            config::get_ai_providers,
-            config::get_channels_config,
-            config::save_channel_config,
-            config::clear_channel_config,
-
-            config::get_or_create_gateway_token,
-            config::get_dashboard_url,
-            // AI 配置管理
-            config::get_official_providers,
-            config::get_ai_config,
-            config::save_provider,
-            config::delete_provider,
-            config::set_primary_model,
-            config::add_available_model,
-            config::remove_available_model,
-            // 飞书插件管理
-            config::check_feishu_plugin,
-            config::install_feishu_plugin,
-            // 诊断测试
-            diagnostics::run_doctor,
-            diagnostics::test_ai_connection,
-            diagnostics::test_channel,
-            diagnostics::get_system_info,
-            diagnostics::start_channel_login,
-            // 安装器
-            installer::check_environment,
-            installer::install_nodejs,
-            installer::install_openclaw,
-            installer::init_openclaw_config,
-            installer::open_install_terminal,
-            installer::uninstall_openclaw,
-            // 版本更新
-            installer::check_openclaw_update,
-            installer::update_openclaw,
-        ])
+        .setup(|app| {
+            // 配置文件被外部修改时通知前端提示重新加载
+            let handle = app.handle().clone();
+            config::start_config_watcher(Some(Box::new(move |status| {
+                let _ = handle.emit("config-changed", status);
+            })));
+            Ok(())
+        })
+        .invoke_handler(with_audit_log(commands::command_table!(tauri_handler)))
         .run(tauri::generate_context!())
         .expect("运行 Tauri 应用时发生错误");
 }
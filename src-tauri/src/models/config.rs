@@ -1,115 +1,153 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// 判断字段是否为默认值（序列化时跳过空结构，避免回写出多余的空对象）
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// 兼容字符串与字符串数组两种写法（如 input: "image"）
+fn string_or_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match StringOrList::deserialize(deserializer)? {
+        StringOrList::One(value) => vec![value],
+        StringOrList::Many(values) => values,
+    })
+}
+
 /// OpenClaw 完整配置 - 对应 openclaw.json 结构
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct OpenClawConfig {
     /// Agent 配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub agents: AgentsConfig,
     /// 模型配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub models: ModelsConfig,
     /// 网关配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub gateway: GatewayConfig,
     /// 渠道配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub channels: HashMap<String, ChannelProviderConfig>,
     /// 插件配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub plugins: PluginsConfig,
     /// 路由绑定配置（支持数组与对象两种常见写法）
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub bindings: Option<BindingsConfig>,
     /// 工具配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tools: Option<serde_json::Value>,
     /// 消息配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub messages: Option<serde_json::Value>,
     /// 命令配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub commands: Option<serde_json::Value>,
     /// Web 配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub web: Option<serde_json::Value>,
     /// 发现配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub discovery: Option<serde_json::Value>,
     /// 元数据
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub meta: MetaConfig,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// Agent 配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct AgentsConfig {
     /// 默认配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub defaults: AgentDefaults,
     /// Agent 列表（兼容官方 agents.list）
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub list: Vec<AgentEntry>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// Agent 默认配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct AgentDefaults {
     /// 模型配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub model: AgentModelConfig,
     /// 可用模型列表 (provider/model -> {})
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub models: HashMap<String, serde_json::Value>,
     /// 压缩配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub compaction: Option<serde_json::Value>,
     /// 上下文裁剪
-    #[serde(rename = "contextPruning", default)]
+    #[serde(rename = "contextPruning", default, skip_serializing_if = "Option::is_none")]
     pub context_pruning: Option<serde_json::Value>,
     /// 心跳配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub heartbeat: Option<serde_json::Value>,
     /// 最大并发数
-    #[serde(rename = "maxConcurrent", default)]
+    #[serde(rename = "maxConcurrent", default, skip_serializing_if = "Option::is_none")]
     pub max_concurrent: Option<u32>,
     /// 子代理配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub subagents: Option<serde_json::Value>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// Agent 模型配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct AgentModelConfig {
     /// 主模型 (格式: provider/model-id)
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub primary: Option<String>,
+    /// 备用模型列表 (格式: provider/model-id)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallbacks: Option<Vec<String>>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// Agent 列表项（强类型 + flatten 兼容未知字段）
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct AgentEntry {
     /// Agent 唯一标识
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     /// 显示名称
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     /// 是否默认 Agent
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub default: Option<bool>,
     /// 工作目录
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workspace: Option<String>,
     /// Agent 模型配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<serde_json::Value>,
     /// Agent 工具配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tools: Option<serde_json::Value>,
     /// Agent 沙箱配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sandbox: Option<serde_json::Value>,
     /// Agent 额外字段（未知字段不报错）
     #[serde(flatten)]
@@ -117,7 +155,7 @@ pub struct AgentEntry {
 }
 
 /// bindings 支持结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum BindingsConfig {
     /// 官方数组结构
@@ -152,78 +190,92 @@ impl BindingsConfig {
 }
 
 /// 单条 bindings 路由
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct BindingEntry {
-    #[serde(rename = "agentId", default)]
+    #[serde(rename = "agentId", default, skip_serializing_if = "Option::is_none")]
     pub agent_id: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub r#match: Option<BindingMatch>,
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// bindings 匹配条件
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct BindingMatch {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub channel: Option<String>,
-    #[serde(rename = "accountId", default)]
+    #[serde(rename = "accountId", default, skip_serializing_if = "Option::is_none")]
     pub account_id: Option<String>,
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// 模型配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct ModelsConfig {
     /// Provider 配置映射
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub providers: HashMap<String, ProviderConfig>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// Provider 配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProviderConfig {
-    /// API 地址
-    #[serde(rename = "baseUrl")]
+    /// API 地址（缺失时为空，由 OpenClaw 使用 Provider 默认地址）
+    #[serde(rename = "baseUrl", default, skip_serializing_if = "String::is_empty")]
     pub base_url: String,
     /// API Key
-    #[serde(rename = "apiKey")]
+    #[serde(rename = "apiKey", default, skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
     /// 模型列表
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub models: Vec<ModelConfig>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// 模型配置详情
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModelConfig {
     /// 模型 ID
     pub id: String,
-    /// 显示名称
+    /// 显示名称（缺失时界面使用模型 ID）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub name: String,
     /// API 类型 (anthropic-messages / openai-completions)
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub api: Option<String>,
-    /// 支持的输入类型
-    #[serde(default)]
+    /// 支持的输入类型（兼容单个字符串写法）
+    #[serde(
+        default,
+        deserialize_with = "string_or_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub input: Vec<String>,
     /// 上下文窗口大小
-    #[serde(rename = "contextWindow", default)]
+    #[serde(rename = "contextWindow", default, skip_serializing_if = "Option::is_none")]
     pub context_window: Option<u32>,
     /// 最大输出 Token
-    #[serde(rename = "maxTokens", default)]
+    #[serde(rename = "maxTokens", default, skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
     /// 是否支持推理模式
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reasoning: Option<bool>,
     /// 成本配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cost: Option<ModelCostConfig>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// 模型成本配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct ModelCostConfig {
     #[serde(default)]
     pub input: f64,
@@ -233,48 +285,57 @@ pub struct ModelCostConfig {
     pub cache_read: f64,
     #[serde(rename = "cacheWrite", default)]
     pub cache_write: f64,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// 网关配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct GatewayConfig {
-    /// 模式：local 或 cloud
-    #[serde(default)]
+    /// 模式：local 或 remote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub mode: Option<String>,
     /// 监听端口
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub port: Option<u16>,
     /// 监听地址
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub bind: Option<String>,
     /// 可信代理列表
-    #[serde(rename = "trustedProxies", default)]
+    #[serde(rename = "trustedProxies", default, skip_serializing_if = "Option::is_none")]
     pub trusted_proxies: Option<Vec<String>>,
     /// 热重载配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reload: Option<serde_json::Value>,
     /// 认证配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub auth: Option<GatewayAuthConfig>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// 网关认证配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct GatewayAuthConfig {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub mode: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub token: Option<String>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// 渠道 Provider 配置（兼容 accounts 多账号）
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct ChannelProviderConfig {
     /// 是否启用
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enabled: Option<bool>,
     /// 多账号配置
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub accounts: HashMap<String, serde_json::Value>,
     /// 其余字段保持兼容
     #[serde(flatten)]
@@ -282,23 +343,29 @@ pub struct ChannelProviderConfig {
 }
 
 /// 插件配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct PluginsConfig {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub allow: Vec<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub entries: HashMap<String, serde_json::Value>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub installs: HashMap<String, serde_json::Value>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// 元数据配置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct MetaConfig {
-    #[serde(rename = "lastTouchedAt", default)]
+    #[serde(rename = "lastTouchedAt", default, skip_serializing_if = "Option::is_none")]
     pub last_touched_at: Option<String>,
-    #[serde(rename = "lastTouchedVersion", default)]
+    #[serde(rename = "lastTouchedVersion", default, skip_serializing_if = "Option::is_none")]
     pub last_touched_version: Option<String>,
+    /// 未建模字段（原样保留，避免回写时丢失）
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 // ============ 前端展示用数据结构 ============
@@ -373,6 +440,15 @@ pub struct ConfiguredModel {
     pub max_tokens: Option<u32>,
     /// 是否为主模型
     pub is_primary: bool,
+    /// 是否支持推理模式（未配置时为 None）
+    #[serde(default)]
+    pub reasoning: Option<bool>,
+    /// 成本配置（未配置时为 None）
+    #[serde(default)]
+    pub cost: Option<ModelCostConfig>,
+    /// 支持的输入类型（text / image 等，未配置时为 ["text"]）
+    #[serde(default)]
+    pub inputs: Vec<String>,
 }
 
 /// AI 配置概览（返回给前端）
@@ -431,13 +507,19 @@ pub struct ChannelConfig {
     pub id: String,
     /// 渠道类型
     pub channel_type: String,
-    /// 是否启用
+    /// 是否启用（channels.<id>.enabled，未设置时已配置的渠道视为启用）
     pub enabled: bool,
+    /// 是否已配置（存在配置字段、账号或已启用）
+    #[serde(default)]
+    pub configured: bool,
     /// 配置详情（兼容旧版前端平铺字段）
     pub config: HashMap<String, serde_json::Value>,
     /// 多账号配置（兼容 channels.<provider>.accounts）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub accounts: Option<HashMap<String, serde_json::Value>>,
+    /// 会话类渠道（WhatsApp/iMessage）的登录状态: linked / unlinked / unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_status: Option<String>,
 }
 
 /// 环境变量配置
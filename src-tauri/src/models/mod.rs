@@ -1,5 +1,7 @@
 pub mod config;
+pub mod schema;
 pub mod status;
 
 pub use config::*;
+pub use schema::*;
 pub use status::*;
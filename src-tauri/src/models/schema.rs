@@ -0,0 +1,428 @@
+use serde_json::{json, Value};
+
+/// 引用 $defs 中的类型
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
+
+/// 对象类型；未建模字段由 extra 原样保留，因此允许额外属性
+fn object(description: &str, properties: Value) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": properties,
+        "additionalProperties": true,
+    })
+}
+
+/// 以任意字符串为 key 的映射
+fn map_of(description: &str, values: Value) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "additionalProperties": values,
+    })
+}
+
+/// 未建模、原样透传的字段（任意 JSON）
+fn any(description: &str) -> Value {
+    json!({ "description": description })
+}
+
+fn string(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+fn string_list(description: &str) -> Value {
+    json!({ "type": "array", "description": description, "items": { "type": "string" } })
+}
+
+/// OpenClawConfig（openclaw.json）的 JSON Schema（draft 2020-12）
+/// 属性名与 models::config 中的 serde 字段名 / rename 保持一致
+pub fn openclaw_config_schema() -> Value {
+    let root = object(
+        "OpenClaw 完整配置（openclaw.json）",
+        json!({
+            "agents": reference("AgentsConfig"),
+            "models": reference("ModelsConfig"),
+            "gateway": reference("GatewayConfig"),
+            "channels": map_of(
+                "渠道配置（渠道 id -> 配置）",
+                reference("ChannelProviderConfig"),
+            ),
+            "plugins": reference("PluginsConfig"),
+            "bindings": reference("BindingsConfig"),
+            "tools": any("工具配置"),
+            "messages": any("消息配置"),
+            "commands": any("命令配置"),
+            "web": any("Web 配置"),
+            "discovery": any("发现配置"),
+            "meta": reference("MetaConfig"),
+        }),
+    );
+
+    let defs = json!({
+        "AgentsConfig": object("Agent 配置", json!({
+            "defaults": reference("AgentDefaults"),
+            "list": {
+                "type": "array",
+                "description": "Agent 列表",
+                "items": reference("AgentEntry"),
+            },
+        })),
+        "AgentDefaults": object("Agent 默认配置", json!({
+            "model": reference("AgentModelConfig"),
+            "models": map_of("可用模型列表（provider/model -> {}）", json!({})),
+            "compaction": any("压缩配置"),
+            "contextPruning": any("上下文裁剪"),
+            "heartbeat": any("心跳配置"),
+            "maxConcurrent": { "type": "integer", "minimum": 0, "description": "最大并发数" },
+            "subagents": any("子代理配置"),
+        })),
+        "AgentModelConfig": object("Agent 模型配置", json!({
+            "primary": string("主模型（provider/model-id）"),
+            "fallbacks": string_list("备用模型列表（provider/model-id）"),
+        })),
+        "AgentEntry": object("Agent 列表项", json!({
+            "id": string("Agent 唯一标识"),
+            "name": string("显示名称"),
+            "default": { "type": "boolean", "description": "是否默认 Agent" },
+            "workspace": string("工作目录"),
+            "model": any("Agent 模型配置"),
+            "tools": any("Agent 工具配置"),
+            "sandbox": any("Agent 沙箱配置"),
+        })),
+        "BindingsConfig": {
+            "description": "路由绑定：官方数组结构，或按渠道/账号的对象结构",
+            "anyOf": [
+                { "type": "array", "items": reference("BindingEntry") },
+                { "type": "object" },
+            ],
+        },
+        "BindingEntry": object("单条 bindings 路由", json!({
+            "agentId": string("目标 Agent id"),
+            "match": reference("BindingMatch"),
+        })),
+        "BindingMatch": object("bindings 匹配条件", json!({
+            "channel": string("渠道 id"),
+            "accountId": string("渠道账号 id"),
+        })),
+        "ModelsConfig": object("模型配置", json!({
+            "providers": map_of(
+                "Provider 配置（名称 -> 配置）",
+                reference("ProviderConfig"),
+            ),
+        })),
+        "ProviderConfig": {
+            "type": "object",
+            "description": "Provider 配置",
+            "properties": {
+                "baseUrl": string("API 地址"),
+                "apiKey": string("API Key"),
+                "models": {
+                    "type": "array",
+                    "description": "模型列表",
+                    "items": reference("ModelConfig"),
+                },
+            },
+            "additionalProperties": true,
+        },
+        "ModelConfig": {
+            "type": "object",
+            "description": "模型配置详情",
+            "required": ["id"],
+            "properties": {
+                "id": string("模型 ID"),
+                "name": string("显示名称"),
+                "api": string("API 类型（anthropic-messages / openai-completions）"),
+                "input": string_list("支持的输入类型"),
+                "contextWindow": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "上下文窗口大小",
+                },
+                "maxTokens": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "最大输出 Token",
+                },
+                "reasoning": { "type": "boolean", "description": "是否支持推理模式" },
+                "cost": reference("ModelCostConfig"),
+            },
+            "additionalProperties": true,
+        },
+        "ModelCostConfig": {
+            "type": "object",
+            "description": "模型成本（USD / 百万 Token）",
+            "properties": {
+                "input": { "type": "number", "minimum": 0 },
+                "output": { "type": "number", "minimum": 0 },
+                "cacheRead": { "type": "number", "minimum": 0 },
+                "cacheWrite": { "type": "number", "minimum": 0 },
+            },
+            "additionalProperties": true,
+        },
+        "GatewayConfig": object("网关配置", json!({
+            "mode": {
+                "type": "string",
+                "enum": ["local", "remote"],
+                "description": "模式：local 或 remote",
+            },
+            "port": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 65535,
+                "description": "监听端口",
+            },
+            "bind": string("监听地址"),
+            "trustedProxies": string_list("可信代理列表"),
+            "reload": any("热重载配置"),
+            "auth": reference("GatewayAuthConfig"),
+        })),
+        "GatewayAuthConfig": object("网关认证配置", json!({
+            "mode": string("认证模式"),
+            "token": string("认证 Token"),
+        })),
+        "ChannelProviderConfig": object("渠道配置（兼容 accounts 多账号）", json!({
+            "enabled": { "type": "boolean", "description": "是否启用" },
+            "accounts": map_of("多账号配置（账号 id -> 配置）", json!({})),
+        })),
+        "PluginsConfig": object("插件配置", json!({
+            "allow": string_list("插件白名单"),
+            "entries": map_of("插件配置（插件 id -> 配置）", json!({})),
+            "installs": map_of("插件安装记录", json!({})),
+        })),
+        "MetaConfig": object("元数据", json!({
+            "lastTouchedAt": string("最后修改时间"),
+            "lastTouchedVersion": string("最后修改的 OpenClaw 版本"),
+        })),
+    });
+
+    let mut schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "OpenClawConfig",
+    });
+    if let (Some(schema_obj), Value::Object(root)) = (schema.as_object_mut(), root) {
+        schema_obj.extend(root);
+        schema_obj.insert("$defs".to_string(), defs);
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::openclaw_config_schema;
+    use crate::models::{
+        AgentDefaults, AgentEntry, AgentModelConfig, AgentsConfig, BindingEntry, BindingMatch,
+        BindingsConfig, ChannelProviderConfig, GatewayAuthConfig, GatewayConfig, MetaConfig,
+        ModelConfig, ModelCostConfig, ModelsConfig, OpenClawConfig, PluginsConfig, ProviderConfig,
+    };
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    const SCHEMA_TYPES: &[&str] =
+        &["object", "array", "string", "number", "integer", "boolean", "null"];
+
+    /// 结构校验：type 取值合法、$ref 均可解析、required 均在 properties 中
+    fn check_schema_node(node: &Value, defs: &serde_json::Map<String, Value>, path: &str) {
+        let node = node.as_object().unwrap_or_else(|| panic!("{} 应为对象", path));
+        if let Some(kind) = node.get("type") {
+            let kind = kind.as_str().unwrap_or_else(|| panic!("{}.type 应为字符串", path));
+            assert!(SCHEMA_TYPES.contains(&kind), "{}.type 无效: {}", path, kind);
+        }
+        if let Some(reference) = node.get("$ref").and_then(|v| v.as_str()) {
+            let name = reference.strip_prefix("#/$defs/").expect("$ref 应指向 $defs");
+            assert!(defs.contains_key(name), "{} 引用了不存在的定义 {}", path, name);
+        }
+        let properties = node.get("properties").and_then(|v| v.as_object());
+        for required in node.get("required").and_then(|v| v.as_array()).into_iter().flatten() {
+            let key = required.as_str().expect("required 项应为字符串");
+            assert!(properties.is_some_and(|p| p.contains_key(key)), "{} 缺少 {}", path, key);
+        }
+        for (key, child) in properties.into_iter().flatten() {
+            check_schema_node(child, defs, &format!("{}.{}", path, key));
+        }
+        for key in ["items", "additionalProperties"] {
+            if let Some(child) = node.get(key).filter(|v| v.is_object()) {
+                check_schema_node(child, defs, &format!("{}.{}", path, key));
+            }
+        }
+        for child in node.get("anyOf").and_then(|v| v.as_array()).into_iter().flatten() {
+            check_schema_node(child, defs, &format!("{}.anyOf", path));
+        }
+    }
+
+    /// 实例中出现的每个属性都必须在 schema 中声明
+    /// （用于发现 serde 重命名与 schema 不同步）
+    fn assert_declared(schema: &Value, defs: &Value, instance: &Value, path: &str) {
+        let schema = match schema.get("$ref").and_then(|v| v.as_str()) {
+            Some(reference) => &defs[reference.trim_start_matches("#/$defs/")],
+            None => schema,
+        };
+        // 未建模字段（任意 JSON）不再深入
+        if schema["type"].is_null() && schema["anyOf"].is_null() {
+            return;
+        }
+        if let Some(variants) = schema.get("anyOf").and_then(|v| v.as_array()) {
+            let matching = variants.iter().find(|variant| match variant["type"].as_str() {
+                Some("array") => instance.is_array(),
+                Some("object") => instance.is_object(),
+                _ => false,
+            });
+            if let Some(variant) = matching {
+                assert_declared(variant, defs, instance, path);
+            }
+            return;
+        }
+        match instance {
+            Value::Object(map) => {
+                assert_eq!(schema["type"], "object", "{}", path);
+                for (key, value) in map {
+                    let child = match schema["properties"].get(key) {
+                        Some(child) => child,
+                        None if schema["additionalProperties"].is_object() => {
+                            &schema["additionalProperties"]
+                        }
+                        None => panic!("schema 未声明属性 {}.{}", path, key),
+                    };
+                    assert_declared(child, defs, value, &format!("{}.{}", path, key));
+                }
+            }
+            Value::Array(items) => {
+                assert_eq!(schema["type"], "array", "{}", path);
+                for item in items {
+                    assert_declared(&schema["items"], defs, item, &format!("{}[]", path));
+                }
+            }
+            Value::String(_) => assert_eq!(schema["type"], "string", "{}", path),
+            Value::Bool(_) => assert_eq!(schema["type"], "boolean", "{}", path),
+            Value::Number(_) => assert!(
+                matches!(schema["type"].as_str(), Some("number" | "integer")),
+                "{}",
+                path
+            ),
+            Value::Null => {}
+        }
+    }
+
+    #[test]
+    fn config_schema_is_valid_and_matches_serde_field_names() {
+        let schema = openclaw_config_schema();
+        let text = serde_json::to_string(&schema).unwrap();
+        let schema: Value = serde_json::from_str(&text).expect("schema 应为合法 JSON");
+        assert_eq!(schema["$schema"], "https://json-schema.org/draft/2020-12/schema");
+        let defs = schema["$defs"].as_object().expect("应包含 $defs");
+        check_schema_node(&schema, defs, "$");
+        for (name, def) in defs {
+            check_schema_node(def, defs, name);
+        }
+
+        // 每个可选字段都赋值，序列化后的所有 key 都应在 schema 中声明
+        let model = ModelConfig {
+            id: "claude".to_string(),
+            name: "Claude".to_string(),
+            api: Some("anthropic-messages".to_string()),
+            input: vec!["text".to_string()],
+            context_window: Some(200000),
+            max_tokens: Some(8192),
+            reasoning: Some(true),
+            cost: Some(ModelCostConfig {
+                input: 3.0,
+                output: 15.0,
+                cache_read: 0.3,
+                cache_write: 3.75,
+                extra: HashMap::new(),
+            }),
+            extra: HashMap::new(),
+        };
+        let provider = ProviderConfig {
+            base_url: "https://api.anthropic.com".to_string(),
+            api_key: Some("sk".to_string()),
+            models: vec![model],
+            extra: HashMap::new(),
+        };
+        let config = OpenClawConfig {
+            agents: AgentsConfig {
+                defaults: AgentDefaults {
+                    model: AgentModelConfig {
+                        primary: Some("anthropic/claude".to_string()),
+                        fallbacks: Some(vec!["openai/gpt-4o".to_string()]),
+                        extra: HashMap::new(),
+                    },
+                    models: HashMap::from([("anthropic/claude".to_string(), json!({}))]),
+                    compaction: Some(json!({ "mode": "safeguard" })),
+                    context_pruning: Some(json!({})),
+                    heartbeat: Some(json!({})),
+                    max_concurrent: Some(4),
+                    subagents: Some(json!({})),
+                    extra: HashMap::new(),
+                },
+                list: vec![AgentEntry {
+                    id: Some("main".to_string()),
+                    name: Some("Main".to_string()),
+                    default: Some(true),
+                    workspace: Some("~/.openclaw/workspace".to_string()),
+                    model: Some(json!("anthropic/claude")),
+                    tools: Some(json!({})),
+                    sandbox: Some(json!({})),
+                    extra: HashMap::new(),
+                }],
+                extra: HashMap::new(),
+            },
+            models: ModelsConfig {
+                providers: HashMap::from([("anthropic".to_string(), provider)]),
+                extra: HashMap::new(),
+            },
+            gateway: GatewayConfig {
+                mode: Some("local".to_string()),
+                port: Some(18789),
+                bind: Some("loopback".to_string()),
+                trusted_proxies: Some(vec!["127.0.0.1".to_string()]),
+                reload: Some(json!({})),
+                auth: Some(GatewayAuthConfig {
+                    mode: Some("token".to_string()),
+                    token: Some("t".to_string()),
+                    extra: HashMap::new(),
+                }),
+                extra: HashMap::new(),
+            },
+            channels: HashMap::from([(
+                "telegram".to_string(),
+                ChannelProviderConfig {
+                    enabled: Some(true),
+                    accounts: HashMap::from([("bot".to_string(), json!({ "botToken": "x" }))]),
+                    extra: HashMap::new(),
+                },
+            )]),
+            plugins: PluginsConfig {
+                allow: vec!["telegram".to_string()],
+                entries: HashMap::from([("telegram".to_string(), json!({ "enabled": true }))]),
+                installs: HashMap::from([("feishu".to_string(), json!({}))]),
+                extra: HashMap::new(),
+            },
+            bindings: Some(BindingsConfig::Entries(vec![BindingEntry {
+                agent_id: Some("main".to_string()),
+                r#match: Some(BindingMatch {
+                    channel: Some("telegram".to_string()),
+                    account_id: Some("bot".to_string()),
+                    extra: HashMap::new(),
+                }),
+                extra: HashMap::new(),
+            }])),
+            tools: Some(json!({})),
+            messages: Some(json!({})),
+            commands: Some(json!({})),
+            web: Some(json!({})),
+            discovery: Some(json!({})),
+            meta: MetaConfig {
+                last_touched_at: Some("2026-01-01T00:00:00Z".to_string()),
+                last_touched_version: Some("2026.1.1".to_string()),
+                extra: HashMap::new(),
+            },
+            extra: HashMap::new(),
+        };
+        let instance = serde_json::to_value(&config).unwrap();
+        assert!(instance.pointer("/models/providers/anthropic/models/0/contextWindow").is_some());
+        assert_declared(&schema, &schema["$defs"], &instance, "$");
+    }
+}
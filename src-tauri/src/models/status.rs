@@ -49,6 +49,28 @@ pub struct SystemInfo {
     pub config_dir: String,
 }
 
+/// 文件路径及其当前是否存在
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathEntry {
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Manager 与 OpenClaw 使用的关键文件路径
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagerPaths {
+    /// openclaw.json（跟随当前配置档）
+    pub config_file: PathEntry,
+    /// env 环境变量文件（跟随当前配置档）
+    pub env_file: PathEntry,
+    /// 配置根目录（~/.openclaw）
+    pub config_dir: PathEntry,
+    /// Gateway 日志文件
+    pub log_file: PathEntry,
+    /// Web 管理面板登录配置
+    pub auth_config: PathEntry,
+}
+
 /// 诊断结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiagnosticResult {
@@ -91,3 +113,42 @@ pub struct ChannelTestResult {
     /// 错误信息
     pub error: Option<String>,
 }
+
+/// 端口占用进程信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortOwner {
+    /// 进程 ID
+    pub pid: u32,
+    /// 进程名称
+    pub name: String,
+    /// 完整命令行（无法获取时为空）
+    pub cmdline: Option<String>,
+}
+
+/// 运行中的 openclaw gateway 进程
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GatewayProcess {
+    /// 进程 ID
+    pub pid: u32,
+    /// 正在监听的 TCP 端口（残留进程可能没有监听任何端口）
+    pub ports: Vec<u16>,
+    /// 启动时间（Unix 为 ps lstart，Windows 为 WMI CreationDate）
+    pub started_at: Option<String>,
+    /// 完整命令行（无法获取时为空）
+    pub cmdline: Option<String>,
+}
+
+/// openclaw 命令透传执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenclawCommandResult {
+    /// 实际执行的参数
+    pub args: Vec<String>,
+    /// 是否成功（退出码为 0）
+    pub success: bool,
+    /// 退出码（被信号终止时为 None）
+    pub exit_code: Option<i32>,
+    /// 标准输出
+    pub stdout: String,
+    /// 标准错误
+    pub stderr: String,
+}
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
 use std::fs;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
 
 /// 读取文件内容
 pub fn read_file(path: &str) -> io::Result<String> {
@@ -16,17 +19,98 @@ pub fn write_file(path: &str, content: &str) -> io::Result<()> {
     fs::write(path, content)
 }
 
-/// 追加文件内容
+/// 原子写入存放密钥的文件（如 env）：先写同目录临时文件再 rename，写入中途崩溃不会损坏原文件
+/// Unix 上权限固定为 0600，避免其他用户读取
+pub fn write_secret_file(path: &str, content: &str) -> io::Result<()> {
+    use std::io::Write;
+
+    let target = Path::new(path);
+    let parent = target.parent().filter(|p| !p.as_os_str().is_empty());
+    if let Some(parent) = parent {
+        fs::create_dir_all(parent)?;
+    }
+    let file_name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // 同一进程内并发写入也使用不同的临时文件，避免互相截断
+    static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+    let temp_name = format!(
+        ".{}.tmp-{}-{}",
+        file_name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let temp_path = match parent {
+        Some(parent) => parent.join(temp_name),
+        None => Path::new(&temp_name).to_path_buf(),
+    };
+
+    let result = (|| {
+        let mut options = fs::OpenOptions::new();
+        options.create(true).write(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&temp_path)?;
+        // 临时文件可能是上次残留的，mode 只对新建文件生效，这里显式收紧
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, target)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// 追加一行文件内容（整行一次写入，多个进程并发追加时不会交错）
 pub fn append_file(path: &str, content: &str) -> io::Result<()> {
     use std::fs::OpenOptions;
     use std::io::Write;
     
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
         .open(path)?;
     
-    writeln!(file, "{}", content)
+    file.write_all(format!("{}\n", content).as_bytes())
+}
+
+/// 追加一行到存放敏感信息的文件（如审计日志），Unix 上权限收紧为 0600
+pub fn append_private_file(path: &str, content: &str) -> io::Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // 旧版本创建的文件可能是 0644，mode 只对新建文件生效
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if file.metadata()?.permissions().mode() & 0o077 != 0 {
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    file.write_all(format!("{}\n", content).as_bytes())
 }
 
 /// 检查文件是否存在
@@ -44,55 +128,426 @@ pub fn read_last_lines(path: &str, n: usize) -> io::Result<Vec<String>> {
     Ok(lines[start..].to_vec())
 }
 
-/// 从环境变量文件读取值
-pub fn read_env_value(env_file: &str, key: &str) -> Option<String> {
-    let content = read_file(env_file).ok()?;
-    
-    for line in content.lines() {
-        let line = line.trim();
-        if line.starts_with(&format!("export {}=", key)) {
-            let value = line
-                .trim_start_matches(&format!("export {}=", key))
-                .trim_matches('"')
-                .trim_matches('\'');
-            return Some(value.to_string());
+/// 按块读取文件时的块大小
+const PAGE_CHUNK_BYTES: usize = 64 * 1024;
+
+/// 文件分页读取结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinePage {
+    pub lines: Vec<String>,
+    /// 本页第一行的字节偏移，作为下一次 before_offset 向前翻页；为 0 表示已到文件开头
+    pub start_offset: u64,
+    /// 文件总字节数
+    pub total_bytes: u64,
+    /// 本页之前是否还有更早的行（start_offset > 0）
+    pub has_more: bool,
+}
+
+/// 读取字节偏移 before_offset（不含，None 表示文件末尾）之前的最多 n 行
+/// 从该偏移向前按块查找换行符，只读取本页涉及的数据，不扫描整个文件
+pub fn read_lines_page(path: &str, n: usize, before_offset: Option<u64>) -> io::Result<LinePage> {
+    read_lines_page_chunked(path, n, before_offset, PAGE_CHUNK_BYTES)
+}
+
+fn read_lines_page_chunked(
+    path: &str,
+    n: usize,
+    before_offset: Option<u64>,
+    chunk_bytes: usize,
+) -> io::Result<LinePage> {
+    let mut file = fs::File::open(path)?;
+    // 以打开时的长度为准，读取过程中继续追加的内容留给下一次刷新
+    let total_bytes = file.metadata()?.len();
+    let end = before_offset.unwrap_or(total_bytes).min(total_bytes);
+    if n == 0 {
+        let has_more = end > 0;
+        return Ok(LinePage { lines: Vec::new(), start_offset: end, total_bytes, has_more });
+    }
+
+    // 向前数到第 n 个换行符，其后即为本页第一行；end 前紧邻的换行符是上一行的结尾，不计入
+    let mut start_offset = 0;
+    let mut found = 0;
+    let mut buf = vec![0u8; chunk_bytes];
+    let mut pos = end;
+    'scan: while pos > 0 {
+        let chunk_start = pos.saturating_sub(chunk_bytes as u64);
+        let chunk = &mut buf[..(pos - chunk_start) as usize];
+        file.seek(SeekFrom::Start(chunk_start))?;
+        file.read_exact(chunk)?;
+        for (index, byte) in chunk.iter().enumerate().rev() {
+            let offset = chunk_start + index as u64;
+            if *byte != b'\n' || offset + 1 == end {
+                continue;
+            }
+            found += 1;
+            if found == n {
+                start_offset = offset + 1;
+                break 'scan;
+            }
         }
+        pos = chunk_start;
     }
-    
-    None
+
+    let mut content = vec![0u8; (end - start_offset) as usize];
+    file.seek(SeekFrom::Start(start_offset))?;
+    file.read_exact(&mut content)?;
+    let lines = String::from_utf8_lossy(&content)
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+    Ok(LinePage { lines, start_offset, total_bytes, has_more: start_offset > 0 })
+}
+
+/// 清空文件内容（保留文件本身，已打开的文件句柄仍然有效）
+pub fn truncate_file(path: &str) -> io::Result<()> {
+    fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)
+        .map(|_| ())
+}
+
+/// 日志轮转：文件超过 max_bytes 时历史文件依次重命名为 .2 ~ .keep，当前内容复制为 .1 后原地截断
+/// 运行中的 gateway 以追加方式持有日志句柄，原地截断保证其后续输出仍写入当前日志文件
+/// 返回是否发生了轮转
+pub fn rotate_file_if_needed(path: &str, max_bytes: u64, keep: usize) -> io::Result<bool> {
+    let size = match fs::metadata(path) {
+        Ok(meta) => meta.len(),
+        Err(_) => return Ok(false),
+    };
+    if size < max_bytes || keep == 0 {
+        return Ok(false);
+    }
+
+    let oldest = format!("{}.{}", path, keep);
+    if Path::new(&oldest).exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for index in (1..keep).rev() {
+        let from = format!("{}.{}", path, index);
+        if Path::new(&from).exists() {
+            fs::rename(&from, format!("{}.{}", path, index + 1))?;
+        }
+    }
+    fs::copy(path, format!("{}.1", path))?;
+    truncate_file(path)?;
+    Ok(true)
+}
+
+/// env 文件读-改-写的进程内锁（Web 服务并发处理请求，避免同时保存时丢失更新）
+static ENV_FILE_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_env_file() -> MutexGuard<'static, ()> {
+    ENV_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 从 env 文件内容中查找 export KEY=value 的值
+fn env_value_from_content(content: &str, key: &str) -> Option<String> {
+    let prefix = format!("export {}=", key);
+    content.lines().find_map(|line| {
+        let value = line.trim().strip_prefix(&prefix)?;
+        Some(value.trim_matches('"').trim_matches('\'').to_string())
+    })
+}
+
+/// 从环境变量文件读取值
+pub fn read_env_value(env_file: &str, key: &str) -> Option<String> {
+    env_value_from_content(&read_file(env_file).ok()?, key)
+}
+
+/// 一次读取 env 文件并返回多个键的值（不存在的键为 None）
+pub fn read_env_values(env_file: &str, keys: &[String]) -> HashMap<String, Option<String>> {
+    let content = read_file(env_file).unwrap_or_default();
+    keys.iter()
+        .map(|key| (key.clone(), env_value_from_content(&content, key)))
+        .collect()
 }
 
 /// 设置环境变量文件中的值
 pub fn set_env_value(env_file: &str, key: &str, value: &str) -> io::Result<()> {
+    set_env_values(env_file, &[(key.to_string(), value.to_string())])
+}
+
+/// 校验写入 env 文件的键值：env 文件会被 shell source，
+/// 键必须匹配 ^[A-Za-z_][A-Za-z0-9_]*$，值不能包含会在双引号中展开或截断的字符
+pub fn validate_env_entry(key: &str, value: &str) -> Result<(), String> {
+    let mut chars = key.chars();
+    let valid_key = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_key {
+        return Err(format!("环境变量名无效: {:?}", key));
+    }
+    if let Some(c) = value.chars().find(|c| matches!(c, '"' | '\\' | '$' | '`' | '\n' | '\r')) {
+        return Err(format!("环境变量 {} 的值包含不允许的字符: {:?}", key, c));
+    }
+    Ok(())
+}
+
+/// 批量设置环境变量文件中的值（已有的键原地替换，新键按顺序追加，只写入一次）
+/// 任一键值未通过 validate_env_entry 时不写入任何内容
+pub fn set_env_values(env_file: &str, values: &[(String, String)]) -> io::Result<()> {
+    for (key, value) in values {
+        validate_env_entry(key, value)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    }
+    let _env_lock = lock_env_file();
     let content = read_file(env_file).unwrap_or_default();
     let mut lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-    
-    let new_line = format!("export {}=\"{}\"", key, value);
-    let mut found = false;
-    
-    for line in &mut lines {
-        if line.starts_with(&format!("export {}=", key)) {
-            *line = new_line.clone();
-            found = true;
-            break;
+
+    for (key, value) in values {
+        let new_line = format!("export {}=\"{}\"", key, value);
+        let prefix = format!("export {}=", key);
+        match lines.iter_mut().find(|line| line.starts_with(&prefix)) {
+            Some(line) => *line = new_line,
+            None => lines.push(new_line),
         }
     }
-    
-    if !found {
-        lines.push(new_line);
-    }
-    
-    write_file(env_file, &lines.join("\n"))
+
+    write_secret_file(env_file, &lines.join("\n"))
 }
 
-/// 从环境变量文件中删除指定的值
+/// 从环境变量文件中删除指定的值（同时支持 export KEY= 与 KEY= 两种写法）
 pub fn remove_env_value(env_file: &str, key: &str) -> io::Result<()> {
+    let _env_lock = lock_env_file();
     let content = read_file(env_file).unwrap_or_default();
     let lines: Vec<String> = content
         .lines()
-        .filter(|line| !line.starts_with(&format!("export {}=", key)))
+        .filter(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            !line.starts_with(&format!("{}=", key))
+        })
         .map(|s| s.to_string())
         .collect();
     
-    write_file(env_file, &lines.join("\n"))
+    write_secret_file(env_file, &lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        read_env_value, read_env_values, read_lines_page, read_lines_page_chunked,
+        remove_env_value, rotate_file_if_needed, set_env_value, set_env_values, truncate_file,
+        validate_env_entry,
+    };
+    use crate::commands::config::tests::unique_temp_dir;
+    use std::fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    #[test]
+    fn env_values_are_read_and_written_in_batches() {
+        let dir = unique_temp_dir("env-batch");
+        let env_file = dir.join("env");
+        let env_path = env_file.to_str().unwrap();
+        fs::write(&env_file, "# comment\nexport A=\"1\"\nexport B='two'").unwrap();
+
+        let keys = ["A", "B", "MISSING"].map(String::from);
+        let values = read_env_values(env_path, &keys);
+        assert_eq!(values.len(), 3);
+        assert_eq!(values["A"].as_deref(), Some("1"));
+        assert_eq!(values["B"].as_deref(), Some("two"));
+        assert_eq!(values["MISSING"], None);
+
+        let updates = [("B", "2"), ("C", "3"), ("D", "")].map(|(k, v)| (k.into(), v.into()));
+        set_env_values(env_path, &updates).expect("应可批量写入");
+        assert_eq!(
+            fs::read_to_string(&env_file).unwrap(),
+            "# comment\nexport A=\"1\"\nexport B=\"2\"\nexport C=\"3\"\nexport D=\"\""
+        );
+        let keys = ["A", "B", "C", "D"].map(String::from);
+        let values = read_env_values(env_path, &keys);
+        let expected = [("A", "1"), ("B", "2"), ("C", "3"), ("D", "")];
+        for (key, value) in expected {
+            assert_eq!(values[key].as_deref(), Some(value), "{}", key);
+        }
+
+        let missing = dir.join("missing");
+        let values = read_env_values(missing.to_str().unwrap(), &keys);
+        assert!(values.values().all(Option::is_none));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn env_keys_and_values_that_break_the_shell_file_are_rejected() {
+        let dir = unique_temp_dir("env-invalid");
+        let env_file = dir.join("env");
+        let env_path = env_file.to_str().unwrap();
+        fs::write(&env_file, "export A=\"1\"").unwrap();
+
+        assert!(validate_env_entry("OPENAI_API_KEY", "sk-abc_123.-/+=").is_ok());
+        assert!(validate_env_entry("_PRIVATE2", "").is_ok());
+        for key in ["", "1KEY", "KEY-NAME", "A B", "A=1", "A;rm"] {
+            assert!(validate_env_entry(key, "v").is_err(), "{:?}", key);
+        }
+        for value in ["a\"b", "$(id)", "`id`", "a\\b", "a\nexport B=1"] {
+            assert!(validate_env_entry("KEY", value).is_err(), "{:?}", value);
+        }
+
+        // 批量写入中任一条无效时整批拒绝，文件保持不变
+        let updates = [("B", "2"), ("C", "$(id)")].map(|(k, v)| (k.into(), v.into()));
+        let error = set_env_values(env_path, &updates).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(set_env_value(env_path, "BAD KEY", "v").is_err());
+        assert_eq!(fs::read_to_string(&env_file).unwrap(), "export A=\"1\"");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn concurrent_env_writes_keep_every_key() {
+        let dir = unique_temp_dir("env-concurrent");
+        let env_file = dir.join("env");
+        let env_path = env_file.to_string_lossy().to_string();
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let env_path = env_path.clone();
+                std::thread::spawn(move || {
+                    for j in 0..10 {
+                        set_env_value(&env_path, &format!("KEY_{}_{}", i, j), "v")
+                            .expect("并发写入不应失败");
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let content = fs::read_to_string(&env_file).unwrap();
+        assert_eq!(content.lines().count(), 80);
+        let names: Vec<_> = fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(names, ["env"], "不应残留临时文件");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn env_writes_are_atomic_and_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = unique_temp_dir("env");
+        let env_file = dir.join("env");
+        let env_path = env_file.to_str().unwrap();
+        fs::write(&env_file, "export OPENAI_API_KEY=\"sk-old\"\nexport KEEP=1").unwrap();
+        fs::set_permissions(&env_file, fs::Permissions::from_mode(0o644)).unwrap();
+        let mode = || fs::metadata(&env_file).unwrap().permissions().mode() & 0o777;
+
+        set_env_value(env_path, "OPENAI_API_KEY", "sk-new").expect("应可写入 env");
+        assert_eq!(mode(), 0o600);
+        assert_eq!(read_env_value(env_path, "OPENAI_API_KEY").as_deref(), Some("sk-new"));
+        assert_eq!(read_env_value(env_path, "KEEP").as_deref(), Some("1"));
+
+        set_env_value(env_path, "ANTHROPIC_API_KEY", "sk-ant").expect("应可追加 env");
+        remove_env_value(env_path, "KEEP").expect("应可删除 env 项");
+        assert_eq!(mode(), 0o600);
+        assert_eq!(
+            fs::read_to_string(&env_file).unwrap(),
+            "export OPENAI_API_KEY=\"sk-new\"\nexport ANTHROPIC_API_KEY=\"sk-ant\""
+        );
+
+        // 不存在的 env 文件新建时即为 0600，且不留下临时文件
+        fs::remove_file(&env_file).unwrap();
+        set_env_value(env_path, "NEW_KEY", "v").expect("应可新建 env");
+        assert_eq!(mode(), 0o600);
+        let names: Vec<_> = fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(names, ["env"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn truncate_keeps_file_path() {
+        let dir = unique_temp_dir("truncate");
+        let path = dir.join("gateway.log");
+        fs::write(&path, "line1\nline2\n").expect("应可写入日志");
+
+        truncate_file(path.to_str().unwrap()).expect("应可清空日志");
+
+        assert!(path.exists(), "清空后文件应仍然存在");
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotate_renames_at_threshold_and_keeps_last_n() {
+        let dir = unique_temp_dir("rotate");
+        let path = dir.join("gateway.log");
+        let path_str = path.to_str().unwrap();
+
+        fs::write(&path, "small").unwrap();
+        assert!(!rotate_file_if_needed(path_str, 10, 3).unwrap(), "未超过阈值不应轮转");
+
+        for round in 1..=4 {
+            fs::write(&path, format!("round-{}-0123456789", round)).unwrap();
+            assert!(rotate_file_if_needed(path_str, 10, 3).unwrap());
+        }
+
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+        assert!(fs::read_to_string(format!("{}.1", path_str)).unwrap().starts_with("round-4"));
+        assert!(fs::read_to_string(format!("{}.2", path_str)).unwrap().starts_with("round-3"));
+        assert!(fs::read_to_string(format!("{}.3", path_str)).unwrap().starts_with("round-2"));
+        assert!(!PathBuf::from(format!("{}.4", path_str)).exists(), "只保留最近 3 份");
+
+        // 持有追加句柄的写入者（运行中的 gateway）轮转后继续写入当前日志文件
+        let mut writer = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writer.write_all(b"before-rotation-0123456789\n").unwrap();
+        assert!(rotate_file_if_needed(path_str, 10, 3).unwrap());
+        writer.write_all(b"after\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "after\n");
+        assert!(fs::read_to_string(format!("{}.1", path_str)).unwrap().starts_with("before"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn line_pages_walk_backward_without_overlap() {
+        let dir = unique_temp_dir("pages");
+        let path = dir.join("gateway.log");
+        let path_str = path.to_str().unwrap();
+        let content: String = (0..25).map(|i| format!("line-{:02}\n", i)).collect();
+        fs::write(&path, &content).unwrap();
+
+        // 块大小小于一行、跨行与整文件三种情况结果一致
+        for chunk in [3, 16, 4096] {
+            let mut collected: Vec<String> = Vec::new();
+            let mut before = None;
+            loop {
+                let page = read_lines_page_chunked(path_str, 10, before, chunk).unwrap();
+                assert_eq!(page.total_bytes, content.len() as u64);
+                assert!(page.lines.len() <= 10);
+                let mut lines = page.lines;
+                lines.append(&mut collected);
+                collected = lines;
+                assert_eq!(page.has_more, page.start_offset > 0);
+                if !page.has_more {
+                    break;
+                }
+                before = Some(page.start_offset);
+            }
+            let expected: Vec<String> = (0..25).map(|i| format!("line-{:02}", i)).collect();
+            assert_eq!(collected, expected, "chunk={}", chunk);
+        }
+
+        // 每行 8 字节：第 k 行从偏移 8k 开始
+        let last = read_lines_page(path_str, 10, None).unwrap();
+        assert_eq!(last.start_offset, 15 * 8);
+        assert_eq!(last.lines.first().map(String::as_str), Some("line-15"));
+        let middle = read_lines_page(path_str, 3, Some(7 * 8)).unwrap();
+        assert_eq!(middle.lines, vec!["line-04", "line-05", "line-06"]);
+        assert_eq!(middle.start_offset, 4 * 8);
+        assert_eq!(read_lines_page(path_str, 10, Some(0)).unwrap().lines, Vec::<String>::new());
+
+        // 末行无换行符、空行与空文件
+        fs::write(&path, "a\n\nb").unwrap();
+        let page = read_lines_page(path_str, 2, None).unwrap();
+        assert_eq!((page.total_bytes, page.start_offset), (4, 2));
+        assert_eq!(page.lines, vec!["", "b"]);
+        assert_eq!(read_lines_page(path_str, 5, Some(3)).unwrap().lines, vec!["a", ""]);
+        fs::write(&path, "").unwrap();
+        let page = read_lines_page(path_str, 10, None).unwrap();
+        assert!(page.lines.is_empty() && page.total_bytes == 0 && !page.has_more);
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -10,46 +10,213 @@ pub fn get_arch() -> String {
     env::consts::ARCH.to_string()
 }
 
+/// 配置目录覆盖环境变量（按优先级排列）
+pub const CONFIG_DIR_ENV_VARS: [&str; 2] = ["OPENCLAW_CONFIG_DIR", "OPENCLAW_HOME"];
+
+/// 读取配置目录覆盖（OPENCLAW_CONFIG_DIR / OPENCLAW_HOME），未设置或为空时返回 None
+pub fn get_config_dir_override() -> Option<String> {
+    CONFIG_DIR_ENV_VARS.iter().find_map(|key| {
+        let value = env::var(key).ok()?;
+        let value = value.trim();
+        let trimmed = value.trim_end_matches(['/', '\\']);
+        match (value.is_empty(), trimmed.is_empty()) {
+            (true, _) => None,
+            // 根目录保留原样
+            (false, true) => Some(value.to_string()),
+            (false, false) => Some(trimmed.to_string()),
+        }
+    })
+}
+
+/// Linux 下的 XDG 配置目录（$XDG_CONFIG_HOME/openclaw，默认 ~/.config/openclaw）
+fn get_xdg_config_dir(home: &str) -> String {
+    match env::var("XDG_CONFIG_HOME") {
+        Ok(base) if base.starts_with('/') => {
+            join_config_path(base.trim_end_matches('/'), "openclaw")
+        }
+        _ => format!("{}/.config/openclaw", home),
+    }
+}
+
 /// 获取配置目录路径
+/// 优先使用 OPENCLAW_CONFIG_DIR / OPENCLAW_HOME；Linux 下 ~/.openclaw 不存在而 XDG 目录存在时使用 XDG 目录
 pub fn get_config_dir() -> String {
-    if let Some(home) = dirs::home_dir() {
-        if is_windows() {
-            format!("{}\\.openclaw", home.display())
-        } else {
-            format!("{}/.openclaw", home.display())
+    if let Some(dir) = get_config_dir_override() {
+        return dir;
+    }
+    let Some(home) = dirs::home_dir() else {
+        return String::from("~/.openclaw");
+    };
+    let home = home.display().to_string();
+    let default_dir = join_config_path(&home, ".openclaw");
+    if is_linux() && !std::path::Path::new(&default_dir).exists() {
+        let xdg_dir = get_xdg_config_dir(&home);
+        if std::path::Path::new(&xdg_dir).is_dir() {
+            return xdg_dir;
         }
-    } else {
-        String::from("~/.openclaw")
     }
+    default_dir
 }
 
-/// 获取环境变量文件路径
-pub fn get_env_file_path() -> String {
+/// 默认配置档名称（直接使用配置目录下的 openclaw.json / env）
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 拼接配置目录下的子路径
+fn join_config_path(dir: &str, name: &str) -> String {
     if is_windows() {
-        format!("{}\\env", get_config_dir())
+        format!("{}\\{}", dir, name)
     } else {
-        format!("{}/env", get_config_dir())
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// 获取配置档根目录 (~/.openclaw/profiles)
+pub fn get_profiles_dir() -> String {
+    join_config_path(&get_config_dir(), "profiles")
+}
+
+/// 获取当前配置档标记文件路径
+pub fn get_active_profile_marker_path() -> String {
+    join_config_path(&get_config_dir(), "manager-profile")
+}
+
+/// 校验配置档名称：仅允许字母、数字、"-"、"_"，防止路径穿越
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 获取指定配置档目录
+pub fn get_profile_dir(name: &str) -> String {
+    join_config_path(&get_profiles_dir(), name)
+}
+
+/// 获取当前激活的配置档（未激活或标记无效时返回 None，即使用默认配置）
+pub fn get_active_profile() -> Option<String> {
+    let name = std::fs::read_to_string(get_active_profile_marker_path()).ok()?;
+    let name = name.trim();
+    if name == DEFAULT_PROFILE || !is_valid_profile_name(name) {
+        return None;
+    }
+    if !std::path::Path::new(&get_profile_dir(name)).is_dir() {
+        return None;
     }
+    Some(name.to_string())
 }
 
-/// 获取 openclaw.json 配置文件路径
+/// 获取当前配置档对应的配置目录（默认配置档即配置目录本身）
+pub fn get_active_config_dir() -> String {
+    match get_active_profile() {
+        Some(name) => get_profile_dir(&name),
+        None => get_config_dir(),
+    }
+}
+
+/// 获取用户选择的 Node.js 路径标记文件
+pub fn get_preferred_node_marker_path() -> String {
+    join_config_path(&get_config_dir(), "manager-node-path")
+}
+
+/// 获取配置写入锁文件路径（Manager 各进程共享）
+pub fn get_config_lock_path() -> String {
+    join_config_path(&get_config_dir(), ".manager.lock")
+}
+
+/// 获取 Manager 记录的 OpenClaw 安装状态文件（~/.openclaw/manager-install.json）
+pub fn get_install_state_path() -> String {
+    join_config_path(&get_config_dir(), "manager-install.json")
+}
+
+/// 获取 Manager 审计日志路径（~/.openclaw/manager-audit.log）
+pub fn get_audit_log_path() -> String {
+    join_config_path(&get_config_dir(), "manager-audit.log")
+}
+
+/// 获取 Web 管理面板登录配置路径（~/.openclaw/manager-web-auth.json）
+pub fn get_web_auth_config_path() -> String {
+    join_config_path(&get_config_dir(), "manager-web-auth.json")
+}
+
+/// 获取 launchd 用户服务 plist 路径（~/Library/LaunchAgents/<label>.plist）
+pub fn get_launch_agent_path(label: &str) -> String {
+    let home = dirs::home_dir()
+        .map(|h| h.display().to_string())
+        .unwrap_or_else(|| String::from("~"));
+    format!("{}/Library/LaunchAgents/{}.plist", home, label)
+}
+
+/// 获取 systemd --user 单元文件路径（$XDG_CONFIG_HOME/systemd/user/<unit>）
+pub fn get_systemd_user_unit_path(unit: &str) -> String {
+    let config_home = dirs::config_dir()
+        .map(|d| d.display().to_string())
+        .unwrap_or_else(|| String::from("~/.config"));
+    format!("{}/systemd/user/{}", config_home, unit)
+}
+
+/// 获取 Windows 开机自启计划任务调用的启动脚本路径
+pub fn get_autostart_script_path() -> String {
+    join_config_path(&get_config_dir(), "manager-autostart.cmd")
+}
+
+/// 获取用户选择的 Node.js 可执行文件路径（未选择或文件已不存在时返回 None）
+pub fn get_preferred_node_path() -> Option<String> {
+    let path = std::fs::read_to_string(get_preferred_node_marker_path()).ok()?;
+    let path = path.trim();
+    if path.is_empty() || !std::path::Path::new(path).is_file() {
+        return None;
+    }
+    Some(path.to_string())
+}
+
+/// 获取环境变量文件路径（跟随当前配置档）
+pub fn get_env_file_path() -> String {
+    join_config_path(&get_active_config_dir(), "env")
+}
+
+/// 获取 openclaw.json 配置文件路径（跟随当前配置档）
 pub fn get_config_file_path() -> String {
-    if is_windows() {
-        format!("{}\\openclaw.json", get_config_dir())
-    } else {
-        format!("{}/openclaw.json", get_config_dir())
+    join_config_path(&get_active_config_dir(), "openclaw.json")
+}
+
+/// 需要注入 openclaw 子进程的配置位置环境变量
+/// 目录覆盖、XDG 目录或非默认配置档使 Manager 解析出的位置与 openclaw 默认的 ~/.openclaw
+/// 不同时，通过 OPENCLAW_STATE_DIR / OPENCLAW_CONFIG_PATH 让 gateway 读取同一份配置
+pub fn get_config_location_env() -> Vec<(&'static str, String)> {
+    let default_dir = dirs::home_dir()
+        .map(|home| join_config_path(&home.display().to_string(), ".openclaw"));
+    let mut vars = Vec::new();
+    let config_dir = get_config_dir();
+    if default_dir.as_deref() != Some(config_dir.as_str()) {
+        vars.push(("OPENCLAW_STATE_DIR", config_dir));
     }
+    if default_dir.as_deref() != Some(get_active_config_dir().as_str()) {
+        vars.push(("OPENCLAW_CONFIG_PATH", get_config_file_path()));
+    }
+    vars
 }
 
-/// 获取日志文件路径
+/// 获取日志文件路径（Unix 默认 /tmp，指定了配置目录覆盖时与配置放在一起）
 pub fn get_log_file_path() -> String {
-    if is_windows() {
-        format!("{}\\openclaw-gateway.log", get_config_dir())
+    if is_windows() || get_config_dir_override().is_some() {
+        join_config_path(&get_config_dir(), "openclaw-gateway.log")
     } else {
         String::from("/tmp/openclaw-gateway.log")
     }
 }
 
+/// 获取日志轮转阈值（字节），可通过 OPENCLAW_LOG_MAX_MB 覆盖，默认 10MB
+pub fn get_log_max_bytes() -> u64 {
+    let mb = env::var("OPENCLAW_LOG_MAX_MB")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(10);
+    mb * 1024 * 1024
+}
+
 /// 检测当前平台是否为 macOS
 pub fn is_macos() -> bool {
     env::consts::OS == "macos"
@@ -64,3 +231,71 @@ pub fn is_windows() -> bool {
 pub fn is_linux() -> bool {
     env::consts::OS == "linux"
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_audit_log_path, get_config_dir, get_config_file_path, get_config_location_env,
+        get_env_file_path, get_log_file_path, get_profiles_dir, get_web_auth_config_path,
+    };
+    use crate::commands::config::tests::{test_env_lock, EnvGuard, TempHomeGuard};
+
+    #[test]
+    fn config_dir_override_moves_every_path_helper() {
+        let _env_lock = test_env_lock();
+        let home_guard = TempHomeGuard::new();
+        let _home_override = EnvGuard::remove("OPENCLAW_HOME");
+        let external = home_guard.temp_home_dir.join("external-drive").join("openclaw");
+        let external_str = external.to_string_lossy().to_string();
+
+        {
+            let _config_dir = EnvGuard::set("OPENCLAW_CONFIG_DIR", &external_str);
+            assert_eq!(get_config_dir(), external_str);
+            for path in [
+                get_config_file_path(),
+                get_env_file_path(),
+                get_log_file_path(),
+                get_profiles_dir(),
+                get_web_auth_config_path(),
+                get_audit_log_path(),
+            ] {
+                assert!(path.starts_with(&external_str), "{} 应位于 {}", path, external_str);
+            }
+            assert!(get_config_file_path().ends_with("openclaw.json"));
+            assert!(get_log_file_path().ends_with("openclaw-gateway.log"));
+            // gateway 与开机自启单元需被告知非默认的配置位置
+            assert_eq!(
+                get_config_location_env(),
+                vec![
+                    ("OPENCLAW_STATE_DIR", external_str.clone()),
+                    ("OPENCLAW_CONFIG_PATH", get_config_file_path()),
+                ]
+            );
+        }
+
+        {
+            let _config_dir = EnvGuard::remove("OPENCLAW_CONFIG_DIR");
+            let _home = EnvGuard::set("OPENCLAW_HOME", &format!("{}/", external_str));
+            assert_eq!(get_config_dir(), external_str);
+            assert!(get_env_file_path().starts_with(&external_str));
+        }
+
+        let _config_dir = EnvGuard::remove("OPENCLAW_CONFIG_DIR");
+        let default_dir = home_guard.temp_home_dir.join(".openclaw");
+        assert_eq!(get_config_dir(), default_dir.to_string_lossy());
+        assert!(get_config_location_env().is_empty());
+        if cfg!(target_os = "linux") {
+            // ~/.openclaw 不存在而 XDG 目录存在时使用 XDG 目录
+            let xdg_home = home_guard.temp_home_dir.join("xdg");
+            std::fs::create_dir_all(xdg_home.join("openclaw")).unwrap();
+            let _xdg = EnvGuard::set("XDG_CONFIG_HOME", &xdg_home.to_string_lossy());
+            assert_eq!(get_config_dir(), default_dir.to_string_lossy());
+            std::fs::remove_dir_all(&default_dir).unwrap();
+            let xdg_dir = xdg_home.join("openclaw").to_string_lossy().to_string();
+            assert_eq!(get_config_dir(), xdg_dir);
+            assert!(get_config_file_path().starts_with(&xdg_dir));
+            let location_env = get_config_location_env();
+            assert_eq!(location_env.first(), Some(&("OPENCLAW_STATE_DIR", xdg_dir)));
+        }
+    }
+}
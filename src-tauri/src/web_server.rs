@@ -9,34 +9,129 @@ use std::{
         atomic::{AtomicU64, Ordering},
         Arc,
     },
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::{TcpListener, TcpStream},
-    sync::RwLock,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+    sync::{Mutex, OwnedMutexGuard, RwLock},
 };
+use tokio_rustls::TlsAcceptor;
 
 mod commands;
 mod models;
 mod utils;
 
-use commands::{config, diagnostics, installer, process, service};
+use commands::{
+    audit, autostart, config, credentials, diagnostics, installer, process, profile, service,
+    sessions,
+};
 
 const SESSION_COOKIE: &str = "openclaw_manager_session";
 const SESSION_TTL_SECONDS: u64 = 60 * 60 * 8;
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+/// 默认只监听回环地址；对外监听需显式设置 OPENCLAW_WEB_HOST 或 OPENCLAW_WEB_EXPOSE=1
+const DEFAULT_WEB_HOST: &str = "127.0.0.1";
+/// OPENCLAW_WEB_EXPOSE=1 且未指定 OPENCLAW_WEB_HOST 时监听所有网卡
+const EXPOSED_WEB_HOST: &str = "0.0.0.0";
+const DEFAULT_WEB_PORT: u16 = 17890;
+/// 默认请求体上限：最大的合法请求为 save_config 提交的完整配置，远小于该值
+const DEFAULT_MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+/// 请求体超出上限时的错误前缀，handle_connection 据此返回 413
+const BODY_TOO_LARGE_ERROR: &str = "请求体过大";
+/// 默认读取超时（TLS 握手与完整请求的总时长），防止慢速连接长期占用任务
+const DEFAULT_READ_TIMEOUT_SECS: u64 = 15;
+
+/// viewer 角色允许调用的命令白名单
+/// 只包含无副作用、且不返回明文密钥的查询；未列出的命令（含新增命令）默认禁止
+const VIEWER_ALLOWED_COMMANDS: &[&str] = &[
+    "get_service_status",
+    "get_logs",
+    "is_autostart_enabled",
+    "get_sessions_info",
+    "get_usage_stats",
+    "list_credentials",
+    "check_openclaw_installed",
+    "get_openclaw_version",
+    "get_node_version",
+    "check_port_in_use",
+    "check_port_owner",
+    "list_gateway_processes",
+    "get_config_schema",
+    "list_config_backups",
+    "list_profiles",
+    "current_profile",
+    "get_agents_list",
+    "get_agent",
+    "get_bindings",
+    "get_env_keys",
+    "get_ai_providers",
+    "validate_channel_accounts",
+    "watch_config",
+    "get_official_providers",
+    "get_ai_config",
+    "get_agent_defaults",
+    "list_plugins",
+    "audit_plugin_consistency",
+    "check_channel_plugin",
+    "check_feishu_plugin",
+    "run_doctor",
+    "test_ai_connection",
+    "test_provider_connection",
+    "test_channel",
+    "get_system_info",
+    "get_paths",
+    "check_environment",
+    "list_node_installations",
+    "check_openclaw_update",
+    "get_audit_log",
+];
+
+/// Web 用户角色：admin 可执行全部命令，viewer 只能调用只读命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum UserRole {
+    Admin,
+    Viewer,
+}
 
+/// 认证配置：顶层字段为初始化时创建的管理员，users 为其后添加的用户
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AuthConfig {
     username: String,
     salt: String,
     password_hash: String,
     created_at: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    users: Vec<AuthUser>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AuthUser {
+    username: String,
+    salt: String,
+    password_hash: String,
+    role: UserRole,
+    created_at: u64,
+}
+
+impl AuthConfig {
+    /// 按用户名查找，返回 (salt, password_hash, role)
+    fn find_user(&self, username: &str) -> Option<(&str, &str, UserRole)> {
+        if self.username == username {
+            return Some((&self.salt, &self.password_hash, UserRole::Admin));
+        }
+        self.users
+            .iter()
+            .find(|user| user.username == username)
+            .map(|user| (user.salt.as_str(), user.password_hash.as_str(), user.role))
+    }
 }
 
 #[derive(Debug, Clone)]
 struct SessionInfo {
     username: String,
+    role: UserRole,
     expires_at: u64,
 }
 
@@ -44,8 +139,15 @@ struct SessionInfo {
 struct AppState {
     sessions: Arc<RwLock<HashMap<String, SessionInfo>>>,
     auth_config_path: PathBuf,
+    /// 串行化认证配置的读取-校验-写入，避免并发初始化互相覆盖
+    auth_config_lock: Arc<Mutex<()>>,
     static_dir: PathBuf,
     cookie_secure: bool,
+    max_body_bytes: usize,
+    /// 读取请求（含 TLS 握手）的超时
+    read_timeout: Duration,
+    /// 只读模式（OPENCLAW_WEB_READONLY=1）：所有用户都不能调用修改类或有副作用的命令
+    read_only: bool,
     session_counter: Arc<AtomicU64>,
 }
 
@@ -68,6 +170,44 @@ struct LoginRequest {
     password: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct AddUserRequest {
+    username: String,
+    password: String,
+    role: UserRole,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoveUserRequest {
+    username: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RevokeSessionsRequest {
+    /// 保留调用者自己的会话（默认 false，即全部退出）
+    #[serde(default)]
+    keep_current: bool,
+}
+
+/// 会话列表项，不包含 token
+#[derive(Debug, Serialize)]
+struct SessionSummary {
+    username: String,
+    role: UserRole,
+    expires_at: u64,
+    /// 是否为调用者自己的会话
+    current: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct UserSummary {
+    username: String,
+    role: UserRole,
+    created_at: u64,
+    /// 初始化时创建的管理员，不可删除
+    primary: bool,
+}
+
 #[derive(Debug, Serialize)]
 struct ApiSuccess<T>
 where
@@ -88,6 +228,8 @@ struct AuthStatusResponse {
     needs_setup: bool,
     authenticated: bool,
     username: Option<String>,
+    role: Option<UserRole>,
+    read_only: bool,
 }
 
 #[derive(Debug)]
@@ -110,28 +252,50 @@ struct SimpleResponse {
 async fn main() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
+    let tls = load_tls_acceptor().expect("加载 TLS 证书失败");
     let state = AppState {
         sessions: Arc::new(RwLock::new(HashMap::new())),
         auth_config_path: get_auth_config_path(),
+        auth_config_lock: Arc::new(Mutex::new(())),
         static_dir: get_static_dir(),
-        cookie_secure: get_cookie_secure(),
+        // HTTPS 下 Cookie 始终加 Secure
+        cookie_secure: tls.is_some() || get_cookie_secure(),
+        max_body_bytes: get_max_body_bytes(),
+        read_timeout: get_read_timeout(),
+        read_only: get_read_only(),
         session_counter: Arc::new(AtomicU64::new(1)),
     };
 
-    let host = std::env::var("OPENCLAW_WEB_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
-    let port = std::env::var("OPENCLAW_WEB_PORT")
-        .ok()
-        .and_then(|v| v.parse::<u16>().ok())
-        .unwrap_or(17890);
+    let host = std::env::var("OPENCLAW_WEB_HOST").ok();
+    let addrs = resolve_listen_addrs(
+        host.as_deref(),
+        get_expose_flag(),
+        std::env::var("OPENCLAW_WEB_PORT").ok().as_deref(),
+    )
+    .await
+    .expect("无效监听地址");
+    let listener = bind_first(&addrs).await.expect("监听失败");
+    let addr = listener.local_addr().expect("读取监听地址失败");
+    if !addr.ip().is_loopback() && tls.is_none() {
+        warn!("⚠️ ============================================================");
+        warn!("⚠️ Web 管理面板以明文 HTTP 监听非回环地址 {}，", addr);
+        warn!("⚠️ 局域网内任何人都可访问登录页，登录密码与 API Key 以明文传输！");
+        warn!("⚠️ 请配置 OPENCLAW_WEB_TLS_CERT/KEY，或改为监听 127.0.0.1 并通过反向代理提供 HTTPS");
+        warn!("⚠️ ============================================================");
+    }
 
-    let addr: SocketAddr = format!("{}:{}", host, port)
-        .parse()
-        .expect("无效监听地址");
+    // Web 端无事件推送，前端通过 watch_config 轮询外部修改
+    config::start_config_watcher(None);
 
-    let listener = TcpListener::bind(addr).await.expect("监听失败");
-    info!("🌐 OpenClaw Manager Web 启动: http://{}", addr);
+    let scheme = if tls.is_some() { "https" } else { "http" };
+    info!("🌐 OpenClaw Manager Web 启动: {}://{}", scheme, addr);
     info!("📦 静态目录: {}", state.static_dir.display());
 
+    serve(listener, state, tls).await;
+}
+
+/// 接收连接并处理；提供 TLS acceptor 时先完成 TLS 握手再按 HTTP 处理
+async fn serve(listener: TcpListener, state: AppState, tls: Option<TlsAcceptor>) {
     loop {
         let (stream, peer) = match listener.accept().await {
             Ok(value) => value,
@@ -142,18 +306,93 @@ async fn main() {
         };
 
         let cloned_state = state.clone();
+        let tls = tls.clone();
         tokio::spawn(async move {
-            if let Err(error) = handle_connection(stream, cloned_state).await {
+            let result = match tls {
+                Some(acceptor) => {
+                    let handshake =
+                        tokio::time::timeout(cloned_state.read_timeout, acceptor.accept(stream));
+                    match handshake.await {
+                        Ok(Ok(tls_stream)) => handle_connection(tls_stream, cloned_state).await,
+                        Ok(Err(error)) => Err(format!("TLS 握手失败: {}", error)),
+                        Err(_) => Err("TLS 握手超时".to_string()),
+                    }
+                }
+                None => handle_connection(stream, cloned_state).await,
+            };
+            if let Err(error) = result {
                 warn!("处理连接失败 {}: {}", peer, error);
             }
         });
     }
 }
 
-async fn handle_connection(mut stream: TcpStream, state: AppState) -> Result<(), String> {
-    let request = match read_http_request(&mut stream).await {
+/// 从 PEM 证书链与私钥构建 TLS acceptor
+fn build_tls_acceptor(cert_pem: &[u8], key_pem: &[u8]) -> Result<TlsAcceptor, String> {
+    use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+
+    let certs = CertificateDer::pem_slice_iter(cert_pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("解析证书失败: {}", e))?;
+    if certs.is_empty() {
+        return Err("证书文件中没有证书".to_string());
+    }
+    let key = PrivateKeyDer::from_pem_slice(key_pem).map_err(|e| format!("解析私钥失败: {}", e))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("初始化 TLS 失败: {}", e))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("证书与私钥不匹配: {}", e))?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// 读取 OPENCLAW_WEB_TLS_CERT / OPENCLAW_WEB_TLS_KEY，两者都设置时启用 HTTPS
+fn load_tls_acceptor() -> Result<Option<TlsAcceptor>, String> {
+    let non_empty = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+    let (cert_path, key_path) = match (
+        non_empty("OPENCLAW_WEB_TLS_CERT"),
+        non_empty("OPENCLAW_WEB_TLS_KEY"),
+    ) {
+        (Some(cert), Some(key)) => (cert, key),
+        (None, None) => return Ok(None),
+        _ => return Err("OPENCLAW_WEB_TLS_CERT 与 OPENCLAW_WEB_TLS_KEY 需同时设置".to_string()),
+    };
+
+    let cert_pem =
+        std::fs::read(&cert_path).map_err(|e| format!("读取证书 {} 失败: {}", cert_path, e))?;
+    let key_pem =
+        std::fs::read(&key_path).map_err(|e| format!("读取私钥 {} 失败: {}", key_path, e))?;
+    let acceptor = build_tls_acceptor(&cert_pem, &key_pem)?;
+    info!("🔒 已启用 HTTPS，证书: {}", cert_path);
+    Ok(Some(acceptor))
+}
+
+async fn handle_connection<S>(mut stream: S, state: AppState) -> Result<(), String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let read = read_http_request(&mut stream, state.max_body_bytes);
+    let request = match tokio::time::timeout(state.read_timeout, read).await {
+        Err(_) => {
+            // 客户端迟迟不发完请求（slowloris），返回 408 后关闭连接
+            let response = text_response(408, "Request Timeout", "读取请求超时");
+            let _ = tokio::time::timeout(state.read_timeout, write_response(&mut stream, response))
+                .await;
+            return Err("读取请求超时".to_string());
+        }
+        Ok(read) => read,
+    };
+    let request = match request {
         Ok(Some(value)) => value,
         Ok(None) => return Ok(()),
+        Err(error) if error.starts_with(BODY_TOO_LARGE_ERROR) => {
+            let response = text_response(413, "Payload Too Large", error);
+            write_response(&mut stream, response).await?;
+            return Ok(());
+        }
         Err(error) => {
             let response = text_response(400, "Bad Request", error);
             write_response(&mut stream, response).await?;
@@ -166,7 +405,14 @@ async fn handle_connection(mut stream: TcpStream, state: AppState) -> Result<(),
     Ok(())
 }
 
-async fn read_http_request(stream: &mut TcpStream) -> Result<Option<SimpleRequest>, String> {
+/// 读取一个 HTTP 请求；请求体超过 max_body 时尽早返回以 BODY_TOO_LARGE_ERROR 开头的错误
+async fn read_http_request<S>(
+    stream: &mut S,
+    max_body: usize,
+) -> Result<Option<SimpleRequest>, String>
+where
+    S: AsyncRead + Unpin,
+{
     let mut buffer = Vec::new();
     let mut temp = [0_u8; 1024];
     let mut header_end = None;
@@ -191,7 +437,7 @@ async fn read_http_request(stream: &mut TcpStream) -> Result<Option<SimpleReques
             break;
         }
 
-        if buffer.len() > 1024 * 1024 {
+        if buffer.len() > MAX_REQUEST_BYTES {
             return Err("请求头过大".to_string());
         }
     }
@@ -229,10 +475,48 @@ async fn read_http_request(stream: &mut TcpStream) -> Result<Option<SimpleReques
         }
     }
 
+    let is_chunked = headers
+        .get("transfer-encoding")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("chunked"));
+    if is_chunked {
+        let mut raw = buffer[header_end..].to_vec();
+        let mut decoder = ChunkedDecoder::default();
+        let body = loop {
+            if let Some(body) = decoder.decode(&raw, max_body)? {
+                break body;
+            }
+            // chunk 头与 trailer 的开销有限，原始数据远超上限说明请求异常
+            if raw.len() > max_body.saturating_add(MAX_REQUEST_BYTES) {
+                return Err(format!("{}（上限 {} 字节）", BODY_TOO_LARGE_ERROR, max_body));
+            }
+            let read = stream
+                .read(&mut temp)
+                .await
+                .map_err(|e| format!("读取请求体失败: {}", e))?;
+            if read == 0 {
+                return Err("chunked 请求体不完整".to_string());
+            }
+            raw.extend_from_slice(&temp[..read]);
+        };
+
+        return Ok(Some(SimpleRequest {
+            method,
+            path,
+            headers,
+            body,
+        }));
+    }
+
     let content_length = headers
         .get("content-length")
         .and_then(|v| v.parse::<usize>().ok())
         .unwrap_or(0);
+    if content_length > max_body {
+        return Err(format!(
+            "{}（{} 字节，上限 {} 字节）",
+            BODY_TOO_LARGE_ERROR, content_length, max_body
+        ));
+    }
 
     let mut body = buffer[header_end..].to_vec();
     while body.len() < content_length {
@@ -255,6 +539,72 @@ async fn read_http_request(stream: &mut TcpStream) -> Result<Option<SimpleReques
     }))
 }
 
+/// 增量解码 chunked 请求体：记录已解析到的偏移，新数据到达后从断点继续，避免每次重新解析
+#[derive(Debug, Default)]
+struct ChunkedDecoder {
+    /// 下一个待解析的 chunk 大小行或 trailer 行在原始数据中的偏移
+    pos: usize,
+    /// 已读到结束 chunk（大小为 0），剩余为 trailer 头
+    in_trailer: bool,
+    body: Vec<u8>,
+}
+
+impl ChunkedDecoder {
+    /// 继续解析 data（此前已传入数据的完整副本加上新数据）；
+    /// 数据尚不完整时返回 Ok(None)，格式错误或超出上限时返回 Err
+    fn decode(&mut self, data: &[u8], max_body: usize) -> Result<Option<Vec<u8>>, String> {
+        loop {
+            let pos = self.pos;
+            let Some(line_len) = find_subsequence(&data[pos..], b"\r\n") else {
+                if !self.in_trailer && data.len() - pos > 1024 {
+                    return Err("chunk 大小行过长".to_string());
+                }
+                return Ok(None);
+            };
+
+            if self.in_trailer {
+                // 跳过 trailer 头，直到遇到空行
+                self.pos += line_len + 2;
+                if line_len == 0 {
+                    return Ok(Some(std::mem::take(&mut self.body)));
+                }
+                continue;
+            }
+
+            let size_line = std::str::from_utf8(&data[pos..pos + line_len])
+                .map_err(|_| "chunk 大小行不是有效 UTF-8".to_string())?;
+            // 忽略 chunk 扩展参数（; 之后的部分）
+            let size_text = size_line.split(';').next().unwrap_or_default().trim();
+            if size_text.is_empty() || !size_text.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("无效的 chunk 大小: {}", size_line));
+            }
+            let size = usize::from_str_radix(size_text, 16)
+                .map_err(|_| format!("无效的 chunk 大小: {}", size_line))?;
+            let data_start = pos + line_len + 2;
+
+            if size == 0 {
+                self.in_trailer = true;
+                self.pos = data_start;
+                continue;
+            }
+
+            if self.body.len().saturating_add(size) > max_body {
+                return Err(format!("{}（上限 {} 字节）", BODY_TOO_LARGE_ERROR, max_body));
+            }
+            // chunk 数据未到齐时不移动偏移，下次只需重新解析这一行大小
+            if data.len() < data_start + size + 2 {
+                return Ok(None);
+            }
+            let data_end = data_start + size;
+            if &data[data_end..data_end + 2] != b"\r\n" {
+                return Err("chunk 数据后缺少 CRLF".to_string());
+            }
+            self.body.extend_from_slice(&data[data_start..data_end]);
+            self.pos = data_end + 2;
+        }
+    }
+}
+
 fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     haystack.windows(needle.len()).position(|window| window == needle)
 }
@@ -278,15 +628,31 @@ async fn route_request(request: SimpleRequest, state: AppState) -> SimpleRespons
         ("POST", "/api/auth/login") => auth_login(request, state).await,
         ("POST", "/api/auth/logout") => auth_logout(request, state).await,
         ("GET", "/api/auth/me") => auth_me(request, state).await,
+        ("GET", "/api/auth/users") => auth_list_users(request, state).await,
+        ("POST", "/api/auth/users") => auth_add_user(request, state).await,
+        ("POST", "/api/auth/users/remove") => auth_remove_user(request, state).await,
+        ("GET", "/api/auth/sessions") => auth_list_sessions(request, state).await,
+        ("POST", "/api/auth/sessions/revoke-all") => auth_revoke_sessions(request, state).await,
         ("POST", "/api/invoke") => api_invoke(request, state).await,
 
-        ("GET", path) if !path.starts_with("/api/") => serve_static_file(path, &state.static_dir),
+        ("GET", path) if !path.starts_with("/api/") => {
+            let accept = request.headers.get("accept").map(String::as_str);
+            serve_static_file(path, accept, &state.static_dir)
+        }
 
         _ => json_error(404, "Not Found", "接口不存在"),
     }
 }
 
-fn serve_static_file(path: &str, static_dir: &PathBuf) -> SimpleResponse {
+/// 判断缺失的路径是否应回退到 index.html（前端路由）
+/// 无扩展名的路径或浏览器导航请求（Accept 含 text/html）视为前端路由，其余按缺失资源处理
+fn should_fallback_to_index(path: &str, accept: Option<&str>) -> bool {
+    let last_segment = path.rsplit('/').next().unwrap_or("");
+    let has_extension = last_segment.contains('.');
+    !has_extension || accept.map(|a| a.contains("text/html")).unwrap_or(false)
+}
+
+fn serve_static_file(path: &str, accept: Option<&str>, static_dir: &PathBuf) -> SimpleResponse {
     let mut relative = path.trim_start_matches('/').to_string();
     if relative.is_empty() {
         relative = "index.html".to_string();
@@ -302,14 +668,14 @@ fn serve_static_file(path: &str, static_dir: &PathBuf) -> SimpleResponse {
         target = target.join("index.html");
     }
 
-    if !target.exists() || target.is_dir() {
+    if (!target.exists() || target.is_dir()) && should_fallback_to_index(path, accept) {
         let index_file = static_dir.join("index.html");
         if index_file.exists() {
             target = index_file;
         }
     }
 
-    if !target.exists() {
+    if !target.exists() || target.is_dir() {
         return text_response(404, "Not Found", "页面不存在");
     }
 
@@ -342,9 +708,9 @@ fn guess_content_type(path: &str) -> &'static str {
         "text/html; charset=utf-8"
     } else if path.ends_with(".css") {
         "text/css; charset=utf-8"
-    } else if path.ends_with(".js") {
+    } else if path.ends_with(".js") || path.ends_with(".mjs") {
         "application/javascript; charset=utf-8"
-    } else if path.ends_with(".json") {
+    } else if path.ends_with(".json") || path.ends_with(".map") {
         "application/json; charset=utf-8"
     } else if path.ends_with(".png") {
         "image/png"
@@ -354,6 +720,14 @@ fn guess_content_type(path: &str) -> &'static str {
         "image/svg+xml"
     } else if path.ends_with(".ico") {
         "image/x-icon"
+    } else if path.ends_with(".woff2") {
+        "font/woff2"
+    } else if path.ends_with(".woff") {
+        "font/woff"
+    } else if path.ends_with(".ttf") {
+        "font/ttf"
+    } else if path.ends_with(".wasm") {
+        "application/wasm"
     } else {
         "application/octet-stream"
     }
@@ -390,9 +764,7 @@ fn now_nanos() -> u128 {
 }
 
 fn get_auth_config_path() -> PathBuf {
-    let mut path = PathBuf::from(utils::platform::get_config_dir());
-    path.push("manager-web-auth.json");
-    path
+    PathBuf::from(utils::platform::get_web_auth_config_path())
 }
 
 fn get_static_dir() -> PathBuf {
@@ -424,6 +796,103 @@ fn get_cookie_secure() -> bool {
         .unwrap_or(false)
 }
 
+/// 对外监听开关（OPENCLAW_WEB_EXPOSE=1）
+fn get_expose_flag() -> bool {
+    std::env::var("OPENCLAW_WEB_EXPOSE")
+        .ok()
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// 拼接 host:port，IPv6 字面量需加方括号
+fn format_host_port(host: &str, port: u16) -> String {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// 解析监听地址：显式指定的 host 优先；未指定时 expose 为 true 监听 0.0.0.0，否则只监听 127.0.0.1
+/// host 可以是 IPv4/IPv6 字面量（IPv6 可带方括号）或主机名，主机名通过 DNS 解析为全部候选地址
+async fn resolve_listen_addrs(
+    host: Option<&str>,
+    expose: bool,
+    port: Option<&str>,
+) -> Result<Vec<SocketAddr>, String> {
+    let host = match host.map(str::trim).filter(|h| !h.is_empty()) {
+        Some(host) => host,
+        None if expose => EXPOSED_WEB_HOST,
+        None => DEFAULT_WEB_HOST,
+    };
+    let port = match port.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(port) => port
+            .parse::<u16>()
+            .map_err(|_| format!("无效监听端口: {}", port))?,
+        None => DEFAULT_WEB_PORT,
+    };
+
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = literal.parse::<std::net::IpAddr>() {
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+    if literal.eq_ignore_ascii_case("localhost") {
+        return Ok(vec![SocketAddr::new(std::net::IpAddr::from([127, 0, 0, 1]), port)]);
+    }
+
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host(format_host_port(host, port))
+        .await
+        .map_err(|e| format!("无效监听地址 OPENCLAW_WEB_HOST={}: 无法解析主机名（{}）", host, e))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("无效监听地址 OPENCLAW_WEB_HOST={}: 未解析到任何 IP", host));
+    }
+    Ok(addrs)
+}
+
+/// 依次尝试绑定候选地址，返回第一个成功的监听器
+async fn bind_first(addrs: &[SocketAddr]) -> Result<TcpListener, String> {
+    let mut errors = Vec::new();
+    for addr in addrs {
+        match TcpListener::bind(addr).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) => {
+                warn!("监听 {} 失败: {}", addr, e);
+                errors.push(format!("{}: {}", addr, e));
+            }
+        }
+    }
+    Err(format!("所有候选地址均无法监听（{}）", errors.join("; ")))
+}
+
+/// 只读模式开关（OPENCLAW_WEB_READONLY=1）
+fn get_read_only() -> bool {
+    std::env::var("OPENCLAW_WEB_READONLY")
+        .ok()
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// 读取请求超时（OPENCLAW_WEB_READ_TIMEOUT_SECS，默认 15 秒）
+fn get_read_timeout() -> Duration {
+    let secs = std::env::var("OPENCLAW_WEB_READ_TIMEOUT_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_READ_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// 请求体大小上限（OPENCLAW_WEB_MAX_BODY_BYTES，默认 2MB）
+fn get_max_body_bytes() -> usize {
+    std::env::var("OPENCLAW_WEB_MAX_BODY_BYTES")
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_MAX_BODY_BYTES)
+}
+
 fn load_auth_config(path: &PathBuf) -> Result<Option<AuthConfig>, String> {
     if !path.exists() {
         return Ok(None);
@@ -441,6 +910,23 @@ fn load_auth_config(path: &PathBuf) -> Result<Option<AuthConfig>, String> {
     Ok(Some(parsed))
 }
 
+/// 认证配置写入守卫：同时持有进程内锁与锁文件（多个 web-server 实例共享配置目录时生效）
+struct AuthConfigGuard {
+    _file_lock: config::ConfigFileLock,
+    _guard: OwnedMutexGuard<()>,
+}
+
+/// 获取认证配置写入锁，持有期间完成整个读取-校验-写入流程
+async fn lock_auth_config(state: &AppState) -> Result<AuthConfigGuard, String> {
+    let guard = state.auth_config_lock.clone().lock_owned().await;
+    let lock_path = state.auth_config_path.with_extension("json.lock");
+    let file_lock = config::lock_file_exclusive(lock_path).await?;
+    Ok(AuthConfigGuard {
+        _file_lock: file_lock,
+        _guard: guard,
+    })
+}
+
 fn save_auth_config(path: &PathBuf, config: &AuthConfig) -> Result<(), String> {
     let content = serde_json::to_string_pretty(config)
         .map_err(|e| format!("序列化认证配置失败: {}", e))?;
@@ -566,12 +1052,16 @@ fn reason_text(status: u16) -> &'static str {
         404 => "Not Found",
         409 => "Conflict",
         412 => "Precondition Failed",
+        413 => "Payload Too Large",
         500 => "Internal Server Error",
         _ => "OK",
     }
 }
 
-async fn write_response(stream: &mut TcpStream, response: SimpleResponse) -> Result<(), String> {
+async fn write_response<S>(stream: &mut S, response: SimpleResponse) -> Result<(), String>
+where
+    S: AsyncWrite + Unpin,
+{
     let mut output = format!("HTTP/1.1 {} {}\r\n", response.status, response.reason);
 
     let mut has_content_length = false;
@@ -600,6 +1090,12 @@ async fn write_response(stream: &mut TcpStream, response: SimpleResponse) -> Res
             .map_err(|e| format!("写响应体失败: {}", e))?;
     }
 
+    // TLS 连接会缓冲加密数据，需要 flush 刷出，并通过 shutdown 发送 close_notify
+    stream
+        .flush()
+        .await
+        .map_err(|e| format!("写响应失败: {}", e))?;
+    let _ = stream.shutdown().await;
     Ok(())
 }
 
@@ -609,25 +1105,16 @@ async fn auth_status(request: SimpleRequest, state: AppState) -> SimpleResponse
         Err(error) => return json_error(500, "Internal Server Error", error),
     };
 
-    let mut authenticated = false;
-    let mut username = None;
-
-    if let Some(token) = get_cookie(&request.headers, SESSION_COOKIE) {
-        let sessions = state.sessions.read().await;
-        if let Some(session) = sessions.get(&token) {
-            if session.expires_at > now_ts() {
-                authenticated = true;
-                username = Some(session.username.clone());
-            }
-        }
-    }
+    let session = current_session(&request, &state).await.ok();
 
     json_response(
         200,
         json_success(AuthStatusResponse {
             needs_setup: auth.is_none(),
-            authenticated,
-            username,
+            authenticated: session.is_some(),
+            username: session.as_ref().map(|s| s.username.clone()),
+            role: session.map(|s| s.role),
+            read_only: state.read_only,
         }),
     )
 }
@@ -646,6 +1133,10 @@ async fn auth_setup(request: SimpleRequest, state: AppState) -> SimpleResponse {
         return json_error(400, "Bad Request", "密码至少 8 位");
     }
 
+    let _auth_guard = match lock_auth_config(&state).await {
+        Ok(guard) => guard,
+        Err(error) => return json_error(500, "Internal Server Error", error),
+    };
     match load_auth_config(&state.auth_config_path) {
         Ok(Some(_)) => return json_error(409, "Conflict", "管理员账号已初始化"),
         Ok(None) => {}
@@ -658,6 +1149,7 @@ async fn auth_setup(request: SimpleRequest, state: AppState) -> SimpleResponse {
         salt: salt.clone(),
         password_hash: derive_password_hash(&salt, &payload.password),
         created_at: now_ts(),
+        users: Vec::new(),
     };
 
     if let Err(error) = save_auth_config(&state.auth_config_path, &config) {
@@ -679,12 +1171,13 @@ async fn auth_login(request: SimpleRequest, state: AppState) -> SimpleResponse {
         Err(error) => return json_error(500, "Internal Server Error", error),
     };
 
-    if auth.username != payload.username {
-        return json_error(401, "Unauthorized", "用户名或密码错误");
-    }
+    let (salt, password_hash, role) = match auth.find_user(&payload.username) {
+        Some(value) => value,
+        None => return json_error(401, "Unauthorized", "用户名或密码错误"),
+    };
 
-    let expected = derive_password_hash(&auth.salt, &payload.password);
-    if expected != auth.password_hash {
+    let expected = derive_password_hash(salt, &payload.password);
+    if expected != password_hash {
         return json_error(401, "Unauthorized", "用户名或密码错误");
     }
 
@@ -694,13 +1187,17 @@ async fn auth_login(request: SimpleRequest, state: AppState) -> SimpleResponse {
         sessions.insert(
             token.clone(),
             SessionInfo {
-                username: auth.username.clone(),
+                username: payload.username.clone(),
+                role,
                 expires_at: now_ts() + SESSION_TTL_SECONDS,
             },
         );
     }
 
-    let mut response = json_response(200, json_success(json!({"username": auth.username})));
+    let mut response = json_response(
+        200,
+        json_success(json!({"username": payload.username, "role": role})),
+    );
     response
         .headers
         .push(("Set-Cookie".to_string(), build_set_cookie(&token, SESSION_TTL_SECONDS, state.cookie_secure)));
@@ -722,37 +1219,234 @@ async fn auth_logout(request: SimpleRequest, state: AppState) -> SimpleResponse
 }
 
 async fn auth_me(request: SimpleRequest, state: AppState) -> SimpleResponse {
-    let token = match get_cookie(&request.headers, SESSION_COOKIE) {
-        Some(value) => value,
-        None => return json_error(401, "Unauthorized", "未登录或会话已过期"),
-    };
+    match current_session(&request, &state).await {
+        Ok(session) => json_response(
+            200,
+            json_success(json!({"username": session.username, "role": session.role})),
+        ),
+        Err(response) => response,
+    }
+}
 
+/// 读取当前请求的有效会话，未登录或已过期时返回 401 响应
+async fn current_session(
+    request: &SimpleRequest,
+    state: &AppState,
+) -> Result<SessionInfo, SimpleResponse> {
+    let unauthorized = || json_error(401, "Unauthorized", "未登录或会话已过期");
+    let token = get_cookie(&request.headers, SESSION_COOKIE).ok_or_else(unauthorized)?;
     let sessions = state.sessions.read().await;
-    if let Some(session) = sessions.get(&token) {
-        if session.expires_at > now_ts() {
-            return json_response(200, json_success(json!({"username": session.username})));
-        }
+    match sessions.get(&token) {
+        Some(session) if session.expires_at > now_ts() => Ok(session.clone()),
+        _ => Err(unauthorized()),
     }
+}
 
-    json_error(401, "Unauthorized", "未登录或会话已过期")
+/// 要求当前会话为 admin，否则返回 401/403 响应
+async fn require_admin(
+    request: &SimpleRequest,
+    state: &AppState,
+) -> Result<SessionInfo, SimpleResponse> {
+    let session = current_session(request, state).await?;
+    if session.role != UserRole::Admin {
+        return Err(json_error(403, "Forbidden", "仅管理员可执行该操作"));
+    }
+    Ok(session)
 }
 
-async fn api_invoke(request: SimpleRequest, state: AppState) -> SimpleResponse {
-    let session_token = match get_cookie(&request.headers, SESSION_COOKIE) {
-        Some(value) => value,
-        None => return json_error(401, "Unauthorized", "未登录或会话已过期"),
+/// viewer 是否可调用该命令：仅允许 VIEWER_ALLOWED_COMMANDS 中的查询
+fn is_viewer_command(command: &str) -> bool {
+    VIEWER_ALLOWED_COMMANDS.contains(&command) && audit::is_read_only_command(command)
+}
+
+/// 只读模式下是否禁止该命令：只放行已归类为只读的查询（修改类与有副作用的命令均禁止）
+fn is_denied_in_read_only_mode(command: &str) -> bool {
+    !audit::is_read_only_command(command)
+}
+
+async fn auth_list_users(request: SimpleRequest, state: AppState) -> SimpleResponse {
+    if let Err(response) = require_admin(&request, &state).await {
+        return response;
+    }
+    let auth = match load_auth_config(&state.auth_config_path) {
+        Ok(Some(value)) => value,
+        Ok(None) => return json_error(412, "Precondition Failed", "请先初始化管理员账号"),
+        Err(error) => return json_error(500, "Internal Server Error", error),
     };
 
-    {
-        let sessions = state.sessions.read().await;
-        let valid = sessions
-            .get(&session_token)
-            .map(|session| session.expires_at > now_ts())
-            .unwrap_or(false);
-        if !valid {
-            return json_error(401, "Unauthorized", "未登录或会话已过期");
+    let mut users = vec![UserSummary {
+        username: auth.username.clone(),
+        role: UserRole::Admin,
+        created_at: auth.created_at,
+        primary: true,
+    }];
+    users.extend(auth.users.iter().map(|user| UserSummary {
+        username: user.username.clone(),
+        role: user.role,
+        created_at: user.created_at,
+        primary: false,
+    }));
+    json_response(200, json_success(users))
+}
+
+async fn auth_add_user(request: SimpleRequest, state: AppState) -> SimpleResponse {
+    if let Err(response) = require_admin(&request, &state).await {
+        return response;
+    }
+    let payload = match parse_json::<AddUserRequest>(&request.body) {
+        Ok(value) => value,
+        Err(error) => return json_error(400, "Bad Request", error),
+    };
+    let username = payload.username.trim().to_string();
+    if username.is_empty() {
+        return json_error(400, "Bad Request", "用户名不能为空");
+    }
+    if payload.password.len() < 8 {
+        return json_error(400, "Bad Request", "密码至少 8 位");
+    }
+
+    let _auth_guard = match lock_auth_config(&state).await {
+        Ok(guard) => guard,
+        Err(error) => return json_error(500, "Internal Server Error", error),
+    };
+    let mut auth = match load_auth_config(&state.auth_config_path) {
+        Ok(Some(value)) => value,
+        Ok(None) => return json_error(412, "Precondition Failed", "请先初始化管理员账号"),
+        Err(error) => return json_error(500, "Internal Server Error", error),
+    };
+    if auth.find_user(&username).is_some() {
+        return json_error(409, "Conflict", format!("用户已存在: {}", username));
+    }
+
+    let salt = new_salt();
+    auth.users.push(AuthUser {
+        username: username.clone(),
+        password_hash: derive_password_hash(&salt, &payload.password),
+        salt,
+        role: payload.role,
+        created_at: now_ts(),
+    });
+    if let Err(error) = save_auth_config(&state.auth_config_path, &auth) {
+        return json_error(500, "Internal Server Error", error);
+    }
+
+    json_response(200, json_success(json!({"message": format!("已添加用户: {}", username)})))
+}
+
+async fn auth_remove_user(request: SimpleRequest, state: AppState) -> SimpleResponse {
+    if let Err(response) = require_admin(&request, &state).await {
+        return response;
+    }
+    let payload = match parse_json::<RemoveUserRequest>(&request.body) {
+        Ok(value) => value,
+        Err(error) => return json_error(400, "Bad Request", error),
+    };
+
+    let _auth_guard = match lock_auth_config(&state).await {
+        Ok(guard) => guard,
+        Err(error) => return json_error(500, "Internal Server Error", error),
+    };
+    let mut auth = match load_auth_config(&state.auth_config_path) {
+        Ok(Some(value)) => value,
+        Ok(None) => return json_error(412, "Precondition Failed", "请先初始化管理员账号"),
+        Err(error) => return json_error(500, "Internal Server Error", error),
+    };
+    if auth.username == payload.username {
+        return json_error(400, "Bad Request", "不能删除初始管理员");
+    }
+    let before = auth.users.len();
+    auth.users.retain(|user| user.username != payload.username);
+    if auth.users.len() == before {
+        return json_error(404, "Not Found", format!("用户不存在: {}", payload.username));
+    }
+    if let Err(error) = save_auth_config(&state.auth_config_path, &auth) {
+        return json_error(500, "Internal Server Error", error);
+    }
+
+    // 已删除用户的会话立即失效
+    state
+        .sessions
+        .write()
+        .await
+        .retain(|_, session| session.username != payload.username);
+
+    json_response(
+        200,
+        json_success(json!({"message": format!("已删除用户: {}", payload.username)})),
+    )
+}
+
+async fn auth_list_sessions(request: SimpleRequest, state: AppState) -> SimpleResponse {
+    if let Err(response) = require_admin(&request, &state).await {
+        return response;
+    }
+    let current_token = get_cookie(&request.headers, SESSION_COOKIE);
+    let now = now_ts();
+    let mut sessions: Vec<SessionSummary> = state
+        .sessions
+        .read()
+        .await
+        .iter()
+        .filter(|(_, session)| session.expires_at > now)
+        .map(|(token, session)| SessionSummary {
+            username: session.username.clone(),
+            role: session.role,
+            expires_at: session.expires_at,
+            current: current_token.as_deref() == Some(token.as_str()),
+        })
+        .collect();
+    sessions.sort_by(|a, b| a.username.cmp(&b.username).then(a.expires_at.cmp(&b.expires_at)));
+    json_response(200, json_success(sessions))
+}
+
+/// 使所有会话失效（怀疑 Cookie 泄露时使用），keep_current 为 true 时保留调用者自己的会话
+async fn auth_revoke_sessions(request: SimpleRequest, state: AppState) -> SimpleResponse {
+    let session = match require_admin(&request, &state).await {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
+    let payload = if request.body.iter().all(u8::is_ascii_whitespace) {
+        RevokeSessionsRequest::default()
+    } else {
+        match parse_json::<RevokeSessionsRequest>(&request.body) {
+            Ok(value) => value,
+            Err(error) => return json_error(400, "Bad Request", error),
         }
+    };
+
+    let current_token = get_cookie(&request.headers, SESSION_COOKIE);
+    let revoked = {
+        let mut sessions = state.sessions.write().await;
+        let before = sessions.len();
+        if payload.keep_current {
+            sessions.retain(|token, _| current_token.as_deref() == Some(token.as_str()));
+        } else {
+            sessions.clear();
+        }
+        before - sessions.len()
+    };
+    warn!(
+        "[Web 认证] 管理员 {} 注销了 {} 个会话{}",
+        session.username,
+        revoked,
+        if payload.keep_current { "（保留当前会话）" } else { "" }
+    );
+
+    let mut response = json_response(200, json_success(json!({"revoked": revoked})));
+    if !payload.keep_current {
+        response.headers.push((
+            "Set-Cookie".to_string(),
+            build_set_cookie("deleted", 0, state.cookie_secure),
+        ));
     }
+    response
+}
+
+async fn api_invoke(request: SimpleRequest, state: AppState) -> SimpleResponse {
+    let session = match current_session(&request, &state).await {
+        Ok(value) => value,
+        Err(response) => return response,
+    };
 
     let payload = match parse_json::<InvokeRequest>(&request.body) {
         Ok(value) => value,
@@ -763,8 +1457,27 @@ async fn api_invoke(request: SimpleRequest, state: AppState) -> SimpleResponse {
         return json_error(400, "Bad Request", "cmd 不能为空");
     }
 
-    match dispatch_command(payload.cmd.trim(), &payload.args).await {
+    let command = payload.cmd.trim();
+    if state.read_only && is_denied_in_read_only_mode(command) {
+        return json_error(403, "Forbidden", format!("只读模式下禁止执行: {}", command));
+    }
+    if session.role == UserRole::Viewer && !is_viewer_command(command) {
+        return json_error(403, "Forbidden", format!("只读用户无权执行: {}", command));
+    }
+
+    let result = dispatch_command(command, &payload.args).await;
+    audit::record_command(
+        Some(&session.username),
+        command,
+        &payload.args,
+        Some(result.as_ref().map(|_| ()).map_err(String::as_str)),
+    );
+
+    match result {
         Ok(value) => json_response(200, json_success(value)),
+        Err(error) if error.starts_with(config::CONFIG_CONFLICT_ERROR) => {
+            json_error(409, "Conflict", error)
+        }
         Err(error) => json_error(400, "Bad Request", error),
     }
 }
@@ -797,158 +1510,1016 @@ fn optional_u32(args: &Value, keys: &[&str]) -> Option<u32> {
         .map(|v| v as u32)
 }
 
-async fn dispatch_command(command: &str, args: &Value) -> Result<Value, String> {
-    match command {
-        "check_environment" => Ok(json!(installer::check_environment().await?)),
-        "install_nodejs" => Ok(json!(installer::install_nodejs().await?)),
-        "install_openclaw" => Ok(json!(installer::install_openclaw().await?)),
-        "init_openclaw_config" => Ok(json!(installer::init_openclaw_config().await?)),
-        "open_install_terminal" => {
-            let install_type = require_string(args, &["installType", "install_type"], "installType")?;
-            Ok(json!(installer::open_install_terminal(install_type).await?))
-        }
-        "uninstall_openclaw" => Ok(json!(installer::uninstall_openclaw().await?)),
-        "check_openclaw_update" => Ok(json!(installer::check_openclaw_update().await?)),
-        "update_openclaw" => Ok(json!(installer::update_openclaw().await?)),
-
-        "get_service_status" => Ok(json!(service::get_service_status().await?)),
-        "start_service" => Ok(json!(service::start_service().await?)),
-        "stop_service" => Ok(json!(service::stop_service().await?)),
-        "restart_service" => Ok(json!(service::restart_service().await?)),
-        "get_logs" => {
-            let lines = optional_u32(args, &["lines"]);
-            Ok(json!(service::get_logs(lines).await?))
-        }
-
-        "check_openclaw_installed" => Ok(json!(process::check_openclaw_installed().await?)),
-        "get_openclaw_version" => Ok(json!(process::get_openclaw_version().await?)),
-        "check_port_in_use" => {
-            let port = require_string(args, &["port"], "port")?
-                .parse::<u16>()
-                .map_err(|_| "port 必须是有效数字".to_string())?;
-            Ok(json!(process::check_port_in_use(port).await?))
-        }
-        "get_node_version" => Ok(json!(process::get_node_version().await?)),
-
-        "get_config" => Ok(config::get_config().await?),
-        "save_config" => {
-            let cfg = read_arg(args, &["config"])
-                .cloned()
-                .ok_or_else(|| "缺少参数: config".to_string())?;
-            Ok(json!(config::save_config(cfg).await?))
-        }
-        "preview_config_change" => {
-            let input_config = read_arg(args, &["inputConfig", "input_config"])
-                .cloned()
-                .ok_or_else(|| "缺少参数: inputConfig".to_string())?;
-            Ok(json!(config::preview_config_change(input_config).await?))
-        }
-        "apply_config_change" => {
-            let input_config = read_arg(args, &["inputConfig", "input_config"])
-                .cloned()
-                .ok_or_else(|| "缺少参数: inputConfig".to_string())?;
-            Ok(json!(config::apply_config_change(input_config).await?))
-        }
-        "list_config_backups" => Ok(json!(config::list_config_backups().await?)),
-        "rollback_config" => {
-            let backup_path = read_arg(args, &["backupPath", "backup_path"])
-                .and_then(|v| v.as_str())
-                .map(|value| value.to_string());
-            Ok(json!(config::rollback_config(backup_path).await?))
-        }
-        "get_agents_list" => Ok(config::get_agents_list().await?),
-        "save_agents_list" => {
-            let agents_list = read_arg(args, &["agentsList", "agents_list", "agentsListJson", "agents_list_json"])
-                .cloned()
-                .ok_or_else(|| "缺少参数: agentsList".to_string())?;
-            Ok(json!(config::save_agents_list(agents_list).await?))
-        }
-        "get_bindings" => Ok(config::get_bindings().await?),
-        "save_bindings" => {
-            let bindings = read_arg(args, &["bindings"])
-                .cloned()
-                .ok_or_else(|| "缺少参数: bindings".to_string())?;
-            Ok(json!(config::save_bindings(bindings).await?))
-        }
-        "get_env_value" => {
-            let key = require_string(args, &["key"], "key")?;
-            Ok(json!(config::get_env_value(key).await?))
-        }
-        "save_env_value" => {
-            let key = require_string(args, &["key"], "key")?;
-            let value = require_string(args, &["value"], "value")?;
-            Ok(json!(config::save_env_value(key, value).await?))
-        }
-        "get_or_create_gateway_token" => Ok(json!(config::get_or_create_gateway_token().await?)),
-        "get_dashboard_url" => Ok(json!(config::get_dashboard_url().await?)),
-
-        "get_official_providers" => Ok(json!(config::get_official_providers().await?)),
-
-        "get_ai_config" => Ok(json!(config::get_ai_config().await?)),
-        "save_provider" => {
-            let provider_name = require_string(args, &["providerName", "provider_name"], "providerName")?;
-            let base_url = require_string(args, &["baseUrl", "base_url"], "baseUrl")?;
-            let api_key = read_arg(args, &["apiKey", "api_key"]).and_then(|v| v.as_str()).map(|v| v.to_string());
-            let api_type = require_string(args, &["apiType", "api_type"], "apiType")?;
-            let models: Vec<models::ModelConfig> = read_arg(args, &["models"])
-                .cloned()
-                .map(serde_json::from_value)
-                .transpose()
-                .map_err(|e| format!("models 参数无效: {}", e))?
-                .unwrap_or_default();
-            Ok(json!(config::save_provider(provider_name, base_url, api_key, api_type, models).await?))
-        }
-        "delete_provider" => {
-            let provider_name = require_string(args, &["providerName", "provider_name"], "providerName")?;
-            Ok(json!(config::delete_provider(provider_name).await?))
-        }
-        "set_primary_model" => {
-            let model_id = require_string(args, &["modelId", "model_id"], "modelId")?;
-            Ok(json!(config::set_primary_model(model_id).await?))
-        }
-        "add_available_model" => {
-            let model_id = require_string(args, &["modelId", "model_id"], "modelId")?;
-            Ok(json!(config::add_available_model(model_id).await?))
-        }
-        "remove_available_model" => {
-            let model_id = require_string(args, &["modelId", "model_id"], "modelId")?;
-            Ok(json!(config::remove_available_model(model_id).await?))
-        }
-        "get_ai_providers" => Ok(json!(config::get_ai_providers().await?)),
-        "get_channels_config" => Ok(json!(config::get_channels_config().await?)),
-        "save_channel_config" => {
-            let channel: models::ChannelConfig = read_arg(args, &["channel"])
-                .cloned()
-                .map(serde_json::from_value)
-                .transpose()
-                .map_err(|e| format!("channel 参数无效: {}", e))?
-                .ok_or_else(|| "缺少参数: channel".to_string())?;
-            Ok(json!(config::save_channel_config(channel).await?))
-        }
-        "clear_channel_config" => {
-            let channel_id = require_string(args, &["channelId", "channel_id"], "channelId")?;
-            Ok(json!(config::clear_channel_config(channel_id).await?))
-        }
-        "check_feishu_plugin" => Ok(json!(config::check_feishu_plugin().await?)),
-        "install_feishu_plugin" => Ok(json!(config::install_feishu_plugin().await?)),
-
-        "run_doctor" => Ok(json!(diagnostics::run_doctor().await?)),
-        "test_ai_connection" => Ok(json!(diagnostics::test_ai_connection().await?)),
-        "test_channel" => {
-            let channel_type = require_string(args, &["channelType", "channel_type"], "channelType")?;
-            Ok(json!(diagnostics::test_channel(channel_type).await?))
-        }
-        "send_test_message" => {
-            let channel_type = require_string(args, &["channelType", "channel_type"], "channelType")?;
-            let target = require_string(args, &["target"], "target")?;
-            Ok(json!(diagnostics::send_test_message(channel_type, target).await?))
-        }
-        "get_system_info" => Ok(json!(diagnostics::get_system_info().await?)),
-        "start_channel_login" => {
-            let channel_type = require_string(args, &["channelType", "channel_type"], "channelType")?;
-            Ok(json!(diagnostics::start_channel_login(channel_type).await?))
-        }
-
-        _ => Err(format!("未知命令: {}", command)),
+/// 由同一张分支表生成 dispatch_command 与其命令名清单，二者不会脱节
+macro_rules! web_dispatch {
+    ($args:ident; $($name:literal => $body:expr),* $(,)?) => {
+        /// Web 端可分发的命令名
+        #[cfg(test)]
+        const WEB_COMMAND_NAMES: &[&str] = &[$($name),*];
+
+        async fn dispatch_command(command: &str, $args: &Value) -> Result<Value, String> {
+            match command {
+                $($name => $body,)*
+                _ => Err(format!("未知命令: {}", command)),
+            }
+        }
+    };
+}
+
+web_dispatch! { args;
+    "check_environment" => Ok(json!(installer::check_environment().await?)),
+    "install_nodejs" => Ok(json!(installer::install_nodejs().await?)),
+    "list_node_installations" => Ok(json!(installer::list_node_installations().await?)),
+    "set_preferred_node_path" => {
+        let path = read_arg(args, &["path"])
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+        Ok(json!(installer::set_preferred_node_path(path).await?))
+    },
+    "install_openclaw" => {
+        let prefer_brew =
+            read_arg(args, &["preferBrew", "prefer_brew"]).and_then(|v| v.as_bool());
+        Ok(json!(installer::install_openclaw(prefer_brew).await?))
+    },
+    "init_openclaw_config" => Ok(json!(installer::init_openclaw_config().await?)),
+    "open_install_terminal" => {
+        let install_type = require_string(args, &["installType", "install_type"], "installType")?;
+        Ok(json!(installer::open_install_terminal(install_type).await?))
+    },
+    "uninstall_openclaw" => {
+        let purge_config = read_arg(args, &["purgeConfig", "purge_config"])
+            .and_then(|v| v.as_bool());
+        Ok(json!(installer::uninstall_openclaw(purge_config).await?))
+    },
+    "check_openclaw_update" => Ok(json!(installer::check_openclaw_update().await?)),
+    "update_openclaw" => Ok(json!(installer::update_openclaw().await?)),
+
+    "get_service_status" => Ok(json!(service::get_service_status().await?)),
+    "start_service" => {
+        let force = read_arg(args, &["force"]).and_then(|v| v.as_bool());
+        let auto_port = read_arg(args, &["autoPort", "auto_port"]).and_then(|v| v.as_bool());
+        Ok(json!(service::start_service(force, auto_port).await?))
+    },
+    "stop_service" => {
+        let force = read_arg(args, &["force"]).and_then(|v| v.as_bool());
+        let timeout_secs = optional_u32(args, &["timeoutSecs", "timeout_secs"]);
+        let kill_all = read_arg(args, &["killAll", "kill_all"]).and_then(|v| v.as_bool());
+        Ok(json!(service::stop_service(force, timeout_secs, kill_all).await?))
+    },
+    "restart_service" => {
+        let force = read_arg(args, &["force"]).and_then(|v| v.as_bool());
+        Ok(json!(service::restart_service(force).await?))
+    },
+    "get_logs" => {
+        let lines = optional_u32(args, &["lines"]);
+        let before_offset =
+            read_arg(args, &["beforeOffset", "before_offset"]).and_then(|v| v.as_u64());
+        Ok(json!(service::get_logs(lines, before_offset).await?))
+    },
+    "clear_logs" => Ok(json!(service::clear_logs().await?)),
+    "install_autostart" => Ok(json!(autostart::install_autostart().await?)),
+    "uninstall_autostart" => Ok(json!(autostart::uninstall_autostart().await?)),
+    "is_autostart_enabled" => Ok(json!(autostart::is_autostart_enabled().await?)),
+    "get_sessions_info" => Ok(json!(sessions::get_sessions_info().await?)),
+    "clear_old_sessions" => {
+        let older_than_days = optional_u32(args, &["olderThanDays", "older_than_days"])
+            .ok_or_else(|| "缺少参数: olderThanDays".to_string())?;
+        let confirm = read_arg(args, &["confirm"]).and_then(|v| v.as_bool());
+        Ok(json!(sessions::clear_old_sessions(older_than_days, confirm).await?))
+    },
+    "get_usage_stats" => {
+        let period = read_arg(args, &["period"])
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+        Ok(json!(sessions::get_usage_stats(period).await?))
+    },
+    "list_credentials" => Ok(json!(credentials::list_credentials().await?)),
+    "clear_credentials" => {
+        let channel = require_string(args, &["channel"], "channel")?;
+        let confirm = read_arg(args, &["confirm"]).and_then(|v| v.as_bool());
+        Ok(json!(credentials::clear_credentials(channel, confirm).await?))
+    },
+
+    "check_openclaw_installed" => Ok(json!(process::check_openclaw_installed().await?)),
+    "get_openclaw_version" => Ok(json!(process::get_openclaw_version().await?)),
+    "check_port_in_use" => {
+        let port = require_string(args, &["port"], "port")?
+            .parse::<u16>()
+            .map_err(|_| "port 必须是有效数字".to_string())?;
+        Ok(json!(process::check_port_in_use(port).await?))
+    },
+    "check_port_owner" => {
+        let port = require_string(args, &["port"], "port")?
+            .parse::<u16>()
+            .map_err(|_| "port 必须是有效数字".to_string())?;
+        Ok(json!(process::check_port_owner(port).await?))
+    },
+    "list_gateway_processes" => Ok(json!(process::list_gateway_processes().await?)),
+    "kill_gateway_process" => {
+        let pid = optional_u32(args, &["pid"]).ok_or_else(|| "缺少参数: pid".to_string())?;
+        Ok(json!(process::kill_gateway_process(pid).await?))
+    },
+    "get_node_version" => Ok(json!(process::get_node_version().await?)),
+    "run_openclaw_command" => {
+        let cmd_args: Vec<String> = read_arg(args, &["args"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: args".to_string())
+            .and_then(|v| {
+                serde_json::from_value(v).map_err(|e| format!("args 参数无效: {}", e))
+            })?;
+        Ok(json!(process::run_openclaw_command(cmd_args).await?))
+    },
+
+    "get_config" => Ok(json!(config::get_config().await?)),
+    "get_config_schema" => Ok(config::get_config_schema().await?),
+    "get_effective_config" => Ok(json!(config::get_effective_config().await?)),
+    "save_config" => {
+        let cfg = read_arg(args, &["config"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: config".to_string())?;
+        let expected_version = read_arg(args, &["expectedVersion", "expected_version"])
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+        Ok(json!(config::save_config(cfg, expected_version).await?))
+    },
+    "preview_config_change" => {
+        let input_config = read_arg(args, &["inputConfig", "input_config"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: inputConfig".to_string())?;
+        Ok(json!(config::preview_config_change(input_config).await?))
+    },
+    "apply_config_change" => {
+        let input_config = read_arg(args, &["inputConfig", "input_config"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: inputConfig".to_string())?;
+        let expected_version = read_arg(args, &["expectedVersion", "expected_version"])
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+        Ok(json!(config::apply_config_change(input_config, expected_version).await?))
+    },
+    "list_config_backups" => Ok(json!(config::list_config_backups().await?)),
+    "export_config_bundle" => {
+        let include_secrets = read_arg(args, &["includeSecrets", "include_secrets"])
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        Ok(json!(config::export_config_bundle(include_secrets).await?))
+    },
+    "import_config_bundle" => {
+        let data = require_string(args, &["data"], "data")?;
+        let overwrite = read_arg(args, &["overwrite"])
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        Ok(json!(config::import_config_bundle(data, overwrite).await?))
+    },
+    "list_profiles" => Ok(json!(profile::list_profiles().await?)),
+    "current_profile" => Ok(json!(profile::current_profile().await?)),
+    "create_profile" => {
+        let name = require_string(args, &["name"], "name")?;
+        Ok(json!(profile::create_profile(name).await?))
+    },
+    "switch_profile" => {
+        let name = require_string(args, &["name"], "name")?;
+        Ok(json!(profile::switch_profile(name).await?))
+    },
+    "rollback_config" => {
+        let backup_path = read_arg(args, &["backupPath", "backup_path"])
+            .and_then(|v| v.as_str())
+            .map(|value| value.to_string());
+        Ok(json!(config::rollback_config(backup_path).await?))
+    },
+    "get_agents_list" => Ok(config::get_agents_list().await?),
+    "save_agents_list" => {
+        let agents_list = read_arg(args, &["agentsList", "agents_list", "agentsListJson", "agents_list_json"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: agentsList".to_string())?;
+        Ok(json!(config::save_agents_list(agents_list).await?))
+    },
+    "get_agent" => {
+        let agent_id = require_string(args, &["agentId", "agent_id"], "agentId")?;
+        Ok(config::get_agent(agent_id).await?)
+    },
+    "set_agent_model" => {
+        let agent_id = require_string(args, &["agentId", "agent_id"], "agentId")?;
+        let primary = read_arg(args, &["primary"])
+            .and_then(|v| v.as_str())
+            .map(|value| value.to_string());
+        let fallbacks: Option<Vec<String>> = read_arg(args, &["fallbacks"])
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| format!("fallbacks 参数无效: {}", e))?;
+        Ok(json!(config::set_agent_model(agent_id, primary, fallbacks).await?))
+    },
+    "add_agent" => {
+        let agent = read_arg(args, &["agent"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: agent".to_string())
+            .and_then(|v| {
+                serde_json::from_value(v).map_err(|e| format!("agent 参数无效: {}", e))
+            })?;
+        Ok(json!(config::add_agent(agent).await?))
+    },
+    "update_agent" => {
+        let agent_id = require_string(args, &["agentId", "agent_id"], "agentId")?;
+        let agent = read_arg(args, &["agent"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: agent".to_string())
+            .and_then(|v| {
+                serde_json::from_value(v).map_err(|e| format!("agent 参数无效: {}", e))
+            })?;
+        Ok(json!(config::update_agent(agent_id, agent).await?))
+    },
+    "delete_agent" => {
+        let agent_id = require_string(args, &["agentId", "agent_id"], "agentId")?;
+        Ok(json!(config::delete_agent(agent_id).await?))
+    },
+    "get_bindings" => Ok(config::get_bindings().await?),
+    "save_bindings" => {
+        let bindings = read_arg(args, &["bindings"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: bindings".to_string())?;
+        Ok(json!(config::save_bindings(bindings).await?))
+    },
+    "repair_orphaned_bindings" => Ok(json!(config::repair_orphaned_bindings().await?)),
+    "migrate_bindings" => {
+        let target_shape =
+            require_string(args, &["targetShape", "target_shape"], "targetShape")?;
+        Ok(json!(config::migrate_bindings(target_shape).await?))
+    },
+    "get_env_value" => {
+        let key = require_string(args, &["key"], "key")?;
+        Ok(json!(config::get_env_value(key).await?))
+    },
+    "get_env_values" => {
+        let keys: Vec<String> = read_arg(args, &["keys"])
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| format!("keys 参数无效: {}", e))?
+            .ok_or_else(|| "缺少参数: keys".to_string())?;
+        Ok(json!(config::get_env_values(keys).await?))
+    },
+    "get_env_keys" => Ok(json!(config::get_env_keys().await?)),
+    "remove_env_key" => {
+        let key = require_string(args, &["key"], "key")?;
+        Ok(json!(config::remove_env_key(key).await?))
+    },
+    "save_env_value" => {
+        let key = require_string(args, &["key"], "key")?;
+        let value = require_string(args, &["value"], "value")?;
+        Ok(json!(config::save_env_value(key, value).await?))
+    },
+    "save_env_values" => {
+        let values: HashMap<String, String> = read_arg(args, &["values"])
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| format!("values 参数无效: {}", e))?
+            .ok_or_else(|| "缺少参数: values".to_string())?;
+        Ok(json!(config::save_env_values(values).await?))
+    },
+    "get_or_create_gateway_token" => Ok(json!(config::get_or_create_gateway_token().await?)),
+    "rotate_gateway_token" => {
+        let restart = read_arg(args, &["restart"]).and_then(|v| v.as_bool());
+        Ok(json!(config::rotate_gateway_token(restart).await?))
+    },
+    "get_dashboard_url" => {
+        let external_host = read_arg(args, &["externalHost", "external_host"])
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+        Ok(json!(config::get_dashboard_url(external_host).await?))
+    },
+    "get_gateway_settings" => Ok(json!(config::get_gateway_settings().await?)),
+    "save_gateway_settings" => {
+        let settings = read_arg(args, &["settings"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: settings".to_string())?;
+        let settings = serde_json::from_value(settings)
+            .map_err(|e| format!("settings 参数无效: {}", e))?;
+        Ok(json!(config::save_gateway_settings(settings).await?))
+    },
+    "verify_gateway_token" => Ok(json!(config::verify_gateway_token().await?)),
+    "watch_config" => {
+        let since = read_arg(args, &["since"]).and_then(|v| v.as_u64());
+        Ok(json!(config::watch_config(since).await?))
+    },
+    "open_config_file" => {
+        let create_if_missing = read_arg(args, &["createIfMissing", "create_if_missing"])
+            .and_then(|v| v.as_bool());
+        Ok(json!(config::open_config_file(create_if_missing).await?))
+    },
+    "open_config_dir" => Ok(json!(config::open_config_dir().await?)),
+
+    "get_official_providers" => Ok(json!(config::get_official_providers().await?)),
+
+    "get_ai_config" => Ok(json!(config::get_ai_config().await?)),
+    "save_provider" => {
+        let provider_name = require_string(args, &["providerName", "provider_name"], "providerName")?;
+        let base_url = require_string(args, &["baseUrl", "base_url"], "baseUrl")?;
+        let api_key = read_arg(args, &["apiKey", "api_key"]).and_then(|v| v.as_str()).map(|v| v.to_string());
+        let api_type = require_string(args, &["apiType", "api_type"], "apiType")?;
+        let models: Vec<models::ModelConfig> = read_arg(args, &["models"])
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| format!("models 参数无效: {}", e))?
+            .unwrap_or_default();
+        Ok(json!(config::save_provider(provider_name, base_url, api_key, api_type, models).await?))
+    },
+    "delete_provider" => {
+        let provider_name = require_string(args, &["providerName", "provider_name"], "providerName")?;
+        Ok(json!(config::delete_provider(provider_name).await?))
+    },
+    "clone_provider" => {
+        let source_name = require_string(args, &["sourceName", "source_name"], "sourceName")?;
+        let new_name = require_string(args, &["newName", "new_name"], "newName")?;
+        Ok(json!(config::clone_provider(source_name, new_name).await?))
+    },
+    "set_primary_model" => {
+        let model_id = require_string(args, &["modelId", "model_id"], "modelId")?;
+        Ok(json!(config::set_primary_model(model_id).await?))
+    },
+    "get_agent_defaults" => Ok(json!(config::get_agent_defaults().await?)),
+    "set_max_concurrent" => {
+        let max_concurrent = optional_u32(args, &["maxConcurrent", "max_concurrent"])
+            .ok_or_else(|| "缺少参数: maxConcurrent".to_string())?;
+        Ok(json!(config::set_max_concurrent(max_concurrent).await?))
+    },
+    "add_available_model" => {
+        let model_id = require_string(args, &["modelId", "model_id"], "modelId")?;
+        Ok(json!(config::add_available_model(model_id).await?))
+    },
+    "remove_available_model" => {
+        let model_id = require_string(args, &["modelId", "model_id"], "modelId")?;
+        Ok(json!(config::remove_available_model(model_id).await?))
+    },
+    "update_model_cost" => {
+        let full_id = require_string(args, &["fullId", "full_id"], "fullId")?;
+        let cost = read_arg(args, &["cost"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: cost".to_string())?;
+        let cost =
+            serde_json::from_value(cost).map_err(|e| format!("cost 参数无效: {}", e))?;
+        Ok(json!(config::update_model_cost(full_id, cost).await?))
+    },
+    "get_ai_providers" => Ok(json!(config::get_ai_providers().await?)),
+    "get_channels_config" => Ok(json!(config::get_channels_config().await?)),
+    "save_channel_config" => {
+        let channel: models::ChannelConfig = read_arg(args, &["channel"])
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| format!("channel 参数无效: {}", e))?
+            .ok_or_else(|| "缺少参数: channel".to_string())?;
+        Ok(json!(config::save_channel_config(channel).await?))
+    },
+    "import_channels" => {
+        let channels: Vec<models::ChannelConfig> = read_arg(args, &["channels"])
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| format!("channels 参数无效: {}", e))?
+            .ok_or_else(|| "缺少参数: channels".to_string())?;
+        Ok(json!(config::import_channels(channels).await?))
+    },
+    "clear_channel_config" => {
+        let channel_id = require_string(args, &["channelId", "channel_id"], "channelId")?;
+        Ok(json!(config::clear_channel_config(channel_id).await?))
+    },
+    "set_channel_enabled" => {
+        let channel_id = require_string(args, &["channelId", "channel_id"], "channelId")?;
+        let enabled = read_arg(args, &["enabled"])
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| "缺少参数: enabled".to_string())?;
+        Ok(json!(config::set_channel_enabled(channel_id, enabled).await?))
+    },
+    "set_channels_enabled" => {
+        let channel_ids: Vec<String> = read_arg(args, &["channelIds", "channel_ids"])
+            .cloned()
+            .ok_or_else(|| "缺少参数: channelIds".to_string())
+            .and_then(|v| {
+                serde_json::from_value(v).map_err(|e| format!("channelIds 参数无效: {}", e))
+            })?;
+        let enabled = read_arg(args, &["enabled"])
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| "缺少参数: enabled".to_string())?;
+        Ok(json!(config::set_channels_enabled(channel_ids, enabled).await?))
+    },
+    "list_plugins" => Ok(json!(config::list_plugins().await?)),
+    "enable_plugin" => {
+        let plugin_id = require_string(args, &["pluginId", "plugin_id", "id"], "pluginId")?;
+        Ok(json!(config::enable_plugin(plugin_id).await?))
+    },
+    "disable_plugin" => {
+        let plugin_id = require_string(args, &["pluginId", "plugin_id", "id"], "pluginId")?;
+        Ok(json!(config::disable_plugin(plugin_id).await?))
+    },
+    "audit_plugin_consistency" => Ok(json!(config::audit_plugin_consistency().await?)),
+    "repair_plugin_consistency" => Ok(json!(config::repair_plugin_consistency().await?)),
+    "validate_channel_accounts" => Ok(json!(config::validate_channel_accounts().await?)),
+    "check_channel_plugin" => {
+        let channel_id = require_string(args, &["channelId", "channel_id"], "channelId")?;
+        Ok(json!(config::check_channel_plugin(channel_id).await?))
+    },
+    "check_feishu_plugin" => Ok(json!(config::check_feishu_plugin().await?)),
+    "install_feishu_plugin" => Ok(json!(config::install_feishu_plugin().await?)),
+
+    "run_doctor" => Ok(json!(diagnostics::run_doctor().await?)),
+    "fix_config_permissions" => Ok(json!(diagnostics::fix_config_permissions().await?)),
+    "test_ai_connection" => Ok(json!(diagnostics::test_ai_connection().await?)),
+    "test_provider_connection" => {
+        let provider_name =
+            require_string(args, &["providerName", "provider_name"], "providerName")?;
+        let model_id = read_arg(args, &["modelId", "model_id"])
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+        Ok(json!(diagnostics::test_provider_connection(provider_name, model_id).await?))
+    },
+    "test_channel" => {
+        let channel_type = require_string(args, &["channelType", "channel_type"], "channelType")?;
+        Ok(json!(diagnostics::test_channel(channel_type).await?))
+    },
+    "send_test_message" => {
+        let channel_type = require_string(args, &["channelType", "channel_type"], "channelType")?;
+        let target = require_string(args, &["target"], "target")?;
+        Ok(json!(diagnostics::send_test_message(channel_type, target).await?))
+    },
+    "get_system_info" => Ok(json!(diagnostics::get_system_info().await?)),
+    "get_paths" => Ok(json!(diagnostics::get_paths().await?)),
+    "start_channel_login" => {
+        let channel_type = require_string(args, &["channelType", "channel_type"], "channelType")?;
+        Ok(json!(diagnostics::start_channel_login(channel_type).await?))
+    },
+    "get_audit_log" => Ok(json!(audit::get_audit_log(optional_u32(args, &["limit"])).await?)),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        auth_setup, build_tls_acceptor, format_host_port, guess_content_type, handle_connection,
+        is_denied_in_read_only_mode, is_viewer_command, load_auth_config, resolve_listen_addrs,
+        route_request, serve, serve_static_file, AppState, ChunkedDecoder, SimpleRequest, UserRole,
+        DEFAULT_MAX_BODY_BYTES, DEFAULT_READ_TIMEOUT_SECS,
+    };
+    use crate::commands::config::tests::unique_temp_dir;
+    use std::collections::{BTreeSet, HashMap};
+    use std::path::PathBuf;
+    use std::sync::{atomic::AtomicU64, Arc};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::{Mutex, RwLock};
+
+    const TEST_TLS_CERT: &[u8] = include_bytes!("../tests/fixtures/web_tls_cert.pem");
+    const TEST_TLS_KEY: &[u8] = include_bytes!("../tests/fixtures/web_tls_key.pem");
+
+    fn test_state(auth_config_path: PathBuf) -> AppState {
+        AppState {
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            auth_config_path,
+            auth_config_lock: Arc::new(Mutex::new(())),
+            static_dir: PathBuf::from("/nonexistent"),
+            cookie_secure: true,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            read_timeout: Duration::from_secs(DEFAULT_READ_TIMEOUT_SECS),
+            read_only: false,
+            session_counter: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    #[test]
+    fn web_dispatch_matches_command_table() {
+        let expected: BTreeSet<&str> = super::commands::COMMAND_NAMES.iter().copied().collect();
+        assert_eq!(expected.len(), super::commands::COMMAND_NAMES.len(), "命令表存在重复项");
+        assert!(expected.contains("send_test_message"));
+
+        let dispatched: BTreeSet<&str> = super::WEB_COMMAND_NAMES.iter().copied().collect();
+        assert_eq!(dispatched.len(), super::WEB_COMMAND_NAMES.len(), "dispatch_command 存在重复分支");
+        let missing: Vec<_> = expected.difference(&dispatched).collect();
+        let unlisted: Vec<_> = dispatched.difference(&expected).collect();
+        assert!(missing.is_empty(), "dispatch_command 缺少命令: {:?}", missing);
+        assert!(unlisted.is_empty(), "dispatch_command 存在未列入命令表的命令: {:?}", unlisted);
+    }
+
+    fn decode_chunked_body(data: &[u8], max_body: usize) -> Result<Option<Vec<u8>>, String> {
+        ChunkedDecoder::default().decode(data, max_body)
+    }
+
+    #[test]
+    fn chunked_body_is_reassembled() {
+        let raw: &[u8] = concat!(
+            "4\r\n{\"cm\r\n",
+            "7;ext=1\r\nd\":\"run\r\n",
+            "A\r\n_doctor\"}\n\r\n",
+            "0\r\nX-Trailer: 1\r\n\r\n"
+        )
+        .as_bytes();
+        let body = decode_chunked_body(raw, 1024).unwrap().expect("完整的 chunked 请求体");
+        assert_eq!(body, b"{\"cmd\":\"run_doctor\"}\n");
+
+        // 数据尚未读完时应等待更多数据
+        assert_eq!(decode_chunked_body(&raw[..10], 1024).unwrap(), None);
+        assert_eq!(decode_chunked_body(b"0\r\n", 1024).unwrap(), None);
+
+        // 逐字节到达时从上次偏移继续解析，结果与一次性解码一致
+        let mut decoder = ChunkedDecoder::default();
+        let mut decoded = None;
+        for end in 1..=raw.len() {
+            assert!(decoded.is_none(), "结束前不应提前返回");
+            decoded = decoder.decode(&raw[..end], 1024).unwrap();
+        }
+        assert_eq!(decoded.as_deref(), Some(&b"{\"cmd\":\"run_doctor\"}\n"[..]));
+    }
+
+    #[test]
+    fn malformed_chunked_body_is_rejected() {
+        assert!(decode_chunked_body(b"zz\r\nhello\r\n0\r\n\r\n", 1024).is_err());
+        assert!(decode_chunked_body(b"\r\nhello\r\n0\r\n\r\n", 1024).is_err());
+        assert!(decode_chunked_body(b"3\r\nhelloXX0\r\n\r\n", 1024).is_err());
+        assert!(decode_chunked_body(b"10\r\n0123456789abcdef\r\n0\r\n\r\n", 8).is_err());
+    }
+
+    #[test]
+    fn oversized_request_body_is_rejected_with_413() {
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        runtime.block_on(async {
+            let send = |request: Vec<u8>| async move {
+                let mut state = test_state(PathBuf::from("/nonexistent/web-auth.json"));
+                state.max_body_bytes = 64;
+                let (mut client, server) = tokio::io::duplex(16 * 1024);
+                let handler = tokio::spawn(handle_connection(server, state));
+                client.write_all(&request).await.expect("应可发送请求");
+                let mut response = Vec::new();
+                client.read_to_end(&mut response).await.expect("应可读取响应");
+                handler.await.expect("任务应正常结束").expect("连接应正常处理");
+                String::from_utf8_lossy(&response).to_string()
+            };
+
+            // 声明的 Content-Length 超限时不读取请求体直接拒绝
+            let mut oversized =
+                b"POST /api/invoke HTTP/1.1\r\nContent-Length: 1048576\r\n\r\n".to_vec();
+            oversized.extend_from_slice(&[b'x'; 128]);
+            let response = send(oversized).await;
+            assert!(response.starts_with("HTTP/1.1 413 Payload Too Large"), "{}", response);
+
+            let chunked = b"POST /api/invoke HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+                            100\r\n".to_vec();
+            let response = send(chunked).await;
+            assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+
+            // 未超限的请求正常进入路由
+            let small = b"POST /api/invoke HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}".to_vec();
+            let response = send(small).await;
+            assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+        });
+    }
+
+    #[test]
+    fn stalled_request_is_closed_with_408() {
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        runtime.block_on(async {
+            let mut state = test_state(PathBuf::from("/nonexistent/web-auth.json"));
+            state.read_timeout = Duration::from_millis(100);
+            let (mut client, server) = tokio::io::duplex(16 * 1024);
+            let handler = tokio::spawn(handle_connection(server, state));
+
+            // 只发送一半请求头后停住，连接保持打开
+            client.write_all(b"GET /api/auth/status HTTP/1.1\r\nHost: loc").await.unwrap();
+            let result = tokio::time::timeout(Duration::from_secs(5), handler)
+                .await
+                .expect("处理函数应在超时后返回而不是一直挂起")
+                .expect("任务应正常结束");
+            assert!(result.unwrap_err().contains("超时"));
+
+            let mut response = Vec::new();
+            client.read_to_end(&mut response).await.expect("应可读取响应");
+            let response = String::from_utf8_lossy(&response);
+            assert!(response.starts_with("HTTP/1.1 408 Request Timeout"), "{}", response);
+        });
+    }
+
+    #[test]
+    fn static_assets_get_browser_compatible_mime_types() {
+        assert_eq!(guess_content_type("assets/inter.woff2"), "font/woff2");
+        assert_eq!(guess_content_type("assets/inter.woff"), "font/woff");
+        assert_eq!(guess_content_type("assets/icons.ttf"), "font/ttf");
+        assert_eq!(guess_content_type("assets/module.wasm"), "application/wasm");
+        assert_eq!(guess_content_type("assets/index.js.map"), "application/json; charset=utf-8");
+        assert_eq!(
+            guess_content_type("assets/worker.mjs"),
+            "application/javascript; charset=utf-8"
+        );
+        assert_eq!(guess_content_type("assets/blob.bin"), "application/octet-stream");
+    }
+
+    #[test]
+    fn spa_fallback_only_applies_to_client_routes() {
+        let static_dir = unique_temp_dir("static");
+        std::fs::create_dir_all(static_dir.join("assets")).expect("创建静态目录");
+        std::fs::write(static_dir.join("index.html"), "<html>app</html>").expect("写入 index.html");
+        std::fs::write(static_dir.join("assets/app.js"), "console.log(1)").expect("写入 app.js");
+
+        let root = serve_static_file("/", None, &static_dir);
+        assert_eq!((root.status, root.body.as_slice()), (200, &b"<html>app</html>"[..]));
+
+        let route = serve_static_file("/foo/bar", None, &static_dir);
+        assert_eq!((route.status, route.body.as_slice()), (200, &b"<html>app</html>"[..]));
+
+        let asset = serve_static_file("/assets/app.js", None, &static_dir);
+        assert_eq!((asset.status, asset.body.as_slice()), (200, &b"console.log(1)"[..]));
+
+        let missing_asset = serve_static_file("/assets/x.js", Some("*/*"), &static_dir);
+        assert_eq!(missing_asset.status, 404);
+
+        // 浏览器导航请求仍回退到 index.html
+        let navigation = serve_static_file("/docs/v1.2", Some("text/html,*/*"), &static_dir);
+        assert_eq!(navigation.status, 200);
+
+        let _ = std::fs::remove_dir_all(&static_dir);
+    }
+
+    #[test]
+    fn tls_acceptor_rejects_missing_certificate() {
+        assert!(build_tls_acceptor(b"", TEST_TLS_KEY).is_err());
+        assert!(build_tls_acceptor(TEST_TLS_CERT, b"").is_err());
+        assert!(build_tls_acceptor(TEST_TLS_CERT, TEST_TLS_KEY).is_ok());
+    }
+
+    #[test]
+    fn https_server_completes_tls_handshake() {
+        use rustls::pki_types::{pem::PemObject, CertificateDer, ServerName};
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.expect("应可监听");
+            let addr = listener.local_addr().expect("应可获取监听地址");
+            let state = test_state(PathBuf::from("/nonexistent/web-auth.json"));
+            let acceptor = build_tls_acceptor(TEST_TLS_CERT, TEST_TLS_KEY).expect("应可加载证书");
+            tokio::spawn(serve(listener, state, Some(acceptor)));
+
+            let mut roots = rustls::RootCertStore::empty();
+            for cert in CertificateDer::pem_slice_iter(TEST_TLS_CERT) {
+                roots.add(cert.expect("应可解析证书")).expect("应可添加根证书");
+            }
+            let provider = Arc::new(rustls::crypto::ring::default_provider());
+            let client_config = rustls::ClientConfig::builder_with_provider(provider)
+                .with_safe_default_protocol_versions()
+                .expect("应可初始化 TLS")
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+
+            let tcp = TcpStream::connect(addr).await.expect("应可连接");
+            let server_name = ServerName::try_from("localhost").expect("应为合法主机名");
+            let mut tls = connector.connect(server_name, tcp).await.expect("TLS 握手应成功");
+            tls.write_all(b"GET /api/health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .await
+                .expect("应可发送请求");
+            let mut response = Vec::new();
+            tls.read_to_end(&mut response).await.expect("应可读取响应");
+            let response = String::from_utf8_lossy(&response);
+            assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+            assert!(response.contains("\"status\":\"ok\""), "{}", response);
+
+            // 明文请求无法通过 TLS 握手
+            let mut plain = TcpStream::connect(addr).await.expect("应可连接");
+            plain
+                .write_all(b"GET /api/health HTTP/1.1\r\n\r\n")
+                .await
+                .expect("应可发送请求");
+            let mut response = Vec::new();
+            let _ = plain.read_to_end(&mut response).await;
+            assert!(!String::from_utf8_lossy(&response).contains("\"status\":\"ok\""));
+        });
+    }
+
+    #[test]
+    fn concurrent_auth_setup_initializes_exactly_once() {
+        let dir = unique_temp_dir("web-auth");
+        let state = test_state(dir.join("manager-web-auth.json"));
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(4)
+            .enable_all()
+            .build()
+            .expect("应可创建异步运行时");
+        let statuses: Vec<u16> = runtime.block_on(async {
+            let tasks: Vec<_> = (0..8)
+                .map(|idx| {
+                    let state = state.clone();
+                    tokio::spawn(async move {
+                        let body =
+                            format!(r#"{{"username":"admin{}","password":"password123"}}"#, idx);
+                        let request = SimpleRequest {
+                            method: "POST".to_string(),
+                            path: "/api/auth/setup".to_string(),
+                            headers: HashMap::new(),
+                            body: body.into_bytes(),
+                        };
+                        auth_setup(request, state).await.status
+                    })
+                })
+                .collect();
+            let mut statuses = Vec::new();
+            for task in tasks {
+                statuses.push(task.await.expect("任务应正常结束"));
+            }
+            statuses
+        });
+
+        assert_eq!(statuses.iter().filter(|&&s| s == 200).count(), 1, "{:?}", statuses);
+        assert_eq!(statuses.iter().filter(|&&s| s == 409).count(), 7, "{:?}", statuses);
+        let saved = load_auth_config(&state.auth_config_path)
+            .expect("应可读取认证配置")
+            .expect("认证配置应已写入");
+        assert!(saved.username.starts_with("admin"));
+        assert!(!dir.join("manager-web-auth.json.lock").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// 发送 JSON 请求并返回响应；token 非空时携带会话 Cookie
+    async fn call(
+        state: &AppState,
+        method: &str,
+        path: &str,
+        token: Option<&str>,
+        body: &str,
+    ) -> super::SimpleResponse {
+        let mut headers = HashMap::new();
+        if let Some(token) = token {
+            headers.insert("cookie".to_string(), format!("{}={}", super::SESSION_COOKIE, token));
+        }
+        let request = SimpleRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers,
+            body: body.as_bytes().to_vec(),
+        };
+        route_request(request, state.clone()).await
+    }
+
+    /// 登录并返回会话 token
+    async fn login(state: &AppState, username: &str, password: &str) -> String {
+        let body = format!(r#"{{"username":"{}","password":"{}"}}"#, username, password);
+        let response = call(state, "POST", "/api/auth/login", None, &body).await;
+        assert_eq!(response.status, 200, "{}", String::from_utf8_lossy(&response.body));
+        let cookie = response
+            .headers
+            .iter()
+            .find(|(key, _)| key == "Set-Cookie")
+            .map(|(_, value)| value.clone())
+            .expect("登录响应应设置 Cookie");
+        let pair = cookie.split(';').next().expect("Cookie 应有值");
+        pair.split_once('=').expect("Cookie 应为键值对").1.to_string()
+    }
+
+    #[test]
+    fn viewer_session_is_denied_mutating_commands() {
+        let dir = unique_temp_dir("web-role");
+        let state = test_state(dir.join("manager-web-auth.json"));
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        runtime.block_on(async {
+            let setup = r#"{"username":"admin","password":"password123"}"#;
+            let response = call(&state, "POST", "/api/auth/setup", None, setup).await;
+            assert_eq!(response.status, 200);
+            let admin = login(&state, "admin", "password123").await;
+
+            let add = r#"{"username":"alice","password":"viewer-pass","role":"viewer"}"#;
+            let response = call(&state, "POST", "/api/auth/users", Some(&admin), add).await;
+            assert_eq!(response.status, 200, "{}", String::from_utf8_lossy(&response.body));
+            let response = call(&state, "POST", "/api/auth/users", Some(&admin), add).await;
+            assert_eq!(response.status, 409);
+
+            let viewer = login(&state, "alice", "viewer-pass").await;
+            let save = r#"{"cmd":"save_config","args":{"config":{}}}"#;
+            let response = call(&state, "POST", "/api/invoke", Some(&viewer), save).await;
+            assert_eq!(response.status, 403);
+            assert!(String::from_utf8_lossy(&response.body).contains("save_config"));
+
+            let response = call(&state, "GET", "/api/auth/users", Some(&viewer), "").await;
+            assert_eq!(response.status, 403);
+            let response = call(&state, "GET", "/api/auth/me", Some(&viewer), "").await;
+            assert!(String::from_utf8_lossy(&response.body).contains(r#""role":"viewer""#));
+
+            let remove_path = "/api/auth/users/remove";
+            let remove = r#"{"username":"alice"}"#;
+            let response = call(&state, "POST", remove_path, Some(&admin), remove).await;
+            assert_eq!(response.status, 200);
+            let response = call(&state, "POST", "/api/invoke", Some(&viewer), save).await;
+            assert_eq!(response.status, 401);
+
+            let remove_primary = r#"{"username":"admin"}"#;
+            let response = call(&state, "POST", remove_path, Some(&admin), remove_primary).await;
+            assert_eq!(response.status, 400);
+        });
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn revoke_all_sessions_clears_map_and_listing_hides_tokens() {
+        let dir = unique_temp_dir("web-sessions");
+        let state = test_state(dir.join("manager-web-auth.json"));
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        runtime.block_on(async {
+            let setup = r#"{"username":"admin","password":"password123"}"#;
+            assert_eq!(call(&state, "POST", "/api/auth/setup", None, setup).await.status, 200);
+            let admin = login(&state, "admin", "password123").await;
+            let other = login(&state, "admin", "password123").await;
+            let add = r#"{"username":"bob","password":"viewer-pass","role":"viewer"}"#;
+            let response = call(&state, "POST", "/api/auth/users", Some(&admin), add).await;
+            assert_eq!(response.status, 200);
+            let viewer = login(&state, "bob", "viewer-pass").await;
+
+            let response = call(&state, "GET", "/api/auth/sessions", Some(&viewer), "").await;
+            assert_eq!(response.status, 403);
+            let response = call(&state, "GET", "/api/auth/sessions", Some(&admin), "").await;
+            assert_eq!(response.status, 200);
+            let body = String::from_utf8_lossy(&response.body).to_string();
+            for token in [&admin, &other, &viewer] {
+                assert!(!body.contains(token.as_str()), "会话列表不应包含 token");
+            }
+            let listed: serde_json::Value = serde_json::from_str(&body).unwrap();
+            let listed = listed["data"].as_array().unwrap();
+            assert_eq!(listed.len(), 3);
+            assert_eq!(listed.iter().filter(|s| s["current"] == true).count(), 1);
+            assert!(listed.iter().any(|s| s["username"] == "bob" && s["expires_at"].is_u64()));
+
+            let revoke = "/api/auth/sessions/revoke-all";
+            let keep = r#"{"keep_current":true}"#;
+            let response = call(&state, "POST", revoke, Some(&admin), keep).await;
+            assert!(String::from_utf8_lossy(&response.body).contains(r#""revoked":2"#));
+            assert_eq!(state.sessions.read().await.len(), 1);
+            assert_eq!(call(&state, "GET", "/api/auth/me", Some(&viewer), "").await.status, 401);
+            assert_eq!(call(&state, "GET", "/api/auth/me", Some(&other), "").await.status, 401);
+
+            let response = call(&state, "POST", revoke, Some(&admin), "").await;
+            assert_eq!(response.status, 200);
+            assert!(state.sessions.read().await.is_empty());
+            assert_eq!(call(&state, "GET", "/api/auth/me", Some(&admin), "").await.status, 401);
+        });
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn viewer_access_is_limited_to_the_allowlist() {
+        for command in super::VIEWER_ALLOWED_COMMANDS {
+            assert!(
+                crate::commands::COMMAND_NAMES.contains(command),
+                "白名单中的命令不存在: {}",
+                command
+            );
+            assert!(crate::commands::audit::is_read_only_command(command), "{}", command);
+        }
+        let prefixes = ["save_", "delete_", "install_", "uninstall_", "update_", "clear_"];
+        for command in crate::commands::COMMAND_NAMES {
+            if prefixes.iter().any(|p| command.starts_with(p)) {
+                assert!(!is_viewer_command(command), "应禁止: {}", command);
+            }
+        }
+        // 返回明文密钥、在服务器上创建文件或启动进程的查询同样禁止
+        let denied = [
+            "start_service",
+            "run_openclaw_command",
+            "get_env_value",
+            "get_env_values",
+            "get_config",
+            "get_effective_config",
+            "get_channels_config",
+            "get_gateway_settings",
+            "export_config_bundle",
+            "get_dashboard_url",
+            "get_or_create_gateway_token",
+            "verify_gateway_token",
+            "open_config_file",
+            "open_config_dir",
+            "open_install_terminal",
+            "send_test_message",
+            "not_a_command",
+        ];
+        for command in denied {
+            assert!(!is_viewer_command(command), "应禁止: {}", command);
+        }
+        let allowed = ["check_openclaw_installed", "test_ai_connection", "run_doctor", "get_logs"];
+        assert!(allowed.iter().all(|command| is_viewer_command(command)));
+
+        // 只读模式只拦截修改类与有副作用的命令，查询类命令对管理员仍然可用
+        assert!(is_denied_in_read_only_mode("save_config"));
+        assert!(is_denied_in_read_only_mode("run_openclaw_command"));
+        assert!(!is_denied_in_read_only_mode("get_config"));
+        assert!(!is_denied_in_read_only_mode("get_env_values"));
+    }
+
+    #[test]
+    fn read_only_mode_rejects_mutating_commands_with_403() {
+        let _lock = crate::commands::config::tests::test_env_lock();
+        let _home = crate::commands::config::tests::TempHomeGuard::new();
+        let dir = unique_temp_dir("web-readonly");
+        let mut state = test_state(dir.join("manager-web-auth.json"));
+        state.read_only = true;
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        runtime.block_on(async {
+            let setup = r#"{"username":"admin","password":"password123"}"#;
+            assert_eq!(call(&state, "POST", "/api/auth/setup", None, setup).await.status, 200);
+            let admin = login(&state, "admin", "password123").await;
+
+            let read = r#"{"cmd":"get_official_providers"}"#;
+            let response = call(&state, "POST", "/api/invoke", Some(&admin), read).await;
+            assert_eq!(response.status, 200, "{}", String::from_utf8_lossy(&response.body));
+
+            let delete = r#"{"cmd":"delete_provider","args":{"providerName":"openai"}}"#;
+            let response = call(&state, "POST", "/api/invoke", Some(&admin), delete).await;
+            assert_eq!(response.status, 403);
+            assert!(String::from_utf8_lossy(&response.body).contains("只读模式"));
+
+            // 管理员在只读模式下仍可读取配置，但不能在服务器上启动进程或创建文件
+            let read = r#"{"cmd":"get_config"}"#;
+            let response = call(&state, "POST", "/api/invoke", Some(&admin), read).await;
+            assert_eq!(response.status, 200, "{}", String::from_utf8_lossy(&response.body));
+            let denied = [
+                "run_openclaw_command",
+                "get_or_create_gateway_token",
+                "open_config_file",
+            ];
+            for cmd in denied {
+                let body = format!(r#"{{"cmd":"{}","args":{{"args":["--version"]}}}}"#, cmd);
+                let response = call(&state, "POST", "/api/invoke", Some(&admin), &body).await;
+                assert_eq!(response.status, 403, "{}", cmd);
+            }
+
+            let response = call(&state, "GET", "/api/auth/status", None, "").await;
+            assert!(String::from_utf8_lossy(&response.body).contains(r#""read_only":true"#));
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn single_admin_auth_config_still_loads() {
+        let dir = unique_temp_dir("web-legacy-auth");
+        let path = dir.join("manager-web-auth.json");
+        std::fs::write(
+            &path,
+            r#"{"username":"admin","salt":"abc","password_hash":"def","created_at":1}"#,
+        )
+        .expect("应可写入认证配置");
+
+        let auth = load_auth_config(&path).expect("应可读取").expect("应存在");
+        assert!(auth.users.is_empty());
+        assert_eq!(auth.find_user("admin"), Some(("abc", "def", UserRole::Admin)));
+        assert_eq!(auth.find_user("alice"), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn listen_addr_defaults_to_loopback_unless_exposed() {
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        let resolve = |host: Option<&str>, expose: bool, port: Option<&str>| {
+            runtime
+                .block_on(resolve_listen_addrs(host, expose, port))
+                .map(|addrs| addrs[0])
+        };
+        let addr = resolve(None, false, None).unwrap();
+        assert_eq!(addr.to_string(), "127.0.0.1:17890");
+        assert!(addr.ip().is_loopback());
+        assert_eq!(resolve(Some(" "), false, Some("")).unwrap(), addr);
+
+        let exposed = resolve(None, true, Some("8080")).unwrap();
+        assert_eq!(exposed.to_string(), "0.0.0.0:8080");
+        let explicit = resolve(Some("0.0.0.0"), false, None).unwrap();
+        assert_eq!(explicit.to_string(), "0.0.0.0:17890");
+        let lan = resolve(Some("192.168.1.10"), true, Some("17891")).unwrap();
+        assert_eq!(lan.to_string(), "192.168.1.10:17891");
+        assert!(resolve(Some("localhost"), false, None).unwrap().ip().is_loopback());
+        assert!(resolve(None, false, Some("70000")).is_err());
+    }
+
+    #[test]
+    fn listen_addr_accepts_ipv6_and_names_unresolvable_hosts() {
+        assert_eq!(format_host_port("::1", 17890), "[::1]:17890");
+        assert_eq!(format_host_port("[fe80::1]", 80), "[fe80::1]:80");
+        assert_eq!(format_host_port("127.0.0.1", 80), "127.0.0.1:80");
+        assert_eq!(format_host_port("example.com", 80), "example.com:80");
+
+        let runtime = tokio::runtime::Runtime::new().expect("应可创建异步运行时");
+        for host in ["::1", "[::1]"] {
+            let addrs = runtime.block_on(resolve_listen_addrs(Some(host), false, None)).unwrap();
+            assert_eq!(addrs[0].to_string(), "[::1]:17890");
+        }
+        let any = runtime.block_on(resolve_listen_addrs(Some("::"), false, Some("8080"))).unwrap();
+        assert_eq!(any[0].to_string(), "[::]:8080");
+
+        // .invalid 为保留顶级域，保证不可解析
+        let error = runtime
+            .block_on(resolve_listen_addrs(Some("openclaw-manager.invalid"), false, None))
+            .unwrap_err();
+        assert!(error.contains("openclaw-manager.invalid"), "{}", error);
+        let error = runtime
+            .block_on(resolve_listen_addrs(Some("not a host"), false, None))
+            .unwrap_err();
+        assert!(error.contains("not a host"), "{}", error);
     }
 }
@@ -32,6 +32,8 @@ import clsx from "clsx";
 
 interface FeishuPluginStatus {
   installed: boolean;
+  /** 获取插件列表失败时为 unknown，此时无法判断插件是否缺失 */
+  state: "builtin" | "installed" | "missing" | "unknown";
   version: string | null;
   plugin_name: string | null;
 }
@@ -622,6 +624,7 @@ export function Channels() {
       console.error("检查飞书插件失败:", e);
       setFeishuPluginStatus({
         installed: false,
+        state: "unknown",
         version: null,
         plugin_name: null,
       });
@@ -761,7 +764,7 @@ export function Channels() {
     const init = async () => {
       try {
         const { channelList, bindingMap } = await fetchAllData();
-        const configured = channelList.find((c) => c.enabled) ?? channelList[0];
+        const configured = channelList.find((c) => c.configured) ?? channelList[0];
         if (configured) {
           hydrateChannelEditor(configured.id, channelList, bindingMap);
         }
@@ -978,7 +981,10 @@ export function Channels() {
         accountConfigPayload[accountId] = configFromForm(form);
       });
 
-      await invoke("save_channel_config", {
+      const saveResult = await invoke<{
+        message: string;
+        issues: { path: string; message: string }[];
+      }>("save_channel_config", {
         channel: {
           ...channel,
           config,
@@ -1016,7 +1022,22 @@ export function Channels() {
       const { channelList, bindingMap } = await fetchAllData();
       hydrateChannelEditor(selectedChannel, channelList, bindingMap);
 
-      alert("渠道配置与账号绑定已保存！");
+      // 随后会通过 save_bindings 重新写入的账号绑定不算丢弃
+      const resavedPaths = new Set(
+        Object.entries(accountBindings)
+          .filter(
+            ([accountId, agentId]) => agentId.trim() && accountForms[accountId]
+          )
+          .map(([accountId]) => `bindings.${selectedChannel}.${accountId}`)
+      );
+      const warnings = saveResult.issues
+        .filter((issue) => !resavedPaths.has(issue.path))
+        .map((issue) => `- ${issue.message}`);
+      alert(
+        warnings.length > 0
+          ? `渠道配置与账号绑定已保存，但存在以下问题：\n${warnings.join("\n")}`
+          : "渠道配置与账号绑定已保存！"
+      );
     } catch (e) {
       console.error("保存失败:", e);
       alert("保存失败: " + e);
@@ -1039,10 +1060,10 @@ export function Channels() {
 
   const hasValidConfig = (channel: ChannelConfig) => {
     const info = channelInfo[channel.channel_type];
-    if (!info) return channel.enabled;
+    if (!info) return channel.configured;
 
     const requiredFields = info.fields.filter((f) => f.required);
-    if (requiredFields.length === 0) return channel.enabled;
+    if (requiredFields.length === 0) return channel.configured;
 
     const topLevelOk = requiredFields.some((field) => {
       const value = channel.config[field.key];
@@ -1057,7 +1078,7 @@ export function Channels() {
       })
     );
 
-    return topLevelOk || accountOk || channel.enabled;
+    return topLevelOk || accountOk || channel.configured;
   };
 
   if (loading) {
@@ -1236,11 +1257,14 @@ export function Channels() {
                           />
                           <div className="flex-1">
                             <p className="text-amber-400 font-medium">
-                              需要安装飞书插件
+                              {feishuPluginStatus?.state === "unknown"
+                                ? "无法确认飞书插件状态"
+                                : "需要安装飞书插件"}
                             </p>
                             <p className="text-xs text-gray-400 mt-1">
-                              飞书渠道需要先安装 @m1heng-clawd/feishu
-                              插件才能使用。
+                              {feishuPluginStatus?.state === "unknown"
+                                ? "获取插件列表失败，请确认已安装 @m1heng-clawd/feishu 插件后刷新状态。"
+                                : "飞书渠道需要先安装 @m1heng-clawd/feishu 插件才能使用。"}
                             </p>
                             <div className="mt-3 flex flex-wrap gap-2">
                               <button
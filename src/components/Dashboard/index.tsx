@@ -5,7 +5,7 @@ import { StatusCard } from "./StatusCard";
 import { QuickActions } from "./QuickActions";
 import { SystemInfo } from "./SystemInfo";
 import { Setup } from "../Setup";
-import { api, ServiceStatus } from "../../lib/tauri";
+import { api, LogPage, ServiceStatus } from "../../lib/tauri";
 import { Terminal, RefreshCw, ChevronDown, ChevronUp } from "lucide-react";
 import clsx from "clsx";
 import { EnvironmentStatus } from "../../App";
@@ -37,8 +37,8 @@ export function Dashboard({ envStatus, onSetupComplete }: DashboardProps) {
 
   const fetchLogs = async () => {
     try {
-      const result = await invoke<string[]>("get_logs", { lines: 50 });
-      setLogs(result);
+      const result = await invoke<LogPage>("get_logs", { lines: 50 });
+      setLogs(result.lines);
     } catch {
       // 静默处理
     }
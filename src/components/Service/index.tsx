@@ -11,6 +11,7 @@ import {
 } from 'lucide-react';
 import clsx from 'clsx';
 import { serviceLogger } from '../../lib/logger';
+import { LogPage } from '../../lib/tauri';
 
 export function ServiceManager() {
   const [logs, setLogs] = useState<string[]>([]);
@@ -22,9 +23,9 @@ export function ServiceManager() {
 
   const fetchLogs = async () => {
     try {
-      const result = await invoke<string[]>('get_logs', { lines: 100 });
-      setLogs(result);
-      serviceLogger.debug(`获取到 ${result.length} 行日志`);
+      const result = await invoke<LogPage>('get_logs', { lines: 100 });
+      setLogs(result.lines);
+      serviceLogger.debug(`获取到 ${result.lines.length} 行日志`);
     } catch (e) {
       serviceLogger.error('获取日志失败', e);
     }
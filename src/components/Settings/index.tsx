@@ -82,6 +82,8 @@ interface PreviewConfigResponse {
 interface ApplyConfigResponse {
   backup_path: string;
   applied_at: string;
+  /** 写入后的配置版本 */
+  version: string;
 }
 
 interface RollbackConfigResponse {
@@ -537,6 +539,8 @@ export function Settings({ onEnvironmentChange }: SettingsProps) {
   const [backupOptions, setBackupOptions] = useState<ConfigBackupItem[]>([]);
   const [selectedBackupPath, setSelectedBackupPath] = useState("");
   const [baselineManagedSignature, setBaselineManagedSignature] = useState("");
+  // 加载编辑数据时的配置版本，应用时用于检测其他窗口的修改
+  const [configVersion, setConfigVersion] = useState<string | null>(null);
 
   const managedConfigSignature = useMemo(() => {
     if (!expertMode) {
@@ -627,7 +631,9 @@ export function Settings({ onEnvironmentChange }: SettingsProps) {
     agentsList: Record<string, unknown>[],
     bindingsPayload: BindingsPayload
   ) => {
-    const fullConfig = await invoke<Record<string, unknown>>("get_config");
+    const { config: fullConfig } = await invoke<{
+      config: Record<string, unknown>;
+    }>("get_config");
     const merged = {
       ...fullConfig,
       agents: {
@@ -789,8 +795,10 @@ export function Settings({ onEnvironmentChange }: SettingsProps) {
         "apply_config_change",
         {
           inputConfig: globalInputConfig,
+          expectedVersion: configVersion ?? undefined,
         }
       );
+      setConfigVersion(applyResult.version);
 
       setVisualAgents(payload.normalizedAgents);
       setVisualBindings(payload.normalizedBindings);
@@ -853,11 +861,13 @@ export function Settings({ onEnvironmentChange }: SettingsProps) {
         backupPath: selectedBackupPath,
       });
 
-      const [agentsResult, bindingsResult, channelsResult] = await Promise.all([
-        invoke<unknown>("get_agents_list"),
-        invoke<unknown>("get_bindings"),
-        invoke<ChannelConfig[]>("get_channels_config"),
-      ]);
+      const [agentsResult, bindingsResult, channelsResult, versioned] =
+        await Promise.all([
+          invoke<unknown>("get_agents_list"),
+          invoke<unknown>("get_bindings"),
+          invoke<ChannelConfig[]>("get_channels_config"),
+          invoke<{ version: string }>("get_config"),
+        ]);
 
       const nextVisualAgents = parseAgentsList(agentsResult);
       const nextVisualBindings = bindingsMapToRules(
@@ -870,6 +880,7 @@ export function Settings({ onEnvironmentChange }: SettingsProps) {
       setAgentsListText(JSON.stringify(agentsResult ?? [], null, 2));
       setBindingsText(JSON.stringify(bindingsResult ?? [], null, 2));
       setChannelsConfig(channelsResult ?? []);
+      setConfigVersion(versioned.version);
       setPreviewResult(null);
       setBaselineManagedSignature(
         buildManagedConfigSignature(nextVisualAgents, nextVisualBindings)
@@ -945,11 +956,12 @@ export function Settings({ onEnvironmentChange }: SettingsProps) {
       setConfigError(null);
 
       try {
-        const [agentsResult, bindingsResult, channelsResult] =
+        const [agentsResult, bindingsResult, channelsResult, versionResult] =
           await Promise.allSettled([
             invoke<unknown>("get_agents_list"),
             invoke<unknown>("get_bindings"),
             invoke<ChannelConfig[]>("get_channels_config"),
+            invoke<{ version: string }>("get_config"),
           ]);
 
         const warnings: string[] = [];
@@ -997,6 +1009,12 @@ export function Settings({ onEnvironmentChange }: SettingsProps) {
         setVisualBindings(nextVisualBindings);
         setBindingsRaw(loadedBindings ?? []);
         setChannelsConfig(loadedChannels ?? []);
+        // 版本读取失败时不做冲突检测，与旧行为一致
+        setConfigVersion(
+          versionResult.status === "fulfilled"
+            ? versionResult.value.version
+            : null
+        );
         setBaselineManagedSignature(
           buildManagedConfigSignature(nextVisualAgents, nextVisualBindings)
         );
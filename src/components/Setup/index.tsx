@@ -42,6 +42,7 @@ export function Setup({ onComplete, embedded = false }: SetupProps) {
   const [installing, setInstalling] = useState<'nodejs' | 'openclaw' | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [step, setStep] = useState<'check' | 'install' | 'complete'>('check');
+  const [preferBrew, setPreferBrew] = useState(false);
 
   const checkEnvironment = async () => {
     setupLogger.info('检查系统环境...');
@@ -116,7 +117,7 @@ export function Setup({ onComplete, embedded = false }: SetupProps) {
     setError(null);
     
     try {
-      const result = await invoke<InstallResult>('install_openclaw');
+      const result = await invoke<InstallResult>('install_openclaw', { preferBrew });
       
       if (result.success) {
         setupLogger.info('✅ OpenClaw 安装成功，初始化配置...');
@@ -246,6 +247,17 @@ export function Setup({ onComplete, embedded = false }: SetupProps) {
                   <p className="text-sm text-dark-400">
                     {envStatus.openclaw_version || '未安装'}
                   </p>
+                  {envStatus.os === 'macos' && !envStatus.openclaw_installed && (
+                    <label className="flex items-center gap-1.5 mt-1 text-xs text-dark-400">
+                      <input
+                        type="checkbox"
+                        checked={preferBrew}
+                        onChange={(e) => setPreferBrew(e.target.checked)}
+                        disabled={installing !== null}
+                      />
+                      使用 Homebrew 安装
+                    </label>
+                  )}
                 </div>
               </div>
               
@@ -58,10 +58,28 @@ export async function invokeCommand<T>(cmd: string, args?: Record<string, unknow
   return payload.data;
 }
 
+export type WebUserRole = 'admin' | 'viewer';
+
 export interface WebAuthStatus {
   needs_setup: boolean;
   authenticated: boolean;
   username?: string;
+  role?: WebUserRole;
+  read_only?: boolean;
+}
+
+export interface WebUser {
+  username: string;
+  role: WebUserRole;
+  created_at: number;
+  primary: boolean;
+}
+
+export interface WebSession {
+  username: string;
+  role: WebUserRole;
+  expires_at: number;
+  current: boolean;
 }
 
 export async function getWebAuthStatus(): Promise<WebAuthStatus> {
@@ -116,3 +134,78 @@ export async function logoutWebAdmin(): Promise<void> {
     throw new Error(`退出登录失败: HTTP ${response.status}`);
   }
 }
+
+export async function listWebUsers(): Promise<WebUser[]> {
+  const response = await fetch('/api/auth/users', {
+    credentials: 'include',
+  });
+  const payload = (await response.json()) as ApiResponse<WebUser[]>;
+  if (!response.ok || !payload.success) {
+    throw new Error(payload.success ? `请求失败: HTTP ${response.status}` : payload.error);
+  }
+  return payload.data;
+}
+
+export async function addWebUser(
+  username: string,
+  password: string,
+  role: WebUserRole
+): Promise<void> {
+  const response = await fetch('/api/auth/users', {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+    },
+    credentials: 'include',
+    body: JSON.stringify({ username, password, role }),
+  });
+
+  const payload = (await response.json()) as ApiResponse<unknown>;
+  if (!response.ok || !payload.success) {
+    throw new Error(payload.success ? `请求失败: HTTP ${response.status}` : payload.error);
+  }
+}
+
+export async function removeWebUser(username: string): Promise<void> {
+  const response = await fetch('/api/auth/users/remove', {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+    },
+    credentials: 'include',
+    body: JSON.stringify({ username }),
+  });
+
+  const payload = (await response.json()) as ApiResponse<unknown>;
+  if (!response.ok || !payload.success) {
+    throw new Error(payload.success ? `请求失败: HTTP ${response.status}` : payload.error);
+  }
+}
+
+export async function listWebSessions(): Promise<WebSession[]> {
+  const response = await fetch('/api/auth/sessions', {
+    credentials: 'include',
+  });
+  const payload = (await response.json()) as ApiResponse<WebSession[]>;
+  if (!response.ok || !payload.success) {
+    throw new Error(payload.success ? `请求失败: HTTP ${response.status}` : payload.error);
+  }
+  return payload.data;
+}
+
+export async function revokeAllWebSessions(keepCurrent = false): Promise<number> {
+  const response = await fetch('/api/auth/sessions/revoke-all', {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+    },
+    credentials: 'include',
+    body: JSON.stringify({ keep_current: keepCurrent }),
+  });
+
+  const payload = (await response.json()) as ApiResponse<{ revoked: number }>;
+  if (!response.ok || !payload.success) {
+    throw new Error(payload.success ? `请求失败: HTTP ${response.status}` : payload.error);
+  }
+  return payload.data.revoked;
+}
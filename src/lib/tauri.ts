@@ -32,6 +32,14 @@ export interface ServiceStatus {
   cpu_percent: number | null;
 }
 
+// 运行中的 gateway 进程
+export interface GatewayProcess {
+  pid: number;
+  ports: number[];
+  started_at: string | null;
+  cmdline: string | null;
+}
+
 // 系统信息
 export interface SystemInfo {
   os: string;
@@ -43,6 +51,136 @@ export interface SystemInfo {
   config_dir: string;
 }
 
+export interface PathEntry {
+  path: string;
+  exists: boolean;
+}
+
+// Manager 与 OpenClaw 使用的关键文件路径
+export interface ManagerPaths {
+  config_file: PathEntry;
+  env_file: PathEntry;
+  config_dir: PathEntry;
+  log_file: PathEntry;
+  auth_config: PathEntry;
+}
+
+// 带版本号的完整配置（保存时回传 version 以检测并发修改）
+export interface VersionedConfig {
+  config: Record<string, unknown>;
+  version: string;
+}
+
+// 配置文件外部修改状态（桌面端同时推送 config-changed 事件）
+export interface ConfigChangeStatus {
+  exists: boolean;
+  last_modified: number | null;
+  version: string;
+  change_count: number;
+  changed: boolean;
+}
+
+// Gateway 网络设置（gateway 子树，未建模字段原样保留）
+export interface GatewaySettings {
+  mode?: string;
+  port?: number;
+  bind?: string;
+  trustedProxies?: string[];
+  reload?: Record<string, unknown>;
+  auth?: { mode?: string; token?: string; [key: string]: unknown };
+  [key: string]: unknown;
+}
+
+export interface GatewaySettingsSaveResult {
+  message: string;
+  restart_required: boolean;
+}
+
+// 审计日志条目（desktop 端 success 为 null：执行前记录）
+export interface AuditEntry {
+  timestamp: string;
+  user: string;
+  command: string;
+  args: string;
+  success: boolean | null;
+  error?: string;
+}
+
+export interface AgentSessionsInfo {
+  agent: string;
+  file_count: number;
+  total_bytes: number;
+}
+
+export interface LogPage {
+  lines: string[];
+  /** 本页第一行在日志文件中的字节偏移，作为 beforeOffset 继续向前翻页；为 0 表示已到开头 */
+  start_offset: number;
+  total_bytes: number;
+  /** 是否还能继续向前翻页；日志文件不存在、回退到 openclaw logs 时为 false */
+  has_more: boolean;
+}
+
+export interface SessionsInfo {
+  root: string;
+  file_count: number;
+  total_bytes: number;
+  agents: AgentSessionsInfo[];
+}
+
+export interface ModelUsage {
+  model: string;
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  estimated_cost: number;
+  /** 配置中是否有该模型的 cost */
+  priced: boolean;
+}
+
+export type UsagePeriod = "day" | "week" | "month" | "all";
+
+export interface UsageStats {
+  period: UsagePeriod;
+  total_input_tokens: number;
+  total_output_tokens: number;
+  estimated_cost: number;
+  by_model: ModelUsage[];
+  skipped_files: number;
+}
+
+export interface CredentialEntry {
+  name: string;
+  /** 所属渠道（目录名或去掉扩展名的文件名） */
+  channel: string;
+  is_dir: boolean;
+  file_count: number;
+  total_bytes: number;
+  /** 最后修改时间（Unix 秒） */
+  modified: number;
+}
+
+export interface CredentialsInfo {
+  root: string;
+  total_bytes: number;
+  entries: CredentialEntry[];
+}
+
+export interface CredentialsClearResult {
+  dry_run: boolean;
+  channel: string;
+  removed: string[];
+  bytes_freed: number;
+}
+
+export interface SessionCleanupResult {
+  dry_run: boolean;
+  deleted_files: number;
+  bytes_freed: number;
+  errors: string[];
+}
+
 // AI Provider 选项（旧版兼容）
 export interface AIProviderOption {
   id: string;
@@ -98,6 +236,17 @@ export interface ConfiguredModel {
   context_window: number | null;
   max_tokens: number | null;
   is_primary: boolean;
+  reasoning: boolean | null;
+  cost: ModelCost | null;
+  inputs: string[];
+}
+
+// 模型成本（与 openclaw.json 中的字段名一致）
+export interface ModelCost {
+  input: number;
+  output: number;
+  cacheRead: number;
+  cacheWrite: number;
 }
 
 // AI 配置概览
@@ -134,6 +283,14 @@ export interface AIConfigOverview {
   bindings: BindingsPayload | null;
 }
 
+// agents.defaults 中的标量设置
+export interface AgentDefaultsSettings {
+  primary_model: string | null;
+  fallbacks: string[];
+  max_concurrent: number | null;
+  workspace: string | null;
+}
+
 // 模型配置
 export interface ModelConfig {
   id: string;
@@ -155,7 +312,10 @@ export interface ModelConfig {
 export interface ChannelConfig {
   id: string;
   channel_type: string;
+  /** channels.<id>.enabled 的实际值；未设置时已配置的渠道视为启用 */
   enabled: boolean;
+  /** 是否已有配置（字段、账号或已启用） */
+  configured: boolean;
   /** 渠道默认配置（顶层字段） */
   config: Record<string, unknown>;
   /**
@@ -163,6 +323,21 @@ export interface ChannelConfig {
    * 兼容旧结构：为空/缺失时表示单账号渠道。
    */
   accounts?: Record<string, Record<string, unknown>>;
+  /** 会话类渠道（WhatsApp/iMessage）的登录状态 */
+  login_status?: "linked" | "unlinked" | "unknown";
+}
+
+// 保存渠道配置的结果：issues 为被丢弃的绑定与仍存在的账号冲突
+export interface SaveChannelResult {
+  message: string;
+  issues: { path: string; message: string }[];
+}
+
+// 批量导入渠道的单项结果
+export interface ChannelImportResult {
+  channel_id: string;
+  success: boolean;
+  message: string;
 }
 
 // 诊断结果
@@ -173,6 +348,13 @@ export interface DiagnosticResult {
   suggestion: string | null;
 }
 
+// 安装结果
+export interface InstallResult {
+  success: boolean;
+  message: string;
+  error: string | null;
+}
+
 // AI 测试结果
 export interface AITestResult {
   success: boolean;
@@ -188,25 +370,66 @@ export const api = {
   // 服务管理
   getServiceStatus: () => invokeWithLog<ServiceStatus>("get_service_status"),
   startService: () => invokeWithLog<string>("start_service"),
-  stopService: () => invokeWithLog<string>("stop_service"),
+  stopService: (killAll?: boolean) => invokeWithLog<string>("stop_service", { killAll }),
   restartService: () => invokeWithLog<string>("restart_service"),
-  getLogs: (lines?: number) => invokeWithLog<string[]>("get_logs", { lines }),
+  getLogs: (lines?: number, beforeOffset?: number) =>
+    invokeWithLog<LogPage>("get_logs", { lines, beforeOffset }),
+  listGatewayProcesses: () => invokeWithLog<GatewayProcess[]>("list_gateway_processes"),
+  killGatewayProcess: (pid: number) => invokeWithLog<string>("kill_gateway_process", { pid }),
+
+  // 开机自启
+  installAutostart: () => invokeWithLog<string>("install_autostart"),
+  uninstallAutostart: () => invokeWithLog<string>("uninstall_autostart"),
+  isAutostartEnabled: () => invokeWithLog<boolean>("is_autostart_enabled"),
+
+  // 会话清理
+  getSessionsInfo: () => invokeWithLog<SessionsInfo>("get_sessions_info"),
+  clearOldSessions: (olderThanDays: number, confirm?: boolean) =>
+    invokeWithLog<SessionCleanupResult>("clear_old_sessions", { olderThanDays, confirm }),
+  getUsageStats: (period?: UsagePeriod) =>
+    invokeWithLog<UsageStats>("get_usage_stats", { period }),
+
+  // 渠道凭证
+  listCredentials: () => invokeWithLog<CredentialsInfo>("list_credentials"),
+  clearCredentials: (channel: string, confirm?: boolean) =>
+    invokeWithLog<CredentialsClearResult>("clear_credentials", { channel, confirm }),
 
   // 系统信息
   getSystemInfo: () => invokeWithLog<SystemInfo>("get_system_info"),
+  getPaths: () => invokeWithLog<ManagerPaths>("get_paths"),
+  openConfigFile: (createIfMissing?: boolean) =>
+    invokeWithLog<string>("open_config_file", { createIfMissing }),
   checkOpenclawInstalled: () =>
     invokeWithLog<boolean>("check_openclaw_installed"),
+  // preferBrew 仅在 macOS 且存在 Homebrew formula 时生效，否则使用 npm 安装
+  installOpenclaw: (preferBrew?: boolean) =>
+    invokeWithLog<InstallResult>("install_openclaw", { preferBrew }),
   getOpenclawVersion: () =>
     invokeWithLog<string | null>("get_openclaw_version"),
 
   // 配置管理
-  getConfig: () => invokeWithLog<unknown>("get_config"),
-  saveConfig: (config: unknown) =>
-    invokeWithLog<string>("save_config", { config }),
+  getConfig: () => invokeWithLog<VersionedConfig>("get_config"),
+  getConfigSchema: () => invokeWithLog<Record<string, unknown>>("get_config_schema"),
+  saveConfig: (config: unknown, expectedVersion?: string) =>
+    invokeWithLog<string>("save_config", { config, expectedVersion }),
+  watchConfig: (since?: number) =>
+    invokeWithLog<ConfigChangeStatus>("watch_config", { since }),
   getEnvValue: (key: string) =>
     invokeWithLog<string | null>("get_env_value", { key }),
   saveEnvValue: (key: string, value: string) =>
     invokeWithLog<string>("save_env_value", { key, value }),
+  getEnvValues: (keys: string[]) =>
+    invokeWithLog<Record<string, string | null>>("get_env_values", { keys }),
+  saveEnvValues: (values: Record<string, string>) =>
+    invokeWithLog<string>("save_env_values", { values }),
+  getEnvKeys: () =>
+    invokeWithLog<{ key: string; masked_value: string }[]>("get_env_keys"),
+  removeEnvKey: (key: string) =>
+    invokeWithLog<string>("remove_env_key", { key }),
+  getGatewaySettings: () =>
+    invokeWithLog<GatewaySettings>("get_gateway_settings"),
+  saveGatewaySettings: (settings: GatewaySettings) =>
+    invokeWithLog<GatewaySettingsSaveResult>("save_gateway_settings", { settings }),
 
   // AI Provider（旧版兼容）
   getAIProviders: () => invokeWithLog<AIProviderOption[]>("get_ai_providers"),
@@ -231,22 +454,38 @@ export const api = {
     }),
   deleteProvider: (providerName: string) =>
     invokeWithLog<string>("delete_provider", { providerName }),
+  cloneProvider: (sourceName: string, newName: string) =>
+    invokeWithLog<string>("clone_provider", { sourceName, newName }),
   setPrimaryModel: (modelId: string) =>
     invokeWithLog<string>("set_primary_model", { modelId }),
+  getAgentDefaults: () => invokeWithLog<AgentDefaultsSettings>("get_agent_defaults"),
+  setMaxConcurrent: (maxConcurrent: number) =>
+    invokeWithLog<string>("set_max_concurrent", { maxConcurrent }),
   addAvailableModel: (modelId: string) =>
     invokeWithLog<string>("add_available_model", { modelId }),
   removeAvailableModel: (modelId: string) =>
     invokeWithLog<string>("remove_available_model", { modelId }),
+  updateModelCost: (fullId: string, cost: ModelCost) =>
+    invokeWithLog<string>("update_model_cost", { fullId, cost }),
 
   // 渠道
   getChannelsConfig: () =>
     invokeWithLog<ChannelConfig[]>("get_channels_config"),
   saveChannelConfig: (channel: ChannelConfig) =>
-    invokeWithLog<string>("save_channel_config", { channel }),
+    invokeWithLog<SaveChannelResult>("save_channel_config", { channel }),
+  importChannels: (channels: ChannelConfig[]) =>
+    invokeWithLog<ChannelImportResult[]>("import_channels", { channels }),
 
   // 诊断测试
   runDoctor: () => invokeWithLog<DiagnosticResult[]>("run_doctor"),
+  fixConfigPermissions: () => invokeWithLog<string[]>("fix_config_permissions"),
   testAIConnection: () => invokeWithLog<AITestResult>("test_ai_connection"),
+  testProviderConnection: (providerName: string, modelId?: string) =>
+    invokeWithLog<AITestResult>("test_provider_connection", { providerName, modelId }),
   testChannel: (channelType: string) =>
     invokeWithLog<unknown>("test_channel", { channelType }),
+
+  // 审计日志
+  getAuditLog: (limit?: number) =>
+    invokeWithLog<AuditEntry[]>("get_audit_log", { limit }),
 };